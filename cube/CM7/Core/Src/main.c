@@ -82,8 +82,12 @@ typedef struct Vector2 {
 void threshold     ( size_t imageAddress, uint32_t size_x, uint32_t size_y);
 size_t blob        ( size_t imageAddress, size_t size_x, size_t size_y );
 void project       ( size_t size_x, size_t size_y);
+void project_temperature ( size_t size_x, size_t size_y, float temperature );
 size_t track       ( size_t allowed_failures );
 size_t track_chunk ( size_t allowed_failures, float ra, float dec );
+void set_search_timeout ( uint32_t ticks );
+size_t track_recursive ( size_t allowed_failures );
+size_t frame       ( size_t address, size_t size_x, size_t size_y, float temperature );
 void vote          ( );
 void get_vote      ( uint8_t* vote );
 
@@ -107,6 +111,8 @@ const uint8_t SEND_ERR = 'E';
 const uint8_t SEND_HANDSHAKE = 'H';
 const uint8_t SEND_RUN = 'R';
 const uint8_t SEND_RUN_CHUNK = 'C';
+const uint8_t SEND_RUN_TRACK = 'T';
+const uint8_t SEND_RUN_FRAME = 'F';
 
 const uint8_t SEND_THRESH          = 'a';
 
@@ -263,6 +269,22 @@ void perform_track_chunk ( float ra, float dec )
 	size_t found = track_chunk(10, ra, dec);
 }
 
+void perform_track_recursive ( )
+{
+	HAL_GPIO_WritePin(GPIOB, GPIO_PIN_0, GPIO_PIN_SET);   // Green
+	HAL_GPIO_WritePin(GPIOE, GPIO_PIN_1, GPIO_PIN_RESET); // Yellow
+	HAL_GPIO_WritePin(GPIOB, GPIO_PIN_14, GPIO_PIN_SET);  // Red
+	size_t found = track_recursive(10);
+}
+
+void perform_frame ( )
+{
+	HAL_GPIO_WritePin(GPIOB, GPIO_PIN_0, GPIO_PIN_SET);   // Green
+	HAL_GPIO_WritePin(GPIOE, GPIO_PIN_1, GPIO_PIN_SET);   // Yellow
+	HAL_GPIO_WritePin(GPIOB, GPIO_PIN_14, GPIO_PIN_RESET);// Red
+	size_t valid = frame(AXIS_RAM_START, 808, 608, 20.0f);
+}
+
 void perform_vote ( )
 {
 	HAL_GPIO_WritePin(GPIOB, GPIO_PIN_0, GPIO_PIN_SET);   // Green
@@ -418,6 +440,18 @@ Error_Handler();
 					perform_track();
 					perform_vote();
 					break;
+
+				case SEND_RUN_TRACK:
+					perform_threshold(); 
+					perform_blob();
+					perform_project(); 
+					perform_track_recursive();
+					perform_vote();
+					break;
+
+				case SEND_RUN_FRAME:
+					perform_frame();
+					break;
   
 				case SEND_GET_VOTE: 
 					perform_get_vote();
@@ -40,18 +40,27 @@ use star_tracker_lib::image_processing::Blob;
 use star_tracker_lib::image_processing::Threshold;
 use star_tracker_lib::projection::IntrinsicParameters;
 use star_tracker_lib::projection::ExtrinsicParameters;
+use star_tracker_lib::projection::ThermalModel;
 use star_tracker_lib::projection::SpaceImage;
 
 
 use star_tracker_lib::tracking_mode::Constellation;
 use star_tracker_lib::tracking_mode::StarTriangleIterator;
 use star_tracker_lib::tracking_mode::Specularity;
-use star_tracker_lib::tracking_mode::AbandonSearchFailures;
+use star_tracker_lib::tracking_mode::AbandonSearchClockFailure;
+use star_tracker_lib::tracking_mode::RecursiveResult;
+use star_tracker_lib::tracking_mode::Recursive;
+use star_tracker_lib::tracking_mode::Expand;
+use star_tracker_lib::tracking_mode::Photometry;
 use star_tracker_lib::tracking_mode::database::ChunkIteratorDeclination;
-use star_tracker_lib::tracking_mode::database::ChunkAreaSearch;
+use star_tracker_lib::tracking_mode::database::ChunkIteratorCone;
+
+use star_tracker_lib::pipeline::StarTrackerConfig;
+use star_tracker_lib::pipeline::StarTracker;
 
 use star_tracker_lib::attitude_determination::Quest;
 use star_tracker_lib::attitude_determination::AttitudeDetermination;
+use star_tracker_lib::attitude_determination::Mounting;
 
 pub mod clist;
 pub mod database;
@@ -113,9 +122,35 @@ static mut stars_2d: ArrayList<Vector2,    100> = ArrayList{array: [Vector2{x: 0
 static mut stars_3d: ArrayList<Equatorial, 100> = ArrayList{array: [Equatorial::north(); 100], end: 0};
 static mut stars_match: ArrayList<Match<usize>, 100> = ArrayList{array: [Match{input: 0, output: 0, weight: 1.0}; 100], end: 0};
 static mut vote_result: Equatorial = Equatorial::north();
+/// The intrinsic parameters used in the last call to project.
+static mut intrinsic_projection: Option<IntrinsicParameters> = None;
+/// The rotation from inertial to camera found in the last frame (None if lost).
+static mut prior_attitude: Option<Quaternion> = None;
+/// The pipeline used by frame, created on the first call.
+static mut star_tracker: Option<StarTracker<'static, 100, 1000, 200>> = None;
+/// The max number of ticks of clock() a lost-in-space search (track and track_chunk) can take.
+static mut search_timeout: u32 = SEARCH_TIMEOUT;
+/// The brightness calibration used by frame in lost-in-space (None to only use the position of the stars).
+static mut photometry: Option<Photometry> = None;
+/// The magnitude of each star in database::CATALOGUE, set with set_photometry.
+static mut magnitudes: Option<CArray<Decimal>> = None;
 
 const REFERENCE_FORWARD : Equatorial = Equatorial::north();
 const REFERENCE_UP      : Equatorial = Equatorial{ra: Degrees(00.0).as_radians(), dec: Radians(0.0)}; // y = +1
+/// The rotation from the camera frame (set by REFERENCE_FORWARD and REFERENCE_UP) to the spacecraft body.
+const MOUNTING          : Mounting   = Mounting::identity();
+/// The temperature (degrees celsius) the lens was calibrated at.
+const TEMPERATURE_REFERENCE : Decimal = 20.0;
+/// How much the focal length changes per degree (parts per million).
+const FOCAL_LENGTH_PPM      : Decimal = 0.0;
+/// How far the boresight given to track_chunk can be from the actual boresight.
+const PRIOR_UNCERTAINTY     : Radians = Degrees(5.0).as_radians();
+/// The default max number of ticks of clock() a lost-in-space search can take, use set_search_timeout to change.  
+/// clock() is from newlib, a tick is 1 / CLOCKS_PER_SEC seconds (1 millisecond with the default newlib config).  
+/// If the syscalls are stubbed (_times), clock() does not advance and only allowed_failures ends the search.
+const SEARCH_TIMEOUT        : u32 = 2000;
+/// Associates stars within 5 pixels of where they were expected from the previous frame.
+const RECURSIVE             : Recursive = Recursive{gate: 5.0, fov: database::FOV, min_stars: 4};
 
 #[no_mangle]
 /// Thresholds the memory location with Nilback threshold.
@@ -176,14 +211,25 @@ pub extern "C" fn blob (
 }
 
 #[no_mangle]
+/// Projects the blobs assuming the lens is at the calibration temperature.
 pub extern "C" fn project ( size_x: usize, size_y: usize )
+{
+	project_temperature(size_x, size_y, TEMPERATURE_REFERENCE as f32);
+}
+
+#[no_mangle]
+/// Projects the blobs with the intrinsic parameters adjusted for the temperature of the lens.
+pub extern "C" fn project_temperature ( size_x: usize, size_y: usize, temperature: f32 )
 {
 	unsafe
 	{
 		let fov = database::FOV;
 		let sensor_horizontal = ((size_x as Decimal).powf(2.0) + (size_y as Decimal).powf(2.0)).sqrt();
 		let img_center = Vector2{x: size_x as Decimal / 2.0, y: size_y as Decimal / 2.0};
-		let intrinsic_projection = IntrinsicParameters::from_fov(fov, sensor_horizontal, img_center);
+		let reference  = IntrinsicParameters::from_fov(fov, sensor_horizontal, img_center);
+		let thermal    = ThermalModel::linear_ppm(reference, TEMPERATURE_REFERENCE, FOCAL_LENGTH_PPM);
+		let intrinsic = thermal.at_temperature(temperature as Decimal);
+		intrinsic_projection = Some(intrinsic);
 		
 		let extrinsic_projection = ExtrinsicParameters::look_at(REFERENCE_FORWARD, REFERENCE_UP)
 		.expect("Ensure extrinsic projection up and forward are not the same value.");	
@@ -191,7 +237,7 @@ pub extern "C" fn project ( size_x: usize, size_y: usize )
 		for i in 0..stars_2d.size()
 		{
 			let point = SpaceImage(stars_2d.get(i));
-			let camera_space = intrinsic_projection.from_image(point);
+			let camera_space = intrinsic.from_image(point);
 			let world_space  = extrinsic_projection.from_image(camera_space);
 			
 			stars_3d.push_back(world_space.0.to_equatorial());
@@ -201,6 +247,33 @@ pub extern "C" fn project ( size_x: usize, size_y: usize )
 }
 
 
+#[no_mangle]
+/// Sets the max number of ticks of clock() a lost-in-space search (track and track_chunk) can take.  
+/// A tick is 1 / CLOCKS_PER_SEC seconds of newlib clock(), see SEARCH_TIMEOUT.
+pub extern "C" fn set_search_timeout ( ticks: u32 )
+{
+	unsafe { search_timeout = ticks; }
+}
+
+#[no_mangle]
+/// Makes frame check the brightness of the stars in lost-in-space.  
+/// `magnitude_address` is an array of `size` f32 with the magnitude of each star in the catalogue.  
+/// Call with a size of 0 to only use the position of the stars.
+pub extern "C" fn set_photometry ( zero_point: f32, tolerance: f32, magnitude_address: usize, size: usize )
+{
+	unsafe
+	{
+		if size == 0
+		{
+			photometry = None;
+			magnitudes = None;
+			return;
+		}
+		photometry = Some(Photometry{zero_point: zero_point as Decimal, tolerance: tolerance as Decimal});
+		magnitudes = Some(CArray{array: magnitude_address as *mut Decimal, size: size});
+	}
+}
+
 #[no_mangle]
 pub extern "C" fn track ( allowed_failures: usize ) -> usize
 {
@@ -212,13 +285,14 @@ pub extern "C" fn track ( allowed_failures: usize ) -> usize
 			&stars_3d, &mut database_iterator,
 			&mut StarTriangleIterator::<1000>::new(),
 			&mut Specularity::default(),
-			&mut AbandonSearchFailures::new(allowed_failures),
+			&mut AbandonSearchClockFailure::new(tick as fn ( ) -> u32, search_timeout, allowed_failures),
 			angle_tolerance,
 			4..=4,
 			&mut stars_match
 		);
 		if let star_tracker_lib::tracking_mode::ConstellationResult::Success{fails: fails} = success
 		{
+			expand_matches();
 			for i in 0..stars_match.size()
 			{
 				let point = database::CATALOGUE[stars_match.get(i).output];
@@ -236,19 +310,20 @@ pub extern "C" fn track_chunk ( allowed_failures: usize, ra: f32, dec: f32 ) ->
 {
 	unsafe
 	{
-		let mut database_iterator = ChunkAreaSearch::from_point(&database::DATABASE, Equatorial{ra: Degrees(ra).to_radians(), dec: Degrees(dec).to_radians()}, database::FOV);
+		let mut database_iterator = ChunkIteratorCone::from_prior(&database::DATABASE, Equatorial{ra: Degrees(ra).to_radians(), dec: Degrees(dec).to_radians()}, PRIOR_UNCERTAINTY, database::FOV);
 		let angle_tolerance = database::angle_tolerance;
 		let success = Constellation::find (
 			&stars_3d, &mut database_iterator,
 			&mut StarTriangleIterator::<1000>::new(),
 			&mut Specularity::default(),
-			&mut AbandonSearchFailures::new(allowed_failures),
+			&mut AbandonSearchClockFailure::new(tick as fn ( ) -> u32, search_timeout, allowed_failures),
 			angle_tolerance,
 			4..=4,
 			&mut stars_match
 		);
 		if let star_tracker_lib::tracking_mode::ConstellationResult::Success{fails: _} = success
 		{
+			expand_matches();
 			for i in 0..stars_match.size()
 			{
 				let point = database::CATALOGUE[stars_match.get(i).output];
@@ -260,6 +335,147 @@ pub extern "C" fn track_chunk ( allowed_failures: usize, ra: f32, dec: f32 ) ->
 	}
 }
 
+/// Identifies every other star in the image with the attitude of the pyramid in stars_match.  
+/// This makes the attitude found from stars_match (track_recursive and vote) far more accurate.
+fn expand_matches ( )
+{
+	unsafe
+	{
+		let mut vectors: ArrayList<Match<Vector3>, 100> = ArrayList::new();
+		for i in 0..stars_match.size()
+		{
+			let input  = stars_3d.get(stars_match.get(i).input).to_vector3();
+			let output = database::CATALOGUE[stars_match.get(i).output].to_vector3();
+			let _ = vectors.push_back(Match{input: input, output: output, weight: 1.0});
+		}
+		let attitude = Quest::estimate(&vectors, None);
+
+		let expand = Expand{tolerance: database::angle_tolerance};
+		let added  = expand.expand(&stars_3d, &database::CATALOGUE, attitude, &mut stars_match);
+		print(format!("Expand {}\n", added).as_str());
+	}
+}
+
+/// The clock used to time each step of frame.
+fn tick ( ) -> u32
+{
+	unsafe { return clock(); }
+}
+
+#[no_mangle]
+/// Runs the whole pipeline on the image (threshold, blob, project, track and vote).  
+/// The star tracker remembers the attitude between calls, so it will track after the first valid frame.  
+/// Returns 1 if the attitude is valid.
+pub extern "C" fn frame ( address: usize, size_x: usize, size_y: usize, temperature: f32 ) -> usize
+{
+	unsafe
+	{
+		let memory_address: usize = address;
+		let array: *mut usize = memory_address as *mut usize;
+		let mut array = CArray{array: array, size: (size_x*size_y/4) as usize};
+
+		let word_size = WordSize{word_size: 32, nibbles_num: 4, nibbles_size: 8};
+		let mut word  = WordList{array: &mut array, size: word_size};
+		let mut image = ImageWord{img: &mut word, size: Pixel{x: size_x as usize, y: size_y as usize}};
+		let threshold = ThresholdGrid::<50, 50>::new(&image, 20, 2);
+
+		let fov = database::FOV;
+		let sensor_horizontal = ((size_x as Decimal).powf(2.0) + (size_y as Decimal).powf(2.0)).sqrt();
+		let img_center = Vector2{x: size_x as Decimal / 2.0, y: size_y as Decimal / 2.0};
+		let reference  = IntrinsicParameters::from_fov(fov, sensor_horizontal, img_center);
+		let thermal    = ThermalModel::linear_ppm(reference, TEMPERATURE_REFERENCE, FOCAL_LENGTH_PPM);
+		let intrinsic  = thermal.at_temperature(temperature as Decimal);
+
+		if star_tracker.is_none()
+		{
+			let config = StarTrackerConfig
+			{
+				intrinsic:        intrinsic,
+				blob_min_size:    2,
+				angle_tolerance:  database::angle_tolerance,
+				num_stars:        4..=4,
+				allowed_failures: 10,
+				chunk_step:       Degrees(10.0).as_radians(),
+				lost_area:        database::FOV,
+				lost_frames:      5,
+				recursive:        RECURSIVE,
+				max_residual:     database::angle_tolerance * 2.0,
+				max_false_probability: 0.001,
+				photometry:       None,
+				voting:           None,
+			};
+			star_tracker = Some(StarTracker::new(&database::DATABASE, config, tick));
+		}
+		let tracker = star_tracker.as_mut().expect("Created above.");
+		tracker.config.intrinsic = intrinsic;
+		tracker.config.photometry = photometry;
+		match magnitudes.as_ref()
+		{
+			Some(magnitude) => tracker.set_magnitudes(Some(magnitude)),
+			None            => tracker.set_magnitudes(None),
+		}
+
+		let result = tracker.frame(&mut image, &threshold);
+		print(format!("Frame {:?} {} {} {} {}\n", result.mode, result.valid, result.stars, result.matched, result.timing.total).as_str());
+		if let Some(verification) = result.verification
+		{
+			print(format!("Verify {} of {} {}\n",
+				verification.matched, verification.tested, verification.false_probability).as_str());
+		}
+		if !result.valid
+		{
+			return 0;
+		}
+
+		let rotate_to_world = result.attitude.conjugate();
+		vote_result = rotate_to_world.rotate_point(Vector3{x: 0.0, y: 0.0, z: 1.0}).to_equatorial();
+		let rotate_to_body: Quaternion = MOUNTING.to_body(result.attitude);
+		print(format!("Attitude {} {} {} {}\n", 
+			rotate_to_body.w, rotate_to_body.x, rotate_to_body.y, rotate_to_body.z).as_str());
+		return 1;
+	}
+}
+
+#[no_mangle]
+/// Tracks the stars with the attitude of the previous frame.  
+/// If there is no previous attitude or too few stars are associated, lost-in-space (track) is used.  
+/// Call project first.
+pub extern "C" fn track_recursive ( allowed_failures: usize ) -> usize
+{
+	unsafe
+	{
+		let intrinsic = match intrinsic_projection
+		{
+			Some(intrinsic) => intrinsic,
+			None            => return 0,
+		};
+
+		let mut vectors: ArrayList<Match<Vector3>, 100> = ArrayList::new();
+		if let Some(attitude) = prior_attitude
+		{
+			let result = RECURSIVE.track::<Quest>(
+				&stars_2d, &database::CATALOGUE, &intrinsic, attitude, &mut stars_match, &mut vectors);
+
+			if let RecursiveResult::Success{found, attitude} = result
+			{
+				prior_attitude = Some(attitude);
+				print(format!("Recursive {}\n", found).as_str());
+				return 1;
+			}
+		}
+
+		// Lost, use lost-in-space.
+		prior_attitude = None;
+		if track(allowed_failures) == 1
+		{
+			Recursive::to_vectors(&stars_2d, &database::CATALOGUE, &intrinsic, &stars_match, &mut vectors);
+			prior_attitude = Some(Quest::estimate(&vectors, None));
+			return 1;
+		}
+		return 0;
+	}
+}
+
 #[no_mangle]
 pub extern "C" fn vote ( )
 {
@@ -279,6 +495,10 @@ pub extern "C" fn vote ( )
 	print(format!("Vote {} {}\n", 
 		world_center.to_equatorial().ra.to_degrees().0, 
 		world_center.to_equatorial().dec.to_degrees().0).as_str());
+
+	let rotate_to_body: Quaternion = MOUNTING.to_body(rotate_to_cam);
+	print(format!("Attitude {} {} {} {}\n", 
+		rotate_to_body.w, rotate_to_body.x, rotate_to_body.y, rotate_to_body.z).as_str());
 	}
 }
 
@@ -35,6 +35,13 @@ impl Star
 		return val;
 	}
 
+
+	/// A star with only a position, used to create a catalogue for tests.
+	#[cfg(test)]
+	pub fn at ( pos: Equatorial ) -> Star
+	{
+		return Star{mag: 0.0, pos: pos, spec: String::new(), name: String::new(), bf: String::new(), hip: String::new()};
+	}
 }
 
 
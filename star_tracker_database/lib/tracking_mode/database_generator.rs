@@ -9,6 +9,8 @@ use star_tracker_lib::util::units::Equatorial;
 use star_tracker_lib::util::units::Radians;
 use star_tracker_lib::util::list::List;
 
+use star_tracker_lib::ephemeris::BodyMask;
+
 use crate::io::Star;
 use crate::distribution::Distribute;
 use crate::tracking_mode::StarDatabaseElement;
@@ -287,6 +289,28 @@ impl DatabaseGenerator
 	}
 
 
+	/// Removes any stars which are covered by the Sun, Moon or a planet.
+	/// The bodies move, so the database is only valid around the epoch of `bodies`.
+	/// # Arguments
+	/// * `stars`  - The stars to observe.
+	/// * `bodies` - The position of each body at the time the database is used.
+	///
+	/// # Returns
+	/// A reduced list of stars excluding the stars covered by a body.
+	pub fn limit_bodies ( stars: &dyn List<Star>, bodies: &BodyMask ) -> Vec<Star>
+	{
+		let mut s : Vec<Star> = Vec::new();
+		for i in 0..stars.size()
+		{
+			if bodies.find(stars.get(i).pos).is_none()
+			{
+				s.push(stars.get(i));
+			}
+		}
+		return s;
+	}
+
+
 	/// Finds the percentage of sky covered by the specified number of stars.
 	/// This is done by finding the percentage of regions in the sky which are satisfied.
 	/// # Arguments
@@ -391,6 +415,88 @@ impl DatabaseGenerator
 
 
 
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+mod test
+{
+	use star_tracker_lib::ephemeris::BodyMask;
+	use star_tracker_lib::ephemeris::Body;
+	use star_tracker_lib::ephemeris::Epoch;
+
+	use star_tracker_lib::util::units::Equatorial;
+	use star_tracker_lib::util::units::Degrees;
+	use star_tracker_lib::util::aliases::Decimal;
+
+	use crate::tracking_mode::DatabaseGenerator;
+	use crate::io::Star;
+
+	fn eq ( ra: Decimal, dec: Decimal ) -> Equatorial
+	{
+		return Equatorial{ra: Degrees(ra).as_radians(), dec: Degrees(dec).as_radians()};
+	}
+
+	// Every body is far from the stars except Jupiter.
+	fn bodies ( ) -> BodyMask
+	{
+		let mut positions = [eq(0.0, -80.0); Body::COUNT];
+		positions[Body::Jupiter as usize] = eq(100.0, 10.0);
+		return BodyMask{margin: Degrees(0.5).as_radians(), positions: positions};
+	}
+
+//###############################################################################################//
+//
+//										DatabaseGenerator
+//
+// pub fn limit_bodies ( &dyn List<Star>, &BodyMask ) -> Vec<Star>
+//
+//###############################################################################################//
+//										~ limit_bodies ~										 //
+	#[test]
+	// The stars within the extent and margin of a body are removed, the order of the rest is kept.
+	fn test_limit_bodies ( )
+	{
+		let stars = vec![Star::at(eq(100.0, 10.0)), Star::at(eq(120.0, 10.0)),
+			Star::at(eq(100.0, 10.4)), Star::at(eq(100.0, 11.0)), Star::at(eq(0.0, 0.0))];
+		let limited = DatabaseGenerator::limit_bodies(&stars, &bodies());
+		assert_eq!(limited.len(), 3);
+		assert_eq!(limited[0].pos, stars[1].pos);
+		assert_eq!(limited[1].pos, stars[3].pos);
+		assert_eq!(limited[2].pos, stars[4].pos);
+	}
+
+	#[test]
+	// The positions from the ephemeris should remove the star at the Sun.
+	fn test_limit_bodies_epoch ( )
+	{
+		let epoch = Epoch::from_utc(2024, 1, 1, 0, 0, 0.0);
+		let sun   = Body::Sun.position(epoch);
+		let mask  = BodyMask::new(epoch, Degrees(0.1).as_radians());
+		let far   = Equatorial{ra: sun.ra, dec: -sun.dec - Degrees(30.0).as_radians()};
+		let stars = vec![Star::at(sun), Star::at(far)];
+		let limited = DatabaseGenerator::limit_bodies(&stars, &mask);
+		assert_eq!(limited.len(), 1);
+		assert_eq!(limited[0].pos, far);
+	}
+
+	#[test]
+	// Without stars, nothing is returned.
+	fn test_limit_bodies_empty ( )
+	{
+		let stars : Vec<Star> = Vec::new();
+		assert_eq!(DatabaseGenerator::limit_bodies(&stars, &bodies()).len(), 0);
+	}
+}
+
+
+
+
 
 // 
 // //###############################################################################################//
@@ -0,0 +1,328 @@
+//! Implementation for [GridDatabaseGenerator](crate::tracking_mode::GridDatabaseGenerator).
+use super::GridDatabaseGenerator;
+
+use star_tracker_lib::tracking_mode::database::GridDatabase;
+
+use star_tracker_lib::util::units::Equatorial;
+use star_tracker_lib::util::units::Radians;
+
+use crate::io::Star;
+
+
+
+impl GridDatabaseGenerator
+{
+	/// Returns the GridDatabase.
+	/// Call gen_database before this.
+	pub fn get_database ( &self ) -> GridDatabase
+	{
+		return GridDatabase {
+			radius       : self.radius,
+			buffer       : self.buffer,
+			grid_size    : self.grid_size,
+			pattern_start: &self.pattern_start,
+			cells        : &self.cells,
+			cell_start   : &self.cell_start,
+			cell_stars   : &self.cell_stars,
+			catalogue    : &self.catalogue,
+		};
+	}
+
+
+
+	/// Creates a grid database on the heap.
+	/// # Arguments
+	/// * `stars`     - The stars to be inserted into the database.
+	/// * `radius`    - The max angle from a star to a neighbour in its pattern (less than half the field of view).
+	/// * `buffer`    - Neighbours closer than this are not in the pattern (around the centroid error).
+	/// * `grid_size` - The number of cells on each side of the grid (odd).
+	/// # Returns
+	/// The generator containing the database.
+	pub fn gen_database ( stars: &Vec<Star>, radius: Radians, buffer: Radians, grid_size: usize ) -> Self
+	{
+		let mut catalogue : Vec<Equatorial> = Vec::new();
+		for i in 0..stars.len() { catalogue.push(stars[i].pos); }
+
+		let mut pattern_start : Vec<usize> = Vec::with_capacity(catalogue.len() + 1);
+		let mut cells         : Vec<usize> = Vec::new();
+		for i in 0..catalogue.len()
+		{
+			// Only the close stars are needed to make the pattern.
+			let mut neighbours : Vec<Equatorial> = Vec::new();
+			for j in 0..catalogue.len()
+			{
+				if i != j && catalogue[i].angle_distance(catalogue[j]) < radius { neighbours.push(catalogue[j]); }
+			}
+
+			let mut pattern : Vec<usize> = Vec::new();
+			GridDatabase::pattern(catalogue[i], &neighbours, radius, buffer, grid_size, &mut pattern)
+				.expect("A Vec can fit any pattern.");
+			pattern_start.push(cells.len());
+			cells.append(&mut pattern);
+		}
+		pattern_start.push(cells.len());
+
+		// The inverse so only the stars with a shared cell are compared.
+		// The stars are added in ascending order so each cell is sorted.
+		let mut cell_lists : Vec<Vec<usize>> = vec![Vec::new(); grid_size * grid_size];
+		for i in 0..catalogue.len()
+		{
+			for j in pattern_start[i]..pattern_start[i + 1] { cell_lists[cells[j]].push(i); }
+		}
+		let mut cell_start : Vec<usize> = Vec::with_capacity(cell_lists.len() + 1);
+		let mut cell_stars : Vec<usize> = Vec::with_capacity(cells.len());
+		for i in 0..cell_lists.len()
+		{
+			cell_start.push(cell_stars.len());
+			cell_stars.append(&mut cell_lists[i]);
+		}
+		cell_start.push(cell_stars.len());
+
+		return Self
+		{
+			pattern_start: pattern_start,
+			cells:         cells,
+			cell_start:    cell_start,
+			cell_stars:    cell_stars,
+			catalogue:     catalogue,
+			radius:        radius,
+			buffer:        buffer,
+			grid_size:     grid_size,
+		};
+	}
+
+
+
+	/// Writes the database as rust source (`GRID`, `GRID_PATTERN_START`, `GRID_CELLS`, `GRID_CELL_START` and `GRID_CELL_STARS`).
+	/// The output expects `CATALOGUE` to be the stars the patterns were generated from.
+	/// # Arguments
+	/// * `mem_section_cells` - The `link_section` attribute of `GRID_CELLS` and `GRID_CELL_STARS` (empty for none).
+	pub fn to_rust ( &self, mem_section_cells: &str ) -> String
+	{
+		let mut pattern_start_str = String::with_capacity(self.pattern_start.len() * 8);
+		for start in &self.pattern_start { pattern_start_str.push_str(&format!("\t{},\n", start)); }
+		let mut cells_str = String::with_capacity(self.cells.len() * 8);
+		for cell in &self.cells { cells_str.push_str(&format!("\t{},\n", cell)); }
+		let mut cell_start_str = String::with_capacity(self.cell_start.len() * 8);
+		for start in &self.cell_start { cell_start_str.push_str(&format!("\t{},\n", start)); }
+		let mut cell_stars_str = String::with_capacity(self.cell_stars.len() * 8);
+		for star in &self.cell_stars { cell_stars_str.push_str(&format!("\t{},\n", star)); }
+
+		return format!(r#"
+pub const GRID: GridDatabase = GridDatabase
+{{
+	radius:        Radians({}),
+	buffer:        Radians({}),
+	grid_size:     {},
+	pattern_start: &GRID_PATTERN_START,
+	cells:         &GRID_CELLS,
+	cell_start:    &GRID_CELL_START,
+	cell_stars:    &GRID_CELL_STARS,
+	catalogue:     &CATALOGUE,
+}};
+
+pub const GRID_PATTERN_START: [usize; {}] =
+[
+{}
+];
+
+{}
+pub const GRID_CELLS: [usize; {}] =
+[
+{}
+];
+
+pub const GRID_CELL_START: [usize; {}] =
+[
+{}
+];
+
+{}
+pub const GRID_CELL_STARS: [usize; {}] =
+[
+{}
+];
+"#,
+		self.radius.0, self.buffer.0, self.grid_size,
+		self.pattern_start.len(), pattern_start_str,
+		mem_section_cells, self.cells.len(), cells_str,
+		self.cell_start.len(), cell_start_str,
+		mem_section_cells, self.cell_stars.len(), cell_stars_str);
+	}
+}
+
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+mod test
+{
+	use star_tracker_lib::tracking_mode::database::GridDatabase;
+
+	use star_tracker_lib::util::units::Equatorial;
+	use star_tracker_lib::util::units::Degrees;
+	use star_tracker_lib::util::units::Radians;
+
+	use crate::tracking_mode::GridDatabaseGenerator;
+	use crate::io::Star;
+
+	// A patch of stars with irregular spacing and a star too far away to be a neighbour.
+	fn stars ( ) -> Vec<Star>
+	{
+		let offsets = [(0.0, 0.0), (1.3, 0.4), (-0.8, 1.9), (2.1, -1.7), (-2.6, -0.9), (0.5, 2.8), (60.0, 0.0)];
+		let mut stars = Vec::new();
+		for (ra, dec) in offsets
+		{
+			stars.push(Star::at(Equatorial{ra: Degrees(100.0 + ra).as_radians(), dec: Degrees(10.0 + dec).as_radians()}));
+		}
+		return stars;
+	}
+
+	fn radius ( ) -> Radians { return Degrees(5.5).as_radians(); }
+	fn buffer ( ) -> Radians { return Degrees(0.1).as_radians(); }
+
+	// The values of a generated array, one element per line.
+	fn array ( output: &str, name: &str ) -> Vec<usize>
+	{
+		let start = output.find(&format!("pub const {}: [", name)).expect("The array should be output.");
+		let body  = &output[start..];
+		let open  = body.find("\n[\n").unwrap() + 3;
+		let close = body.find("\n];").unwrap();
+		return body[open..close].lines()
+			.map(|line| line.trim().trim_end_matches(','))
+			.filter(|line| !line.is_empty()).map(|line| line.parse().unwrap()).collect();
+	}
+
+
+//###############################################################################################//
+//
+//										GridDatabaseGenerator
+//
+// pub fn get_database ( &self ) -> GridDatabase
+// pub fn gen_database ( &Vec<Star>, Radians, Radians, usize ) -> Self
+// pub fn to_rust      ( &self, &str ) -> String
+//
+//###############################################################################################//
+//										~ gen_database ~										 //
+	#[test]
+	// The pattern of each star should be the pattern of the star with every other star.
+	fn test_gen_database_patterns ( )
+	{
+		let stars     = stars();
+		let generator = GridDatabaseGenerator::gen_database(&stars, radius(), buffer(), 11);
+		let catalogue : Vec<Equatorial> = stars.iter().map(|s| s.pos).collect();
+		assert_eq!(generator.catalogue, catalogue);
+		assert_eq!(generator.pattern_start.len(), stars.len() + 1);
+		assert_eq!(generator.pattern_start[stars.len()], generator.cells.len());
+
+		for i in 0..stars.len()
+		{
+			let mut expected : Vec<usize> = Vec::new();
+			GridDatabase::pattern(catalogue[i], &catalogue, radius(), buffer(), 11, &mut expected).unwrap();
+			let start = generator.pattern_start[i];
+			let end   = generator.pattern_start[i + 1];
+			assert_eq!(generator.cells[start..end].to_vec(), expected);
+		}
+
+		// The far star has no neighbours.
+		assert_eq!(generator.pattern_start[6], generator.pattern_start[7]);
+	}
+
+	#[test]
+	// Each cell should have every star with that cell in its pattern in ascending order.
+	fn test_gen_database_cells ( )
+	{
+		let stars     = stars();
+		let generator = GridDatabaseGenerator::gen_database(&stars, radius(), buffer(), 11);
+		assert_eq!(generator.cell_start.len(), 11 * 11 + 1);
+		assert_eq!(generator.cell_start[11 * 11], generator.cell_stars.len());
+		assert_eq!(generator.cell_stars.len(), generator.cells.len());
+
+		for cell in 0..(11 * 11)
+		{
+			let mut expected : Vec<usize> = Vec::new();
+			for i in 0..stars.len()
+			{
+				let pattern = &generator.cells[generator.pattern_start[i]..generator.pattern_start[i + 1]];
+				if pattern.contains(&cell) { expected.push(i); }
+			}
+			let start = generator.cell_start[cell];
+			let end   = generator.cell_start[cell + 1];
+			assert_eq!(generator.cell_stars[start..end].to_vec(), expected);
+		}
+	}
+
+	#[test]
+	// An empty catalogue should give an empty database.
+	fn test_gen_database_empty ( )
+	{
+		let generator = GridDatabaseGenerator::gen_database(&Vec::new(), radius(), buffer(), 3);
+		assert_eq!(generator.pattern_start, vec![0]);
+		assert_eq!(generator.cells.len(), 0);
+		assert_eq!(generator.cell_start, vec![0; 10]);
+		assert_eq!(generator.cell_stars.len(), 0);
+	}
+
+//										~ get_database ~										 //
+	#[test]
+	// The database should point to the generated data and find every star from its own pattern.
+	fn test_get_database ( )
+	{
+		let stars     = stars();
+		let generator = GridDatabaseGenerator::gen_database(&stars, radius(), buffer(), 11);
+		let database  = generator.get_database();
+		assert_eq!(database.radius, radius());
+		assert_eq!(database.buffer, buffer());
+		assert_eq!(database.grid_size, 11);
+		assert_eq!(database.pattern_start.size(), generator.pattern_start.len());
+		assert_eq!(database.cell_start.size(), generator.cell_start.len());
+		assert_eq!(database.catalogue.size(), stars.len());
+
+		for i in 0..6
+		{
+			let start   = generator.pattern_start[i];
+			let end     = generator.pattern_start[i + 1];
+			let pattern = generator.cells[start..end].to_vec();
+			assert_eq!(database.find_best(&pattern, 1), Some((i, pattern.len())));
+		}
+	}
+
+//										~ to_rust ~												 //
+	#[test]
+	// The output should read back as the same patterns and cells.
+	fn test_to_rust_round_trip ( )
+	{
+		let generator = GridDatabaseGenerator::gen_database(&stars(), radius(), buffer(), 11);
+		let output    = generator.to_rust("");
+
+		assert!(output.contains(&format!("radius:        Radians({}),", radius().0)));
+		assert!(output.contains(&format!("buffer:        Radians({}),", buffer().0)));
+		assert!(output.contains("grid_size:     11,"));
+		assert!(output.contains("catalogue:     &CATALOGUE,"));
+		assert!(output.contains(&format!("pub const GRID_CELLS: [usize; {}]", generator.cells.len())));
+		assert!(output.contains(&format!("pub const GRID_CELL_STARS: [usize; {}]", generator.cell_stars.len())));
+
+		assert_eq!(array(&output, "GRID_PATTERN_START"), generator.pattern_start);
+		assert_eq!(array(&output, "GRID_CELLS"),         generator.cells);
+		assert_eq!(array(&output, "GRID_CELL_START"),    generator.cell_start);
+		assert_eq!(array(&output, "GRID_CELL_STARS"),    generator.cell_stars);
+	}
+
+	#[test]
+	// The memory section should be placed on the cells and the stars of each cell.
+	fn test_to_rust_mem_section ( )
+	{
+		let generator = GridDatabaseGenerator::gen_database(&stars(), radius(), buffer(), 11);
+		let output    = generator.to_rust("#[link_section = \".cells\"]");
+		assert!(output.contains("#[link_section = \".cells\"]\npub const GRID_CELLS"));
+		assert!(output.contains("#[link_section = \".cells\"]\npub const GRID_CELL_STARS"));
+	}
+}
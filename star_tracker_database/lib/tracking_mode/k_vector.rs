@@ -23,8 +23,7 @@ impl KVectorGenerator for KVector
 	/// Too larger: Excessive space.
 	fn ideal_bins ( sorted_database: &Vec<StarDatabaseElement>, tolerance: Radians ) -> usize
 	{
-		let range = sorted_database[sorted_database.len() - 1].dist - sorted_database[0].dist;
-		return (range / tolerance).0.ceil() as usize;
+		return Self::ideal_bins_distance(&distances(sorted_database), tolerance);
 	}
 	
 	/// Creates a vector containging the bounds for each bin.
@@ -32,7 +31,22 @@ impl KVectorGenerator for KVector
 	/// * "sorted_database" - The database in sorted order to reference.
 	fn generate_bins ( &self, sorted_database: &Vec<StarDatabaseElement> ) -> Error<Vec<usize>>
 	{
-		if sorted_database.size() < 3
+		return self.generate_bins_distance(&distances(sorted_database));
+	}
+	
+	/// Finds the ideal number of bins for the kvector from the sorted distances.
+	fn ideal_bins_distance ( sorted_distances: &Vec<Radians>, tolerance: Radians ) -> usize
+	{
+		let range = sorted_distances[sorted_distances.len() - 1] - sorted_distances[0];
+		return (range / tolerance).0.ceil() as usize;
+	}
+	
+	/// Creates a vector containging the bounds for each bin from the sorted distances.
+	/// # Arguments
+	/// * "sorted_distances" - The distance of each element of the database in sorted order.
+	fn generate_bins_distance ( &self, sorted_distances: &Vec<Radians> ) -> Error<Vec<usize>>
+	{
+		if sorted_distances.size() < 3 || self.num_bins == 0
 		{
 			return Err(Errors::InvalidSize);
 		}
@@ -50,13 +64,13 @@ impl KVectorGenerator for KVector
 			{
 				jj = vec.get(ii - 1);
 			}
-			while sorted_database[jj].dist.0 < max_value
+			while sorted_distances[jj].0 < max_value
 			{
 				jj+=1;
 			}
 			vec.push(jj);
 		}
-		vec.push(sorted_database.size());
+		vec.push(sorted_distances.size());
 		return Ok(vec);
 	}
 	
@@ -85,6 +99,14 @@ impl KVectorGenerator for KVector
 
 
 
+/// The distance of each element of the database.
+fn distances ( sorted_database: &Vec<StarDatabaseElement> ) -> Vec<Radians>
+{
+	return sorted_database.iter().map(|element| element.dist).collect();
+}
+
+
+
 
 
 //###############################################################################################//
@@ -131,6 +153,8 @@ pub mod test
 //
 // fn ideal_bins ( sorted_database: &Vec<StarDatabaseElement>, tolerance: Radians ) -> usize
 // fn generate_bins ( &self, sorted_database: &Vec<StarDatabaseElement> ) -> Error<Vec<usize>>
+// fn ideal_bins_distance ( sorted_distances: &Vec<Radians>, tolerance: Radians ) -> usize
+// fn generate_bins_distance ( &self, sorted_distances: &Vec<Radians> ) -> Error<Vec<usize>>
 //
 //
 //###############################################################################################//
@@ -278,5 +302,47 @@ pub mod test
 
 
 
+//									~ ideal_bins_distance ~										 //
+	#[test]
+	// The distances should give the same number of bins as the database elements.
+	fn test_ideal_bins_distance ( )
+	{
+		let val = vec![1.0, 2.0, 3.0, 3.0, 4.0, 4.5, 5.0, 6.0, 6.0, 6.1, 6.2, 6.3, 10.0];
+		let lst = convert_dec_to_star_database_element(val.clone());
+		let distances : Vec<Radians> = val.iter().map(|v| Radians(*v)).collect();
+		assert_eq!(KVector::ideal_bins_distance(&distances, Radians(1.0)), 9);
+		assert_eq!(KVector::ideal_bins_distance(&distances, Radians(1.0)), KVector::ideal_bins(&lst, Radians(1.0)));
+	}
+
+
+
+//									~ generate_bins_distance ~									 //
+	#[test]
+	// The distances should give the same bins as the database elements.
+	fn test_generate_bins_distance ( )
+	{
+		let dec = vec![0.0, 0.0, 0.0, 1.0, 1.0, 2.0, 3.0, 5.0, 6.0, 10.0, 11.0, 27.0, 33.0, 33.0, 34.0];
+		let lst = convert_dec_to_star_database_element(dec.clone());
+		let distances : Vec<Radians> = dec.iter().map(|v| Radians(*v)).collect();
+
+		let kvec = KVector::new(5, dec[0] as Decimal, dec[14] as Decimal);
+		let vec  = kvec.generate_bins_distance(&distances).expect("Should not fail");
+		assert_eq!(vec, vec![0, 9, 11, 11, 15]);
+		assert_eq!(vec, kvec.generate_bins(&lst).expect("Should not fail"));
+	}
+
+	#[test]
+	// There must be at least 3 distances and 1 bin.
+	fn test_generate_bins_distance_failure ( )
+	{
+		let distances = vec![Radians(0.0), Radians(1.0), Radians(2.0)];
+		KVector::new(0, 0.0, 2.0).generate_bins_distance(&distances).expect_err("Should fail.");
+		KVector::new(1, 0.0, 2.0).generate_bins_distance(&distances[0..2].to_vec()).expect_err("Should fail.");
+		assert_eq!(KVector::new(1, 0.0, 2.0).generate_bins_distance(&distances), Ok(vec![3]));
+	}
+
+
+
+
 
 }
\ No newline at end of file
@@ -5,12 +5,16 @@ use star_tracker_lib::util::units::Radians;
 use star_tracker_lib::util::err::Error;
 
 use star_tracker_lib::tracking_mode::StarPair;
+use star_tracker_lib::tracking_mode::StarTriangle;
 use star_tracker_lib::tracking_mode::database::KVector;
 
 // use star_tracker_lib::tracking_mode::database::Database;
 
 pub mod star_database_element;
 pub mod database_generator;
+pub mod triangle_database_generator;
+pub mod grid_database_generator;
+pub mod tiled_database_generator;
 pub mod k_vector;
 
 
@@ -21,6 +25,10 @@ pub trait KVectorGenerator
 {
 	fn ideal_bins ( sorted_database: &Vec<StarDatabaseElement>, tolerance: Radians ) -> usize;
 	fn generate_bins ( &self, sorted_database: &Vec<StarDatabaseElement> ) ->Error<Vec<usize>>;
+	/// The same as ideal_bins for a database which is not made of star pairs (e.g. triangles).
+	fn ideal_bins_distance ( sorted_distances: &Vec<Radians>, tolerance: Radians ) -> usize;
+	/// The same as generate_bins for a database which is not made of star pairs (e.g. triangles).
+	fn generate_bins_distance ( &self, sorted_distances: &Vec<Radians> ) -> Error<Vec<usize>>;
 	fn display ( &self ) -> String;
 }
 
@@ -52,3 +60,63 @@ pub struct DatabaseGenerator
 	k_lookup: KVector,
 }
 
+
+
+/// Tool to construct a [TriangleDatabase](star_tracker_lib::tracking_mode::database::TriangleDatabase).  
+/// The triangles are sorted by their smallest interior angle so they can be looked up without knowing the focal length.
+pub struct TriangleDatabaseGenerator
+{
+	/// The bins of the k-vector (indexes of `triangles`).
+	pub k_vector  : Vec<usize>,
+	/// Every triangle which fits in the field of view, ordered by (smallest, middle, largest angle).
+	pub triangles : Vec<StarTriangle<usize>>,
+	/// The stars the triangles point to.
+	pub catalogue : Vec<Equatorial>,
+
+	/// The field of view used when generating the database.
+	/// This is the widest a side of a triangle can be.
+	fov : Radians,
+	k_lookup: KVector,
+}
+
+
+
+/// Tool to construct a [GridDatabase](star_tracker_lib::tracking_mode::database::GridDatabase).  
+/// The grid pattern of every star is stored so it can be compared with the pattern of an observed star.
+pub struct GridDatabaseGenerator
+{
+	/// The index in `cells` where the pattern of each star starts (with the size of `cells` at the end).
+	pub pattern_start : Vec<usize>,
+	/// The occupied cells of every pattern.
+	pub cells         : Vec<usize>,
+	/// The index in `cell_stars` where the stars of each cell start (with the size of `cell_stars` at the end).
+	pub cell_start    : Vec<usize>,
+	/// The stars with each cell in their pattern.
+	pub cell_stars    : Vec<usize>,
+	/// The stars the patterns belong to.
+	pub catalogue     : Vec<Equatorial>,
+
+	/// The max angle from a star to a neighbour in its pattern.
+	radius    : Radians,
+	/// Neighbours closer than this are not in the pattern.
+	buffer    : Radians,
+	/// The number of cells on each side of the grid.
+	grid_size : usize,
+}
+
+
+
+/// Tool to construct a [TiledDatabase](star_tracker_lib::tracking_mode::database::TiledDatabase).  
+/// The sky is split into tiles from a Fibonacci lattice and the pairs of a database are sorted into each tile they fit in.
+pub struct TiledDatabaseGenerator
+{
+	/// The center of each tile.
+	pub tiles      : Vec<Equatorial>,
+	/// The index in `tile_pairs` where each tile starts (with the size of `tile_pairs` at the end).
+	pub tile_start : Vec<usize>,
+	/// The pairs (index of the database pairs) of every tile.
+	pub tile_pairs : Vec<usize>,
+
+	/// The max angle between a tile and the stars stored in it.
+	reach : Radians,
+}
@@ -0,0 +1,313 @@
+//! Implementation for [TiledDatabaseGenerator](crate::tracking_mode::TiledDatabaseGenerator).
+use super::TiledDatabaseGenerator;
+
+use star_tracker_lib::tracking_mode::database::TiledDatabase;
+use star_tracker_lib::tracking_mode::database::Database;
+
+use star_tracker_lib::util::units::Equatorial;
+use star_tracker_lib::util::units::Radians;
+
+use crate::distribution::Distribute;
+
+
+
+impl TiledDatabaseGenerator
+{
+	/// Returns the TiledDatabase of `database`.
+	/// Call gen_database with the same database before this.
+	pub fn get_database <'a> ( &'a self, database: &'a dyn Database ) -> TiledDatabase<'a>
+	{
+		return TiledDatabase {
+			database  : database,
+			reach     : self.reach,
+			tiles     : &self.tiles,
+			tile_start: &self.tile_start,
+			tile_pairs: &self.tile_pairs,
+		};
+	}
+
+
+
+	/// Sorts the pairs of a database into tiles on the heap.
+	/// A pair is in a tile if both stars are within the tile separation + 1/2 `fov` of the tile.
+	/// Wherever the camera points, the closest tile is less than the separation away, so every pair in the image is in that tile.
+	/// # Arguments
+	/// * `database`   - The database to split, this should be generated by `DatabaseGenerator`.
+	/// * `separation` - The angle between neighbouring tiles, the smaller the angle the more tiles and memory.
+	/// * `fov`        - The diagonal field of view of the sensor.
+	/// # Returns
+	/// The generator containing the tiles.
+	pub fn gen_database ( database: &dyn Database, separation: Radians, fov: Radians ) -> Self
+	{
+		let tiles : Vec<Equatorial> = Distribute::fibonacci_lattice(Distribute::angle_to_points(separation));
+		let reach = Distribute::points_to_angle(tiles.len()) + fov / 2.0;
+
+		let mut tile_start : Vec<usize> = Vec::with_capacity(tiles.len() + 1);
+		let mut tile_pairs : Vec<usize> = Vec::new();
+		for i in 0..tiles.len()
+		{
+			// Finding the stars in the tile first is faster than checking both stars of every pair.
+			let mut in_tile : Vec<bool> = Vec::with_capacity(database.get_catalogue_size());
+			for j in 0..database.get_catalogue_size()
+			{
+				in_tile.push(tiles[i].angle_distance(database.get_catalogue(j)) <= reach);
+			}
+
+			tile_start.push(tile_pairs.len());
+			for j in 0..database.get_pairs_size()
+			{
+				let pair = database.get_pairs(j);
+				if in_tile[pair.0] && in_tile[pair.1] { tile_pairs.push(j); }
+			}
+		}
+		tile_start.push(tile_pairs.len());
+
+		return Self
+		{
+			tiles:      tiles,
+			tile_start: tile_start,
+			tile_pairs: tile_pairs,
+			reach:      reach,
+		};
+	}
+
+
+
+	/// Writes the tiles as rust source (`TILED`, `TILES`, `TILE_START` and `TILE_PAIRS`).
+	/// The output expects `DATABASE` to be the database the tiles were generated from.
+	/// # Arguments
+	/// * `mem_section_pairs` - The `link_section` attribute of `TILE_PAIRS` (empty for none).
+	pub fn to_rust ( &self, mem_section_pairs: &str ) -> String
+	{
+		let mut tiles_str = String::with_capacity(self.tiles.len() * 50);
+		for tile in &self.tiles
+		{
+			tiles_str.push_str(&format!("\tEquatorial{{ra: Radians({}), dec: Radians({})}},\n", tile.ra.0, tile.dec.0));
+		}
+		let mut tile_start_str = String::with_capacity(self.tile_start.len() * 8);
+		for start in &self.tile_start { tile_start_str.push_str(&format!("\t{},\n", start)); }
+		let mut tile_pairs_str = String::with_capacity(self.tile_pairs.len() * 8);
+		for pair in &self.tile_pairs { tile_pairs_str.push_str(&format!("\t{},\n", pair)); }
+
+		return format!(r#"
+pub const TILED: TiledDatabase = TiledDatabase
+{{
+	database:   &DATABASE,
+	reach:      Radians({}),
+	tiles:      &TILES,
+	tile_start: &TILE_START,
+	tile_pairs: &TILE_PAIRS,
+}};
+
+pub const TILES: [Equatorial; {}] =
+[
+{}
+];
+
+pub const TILE_START: [usize; {}] =
+[
+{}
+];
+
+{}
+pub const TILE_PAIRS: [usize; {}] =
+[
+{}
+];
+"#,
+		self.reach.0,
+		self.tiles.len(), tiles_str,
+		self.tile_start.len(), tile_start_str,
+		mem_section_pairs, self.tile_pairs.len(), tile_pairs_str);
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+mod test
+{
+	use star_tracker_lib::tracking_mode::database::Database;
+
+	use star_tracker_lib::util::units::Equatorial;
+	use star_tracker_lib::util::units::Degrees;
+	use star_tracker_lib::util::units::Radians;
+
+	use crate::tracking_mode::TiledDatabaseGenerator;
+	use crate::tracking_mode::DatabaseGenerator;
+	use crate::distribution::Distribute;
+	use crate::io::Star;
+
+	// A patch of stars with irregular spacing and a star on the other side of the sky.
+	fn stars ( ) -> Vec<Star>
+	{
+		let offsets = [(0.0, 0.0), (1.3, 0.4), (-0.8, 1.9), (2.1, -1.7), (-2.6, -0.9), (0.5, 2.8), (180.0, 0.0)];
+		let mut stars = Vec::new();
+		for (ra, dec) in offsets
+		{
+			stars.push(Star::at(Equatorial{ra: Degrees(100.0 + ra).as_radians(), dec: Degrees(10.0 + dec).as_radians()}));
+		}
+		return stars;
+	}
+
+	fn fov        ( ) -> Radians { return Degrees(10.0).as_radians(); }
+	fn separation ( ) -> Radians { return Degrees(20.0).as_radians(); }
+
+	// The values of a generated array, one element per line.
+	fn array ( output: &str, name: &str ) -> Vec<String>
+	{
+		let start = output.find(&format!("pub const {}: [", name)).expect("The array should be output.");
+		let body  = &output[start..];
+		let open  = body.find("\n[\n").unwrap() + 3;
+		let close = body.find("\n];").unwrap();
+		return body[open..close].lines()
+			.map(|line| line.trim().trim_end_matches(',').to_string())
+			.filter(|line| !line.is_empty()).collect();
+	}
+
+
+//###############################################################################################//
+//
+//										TiledDatabaseGenerator
+//
+// pub fn get_database ( &self, &dyn Database ) -> TiledDatabase
+// pub fn gen_database ( &dyn Database, Radians, Radians ) -> Self
+// pub fn to_rust      ( &self, &str ) -> String
+//
+//###############################################################################################//
+//										~ get_database ~										 //
+	#[test]
+	fn test_get_database ( )
+	{
+		let gen       = DatabaseGenerator::gen_database(&stars(), fov(), fov(), Degrees(0.1).as_radians());
+		let database  = gen.get_database();
+		let generator = TiledDatabaseGenerator::gen_database(&database, separation(), fov());
+		let tiled     = generator.get_database(&database);
+		assert_eq!(tiled.reach, generator.reach);
+		assert_eq!(tiled.tiles.size(), generator.tiles.len());
+		assert_eq!(tiled.tile_start.size(), generator.tile_start.len());
+		assert_eq!(tiled.tile_pairs.size(), generator.tile_pairs.len());
+	}
+
+//										~ gen_database ~										 //
+	#[test]
+	// The tiles should be a fibonacci lattice reaching the separation and half the field of view.
+	fn test_gen_database_tiles ( )
+	{
+		let gen       = DatabaseGenerator::gen_database(&stars(), fov(), fov(), Degrees(0.1).as_radians());
+		let database  = gen.get_database();
+		let generator = TiledDatabaseGenerator::gen_database(&database, separation(), fov());
+		let tiles     = Distribute::fibonacci_lattice(Distribute::angle_to_points(separation()));
+		assert_eq!(generator.tiles, tiles);
+		assert_eq!(generator.reach, Distribute::points_to_angle(tiles.len()) + fov() / 2.0);
+		assert_eq!(generator.tile_start.len(), tiles.len() + 1);
+		assert_eq!(generator.tile_start[0], 0);
+		assert_eq!(generator.tile_start[tiles.len()], generator.tile_pairs.len());
+	}
+
+	#[test]
+	// Each tile should have every pair with both stars within reach in ascending order.
+	fn test_gen_database_pairs ( )
+	{
+		let gen       = DatabaseGenerator::gen_database(&stars(), fov(), fov(), Degrees(0.1).as_radians());
+		let database  = gen.get_database();
+		let generator = TiledDatabaseGenerator::gen_database(&database, separation(), fov());
+		assert!(0 < database.get_pairs_size());
+		assert!(0 < generator.tile_pairs.len());
+
+		for i in 0..generator.tiles.len()
+		{
+			let mut expected : Vec<usize> = Vec::new();
+			for j in 0..database.get_pairs_size()
+			{
+				let pair = database.get_pairs(j);
+				let a = generator.tiles[i].angle_distance(database.get_catalogue(pair.0));
+				let b = generator.tiles[i].angle_distance(database.get_catalogue(pair.1));
+				if a <= generator.reach && b <= generator.reach { expected.push(j); }
+			}
+			let start = generator.tile_start[i];
+			let end   = generator.tile_start[i + 1];
+			assert_eq!(generator.tile_pairs[start..end].to_vec(), expected);
+		}
+	}
+
+	#[test]
+	// Wherever the camera points, the closest tile should have every pair in the image.
+	fn test_gen_database_closest_tile ( )
+	{
+		let gen       = DatabaseGenerator::gen_database(&stars(), fov(), fov(), Degrees(0.1).as_radians());
+		let database  = gen.get_database();
+		let generator = TiledDatabaseGenerator::gen_database(&database, separation(), fov());
+
+		let boresights = Distribute::fibonacci_lattice(500);
+		for boresight in boresights
+		{
+			let mut closest = 0;
+			for i in 0..generator.tiles.len()
+			{
+				if boresight.angle_distance(generator.tiles[i]) < boresight.angle_distance(generator.tiles[closest])
+				{
+					closest = i;
+				}
+			}
+			let tile = &generator.tile_pairs[generator.tile_start[closest]..generator.tile_start[closest + 1]];
+
+			for j in 0..database.get_pairs_size()
+			{
+				let pair = database.get_pairs(j);
+				let a = boresight.angle_distance(database.get_catalogue(pair.0));
+				let b = boresight.angle_distance(database.get_catalogue(pair.1));
+				if a <= fov() / 2.0 && b <= fov() / 2.0 { assert!(tile.contains(&j)); }
+			}
+		}
+	}
+
+//										~ to_rust ~												 //
+	#[test]
+	// The output should read back as the same tiles, starts and pairs.
+	fn test_to_rust_round_trip ( )
+	{
+		let gen       = DatabaseGenerator::gen_database(&stars(), fov(), fov(), Degrees(0.1).as_radians());
+		let database  = gen.get_database();
+		let generator = TiledDatabaseGenerator::gen_database(&database, separation(), fov());
+		let output    = generator.to_rust("");
+
+		assert!(output.contains(&format!("reach:      Radians({}),", generator.reach.0)));
+		assert!(output.contains(&format!("pub const TILES: [Equatorial; {}]", generator.tiles.len())));
+		assert!(output.contains(&format!("pub const TILE_START: [usize; {}]", generator.tile_start.len())));
+		assert!(output.contains(&format!("pub const TILE_PAIRS: [usize; {}]", generator.tile_pairs.len())));
+
+		let tiles : Vec<Equatorial> = array(&output, "TILES").iter().map(|tile|
+		{
+			let values : Vec<f64> = tile
+				.trim_start_matches("Equatorial{ra: Radians(").trim_end_matches(")}")
+				.split("), dec: Radians(").map(|v| v.parse().unwrap()).collect();
+			return Equatorial{ra: Radians(values[0]), dec: Radians(values[1])};
+		}).collect();
+		assert_eq!(tiles, generator.tiles);
+
+		let tile_start : Vec<usize> = array(&output, "TILE_START").iter().map(|v| v.parse().unwrap()).collect();
+		let tile_pairs : Vec<usize> = array(&output, "TILE_PAIRS").iter().map(|v| v.parse().unwrap()).collect();
+		assert_eq!(tile_start, generator.tile_start);
+		assert_eq!(tile_pairs, generator.tile_pairs);
+	}
+
+	#[test]
+	// The memory section should be placed on the pairs.
+	fn test_to_rust_mem_section ( )
+	{
+		let gen       = DatabaseGenerator::gen_database(&stars(), fov(), fov(), Degrees(0.1).as_radians());
+		let database  = gen.get_database();
+		let generator = TiledDatabaseGenerator::gen_database(&database, separation(), fov());
+		let output    = generator.to_rust("#[link_section = \".pairs\"]");
+		assert!(output.contains("#[link_section = \".pairs\"]\npub const TILE_PAIRS"));
+	}
+}
@@ -0,0 +1,338 @@
+//! Implementation for [TriangleDatabaseGenerator](crate::tracking_mode::TriangleDatabaseGenerator).
+use super::TriangleDatabaseGenerator;
+
+use star_tracker_lib::tracking_mode::StarTriangle;
+use star_tracker_lib::tracking_mode::TriangleAngles;
+use star_tracker_lib::tracking_mode::database::TriangleDatabase;
+use star_tracker_lib::tracking_mode::database::KVector;
+
+use star_tracker_lib::util::aliases::Decimal;
+use star_tracker_lib::util::units::Equatorial;
+use star_tracker_lib::util::units::Radians;
+use star_tracker_lib::util::err::Errors;
+use star_tracker_lib::util::err::Error;
+
+use crate::io::Star;
+use crate::tracking_mode::KVectorGenerator;
+
+
+
+impl TriangleDatabaseGenerator
+{
+	/// Returns the TriangleDatabase.
+	/// Call gen_database before this.
+	pub fn get_database ( &self ) -> TriangleDatabase
+	{
+		return TriangleDatabase {
+			fov      : self.fov,
+			k_lookup : self.k_lookup,
+			k_vector : &self.k_vector,
+			triangles: &self.triangles,
+			catalogue: &self.catalogue,
+		};
+	}
+
+
+
+	/// Creates a triangle database on the heap.
+	/// Every triangle where each side is smaller than `fov` is inserted.
+	/// # Arguments
+	/// * `stars`     - The stars to be inserted into the database.
+	/// * `fov`       - The max angular separation of any side of a triangle.
+	/// * `tolerance` - The allowed error of an interior angle until it is not considered a match.
+	/// # Returns
+	/// The generator containing the database.  
+	/// Err(Errors::InvalidSize) if less than 3 triangles fit in `fov` (increase the cutoff magnitude or `fov`).
+	pub fn gen_database ( stars: &Vec<Star>, fov: Radians, tolerance: Radians ) -> Error<Self>
+	{
+		let mut catalogue : Vec<Equatorial> = Vec::new();
+		for i in 0..stars.len() { catalogue.push(stars[i].pos); }
+
+		// The neighbours of each star which have a higher index (so each triangle is only added once).
+		let mut neighbours : Vec<Vec<usize>> = Vec::with_capacity(catalogue.len());
+		for i in 0..catalogue.len()
+		{
+			let mut close = Vec::new();
+			for j in (i + 1)..catalogue.len()
+			{
+				if catalogue[i].angle_distance(catalogue[j]) < fov { close.push(j); }
+			}
+			neighbours.push(close);
+		}
+
+		let mut unsorted : Vec<(Radians, StarTriangle<usize>)> = Vec::new();
+		for i in 0..catalogue.len()
+		{
+			for jj in 0..neighbours[i].len()
+			{
+				for kk in (jj + 1)..neighbours[i].len()
+				{
+					let j = neighbours[i][jj];
+					let k = neighbours[i][kk];
+					if catalogue[j].angle_distance(catalogue[k]) < fov
+					{
+						let points = StarTriangle(catalogue[i], catalogue[j], catalogue[k]);
+						let (angles, order) = TriangleAngles::from_equatorial(points);
+						unsorted.push((angles.smallest, TriangleAngles::reorder(StarTriangle(i, j, k), order)));
+					}
+				}
+			}
+		}
+		unsorted.sort_by(|a, b| a.0.0.partial_cmp(&b.0.0).expect("Angles are valid."));
+
+		// The k-vector is indexed by the smallest angle.
+		let mut triangles : Vec<StarTriangle<usize>> = Vec::with_capacity(unsorted.len());
+		let mut distances : Vec<Radians>             = Vec::with_capacity(unsorted.len());
+		for i in 0..unsorted.len()
+		{
+			triangles.push(unsorted[i].1);
+			distances.push(unsorted[i].0);
+		}
+
+		// A k-vector needs a range of values.
+		if distances.len() < 3
+		{
+			return Err(Errors::InvalidSize);
+		}
+
+		let num_bins = std::cmp::max(1, KVector::ideal_bins_distance(&distances, tolerance));
+		let k_lookup = KVector::new(num_bins, distances[0].0 as Decimal,
+											distances[distances.len() - 1].0 as Decimal);
+
+		let k_vector = k_lookup.generate_bins_distance(&distances)?;
+
+		return Ok(Self
+		{
+			k_vector:  k_vector,
+			triangles: triangles,
+			catalogue: catalogue,
+			fov:       fov,
+			k_lookup:  k_lookup,
+		});
+	}
+
+
+
+	/// Writes the database as rust source (`TRIANGLE_DATABASE`, `TRIANGLE_K_LOOKUP`, `TRIANGLE_K_VECTOR` and `TRIANGLES`).
+	/// The output expects `CATALOGUE` to be the stars the triangles were generated from.
+	/// # Arguments
+	/// * `mem_section_triangles` - The `link_section` attribute of `TRIANGLES` (empty for none).
+	pub fn to_rust ( &self, mem_section_triangles: &str ) -> String
+	{
+		let mut k_vector_str = String::with_capacity(self.k_vector.len() * 8);
+		for bin in &self.k_vector { k_vector_str.push_str(&format!("\t{},\n", bin)); }
+		let mut triangles_str = String::with_capacity(self.triangles.len() * 30);
+		for triangle in &self.triangles
+		{
+			triangles_str.push_str(&format!("\tStarTriangle({}, {}, {}),\n", triangle.0, triangle.1, triangle.2));
+		}
+
+		return format!(r#"
+pub const TRIANGLE_DATABASE: TriangleDatabase = TriangleDatabase
+{{
+	fov:       Radians({}),
+	k_lookup:  TRIANGLE_K_LOOKUP,
+	k_vector:  &TRIANGLE_K_VECTOR,
+	triangles: &TRIANGLES,
+	catalogue: &CATALOGUE,
+}};
+
+pub const TRIANGLE_K_LOOKUP: KVector = KVector
+{{
+	gradient:  {},
+	intercept: {},
+	min_value: Radians({}),
+	max_value: Radians({}),
+	num_bins:  {},
+}};
+
+pub const TRIANGLE_K_VECTOR: [usize; {}] =
+[
+{}
+];
+
+{}
+pub const TRIANGLES: [StarTriangle<usize>; {}] =
+[
+{}
+];
+"#,
+		self.fov.0,
+		self.k_lookup.gradient, self.k_lookup.intercept,
+		self.k_lookup.min_value.0, self.k_lookup.max_value.0, self.k_lookup.num_bins,
+		self.k_vector.len(), k_vector_str,
+		mem_section_triangles, self.triangles.len(), triangles_str);
+	}
+}
+
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+mod test
+{
+	use star_tracker_lib::tracking_mode::StarTriangle;
+	use star_tracker_lib::tracking_mode::TriangleAngles;
+
+	use star_tracker_lib::util::units::Equatorial;
+	use star_tracker_lib::util::units::Degrees;
+	use star_tracker_lib::util::units::Radians;
+	use star_tracker_lib::util::err::Errors;
+
+	use crate::tracking_mode::TriangleDatabaseGenerator;
+	use crate::io::Star;
+
+	// A patch of stars with irregular spacing and a star too far away to be in a triangle.
+	fn stars ( ) -> Vec<Star>
+	{
+		let offsets = [(0.0, 0.0), (1.3, 0.4), (-0.8, 1.9), (2.1, -1.7), (-2.6, -0.9), (60.0, 0.0)];
+		let mut stars = Vec::new();
+		for (ra, dec) in offsets
+		{
+			stars.push(Star::at(Equatorial{ra: Degrees(100.0 + ra).as_radians(), dec: Degrees(10.0 + dec).as_radians()}));
+		}
+		return stars;
+	}
+
+	fn fov       ( ) -> Radians { return Degrees(10.0).as_radians(); }
+	fn tolerance ( ) -> Radians { return Degrees(0.1).as_radians(); }
+
+	// The values of a generated array, one element per line.
+	fn array ( output: &str, name: &str ) -> Vec<String>
+	{
+		let start = output.find(&format!("pub const {}: [", name)).expect("The array should be output.");
+		let body  = &output[start..];
+		let open  = body.find("\n[\n").unwrap() + 3;
+		let close = body.find("\n];").unwrap();
+		return body[open..close].lines()
+			.map(|line| line.trim().trim_end_matches(',').to_string())
+			.filter(|line| !line.is_empty()).collect();
+	}
+
+
+//###############################################################################################//
+//
+//										TriangleDatabaseGenerator
+//
+// pub fn get_database ( &self ) -> TriangleDatabase
+// pub fn gen_database ( &Vec<Star>, Radians, Radians ) -> Error<Self>
+// pub fn to_rust      ( &self, &str ) -> String
+//
+//###############################################################################################//
+//										~ gen_database ~										 //
+	#[test]
+	// Every triangle in the field of view should be added once, sorted by the smallest angle.
+	fn test_gen_database ( )
+	{
+		let generator = TriangleDatabaseGenerator::gen_database(&stars(), fov(), tolerance()).unwrap();
+		assert_eq!(generator.triangles.len(), 10);
+		assert_eq!(generator.catalogue.len(), 6);
+
+		let mut previous = Radians(0.0);
+		for i in 0..generator.triangles.len()
+		{
+			let triangle = generator.triangles[i];
+			assert!(triangle.0 != 5 && triangle.1 != 5 && triangle.2 != 5);
+
+			// The vertices are ordered (smallest, middle, largest angle).
+			let points = StarTriangle(
+				generator.catalogue[triangle.0], generator.catalogue[triangle.1], generator.catalogue[triangle.2]);
+			let (angles, order) = TriangleAngles::from_equatorial(points);
+			assert_eq!(order, StarTriangle(0, 1, 2));
+			assert!(previous <= angles.smallest);
+			previous = angles.smallest;
+		}
+	}
+
+	#[test]
+	// The k-vector should cover every triangle.
+	fn test_gen_database_k_vector ( )
+	{
+		let generator = TriangleDatabaseGenerator::gen_database(&stars(), fov(), tolerance()).unwrap();
+		assert_eq!(generator.k_vector.len(), generator.k_lookup.num_bins);
+		assert_eq!(generator.k_vector[generator.k_vector.len() - 1], generator.triangles.len());
+		for i in 1..generator.k_vector.len()
+		{
+			assert!(generator.k_vector[i - 1] <= generator.k_vector[i]);
+		}
+	}
+
+	#[test]
+	// If there are not enough triangles for a k-vector, an error is returned instead of panicking.
+	fn test_gen_database_too_few ( )
+	{
+		let stars = stars();
+		assert_eq!(TriangleDatabaseGenerator::gen_database(&Vec::new(), fov(), tolerance()).err(),
+			Some(Errors::InvalidSize));
+		assert_eq!(TriangleDatabaseGenerator::gen_database(&stars[0..3].to_vec(), fov(), tolerance()).err(),
+			Some(Errors::InvalidSize));
+		assert_eq!(TriangleDatabaseGenerator::gen_database(&stars, Degrees(0.1).as_radians(), tolerance()).err(),
+			Some(Errors::InvalidSize));
+	}
+
+//										~ get_database ~										 //
+	#[test]
+	// Every triangle should be found from its own angles.
+	fn test_get_database ( )
+	{
+		let generator = TriangleDatabaseGenerator::gen_database(&stars(), fov(), tolerance()).unwrap();
+		let database  = generator.get_database();
+		assert_eq!(database.fov, fov());
+
+		for i in 0..generator.triangles.len()
+		{
+			let triangle = generator.triangles[i];
+			let mut found : Vec<StarTriangle<usize>> = Vec::new();
+			database.find_close_ref(database.angles(triangle), Degrees(0.01).as_radians(), &mut found);
+			assert!(found.contains(&triangle));
+		}
+	}
+
+//										~ to_rust ~												 //
+	#[test]
+	// The output should read back as the same k-vector and triangles.
+	fn test_to_rust_round_trip ( )
+	{
+		let generator = TriangleDatabaseGenerator::gen_database(&stars(), fov(), tolerance()).unwrap();
+		let output    = generator.to_rust("");
+
+		assert!(output.contains(&format!("fov:       Radians({}),", fov().0)));
+		assert!(output.contains(&format!("gradient:  {},", generator.k_lookup.gradient)));
+		assert!(output.contains(&format!("intercept: {},", generator.k_lookup.intercept)));
+		assert!(output.contains(&format!("min_value: Radians({}),", generator.k_lookup.min_value.0)));
+		assert!(output.contains(&format!("max_value: Radians({}),", generator.k_lookup.max_value.0)));
+		assert!(output.contains(&format!("num_bins:  {},", generator.k_lookup.num_bins)));
+		assert!(output.contains("catalogue: &CATALOGUE,"));
+		assert!(output.contains(&format!("pub const TRIANGLE_K_VECTOR: [usize; {}]", generator.k_vector.len())));
+		assert!(output.contains(&format!("pub const TRIANGLES: [StarTriangle<usize>; {}]", generator.triangles.len())));
+
+		let k_vector : Vec<usize> = array(&output, "TRIANGLE_K_VECTOR").iter().map(|v| v.parse().unwrap()).collect();
+		assert_eq!(k_vector, generator.k_vector);
+
+		let triangles : Vec<StarTriangle<usize>> = array(&output, "TRIANGLES").iter().map(|triangle|
+		{
+			let values : Vec<usize> = triangle
+				.trim_start_matches("StarTriangle(").trim_end_matches(")")
+				.split(", ").map(|v| v.parse().unwrap()).collect();
+			return StarTriangle(values[0], values[1], values[2]);
+		}).collect();
+		assert_eq!(triangles, generator.triangles);
+	}
+
+	#[test]
+	// The memory section should be placed on the triangles.
+	fn test_to_rust_mem_section ( )
+	{
+		let generator = TriangleDatabaseGenerator::gen_database(&stars(), fov(), tolerance()).unwrap();
+		let output    = generator.to_rust("#[link_section = \".triangles\"]");
+		assert!(output.contains("#[link_section = \".triangles\"]\npub const TRIANGLES"));
+	}
+}
@@ -6,8 +6,11 @@ use star_tracker_lib::util::aliases::Decimal;
 use star_tracker_lib::util::units::Radians;
 use star_tracker_lib::util::units::Degrees;
 use star_tracker_lib::util::list::List;
+use star_tracker_lib::ephemeris::BodyMask;
+use star_tracker_lib::ephemeris::Epoch;
 
 use star_tracker_database::tracking_mode::DatabaseGenerator;
+use star_tracker_database::tracking_mode::TriangleDatabaseGenerator;
 use star_tracker_database::io::Star;
 use star_tracker_database::io::Io;
 
@@ -75,6 +78,10 @@ OPTIONAL:
 		The dullest star brightness allowed.
 		By having this too high, the database will be bigger, if the magnitude is too low, you wont have enough coverage.
 		The default value is calculated based on the input field of view, you probably should just use that.
+
+	triangle_database:
+		If true, every triangle which fits in the field of view is also output (`triangle_k_lookup`, `triangle_k_vector` and `triangles`).
+		The triangles index the same catalogue as the pairs.
 	
 	
 	
@@ -126,6 +133,18 @@ cargo run --bin gen_database{{path to output}} {{path to config file}}
 	
 	let mut region_num: usize = 8;
 	if let Some(val) = json.get("region_num_stars") { region_num = val.as_i64().expect("INVALID TYPE IN LOG `region_num_stars`") as usize; }
+
+	let mut bodies_margin: Radians = Degrees(0.5).to_radians();
+	if let Some(val) = json.get("exclude_bodies_margin_deg")
+	{ bodies_margin = Degrees(val.as_f64().expect("INVALID TYPE IN LOG `exclude_bodies_margin_deg`") as Decimal).to_radians(); }
+
+	let mut bodies: Option<BodyMask> = None;
+	if let Some(val) = json.get("exclude_bodies_unix")
+	{ bodies = Some(BodyMask::new(Epoch::from_unix(val.as_i64().expect("INVALID TYPE IN LOG `exclude_bodies_unix`")), bodies_margin)); }
+	
+	let mut triangle_database: bool = false;
+	if let Some(val) = json.get("triangle_database")
+	{ triangle_database = val.as_bool().expect("INVALID TYPE IN LOG `triangle_database`"); }
 	
 	std::mem::drop(file_config);
 
@@ -157,14 +176,28 @@ cargo run --bin gen_database{{path to output}} {{path to config file}}
 	let stars_limit_mag    = DatabaseGenerator::limit_magnitude (&stars, magnitude_min, magnitude_max);
 	println!("\tLimiting Double Stars.");
 	let stars_limit_double = DatabaseGenerator::limit_double_stars(&stars_limit_mag, double_star_tolerance);
+	let mut stars_limit_bodies = stars_limit_double;
+	if let Some(bodies) = bodies
+	{
+		println!("\tExcluding Sun, Moon and Planets.");
+		stars_limit_bodies = DatabaseGenerator::limit_bodies(&stars_limit_bodies, &bodies);
+	}
 	println!("\tLimiting Regions.");
-	let stars_limit_reg    = DatabaseGenerator::limit_regions(&stars_limit_double, region_size, region_num);
+	let stars_limit_reg    = DatabaseGenerator::limit_regions(&stars_limit_bodies, region_size, region_num);
 
 	println!("\tCreating Database.");	
 
 	let gen : DatabaseGenerator = DatabaseGenerator::gen_database(&stars_limit_reg, fov, fov / 1.3, angle_tolerance);
 	let database = gen.get_database();
 
+	let mut triangles : Option<TriangleDatabaseGenerator> = None;
+	if triangle_database
+	{
+		println!("\tCreating Triangles.");
+		triangles = Some(TriangleDatabaseGenerator::gen_database(&stars_limit_reg, fov, angle_tolerance)
+			.expect("Not enough triangles fit in the field of view, increase `magnitude_max`."));
+	}
+
 
 
 
@@ -221,6 +254,54 @@ cargo run --bin gen_database{{path to output}} {{path to config file}}
 
 
 
+	// The triangles are only output if requested.
+	let mut triangles_str = String::new();
+	if let Some(triangles) = &triangles
+	{
+		let triangle_database = triangles.get_database();
+		println!("Triangles: {} elements   \t {} B at 32bit \t {} B at 64bit",
+			triangle_database.triangles.size(), triangle_database.triangles.size() * 12, triangle_database.triangles.size() * 24);
+
+		let mut triangle_k_vector_str = String::with_capacity(triangle_database.k_vector.size() * 5);
+		for i in 0..triangle_database.k_vector.size() - 1
+		{
+			triangle_k_vector_str.push_str(&format!("\t{},\n", triangle_database.k_vector.get(i)));
+		}
+		triangle_k_vector_str.push_str(&format!("\t{}", triangle_database.k_vector.get(triangle_database.k_vector.size() - 1)));
+
+		let mut triangle_str = String::with_capacity(triangle_database.triangles.size() * 20);
+		for i in 0..triangle_database.triangles.size()
+		{
+			let triangle = triangle_database.triangles.get(i);
+			triangle_str.push_str(&format!("\t[{}, {}, {}]", triangle.0, triangle.1, triangle.2));
+			if i + 1 < triangle_database.triangles.size() { triangle_str.push_str(",\n"); }
+		}
+
+		triangles_str = format!(r#",
+
+"triangle_k_lookup": {{
+    "gradient":  {},
+    "intercept": {},
+    "min_value_rad": {},
+    "max_value_rad": {},
+    "num_bins": {}
+}},
+
+"triangle_k_vector": [
+{}
+],
+
+"triangles": [
+{}
+]"#,
+		triangle_database.k_lookup.gradient, triangle_database.k_lookup.intercept,
+		triangle_database.k_lookup.min_value.0, triangle_database.k_lookup.max_value.0, triangle_database.k_lookup.num_bins,
+		triangle_k_vector_str,
+		triangle_str);
+	}
+
+
+
 	// Outputs database. 
 	let output = format!(r#"
 {{
@@ -250,7 +331,7 @@ cargo run --bin gen_database{{path to output}} {{path to config file}}
 
 "catalogue": [
 {}
-]
+]{}
 }}	
 	"#, 
 	angle_tolerance.to_degrees().0, magnitude_max, region_size.to_degrees().0, region_num, fov.to_degrees().0, fov.0,
@@ -259,7 +340,8 @@ cargo run --bin gen_database{{path to output}} {{path to config file}}
 	
 	k_vector_str,
 	pairs_str,
-	catalog_str
+	catalog_str,
+	triangles_str
 );
 
 
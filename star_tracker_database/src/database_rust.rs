@@ -6,8 +6,13 @@ use star_tracker_lib::util::aliases::Decimal;
 use star_tracker_lib::util::units::Radians;
 use star_tracker_lib::util::units::Degrees;
 use star_tracker_lib::util::list::List;
+use star_tracker_lib::ephemeris::BodyMask;
+use star_tracker_lib::ephemeris::Epoch;
 
 use star_tracker_database::tracking_mode::DatabaseGenerator;
+use star_tracker_database::tracking_mode::TiledDatabaseGenerator;
+use star_tracker_database::tracking_mode::TriangleDatabaseGenerator;
+use star_tracker_database::tracking_mode::GridDatabaseGenerator;
 use star_tracker_database::io::Star;
 use star_tracker_database::io::Io;
 
@@ -76,6 +81,23 @@ OPTIONAL:
 		By having this too high, the database will be bigger, if the magnitude is too low, you wont have enough coverage.
 		The default value is calculated based on the input field of view, you probably should just use that.
 
+	tile_separation_deg:
+		If provided, the sky is split into tiles this far apart and the pairs of each tile are output (TILED).
+		Use ChunkIteratorTiled with TILED so only pairs which can be in the same image are searched.
+		The smaller the separation, the more memory is used.
+
+	triangle_database:
+		If true, every triangle which fits in the field of view is also output (TRIANGLE_DATABASE).
+		This uses the same catalogue as DATABASE and is much bigger than the pairs.
+
+	grid_size:
+		If provided, the grid pattern of every star is also output (GRID) with this many cells on each side (odd).
+		This uses the same catalogue as DATABASE.
+
+	grid_radius_deg:
+		The max angle from a star to a neighbour in its grid pattern, this must be less than fov / 2.
+		Default = fov / 3.
+
 	mem_section_k_vector:
 		In a microcontroller, the memory can be fragmented.
 		Sometimes you need to specify memory locations `#[link_section = ".my_section"]`.
@@ -139,7 +161,31 @@ cargo run --bin gen_database{{path to output}} {{path to config file}}
 	
 	let mut region_num: usize = 8;
 	if let Some(val) = json.get("region_num_stars") { region_num = val.as_i64().expect("INVALID TYPE IN LOG `region_num_stars`") as usize; }
+
+	let mut bodies_margin: Radians = Degrees(0.5).to_radians();
+	if let Some(val) = json.get("exclude_bodies_margin_deg")
+	{ bodies_margin = Degrees(val.as_f64().expect("INVALID TYPE IN LOG `exclude_bodies_margin_deg`") as Decimal).to_radians(); }
+
+	let mut bodies: Option<BodyMask> = None;
+	if let Some(val) = json.get("exclude_bodies_unix")
+	{ bodies = Some(BodyMask::new(Epoch::from_unix(val.as_i64().expect("INVALID TYPE IN LOG `exclude_bodies_unix`")), bodies_margin)); }
 	
+	let mut tile_separation: Option<Radians> = None;
+	if let Some(val) = json.get("tile_separation_deg")
+	{ tile_separation = Some(Degrees(val.as_f64().expect("INVALID TYPE IN LOG `tile_separation_deg`") as Decimal).to_radians()); }
+
+	let mut triangle_database: bool = false;
+	if let Some(val) = json.get("triangle_database")
+	{ triangle_database = val.as_bool().expect("INVALID TYPE IN LOG `triangle_database`"); }
+
+	let mut grid_size: Option<usize> = None;
+	if let Some(val) = json.get("grid_size")
+	{ grid_size = Some(val.as_u64().expect("INVALID TYPE IN LOG `grid_size`") as usize); }
+
+	let mut grid_radius: Radians = fov / 3.0;
+	if let Some(val) = json.get("grid_radius_deg")
+	{ grid_radius = Degrees(val.as_f64().expect("INVALID TYPE IN LOG `grid_radius_deg`") as Decimal).to_radians(); }
+
 	let mut mem_section_k_vector: String = "".to_string();
 	if let Some(val) = json.get("mem_section_k_vector") 
 	{ mem_section_k_vector = format!("#[link_section = \"{:?}\"]", val.as_str().expect("INVALID TYPE IN LOG `mem_section_pairs`") as &str) }
@@ -193,8 +239,14 @@ cargo run --bin gen_database{{path to output}} {{path to config file}}
 	let stars_limit_mag    = DatabaseGenerator::limit_magnitude (&stars, magnitude_min, magnitude_max);
 	println!("\tLimiting Double Stars.");
 	let stars_limit_double = DatabaseGenerator::limit_double_stars(&stars_limit_mag, double_star_tolerance);
+	let mut stars_limit_bodies = stars_limit_double;
+	if let Some(bodies) = bodies
+	{
+		println!("\tExcluding Sun, Moon and Planets.");
+		stars_limit_bodies = DatabaseGenerator::limit_bodies(&stars_limit_bodies, &bodies);
+	}
 	println!("\tLimiting Regions.");
-	let stars_limit_reg    = DatabaseGenerator::limit_regions(&stars_limit_double, region_size, region_num);
+	let stars_limit_reg    = DatabaseGenerator::limit_regions(&stars_limit_bodies, region_size, region_num);
 
 	// 4. Create the database.
 	//    This creates the database.
@@ -202,6 +254,32 @@ cargo run --bin gen_database{{path to output}} {{path to config file}}
 	let gen : DatabaseGenerator = DatabaseGenerator::gen_database(&stars_limit_reg, fov, fov / 1.3, angle_tolerance);
 	let database = gen.get_database();
 
+	// 5. (Optional) Split into tiles.
+	//    Each tile only stores the pairs which can be in the same image.
+	let tiled = tile_separation.map(|separation|
+	{
+		println!("\tCreating Tiles.");
+		return TiledDatabaseGenerator::gen_database(&database, separation, fov);
+	});
+
+	// 6. (Optional) Create the triangle database.
+	//    The triangles share the catalogue of the database.
+	let mut triangles : Option<TriangleDatabaseGenerator> = None;
+	if triangle_database
+	{
+		println!("\tCreating Triangles.");
+		triangles = Some(TriangleDatabaseGenerator::gen_database(&stars_limit_reg, fov, angle_tolerance)
+			.expect("Not enough triangles fit in the field of view, increase `magnitude_max`."));
+	}
+
+	// 7. (Optional) Create the grid database.
+	//    The patterns share the catalogue of the database.
+	let grid = grid_size.map(|size|
+	{
+		println!("\tCreating Grid.");
+		return GridDatabaseGenerator::gen_database(&stars_limit_reg, grid_radius, double_star_tolerance, size);
+	});
+
 
 // DONE
 // Thats how you make a database :P
@@ -252,6 +330,35 @@ cargo run --bin gen_database{{path to output}} {{path to config file}}
 
 
 
+	// The tiles are only output if requested.
+	let mut tiled_str = String::new();
+	if let Some(tiled) = &tiled
+	{
+		println!("Tiles:     {} tiles, {} elements \t {} B at 32bit \t {} B at 64bit",
+			tiled.tiles.len(), tiled.tile_pairs.len(), tiled.tile_pairs.len() * 4, tiled.tile_pairs.len() * 8);
+		tiled_str = tiled.to_rust(&mem_section_pairs);
+	}
+
+	// The triangles are only output if requested.
+	let mut triangles_str = String::new();
+	if let Some(triangles) = &triangles
+	{
+		println!("Triangles: {} elements   \t {} B at 32bit \t {} B at 64bit",
+			triangles.triangles.len(), triangles.triangles.len() * 12, triangles.triangles.len() * 24);
+		triangles_str = triangles.to_rust(&mem_section_pairs);
+	}
+
+	// The grid is only output if requested.
+	let mut grid_str = String::new();
+	if let Some(grid) = &grid
+	{
+		let size = grid.cells.len() + grid.cell_stars.len() + grid.pattern_start.len() + grid.cell_start.len();
+		println!("Grid:      {} elements   \t {} B at 32bit \t {} B at 64bit", size, size * 4, size * 8);
+		grid_str = grid.to_rust(&mem_section_pairs);
+	}
+
+
+
 	// Outputs database. 
 	let output = format!(r#"
 //! This is a generated file from gen_database with the configuration of:
@@ -264,8 +371,16 @@ cargo run --bin gen_database{{path to output}} {{path to config file}}
 use star_tracker_lib::util::units::Radians;
 use star_tracker_lib::util::units::Equatorial;
 use star_tracker_lib::tracking_mode::StarPair;
+#[allow(unused_imports)]
+use star_tracker_lib::tracking_mode::StarTriangle;
 use star_tracker_lib::tracking_mode::database::KVector;
 use star_tracker_lib::tracking_mode::database::PyramidDatabase;
+#[allow(unused_imports)]
+use star_tracker_lib::tracking_mode::database::TiledDatabase;
+#[allow(unused_imports)]
+use star_tracker_lib::tracking_mode::database::TriangleDatabase;
+#[allow(unused_imports)]
+use star_tracker_lib::tracking_mode::database::GridDatabase;
 
 pub const angle_tolerance: Radians = Radians({});
 
@@ -306,7 +421,9 @@ pub const CATALOGUE: [Equatorial; {}] =
 [
 {}
 ];
-	
+{}
+{}
+{}	
 	
 	"#, 
 	fov.to_degrees(), angle_tolerance.to_degrees(), magnitude_max, region_size.to_degrees(), region_num,
@@ -317,7 +434,10 @@ pub const CATALOGUE: [Equatorial; {}] =
 	
 	mem_section_k_vector, database.k_vector.size(), k_vector_str,
 	mem_section_pairs, database.pairs.size(), pairs_str,
-	mem_section_catalogue, database.catalogue.size(), catalog_str
+	mem_section_catalogue, database.catalogue.size(), catalog_str,
+	tiled_str,
+	triangles_str,
+	grid_str
 );
 
 
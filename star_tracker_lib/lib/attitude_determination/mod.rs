@@ -78,15 +78,142 @@
 //!	let rotate_to_world: Quaternion = rotate_to_cam.conjugate();           // Quaternion to rotate camera space to world space.
 //!	let world_center = rotate_to_world.rotate_point(reference_forward);    // Rotate spacecraft front to world coordinates
 //! ```
+//!
+//! # Outlier Rejection
+//! Every match given to `estimate` pulls on the attitude, a single misidentified star will drag the attitude away from the truth.  
+//! `Ransac` finds the attitude from every pair of matches and keeps the attitude which the most matches agree with.  
+//! The attitude is then refined with only the matches which agree with it (the inliers), the rest are rejected.
+//! ```
+//! use star_tracker_lib::attitude_determination::Ransac;
+//! use star_tracker_lib::attitude_determination::Quest;
+//! use star_tracker_lib::util::units::Vector3;
+//! use star_tracker_lib::util::units::Degrees;
+//! use star_tracker_lib::util::units::Match;
+//!
+//! let found_stars = vec!
+//! [
+//! 	Match{input: Vector3{x: 0.0, y: 0.0, z: 1.0}, output: Vector3{x: 0.0, y: 0.0, z: 1.0}, weight: 1.0},
+//! 	Match{input: Vector3{x: 0.0, y: 1.0, z: 0.0}, output: Vector3{x: 0.0, y: 1.0, z: 0.0}, weight: 1.0},
+//! 	Match{input: Vector3{x: 1.0, y: 0.0, z: 0.0}, output: Vector3{x: 1.0, y: 0.0, z: 0.0}, weight: 1.0},
+//! 	// This star was misidentified.
+//! 	Match{input: Vector3{x: 0.0, y: 1.0, z: 1.0}, output: Vector3{x: 1.0, y: 0.0, z: 1.0}, weight: 1.0},
+//! ];
+//!
+//! let ransac = Ransac{threshold: Degrees(0.1).as_radians(), min_inliers: 3};
+//! let mut inliers  : Vec<Match<Vector3>> = Vec::new();
+//! let mut rejected : Vec<usize> = Vec::new();
+//! let rotate_to_cam = ransac.estimate::<Quest>(&found_stars, &mut inliers, &mut rejected);
+//! assert!(rotate_to_cam.is_some());
+//! assert_eq!(rejected, vec![3]);
+//! ```
+//!
+//! # Body Frame
+//! The camera is rarely aligned with the spacecraft body.  
+//! `Mounting` holds the rotation from the camera frame to the body frame so the output of `estimate` can be reported as inertial to body.  
+//! If the mounting is not known precisely, `Mounting::estimate` can find it from a set of attitudes where the body attitude was known by some other means.
+//! ```
+//! use star_tracker_lib::attitude_determination::Mounting;
+//! use star_tracker_lib::util::units::Quaternion;
+//! use star_tracker_lib::util::units::AngleAxis;
+//! use star_tracker_lib::util::units::Vector3;
+//! use star_tracker_lib::util::units::Degrees;
+//!
+//! // The camera is pointing out the side of the spacecraft (90 degrees around the y axis).
+//! let camera_to_body = AngleAxis{angle: Degrees(90.0).as_radians(), axis: Vector3{x: 0.0, y: 1.0, z: 0.0}};
+//! let mounting = Mounting{camera_to_body: camera_to_body.to_quaternion()};
+//!
+//! // The result of Quest::estimate (inertial to camera).
+//! let inertial_to_camera = Quaternion{w: 1.0, x: 0.0, y: 0.0, z: 0.0};
+//! let inertial_to_body   = mounting.to_body(inertial_to_camera);
+//!
+//! // Forward in the camera frame is out the side of the body.
+//! let side = inertial_to_body.rotate_point(Vector3{x: 0.0, y: 0.0, z: 1.0});
+//! assert_eq!(side, mounting.camera_to_body.rotate_point(Vector3{x: 0.0, y: 0.0, z: 1.0}));
+//! ```
+//!
+//! # Relative Attitude (Optical Gyro)
+//! Between two consecutive frames, the same stars move by a small rotation.  
+//! `RelativeAttitude` associates the stars of each frame and runs `estimate` on the (current, previous) pairs.  
+//! This does not use the database, the rotation and angular rate are available while the star tracker is lost in space.  
+//! When the catalogue attitude is found, `RelativeRotation::cross_check` compares it with the last attitude rotated by the relative rotation.
+//! ```
+//! use star_tracker_lib::attitude_determination::RelativeAttitude;
+//! use star_tracker_lib::attitude_determination::RelativeRotation;
+//! use star_tracker_lib::attitude_determination::Quest;
+//! use star_tracker_lib::util::units::Quaternion;
+//! use star_tracker_lib::util::units::AngleAxis;
+//! use star_tracker_lib::util::units::Vector3;
+//! use star_tracker_lib::util::units::Degrees;
+//! use star_tracker_lib::util::units::Match;
+//!
+//! // The stars of the last frame (camera space).
+//! let previous = vec![
+//! 	Vector3{x:  0.00, y:  0.00, z: 1.0}.normalized().unwrap(),
+//! 	Vector3{x:  0.10, y:  0.02, z: 1.0}.normalized().unwrap(),
+//! 	Vector3{x: -0.05, y:  0.08, z: 1.0}.normalized().unwrap(),
+//! 	Vector3{x:  0.03, y: -0.12, z: 1.0}.normalized().unwrap()];
+//!
+//! // The stars moved 0.5 degrees between the frames.
+//! let delta = AngleAxis{angle: Degrees(0.5).as_radians(), axis: Vector3{x: 0.0, y: 1.0, z: 0.0}}.to_quaternion();
+//! let current : Vec<Vector3> = previous.iter().map(|star| delta.rotate_point(*star)).collect();
+//!
+//! let gyro = RelativeAttitude{max_motion: Degrees(2.0).as_radians(), min_matches: 3};
+//! let mut matches : Vec<Match<Vector3>> = Vec::new();
+//! // There is no prediction of the motion, use no rotation.
+//! let prediction = Quaternion{w: 1.0, x: 0.0, y: 0.0, z: 0.0};
+//! // The frames were 0.1 seconds apart.
+//! let rotation : RelativeRotation = gyro.estimate::<Quest>(&previous, &current, prediction, 0.1, &mut matches).unwrap();
+//! assert_eq!(rotation.matches, 4);
+//!
+//! // The camera is rotating at 5 degrees per second.
+//! assert!((rotation.rate.magnitude() - Degrees(5.0).as_radians().0).abs() < 0.001);
+//! ```
+//!
+//! # Multiple Solution Voting
+//! In dense star fields, a single pyramid can match a wrong pattern which is still self consistent.  
+//! `AttitudeVoting::subset` splits the observed stars into disjoint subsets, each subset is identified and `estimate` is run separately.  
+//! `AttitudeVoting::vote` then only accepts the attitude if `quorum` of the attitudes agree within `tolerance`.  
+//! The agreeing attitudes are averaged with Markley's method (the eigenvector of the sum of the outer products).
+//! ```
+//! use star_tracker_lib::attitude_determination::AttitudeVoting;
+//! use star_tracker_lib::util::units::Quaternion;
+//! use star_tracker_lib::util::units::AngleAxis;
+//! use star_tracker_lib::util::units::Vector3;
+//! use star_tracker_lib::util::units::Degrees;
+//!
+//! // The observed stars are split between 3 searches.
+//! let stars : Vec<usize> = (0..10).collect();
+//! let mut subset : Vec<usize> = Vec::new();
+//! AttitudeVoting::subset(&stars, 3, 1, &mut subset);
+//! assert_eq!(subset, vec![1, 4, 7]);
+//!
+//! // Each subset found an attitude, the last one is wrong.
+//! let axis = Vector3{x: 0.0, y: 0.0, z: 1.0};
+//! let attitudes = vec![
+//! 	AngleAxis{angle: Degrees(10.00).as_radians(), axis: axis}.to_quaternion(),
+//! 	AngleAxis{angle: Degrees(10.01).as_radians(), axis: axis}.to_quaternion(),
+//! 	AngleAxis{angle: Degrees(50.00).as_radians(), axis: axis}.to_quaternion()];
+//!
+//! let voting = AttitudeVoting{tolerance: Degrees(0.1).as_radians(), quorum: 2, subsets: 3};
+//! let mut agreed : Vec<Quaternion> = Vec::new();
+//! let attitude = voting.vote(&attitudes, &mut agreed);
+//! assert!(attitude.is_some());
+//! assert_eq!(agreed.len(), 2);
+//! ```
 
 use crate::util::units::Match;
 use crate::util::units::Vector3;
 use crate::util::units::Quaternion;
+use crate::util::units::Radians;
 use crate::util::aliases::Decimal;
 use crate::util::list::List;
 
 pub mod wahba;
 pub mod quest;
+pub mod mounting;
+pub mod ransac;
+pub mod relative_attitude;
+pub mod voting;
 
 pub trait AttitudeDetermination
 {
@@ -115,6 +242,76 @@ pub struct Wahba ( );
 /// Use `estimate` to estimate the attitude of the camera.
 pub struct Quest( );
 
+/// Robust attitude determination which rejects misidentified stars (Random Sample Consensus).  
+///
+/// An attitude is found from every pair of matches which have a consistent separation.  
+/// A match is an inlier of an attitude if the observed star is within `threshold` of the rotated reference star.  
+/// The attitude with the most inliers is refined with only its inliers until the inliers stop changing.  
+/// Use [estimate](crate::attitude_determination::Ransac::estimate).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Ransac
+{
+	/// The max angle between an observed star and its rotated reference star for the match to be an inlier.
+	pub threshold   : Radians,
+	/// The min number of inliers for the attitude to be trusted.
+	pub min_inliers : usize,
+}
+
+/// The rotation of the camera relative to the spacecraft body.  
+/// `AttitudeDetermination::estimate` provides the rotation from inertial to camera,
+/// use `to_body` to convert it to the rotation from inertial to body.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Mounting
+{
+	/// Rotates a point in the camera frame into the body frame (with `rotate_point`).
+	pub camera_to_body: Quaternion,
+}
+
+/// Estimates the rotation of the camera between two consecutive frames without the database (an optical gyro).  
+///
+/// Each star is associated with the closest star of the previous frame,
+/// a star is only associated if they are both the closest to each other and within `max_motion`.  
+/// The rotation is then found from the associated stars with an [AttitudeDetermination](crate::attitude_determination::AttitudeDetermination).  
+/// Use [estimate](crate::attitude_determination::RelativeAttitude::estimate).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RelativeAttitude
+{
+	/// The max angle a star can move between frames (after the prediction) to be associated.
+	pub max_motion  : Radians,
+	/// The min number of associated stars for the rotation to be trusted, 2 are always required.
+	pub min_matches : usize,
+}
+
+/// The rotation of the camera between two frames found by [RelativeAttitude](crate::attitude_determination::RelativeAttitude).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct RelativeRotation
+{
+	/// Rotates a point in the previous camera frame into the current camera frame.
+	pub delta   : Quaternion,
+	/// The angular velocity of the camera in the camera frame (the axis scaled by radians per second).  
+	/// The stars appear to rotate the opposite way.
+	pub rate    : Vector3,
+	/// The number of stars associated between the frames.
+	pub matches : usize,
+}
+
+/// Accepts an attitude only if enough independent solutions agree (multiple solution voting).  
+///
+/// Each solution should be found from a different (disjoint) set of observed stars, use [subset](crate::attitude_determination::AttitudeVoting::subset).  
+/// The largest group of solutions within `tolerance` of one solution is found,
+/// if it has at least `quorum` solutions, the group is averaged.  
+/// Use [vote](crate::attitude_determination::AttitudeVoting::vote).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AttitudeVoting
+{
+	/// The max angle between two attitudes for them to agree.
+	pub tolerance : Radians,
+	/// The min number of attitudes which must agree.
+	pub quorum    : usize,
+	/// The number of disjoint subsets the stars are split into, each subset is searched separately.
+	pub subsets   : usize,
+}
+
 /// For quest algorithm, to find the correct attitude, the neuton raphson method is used.  
 /// This method will loop and slowly decrease the gap between the current and previous prediction.  
 /// Achieving perfect precision comparing the 2 values will take up computation power.  
@@ -0,0 +1,224 @@
+//! Implementation of [Mounting](crate::attitude_determination::Mounting)
+use crate::core_include::*;
+
+use crate::attitude_determination::Mounting;
+use crate::attitude_determination::AttitudeDetermination;
+
+use crate::util::units::Quaternion;
+use crate::util::units::Vector3;
+use crate::util::units::Match;
+use crate::util::list::List;
+use crate::util::err::Errors;
+use crate::util::err::Error;
+
+impl Mounting
+{
+	/// The directions used to compare the camera and body frames in `estimate`.
+	const AXIS: [Vector3; 3] = [
+		Vector3{x: 1.0, y: 0.0, z: 0.0},
+		Vector3{x: 0.0, y: 1.0, z: 0.0},
+		Vector3{x: 0.0, y: 0.0, z: 1.0}];
+
+	/// The camera is aligned with the body.
+	pub const fn identity ( ) -> Self
+	{
+		return Self{camera_to_body: Quaternion{w: 1.0, x: 0.0, y: 0.0, z: 0.0}};
+	}
+
+
+	/// Converts the output of `AttitudeDetermination::estimate` to the attitude of the body.
+	/// # Arguments
+	/// * `inertial_to_camera` - The rotation from inertial to camera (result of estimate).
+	/// # Returns
+	/// The rotation from inertial to body.
+	pub fn to_body ( &self, inertial_to_camera: Quaternion ) -> Quaternion
+	{
+		// a.rotate_point(b.rotate_point(p)) == (b * a).rotate_point(p)
+		return inertial_to_camera * self.camera_to_body;
+	}
+
+
+	/// Finds the rotation between two attitudes of the same frame.
+	/// If only a single reference attitude is available, this is the mounting.
+	/// # Arguments
+	/// * `inertial_to_camera` - The attitude measured by the star tracker.
+	/// * `inertial_to_body`   - The attitude of the body found with some other sensor.
+	/// # Returns
+	/// The mounting which would turn `inertial_to_camera` into `inertial_to_body`.
+	pub fn from_reference ( inertial_to_camera: Quaternion, inertial_to_body: Quaternion ) -> Self
+	{
+		return Self{camera_to_body: inertial_to_camera.conjugate() * inertial_to_body};
+	}
+
+
+	/// Estimates the mounting from multiple reference attitudes.
+	/// Each attitude will have some noise, this finds the rotation which best fits all of them.
+	///
+	/// # Arguments
+	/// * `attitudes` - The (input: inertial to camera, output: inertial to body, weight).
+	/// 	The input is the output of the star tracker, the output is the attitude of the body found with some other sensor.
+	/// * `vectors` - An empty list which is used to hold 3 vector matches per attitude.
+	///	  This is required as the library is no_std.
+	///
+	/// # Returns
+	/// The mounting or Errors::InvalidSize if `attitudes` is empty or `vectors` is too small.
+	pub fn estimate <T: AttitudeDetermination> (
+		attitudes: &dyn List<Match<Quaternion>>,
+		vectors: &mut dyn List<Match<Vector3>> ) -> Error<Self>
+	{
+		vectors.clear();
+		if attitudes.size() == 0 || vectors.capacity() < attitudes.size() * Self::AXIS.len()
+		{
+			return Err(Errors::InvalidSize);
+		}
+
+		for i in 0..attitudes.size()
+		{
+			let attitude = attitudes.get(i);
+			for axis in Self::AXIS
+			{
+				let camera = attitude.input.rotate_point(axis);
+				let body   = attitude.output.rotate_point(axis);
+				vectors.push_back(Match{input: body, output: camera, weight: attitude.weight})?;
+			}
+		}
+
+		// estimate finds the rotation from output to input.
+		return Ok(Self{camera_to_body: T::estimate(vectors, None)});
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use rand::prelude::*;
+
+	use crate::attitude_determination::Mounting;
+	use crate::attitude_determination::Quest;
+
+	use crate::util::units::Quaternion;
+	use crate::util::units::AngleAxis;
+	use crate::util::units::Vector3;
+	use crate::util::units::Degrees;
+	use crate::util::units::Match;
+	use crate::util::list::ArrayList;
+	use crate::util::list::List;
+	use crate::util::err::Errors;
+
+	#[coverage(off)]
+	fn random_rotation ( rng: &mut ThreadRng ) -> Quaternion
+	{
+		let mut axis = Vector3{
+			x: rng.gen_range(-1.0..1.0), y: rng.gen_range(-1.0..1.0), z: rng.gen_range(-1.0..1.0)};
+		axis.normalize().expect("Will not be 0,0,0");
+		let angle = Degrees(rng.gen_range(-180.0..180.0)).as_radians();
+		return AngleAxis{angle: angle, axis: axis}.to_quaternion();
+	}
+
+	#[coverage(off)]
+	// Quaternions q and -q are the same rotation.
+	fn same_rotation ( a: Quaternion, b: Quaternion ) -> bool
+	{
+		return 1.0 - a.dot(b).abs() < 0.0001;
+	}
+
+//###############################################################################################//
+//
+//										Mounting
+//
+// pub const fn identity ( ) -> Self
+// pub fn to_body        ( &self, Quaternion ) -> Quaternion
+// pub fn from_reference ( Quaternion, Quaternion ) -> Self
+// pub fn estimate       ( &dyn List<Match<Quaternion>>, &mut dyn List<Match<Vector3>> ) -> Error<Self>
+//
+//###############################################################################################//
+//										~ identity ~											 //
+	#[test]
+	// The identity should not change the attitude.
+	fn test_identity ( )
+	{
+		let mut rng = rand::thread_rng();
+		let attitude = random_rotation(&mut rng);
+		assert!(same_rotation(Mounting::identity().to_body(attitude), attitude));
+	}
+
+//										~ to_body ~												 //
+	#[test]
+	// Rotating to the camera then to the body should be the same as the combined rotation.
+	fn test_to_body ( )
+	{
+		let mut rng = rand::thread_rng();
+		for _ in 0..100
+		{
+			let mounting = Mounting{camera_to_body: random_rotation(&mut rng)};
+			let attitude = random_rotation(&mut rng);
+			let point    = Vector3{x: 0.1, y: 0.2, z: 0.9};
+
+			let expected = mounting.camera_to_body.rotate_point(attitude.rotate_point(point));
+			let actual   = mounting.to_body(attitude).rotate_point(point);
+			assert_eq!(actual, expected);
+		}
+	}
+
+//										~ from_reference ~										 //
+	#[test]
+	// from_reference should undo to_body.
+	fn test_from_reference ( )
+	{
+		let mut rng = rand::thread_rng();
+		for _ in 0..100
+		{
+			let mounting = Mounting{camera_to_body: random_rotation(&mut rng)};
+			let attitude = random_rotation(&mut rng);
+			let found    = Mounting::from_reference(attitude, mounting.to_body(attitude));
+			assert!(same_rotation(found.camera_to_body, mounting.camera_to_body));
+		}
+	}
+
+//										~ estimate ~											 //
+	#[test]
+	// No attitudes or not enough room for the vectors is an error.
+	fn test_estimate_invalid_size ( )
+	{
+		let mut attitudes: ArrayList<Match<Quaternion>, 2> = ArrayList::new();
+		let mut vectors:   ArrayList<Match<Vector3>, 3>    = ArrayList::new();
+		assert_eq!(Mounting::estimate::<Quest>(&attitudes, &mut vectors), Err(Errors::InvalidSize));
+
+		let attitude = Mounting::identity().camera_to_body;
+		attitudes.push_back(Match{input: attitude, output: attitude, weight: 1.0});
+		attitudes.push_back(Match{input: attitude, output: attitude, weight: 1.0});
+		assert_eq!(Mounting::estimate::<Quest>(&attitudes, &mut vectors), Err(Errors::InvalidSize));
+	}
+
+	#[test]
+	// With perfect reference attitudes, the mounting should be found.
+	fn test_estimate ( )
+	{
+		let mut rng = rand::thread_rng();
+		for _ in 0..10
+		{
+			let mounting = Mounting{camera_to_body: random_rotation(&mut rng)};
+			let mut attitudes: ArrayList<Match<Quaternion>, 10> = ArrayList::new();
+			let mut vectors:   ArrayList<Match<Vector3>, 30>    = ArrayList::new();
+			while !attitudes.is_full()
+			{
+				let attitude = random_rotation(&mut rng);
+				attitudes.push_back(Match{input: attitude, output: mounting.to_body(attitude), weight: 1.0});
+			}
+
+			let found = Mounting::estimate::<Quest>(&attitudes, &mut vectors).expect("Valid size");
+			assert!(same_rotation(found.camera_to_body, mounting.camera_to_body));
+		}
+	}
+}
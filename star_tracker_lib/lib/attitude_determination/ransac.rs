@@ -0,0 +1,336 @@
+//! Implementation of [Ransac](crate::attitude_determination::Ransac)
+use crate::core_include::*;
+
+use crate::attitude_determination::AttitudeDetermination;
+use crate::attitude_determination::Ransac;
+
+use crate::util::units::Quaternion;
+use crate::util::units::Vector3;
+use crate::util::units::Match;
+use crate::util::aliases::Decimal;
+use crate::util::list::ArrayList;
+use crate::util::list::List;
+
+impl Ransac
+{
+	/// The max number of times the attitude is refined with its inliers.
+	const ITERATIONS: usize = 10;
+
+	/// Finds the attitude which the most matches agree with, then refines it with only those matches.
+	///
+	/// # Arguments
+	/// * `positions` - The (input: observed, output: reference, weight) same as [AttitudeDetermination::estimate](crate::attitude_determination::AttitudeDetermination::estimate).
+	/// * `inliers`   - The output, the matches which agree with the attitude, cleared before use.
+	/// * `rejected`  - The output, the index in `positions` of each match which does not agree, cleared before use.
+	///
+	/// # Returns
+	/// The rotation from output to input (same as `estimate`) found from the inliers.
+	/// None if there are less than `min_inliers` inliers or no pair of matches could be used.
+	pub fn estimate <T: AttitudeDetermination> (
+		&self,
+		positions: &dyn List<Match<Vector3>>,
+		inliers  : &mut dyn List<Match<Vector3>>,
+		rejected : &mut dyn List<usize>,
+	) -> Option<Quaternion>
+	{
+		inliers.clear();
+		rejected.clear();
+
+		let mut best       : Option<Quaternion> = None;
+		let mut best_count = 0;
+		let mut best_error = 0.0;
+		for i in 0..positions.size()
+		{
+			for j in (i + 1)..positions.size()
+			{
+				let a = positions.get(i);
+				let b = positions.get(j);
+
+				// If the separation is different, one of the matches is wrong.
+				// If the stars are too close, the roll around them cannot be found.
+				let observed  = a.input.angle_distance(b.input);
+				let reference = a.output.angle_distance(b.output);
+				if (self.threshold * 2.0).0 < (observed - reference).0.abs() || observed < self.threshold
+				{
+					continue;
+				}
+
+				let mut pair : ArrayList<Match<Vector3>, 2> = ArrayList::new();
+				let _ = pair.push_back(a);
+				let _ = pair.push_back(b);
+				let attitude = T::estimate(&pair, None);
+
+				let (count, error) = self.score(positions, attitude);
+				if best.is_none() || best_count < count || (best_count == count && error < best_error)
+				{
+					best       = Some(attitude);
+					best_count = count;
+					best_error = error;
+				}
+			}
+		}
+
+		let mut attitude = match best { Some(attitude) => attitude, None => return None };
+		let mut count    = best_count;
+		for _ in 0..Self::ITERATIONS
+		{
+			self.split(positions, attitude, inliers, rejected);
+			let refined = T::estimate(inliers, None);
+			let (refined_count, _) = self.score(positions, refined);
+			if refined_count < count { break; }
+
+			attitude = refined;
+			if refined_count == count { break; }
+			count = refined_count;
+		}
+
+		self.split(positions, attitude, inliers, rejected);
+		if inliers.size() < self.min_inliers
+		{
+			return None;
+		}
+		return Some(attitude);
+	}
+
+
+
+	/// Finds how many matches agree with `attitude`.
+	/// # Returns
+	/// (number of inliers, sum of the residuals of the inliers).
+	fn score ( &self, positions: &dyn List<Match<Vector3>>, attitude: Quaternion ) -> (usize, Decimal)
+	{
+		let mut count = 0;
+		let mut error = 0.0;
+		for i in 0..positions.size()
+		{
+			let position = positions.get(i);
+			let residual = position.input.angle_distance(attitude.rotate_point(position.output));
+			if residual <= self.threshold
+			{
+				count += 1;
+				error += residual.0;
+			}
+		}
+		return (count, error);
+	}
+
+
+
+	/// Splits the matches into the inliers and the index of the rejected matches of `attitude`.
+	/// If either list is full, the remaining elements are not added.
+	fn split (
+		&self,
+		positions: &dyn List<Match<Vector3>>,
+		attitude : Quaternion,
+		inliers  : &mut dyn List<Match<Vector3>>,
+		rejected : &mut dyn List<usize>,
+	)
+	{
+		inliers.clear();
+		rejected.clear();
+		for i in 0..positions.size()
+		{
+			let position = positions.get(i);
+			let residual = position.input.angle_distance(attitude.rotate_point(position.output));
+			if residual <= self.threshold
+			{
+				let _ = inliers.push_back(position);
+			}
+			else
+			{
+				let _ = rejected.push_back(i);
+			}
+		}
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::attitude_determination::Ransac;
+	use crate::attitude_determination::Quest;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::Quaternion;
+	use crate::util::units::AngleAxis;
+	use crate::util::units::Vector3;
+	use crate::util::units::Degrees;
+	use crate::util::units::Match;
+	use crate::util::list::ArrayList;
+	use crate::util::list::List;
+	use crate::util::test::TestEqual;
+
+	// An arbitrary rotation from inertial to camera.
+	#[coverage(off)]
+	fn rotation ( ) -> Quaternion
+	{
+		return AngleAxis{angle: Degrees(40.0).as_radians(),
+			axis: Vector3{x: 0.3, y: -0.5, z: 0.8}.normalized().unwrap()}.to_quaternion();
+	}
+
+	// Stars within a 20 degree field of view, input is the rotated output.
+	#[coverage(off)]
+	fn positions ( ) -> Vec<Match<Vector3>>
+	{
+		let offsets = [(0.0, 0.0), (2.3, 1.4), (-3.8, 4.9), (5.1, -2.7), (-6.6, -0.9), (1.5, 7.8), (-2.4, -6.4)];
+		let mut positions = Vec::new();
+		for (ra, dec) in offsets
+		{
+			let output = Equatorial{ra: Degrees(80.0 + ra).as_radians(), dec: Degrees(30.0 + dec).as_radians()}
+				.to_vector3();
+			positions.push(Match{input: rotation().rotate_point(output), output: output, weight: 1.0});
+		}
+		return positions;
+	}
+
+	#[coverage(off)]
+	fn positions_first ( ) -> Match<Vector3>
+	{
+		return positions()[0];
+	}
+
+	#[coverage(off)]
+	fn ransac ( ) -> Ransac
+	{
+		return Ransac{threshold: Degrees(0.05).as_radians(), min_inliers: 4};
+	}
+
+	// The angle between the rotation of a point with each quaternion.
+	#[coverage(off)]
+	fn error ( a: Quaternion, b: Quaternion ) -> Degrees
+	{
+		let point = Vector3{x: 0.0, y: 0.0, z: 1.0};
+		return a.rotate_point(point).angle_distance(b.rotate_point(point)).to_degrees();
+	}
+
+
+//###############################################################################################//
+//
+//										Ransac
+//
+// pub fn estimate <T> ( &self, &dyn List<Match<Vector3>>, &mut dyn List<Match<Vector3>>, &mut dyn List<usize> )
+//                     -> Option<Quaternion>
+// fn score            ( &self, &dyn List<Match<Vector3>>, Quaternion ) -> (usize, Decimal)
+// fn split            ( &self, &dyn List<Match<Vector3>>, Quaternion, &mut dyn List<Match<Vector3>>, &mut dyn List<usize> )
+//
+//###############################################################################################//
+//										~ estimate ~											 //
+	#[test]
+	// If every match is correct, nothing should be rejected.
+	fn test_estimate ( )
+	{
+		let positions = positions();
+		let mut inliers  : Vec<Match<Vector3>> = Vec::new();
+		let mut rejected : Vec<usize> = Vec::new();
+		let attitude = ransac().estimate::<Quest>(&positions, &mut inliers, &mut rejected).unwrap();
+		assert_eq!(inliers.size(), 7);
+		assert_eq!(rejected.size(), 0);
+		error(attitude, rotation()).0.assert_close(&0.0, 0.001);
+	}
+
+	#[test]
+	// A misidentified star should be rejected and not affect the attitude.
+	fn test_estimate_outlier ( )
+	{
+		let mut positions = positions();
+		positions[3].output = positions[5].output;
+		positions[0].output = Equatorial{ra: Degrees(81.0).as_radians(), dec: Degrees(30.0).as_radians()}.to_vector3();
+
+		let mut inliers  : Vec<Match<Vector3>> = Vec::new();
+		let mut rejected : Vec<usize> = Vec::new();
+		let attitude = ransac().estimate::<Quest>(&positions, &mut inliers, &mut rejected).unwrap();
+		assert_eq!(rejected, vec![0, 3]);
+		assert_eq!(inliers.size(), 5);
+		error(attitude, rotation()).0.assert_close(&0.0, 0.001);
+	}
+
+	#[test]
+	// If there are not enough inliers, the attitude should not be trusted.
+	fn test_estimate_insufficient ( )
+	{
+		// Only 3 matches are correct.
+		let mut positions = positions();
+		positions[0].output = positions[1].output;
+		positions[1].output = positions[2].output;
+		positions[3].output = positions[4].output;
+		positions[5].output = positions[6].output;
+
+		let mut inliers  : Vec<Match<Vector3>> = Vec::new();
+		let mut rejected : Vec<usize> = Vec::new();
+		assert!(ransac().estimate::<Quest>(&positions, &mut inliers, &mut rejected).is_none());
+	}
+
+	#[test]
+	// There must be at least one pair of matches with a consistent separation.
+	fn test_estimate_no_pairs ( )
+	{
+		let mut inliers  : Vec<Match<Vector3>> = Vec::new();
+		let mut rejected : Vec<usize> = vec![1];
+		let ransac = Ransac{threshold: Degrees(0.05).as_radians(), min_inliers: 0};
+
+		let positions : Vec<Match<Vector3>> = Vec::new();
+		assert!(ransac.estimate::<Quest>(&positions, &mut inliers, &mut rejected).is_none());
+		assert_eq!(rejected.size(), 0);
+
+		let positions = vec![positions_first()];
+		assert!(ransac.estimate::<Quest>(&positions, &mut inliers, &mut rejected).is_none());
+
+		// The same star twice cannot find the roll.
+		let positions = vec![positions_first(), positions_first()];
+		assert!(ransac.estimate::<Quest>(&positions, &mut inliers, &mut rejected).is_none());
+	}
+
+	#[test]
+	// If the output lists are full, it should not panic.
+	fn test_estimate_full ( )
+	{
+		let positions = positions();
+		let mut inliers  : ArrayList<Match<Vector3>, 3> = ArrayList::new();
+		let mut rejected : ArrayList<usize, 0> = ArrayList::new();
+		let ransac = Ransac{threshold: Degrees(0.05).as_radians(), min_inliers: 3};
+		let attitude = ransac.estimate::<Quest>(&positions, &mut inliers, &mut rejected).unwrap();
+		assert_eq!(inliers.size(), 3);
+		error(attitude, rotation()).0.assert_close(&0.0, 0.01);
+	}
+
+//										~ score ~												 //
+	#[test]
+	// The score is the number of inliers and the sum of their residuals.
+	fn test_score ( )
+	{
+		let mut positions = positions();
+		positions[2].input = AngleAxis{angle: Degrees(0.03).as_radians(), axis: Vector3{x: 1.0, y: 0.0, z: 0.0}}
+			.to_quaternion().rotate_point(positions[2].input);
+		positions[4].input = positions[1].input;
+
+		let (count, error) = ransac().score(&positions, rotation());
+		assert_eq!(count, 6);
+		assert!(Degrees(0.001).as_radians().0 < error && error < Degrees(0.03).as_radians().0);
+	}
+
+//										~ split ~												 //
+	#[test]
+	// The inliers and the index of the outliers should be found.
+	fn test_split ( )
+	{
+		let mut positions = positions();
+		positions[4].input = positions[1].input;
+
+		let mut inliers  : Vec<Match<Vector3>> = vec![positions[0]];
+		let mut rejected : Vec<usize> = vec![2];
+		ransac().split(&positions, rotation(), &mut inliers, &mut rejected);
+		assert_eq!(inliers.size(), 6);
+		assert_eq!(rejected, vec![4]);
+	}
+}
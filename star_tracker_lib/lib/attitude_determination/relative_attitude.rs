@@ -0,0 +1,529 @@
+//! Implementation of [RelativeAttitude](crate::attitude_determination::RelativeAttitude) and [RelativeRotation](crate::attitude_determination::RelativeRotation).
+use crate::core_include::*;
+
+use crate::attitude_determination::AttitudeDetermination;
+use crate::attitude_determination::RelativeAttitude;
+use crate::attitude_determination::RelativeRotation;
+
+use crate::util::units::Quaternion;
+use crate::util::units::AngleAxis;
+use crate::util::units::Vector3;
+use crate::util::units::Radians;
+use crate::util::units::Match;
+use crate::util::aliases::Decimal;
+use crate::util::list::List;
+use crate::util::err::Errors;
+use crate::util::err::Error;
+
+use crate::util::Maths;
+
+impl RelativeAttitude
+{
+	/// Below this, the rotation is too small to find the axis.
+	const MIN_ROTATION: Decimal = 1e-6;
+
+	/// Finds the rotation of the camera between two frames.
+	///
+	/// # Arguments
+	/// * `previous`   - The stars of the previous frame in camera space.
+	/// * `current`    - The stars of the current frame in camera space.
+	/// * `prediction` - The expected rotation from the previous to current frame, this is applied before associating stars.
+	///   Use [prediction](crate::attitude_determination::RelativeRotation::prediction) of the last rotation or no rotation if it is unknown.
+	/// * `dt`         - The time between the frames in seconds, must be above 0.
+	/// * `matches`    - The output, the (input: current, output: previous) stars which were associated, cleared before use.
+	///
+	/// # Returns
+	/// The rotation and angular rate.
+	/// None if less than `min_matches` (or 2) stars could be associated or `dt` is not above 0.
+	pub fn estimate <T: AttitudeDetermination> (
+		&self,
+		previous  : &dyn List<Vector3>,
+		current   : &dyn List<Vector3>,
+		prediction: Quaternion,
+		dt        : Decimal,
+		matches   : &mut dyn List<Match<Vector3>>,
+	) -> Option<RelativeRotation>
+	{
+		self.associate(previous, current, prediction, matches);
+		if matches.size() < self.min_matches.max(2)
+		{
+			return None;
+		}
+
+		let delta = T::estimate(matches, None);
+		let rate  = Self::rate(delta, dt).ok()?;
+		return Some(RelativeRotation{delta: delta, rate: rate, matches: matches.size()});
+	}
+
+
+
+	/// Associates each star of the current frame with the same star of the previous frame.
+	/// The previous stars are rotated by `prediction`, then a pair of stars is associated if:
+	/// * They are within `max_motion`.
+	/// * The previous star is the closest to the current star.
+	/// * The current star is the closest to the previous star.
+	///
+	/// # Arguments
+	/// * `previous`   - The stars of the previous frame in camera space.
+	/// * `current`    - The stars of the current frame in camera space.
+	/// * `prediction` - The expected rotation from the previous to current frame.
+	/// * `matches`    - The output (input: current, output: previous), cleared before use, if full the remaining stars are not added.
+	pub fn associate (
+		&self,
+		previous  : &dyn List<Vector3>,
+		current   : &dyn List<Vector3>,
+		prediction: Quaternion,
+		matches   : &mut dyn List<Match<Vector3>>,
+	)
+	{
+		matches.clear();
+		for i in 0..current.size()
+		{
+			let star = current.get(i);
+			let closest = match Self::closest(star, previous, prediction)
+			{
+				Some(closest) => closest,
+				None          => continue,
+			};
+
+			let moved = prediction.rotate_point(previous.get(closest));
+			if self.max_motion < star.angle_distance(moved) { continue; }
+
+			// If another current star is closer, the association is ambiguous.
+			let identity = Quaternion{w: 1.0, x: 0.0, y: 0.0, z: 0.0};
+			if Self::closest(moved, current, identity) != Some(i) { continue; }
+
+			let _ = matches.push_back(Match{input: star, output: previous.get(closest), weight: 1.0});
+		}
+	}
+
+
+
+	/// Finds the angular velocity of the camera from the rotation of the stars.
+	/// # Arguments
+	/// * `delta` - Rotates a point in the previous camera frame into the current camera frame.
+	/// * `dt`    - The time between the frames in seconds.
+	/// # Returns
+	/// The axis of rotation scaled by the radians per second.
+	/// Errors::InvalidValue if `dt` is not above 0 (or NaN).
+	pub fn rate ( delta: Quaternion, dt: Decimal ) -> Error<Vector3>
+	{
+		if !(0.0 < dt)
+		{
+			return Err(Errors::InvalidValue);
+		}
+
+		// The camera rotates the opposite way to the stars.
+		let mut camera = delta.conjugate();
+		if camera.w < 0.0
+		{
+			camera = Quaternion{w: -camera.w, x: -camera.x, y: -camera.y, z: -camera.z};
+		}
+
+		let axis = Vector3{x: camera.x, y: camera.y, z: camera.z};
+		let sin  = axis.magnitude();
+		if sin < Self::MIN_ROTATION
+		{
+			// sin(angle / 2) ~= angle / 2
+			return Ok(axis * (2.0 / dt));
+		}
+		let angle = 2.0 * sin.atan2(camera.w);
+		return Ok(axis * (angle / (sin * dt)));
+	}
+
+
+
+	/// Finds the index of the closest star in `stars` to `star` once `stars` are rotated by `rotation`.
+	fn closest ( star: Vector3, stars: &dyn List<Vector3>, rotation: Quaternion ) -> Option<usize>
+	{
+		let mut best          : Option<usize> = None;
+		let mut best_distance = Radians(0.0);
+		for i in 0..stars.size()
+		{
+			let distance = star.angle_distance(rotation.rotate_point(stars.get(i)));
+			if best.is_none() || distance < best_distance
+			{
+				best          = Some(i);
+				best_distance = distance;
+			}
+		}
+		return best;
+	}
+}
+
+
+
+impl RelativeRotation
+{
+	/// Rotates the attitude of the previous frame to find the attitude of the current frame.
+	/// # Arguments
+	/// * `inertial_to_previous` - The attitude of the previous frame (result of `estimate`).
+	/// # Returns
+	/// The rotation from inertial to the current camera frame.
+	pub fn propagate ( &self, inertial_to_previous: Quaternion ) -> Quaternion
+	{
+		// a.rotate_point(b.rotate_point(p)) == (b * a).rotate_point(p)
+		return inertial_to_previous * self.delta;
+	}
+
+
+	/// Compares the attitude found from the database with the previous attitude rotated by this rotation.
+	/// If the angle is large, either the database attitude or the relative rotation is wrong.
+	/// # Arguments
+	/// * `inertial_to_previous` - The attitude of the previous frame.
+	/// * `inertial_to_current`  - The attitude of the current frame found from the database.
+	/// # Returns
+	/// The angle between the attitudes.
+	pub fn cross_check ( &self, inertial_to_previous: Quaternion, inertial_to_current: Quaternion ) -> Radians
+	{
+		let predicted = self.propagate(inertial_to_previous);
+		let dot = predicted.dot(inertial_to_current).abs().min(1.0);
+		return Radians(2.0 * dot.acos());
+	}
+
+
+	/// The rotation expected between the current frame and a frame `dt` seconds later if the rate is constant.
+	/// Use this as the prediction of the next [estimate](crate::attitude_determination::RelativeAttitude::estimate).
+	/// # Arguments
+	/// * `dt` - The time until the next frame in seconds.
+	pub fn prediction ( &self, dt: Decimal ) -> Quaternion
+	{
+		let speed = self.rate.magnitude();
+		if speed * dt < RelativeAttitude::MIN_ROTATION
+		{
+			return Quaternion{w: 1.0, x: 0.0, y: 0.0, z: 0.0};
+		}
+		// The stars rotate the opposite way to the camera.
+		return AngleAxis{angle: Radians(speed * dt), axis: self.rate / speed}.to_quaternion().conjugate();
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::attitude_determination::RelativeAttitude;
+	use crate::attitude_determination::RelativeRotation;
+	use crate::attitude_determination::Quest;
+
+	use crate::util::units::Quaternion;
+	use crate::util::units::AngleAxis;
+	use crate::util::units::Vector3;
+	use crate::util::units::Degrees;
+	use crate::util::units::Match;
+	use crate::util::list::ArrayList;
+	use crate::util::list::List;
+	use crate::util::aliases::Decimal;
+	use crate::util::err::Errors;
+	use crate::util::test::TestEqual;
+
+	#[coverage(off)]
+	fn identity ( ) -> Quaternion
+	{
+		return Quaternion{w: 1.0, x: 0.0, y: 0.0, z: 0.0};
+	}
+
+	// The stars of the previous frame, spread around the boresight.
+	#[coverage(off)]
+	fn previous ( ) -> Vec<Vector3>
+	{
+		let offsets = [(0.0, 0.0), (0.10, 0.02), (-0.05, 0.08), (0.03, -0.12), (-0.11, -0.04), (0.07, 0.13)];
+		let mut stars = Vec::new();
+		for (x, y) in offsets
+		{
+			stars.push(Vector3{x: x, y: y, z: 1.0}.normalized().unwrap());
+		}
+		return stars;
+	}
+
+	// A small rotation of the stars between frames.
+	#[coverage(off)]
+	fn delta ( ) -> Quaternion
+	{
+		return AngleAxis{angle: Degrees(0.5).as_radians(),
+			axis: Vector3{x: 0.2, y: 1.0, z: 0.1}.normalized().unwrap()}.to_quaternion();
+	}
+
+	// The previous stars rotated by `rotation`.
+	#[coverage(off)]
+	fn rotated ( rotation: Quaternion ) -> Vec<Vector3>
+	{
+		return previous().iter().map(|star| rotation.rotate_point(*star)).collect();
+	}
+
+	#[coverage(off)]
+	fn gyro ( ) -> RelativeAttitude
+	{
+		return RelativeAttitude{max_motion: Degrees(1.0).as_radians(), min_matches: 3};
+	}
+
+
+//###############################################################################################//
+//
+//										RelativeAttitude
+//
+// pub fn estimate  ( &self, &dyn List<Vector3>, &dyn List<Vector3>, Quaternion, Decimal,
+//                    &mut dyn List<Match<Vector3>> ) -> Option<RelativeRotation>
+// pub fn associate ( &self, &dyn List<Vector3>, &dyn List<Vector3>, Quaternion,
+//                    &mut dyn List<Match<Vector3>> )
+// pub fn rate      ( Quaternion, Decimal ) -> Error<Vector3>
+// fn closest       ( Vector3, &dyn List<Vector3>, Quaternion ) -> Option<usize>
+//
+//###############################################################################################//
+//										~ estimate ~											 //
+	#[test]
+	// The rotation of the stars should be found.
+	fn test_estimate ( )
+	{
+		let previous = previous();
+		let current  = rotated(delta());
+		let mut matches : Vec<Match<Vector3>> = Vec::new();
+
+		let rotation = gyro().estimate::<Quest>(&previous, &current, identity(), 0.1, &mut matches).unwrap();
+		assert_eq!(rotation.matches, 6);
+		assert_eq!(matches.len(), 6);
+		assert!(1.0 - rotation.delta.dot(delta()).abs() < 0.000001);
+		rotation.rate.magnitude().assert_close(&Degrees(5.0).as_radians().0, 0.0001);
+	}
+
+	#[test]
+	// If not enough stars are associated, there is no rotation.
+	fn test_estimate_insufficient ( )
+	{
+		let previous = previous();
+		let current  = rotated(delta());
+		let mut matches : Vec<Match<Vector3>> = Vec::new();
+
+		let gyro = RelativeAttitude{max_motion: Degrees(1.0).as_radians(), min_matches: 7};
+		assert_eq!(gyro.estimate::<Quest>(&previous, &current, identity(), 0.1, &mut matches), None);
+		assert_eq!(matches.len(), 6);
+	}
+
+	#[test]
+	// If the time between frames is not above 0, there is no rate.
+	fn test_estimate_invalid_dt ( )
+	{
+		let previous = previous();
+		let current  = rotated(delta());
+		let mut matches : Vec<Match<Vector3>> = Vec::new();
+		assert_eq!(gyro().estimate::<Quest>(&previous, &current, identity(), 0.0, &mut matches), None);
+	}
+
+	#[test]
+	// At least 2 stars are required to find a rotation.
+	fn test_estimate_min_two ( )
+	{
+		let previous = vec![previous()[0]];
+		let current  = vec![delta().rotate_point(previous[0])];
+		let mut matches : Vec<Match<Vector3>> = Vec::new();
+
+		let gyro = RelativeAttitude{max_motion: Degrees(1.0).as_radians(), min_matches: 0};
+		assert_eq!(gyro.estimate::<Quest>(&previous, &current, identity(), 0.1, &mut matches), None);
+		assert_eq!(matches.len(), 1);
+	}
+
+//										~ associate ~											 //
+	#[test]
+	// Each star should be matched with the same star of the previous frame.
+	fn test_associate ( )
+	{
+		let previous = previous();
+		let mut current = rotated(delta());
+		current.reverse();
+		let mut matches : Vec<Match<Vector3>> = Vec::new();
+
+		gyro().associate(&previous, &current, identity(), &mut matches);
+		assert_eq!(matches.len(), 6);
+		for i in 0..matches.len()
+		{
+			assert_eq!(matches[i].input, current[i]);
+			assert_eq!(matches[i].output, previous[previous.len() - 1 - i]);
+			assert_eq!(matches[i].weight, 1.0);
+		}
+	}
+
+	#[test]
+	// Stars which moved too far should not be associated.
+	fn test_associate_max_motion ( )
+	{
+		let previous = previous();
+		let current  = rotated(delta());
+		let mut matches : Vec<Match<Vector3>> = Vec::new();
+
+		let gyro = RelativeAttitude{max_motion: Degrees(0.4).as_radians(), min_matches: 3};
+		gyro.associate(&previous, &current, identity(), &mut matches);
+		assert_eq!(matches.len(), 0);
+	}
+
+	#[test]
+	// If the motion is predicted, large rotations can be associated.
+	fn test_associate_prediction ( )
+	{
+		let large = AngleAxis{angle: Degrees(3.0).as_radians(), axis: Vector3{x: 1.0, y: 0.0, z: 0.0}}.to_quaternion();
+		let previous = previous();
+		let current  = rotated(large);
+		let mut matches : Vec<Match<Vector3>> = Vec::new();
+
+		gyro().associate(&previous, &current, identity(), &mut matches);
+		assert!(matches.len() < 6);
+
+		gyro().associate(&previous, &current, large, &mut matches);
+		assert_eq!(matches.len(), 6);
+		for i in 0..matches.len()
+		{
+			assert_eq!(matches[i].output, previous[i]);
+		}
+	}
+
+	#[test]
+	// If two current stars have the same closest previous star, only the closest is associated.
+	fn test_associate_ambiguous ( )
+	{
+		let previous = vec![Vector3{x: 0.0, y: 0.0, z: 1.0}];
+		let current  = vec![
+			Vector3{x: 0.005, y: 0.0, z: 1.0}.normalized().unwrap(),
+			Vector3{x: 0.001, y: 0.0, z: 1.0}.normalized().unwrap()];
+		let mut matches : Vec<Match<Vector3>> = Vec::new();
+
+		gyro().associate(&previous, &current, identity(), &mut matches);
+		assert_eq!(matches.len(), 1);
+		assert_eq!(matches[0].input, current[1]);
+	}
+
+	#[test]
+	// If the output is full, the remaining stars are ignored.
+	fn test_associate_full ( )
+	{
+		let previous = previous();
+		let current  = rotated(delta());
+		let mut matches : ArrayList<Match<Vector3>, 2> = ArrayList::new();
+
+		gyro().associate(&previous, &current, identity(), &mut matches);
+		assert_eq!(matches.size(), 2);
+	}
+
+	#[test]
+	// No stars, no matches.
+	fn test_associate_empty ( )
+	{
+		let current  = previous();
+		let mut matches : Vec<Match<Vector3>> = vec![Match{input: current[0], output: current[0], weight: 1.0}];
+
+		gyro().associate(&Vec::new(), &current, identity(), &mut matches);
+		assert_eq!(matches.len(), 0);
+	}
+
+//										~ rate ~												 //
+	#[test]
+	// The camera rotates the opposite way to the stars.
+	fn test_rate ( )
+	{
+		let axis  = Vector3{x: 0.0, y: 0.0, z: 1.0};
+		let delta = AngleAxis{angle: Degrees(2.0).as_radians(), axis: axis}.to_quaternion();
+		let rate  = RelativeAttitude::rate(delta, 0.5).unwrap();
+		rate.assert_close(&(-axis * Degrees(4.0).as_radians().0), 0.00001);
+	}
+
+	#[test]
+	// The sign of the quaternion should not change the rate.
+	fn test_rate_negative ( )
+	{
+		let axis  = Vector3{x: 0.0, y: 1.0, z: 0.0};
+		let delta = AngleAxis{angle: Degrees(2.0).as_radians(), axis: axis}.to_quaternion();
+		let flip  = Quaternion{w: -delta.w, x: -delta.x, y: -delta.y, z: -delta.z};
+		RelativeAttitude::rate(flip, 1.0).unwrap().assert_close(&RelativeAttitude::rate(delta, 1.0).unwrap(), 0.00001);
+	}
+
+	#[test]
+	// No rotation should not divide by 0.
+	fn test_rate_none ( )
+	{
+		assert_eq!(RelativeAttitude::rate(identity(), 0.1), Ok(Vector3{x: 0.0, y: 0.0, z: 0.0}));
+	}
+
+	#[test]
+	// The time between frames must be above 0.
+	fn test_rate_invalid_dt ( )
+	{
+		assert_eq!(RelativeAttitude::rate(delta(), 0.0), Err(Errors::InvalidValue));
+		assert_eq!(RelativeAttitude::rate(delta(), -0.1), Err(Errors::InvalidValue));
+		assert_eq!(RelativeAttitude::rate(delta(), Decimal::NAN), Err(Errors::InvalidValue));
+	}
+
+//										~ closest ~												 //
+	#[test]
+	fn test_closest ( )
+	{
+		let stars = previous();
+		assert_eq!(RelativeAttitude::closest(stars[3], &stars, identity()), Some(3));
+		assert_eq!(RelativeAttitude::closest(stars[3], &Vec::new(), identity()), None);
+	}
+
+
+
+//###############################################################################################//
+//
+//										RelativeRotation
+//
+// pub fn propagate   ( &self, Quaternion ) -> Quaternion
+// pub fn cross_check ( &self, Quaternion, Quaternion ) -> Radians
+// pub fn prediction  ( &self, Decimal ) -> Quaternion
+//
+//###############################################################################################//
+//										~ propagate ~											 //
+	#[test]
+	// A star seen in the previous frame should be seen in the current frame.
+	fn test_propagate ( )
+	{
+		let inertial_to_previous = AngleAxis{angle: Degrees(40.0).as_radians(),
+			axis: Vector3{x: 0.3, y: -0.5, z: 0.8}.normalized().unwrap()}.to_quaternion();
+		let rotation = RelativeRotation{delta: delta(), rate: Vector3{x: 0.0, y: 0.0, z: 0.0}, matches: 3};
+		let star = Vector3{x: 0.0, y: 1.0, z: 0.0};
+
+		let expected = delta().rotate_point(inertial_to_previous.rotate_point(star));
+		let actual   = rotation.propagate(inertial_to_previous).rotate_point(star);
+		actual.assert_close(&expected, 0.00001);
+	}
+
+//										~ cross_check ~											 //
+	#[test]
+	// If the database attitude agrees, the angle is 0.
+	fn test_cross_check ( )
+	{
+		let rotation = RelativeRotation{delta: delta(), rate: Vector3{x: 0.0, y: 0.0, z: 0.0}, matches: 3};
+		let previous = identity();
+		let current  = delta();
+		rotation.cross_check(previous, current).0.assert_close(&0.0, 0.001);
+
+		let wrong = AngleAxis{angle: Degrees(10.0).as_radians(), axis: Vector3{x: 1.0, y: 0.0, z: 0.0}}.to_quaternion();
+		rotation.cross_check(previous, delta() * wrong).0.assert_close(&Degrees(10.0).as_radians().0, 0.001);
+	}
+
+//										~ prediction ~											 //
+	#[test]
+	// If the rate is constant, the prediction should be the same as the last rotation.
+	fn test_prediction ( )
+	{
+		let rate     = RelativeAttitude::rate(delta(), 0.1).unwrap();
+		let rotation = RelativeRotation{delta: delta(), rate: rate, matches: 3};
+		assert!(1.0 - rotation.prediction(0.1).dot(delta()).abs() < 0.000001);
+	}
+
+	#[test]
+	// No rate, no rotation.
+	fn test_prediction_none ( )
+	{
+		let rotation = RelativeRotation{delta: identity(), rate: Vector3{x: 0.0, y: 0.0, z: 0.0}, matches: 3};
+		assert_eq!(rotation.prediction(0.1), identity());
+	}
+}
@@ -0,0 +1,341 @@
+//! Implementation of [AttitudeVoting](crate::attitude_determination::AttitudeVoting)
+use crate::core_include::*;
+
+use crate::attitude_determination::AttitudeVoting;
+
+use crate::util::units::Quaternion;
+use crate::util::units::Radians;
+use crate::util::aliases::Decimal;
+use crate::util::list::List;
+
+use crate::util::Maths;
+
+impl AttitudeVoting
+{
+	/// The max number of power iterations used to find the average.
+	const ITERATIONS: usize = 20;
+
+	/// Once the average changes less than this, it has converged.
+	const CONVERGED: Decimal = 1e-9;
+
+
+	/// Splits the observed stars into disjoint subsets.
+	/// The stars are dealt like cards (star `i` is in subset `i % subsets`),
+	/// if the stars are sorted by brightness, each subset gets a fair share of the bright stars.
+	///
+	/// # Arguments
+	/// * `stars`   - The observed stars.
+	/// * `subsets` - The number of subsets.
+	/// * `index`   - The subset to find (0 to subsets - 1).
+	/// * `subset`  - The output, cleared before use, if full the remaining stars are not added.
+	pub fn subset <T: Clone> ( stars: &dyn List<T>, subsets: usize, index: usize, subset: &mut dyn List<T> )
+	{
+		subset.clear();
+		let mut i = index;
+		while i < stars.size()
+		{
+			if subset.push_back(stars.get(i)).is_err() { return; }
+			i += subsets.max(1);
+		}
+	}
+
+
+	/// Finds the attitude which the most attitudes agree with.
+	///
+	/// # Arguments
+	/// * `attitudes` - The attitude found from each subset.
+	/// * `agreed`    - The output, the largest group of attitudes which agree, cleared before use.
+	///   If full, the remaining attitudes are not added and are not in the average.
+	///
+	/// # Returns
+	/// The average of `agreed`.
+	/// None if less than `quorum` attitudes agree.
+	pub fn vote ( &self, attitudes: &dyn List<Quaternion>, agreed: &mut dyn List<Quaternion> ) -> Option<Quaternion>
+	{
+		agreed.clear();
+		let mut best       : Option<usize> = None;
+		let mut best_count = 0;
+		for i in 0..attitudes.size()
+		{
+			let mut count = 0;
+			for j in 0..attitudes.size()
+			{
+				if Self::angle(attitudes.get(i), attitudes.get(j)) <= self.tolerance { count += 1; }
+			}
+
+			if best_count < count
+			{
+				best       = Some(i);
+				best_count = count;
+			}
+		}
+
+		let center = attitudes.get(best?);
+		for i in 0..attitudes.size()
+		{
+			let attitude = attitudes.get(i);
+			if Self::angle(center, attitude) <= self.tolerance
+			{
+				let _ = agreed.push_back(attitude);
+			}
+		}
+
+		if best_count < self.quorum
+		{
+			return None;
+		}
+		return Some(Self::average(agreed));
+	}
+
+
+	/// Finds the average of a set of attitudes (Markley et al. Averaging Quaternions, 2007).
+	/// The average is the eigenvector with the largest eigenvalue of the sum of `q * q^T`.
+	/// This is found with power iteration starting from the first attitude.
+	/// The sign of each quaternion does not matter (q and -q are the same rotation).
+	///
+	/// # Arguments
+	/// * `attitudes` - The attitudes to average, this must not be empty.
+	///
+	/// # Returns
+	/// The average attitude with the same sign as the first attitude.
+	pub fn average ( attitudes: &dyn List<Quaternion> ) -> Quaternion
+	{
+		let mut m : [[Decimal; 4]; 4] = [[0.0; 4]; 4];
+		for i in 0..attitudes.size()
+		{
+			let q = Self::to_array(attitudes.get(i));
+			for row in 0..4
+			{
+				for col in 0..4
+				{
+					m[row][col] += q[row] * q[col];
+				}
+			}
+		}
+
+		let mut average = Self::to_array(attitudes.get(0));
+		for _ in 0..Self::ITERATIONS
+		{
+			let mut next = [0.0; 4];
+			let mut magnitude = 0.0;
+			for row in 0..4
+			{
+				for col in 0..4
+				{
+					next[row] += m[row][col] * average[col];
+				}
+				magnitude += next[row] * next[row];
+			}
+
+			let magnitude = magnitude.sqrt();
+			let mut change = 0.0;
+			for row in 0..4
+			{
+				next[row] /= magnitude;
+				change += (next[row] - average[row]).abs();
+			}
+			average = next;
+			if change < Self::CONVERGED { break; }
+		}
+		return Quaternion{w: average[0], x: average[1], y: average[2], z: average[3]};
+	}
+
+
+	/// The angle of the rotation between two attitudes.
+	fn angle ( a: Quaternion, b: Quaternion ) -> Radians
+	{
+		let dot = a.dot(b).abs().min(1.0);
+		return Radians(2.0 * dot.acos());
+	}
+
+
+	/// The elements of the quaternion (w, x, y, z).
+	fn to_array ( q: Quaternion ) -> [Decimal; 4]
+	{
+		return [q.w, q.x, q.y, q.z];
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::attitude_determination::AttitudeVoting;
+
+	use crate::util::units::Quaternion;
+	use crate::util::units::AngleAxis;
+	use crate::util::units::Vector3;
+	use crate::util::units::Degrees;
+	use crate::util::aliases::Decimal;
+	use crate::util::list::ArrayList;
+	use crate::util::list::List;
+	use crate::util::test::TestEqual;
+
+	// A rotation around z.
+	#[coverage(off)]
+	fn around_z ( degrees: Decimal ) -> Quaternion
+	{
+		return AngleAxis{angle: Degrees(degrees).as_radians(), axis: Vector3{x: 0.0, y: 0.0, z: 1.0}}.to_quaternion();
+	}
+
+	#[coverage(off)]
+	fn voting ( ) -> AttitudeVoting
+	{
+		return AttitudeVoting{tolerance: Degrees(0.5).as_radians(), quorum: 2, subsets: 3};
+	}
+
+
+//###############################################################################################//
+//
+//										AttitudeVoting
+//
+// pub fn subset  ( &dyn List<T>, usize, usize, &mut dyn List<T> )
+// pub fn vote    ( &self, &dyn List<Quaternion>, &mut dyn List<Quaternion> ) -> Option<Quaternion>
+// pub fn average ( &dyn List<Quaternion> ) -> Quaternion
+// fn angle       ( Quaternion, Quaternion ) -> Radians
+// fn to_array    ( Quaternion ) -> [Decimal; 4]
+//
+//###############################################################################################//
+//										~ subset ~												 //
+	#[test]
+	// Each star should be in exactly one subset.
+	fn test_subset ( )
+	{
+		let stars : Vec<usize> = (0..7).collect();
+		let mut subset : Vec<usize> = vec![100];
+		AttitudeVoting::subset(&stars, 3, 0, &mut subset);
+		assert_eq!(subset, vec![0, 3, 6]);
+		AttitudeVoting::subset(&stars, 3, 1, &mut subset);
+		assert_eq!(subset, vec![1, 4]);
+		AttitudeVoting::subset(&stars, 3, 2, &mut subset);
+		assert_eq!(subset, vec![2, 5]);
+	}
+
+	#[test]
+	// 0 subsets is the same as 1.
+	fn test_subset_zero ( )
+	{
+		let stars : Vec<usize> = (0..3).collect();
+		let mut subset : Vec<usize> = Vec::new();
+		AttitudeVoting::subset(&stars, 0, 0, &mut subset);
+		assert_eq!(subset, vec![0, 1, 2]);
+	}
+
+	#[test]
+	// If the output is full, the remaining stars are not added.
+	fn test_subset_full ( )
+	{
+		let stars : Vec<usize> = (0..10).collect();
+		let mut subset : ArrayList<usize, 2> = ArrayList::new();
+		AttitudeVoting::subset(&stars, 2, 1, &mut subset);
+		assert_eq!(subset.size(), 2);
+		assert_eq!(subset.get(1), 3);
+	}
+
+//										~ vote ~												 //
+	#[test]
+	// The wrong attitude should be ignored and the rest averaged.
+	fn test_vote ( )
+	{
+		let attitudes = vec![around_z(10.0), around_z(40.0), around_z(10.2)];
+		let mut agreed : Vec<Quaternion> = Vec::new();
+		let attitude = voting().vote(&attitudes, &mut agreed).unwrap();
+		assert_eq!(agreed, vec![around_z(10.0), around_z(10.2)]);
+		attitude.assert_close(&around_z(10.1), 0.00001);
+	}
+
+	#[test]
+	// If the quorum is not met, there is no attitude.
+	fn test_vote_no_quorum ( )
+	{
+		let attitudes = vec![around_z(10.0), around_z(40.0), around_z(70.0)];
+		let mut agreed : Vec<Quaternion> = Vec::new();
+		assert_eq!(voting().vote(&attitudes, &mut agreed), None);
+		assert_eq!(agreed.len(), 1);
+	}
+
+	#[test]
+	// The sign of the quaternion should not matter.
+	fn test_vote_sign ( )
+	{
+		let q = around_z(10.0);
+		let negative = Quaternion{w: -q.w, x: -q.x, y: -q.y, z: -q.z};
+		let attitudes = vec![q, negative];
+		let mut agreed : Vec<Quaternion> = Vec::new();
+		let attitude = voting().vote(&attitudes, &mut agreed).unwrap();
+		assert_eq!(agreed.len(), 2);
+		assert!(1.0 - attitude.dot(q).abs() < 0.00001);
+	}
+
+	#[test]
+	// The largest group should win even if it is not first.
+	fn test_vote_largest ( )
+	{
+		let attitudes = vec![around_z(40.0), around_z(40.1), around_z(10.0), around_z(10.1), around_z(10.2)];
+		let mut agreed : Vec<Quaternion> = Vec::new();
+		let attitude = voting().vote(&attitudes, &mut agreed).unwrap();
+		assert_eq!(agreed.len(), 3);
+		attitude.assert_close(&around_z(10.1), 0.00001);
+	}
+
+	#[test]
+	// No attitudes, no vote.
+	fn test_vote_empty ( )
+	{
+		let mut agreed : Vec<Quaternion> = vec![around_z(1.0)];
+		assert_eq!(voting().vote(&Vec::new(), &mut agreed), None);
+		assert_eq!(agreed.len(), 0);
+	}
+
+//										~ average ~												 //
+	#[test]
+	// A single attitude is its own average.
+	fn test_average_single ( )
+	{
+		AttitudeVoting::average(&vec![around_z(30.0)]).assert_close(&around_z(30.0), 0.00001);
+	}
+
+	#[test]
+	// Attitudes around different axis.
+	fn test_average ( )
+	{
+		let a = AngleAxis{angle: Degrees(0.2).as_radians(), axis: Vector3{x: 1.0, y: 0.0, z: 0.0}}.to_quaternion();
+		let b = AngleAxis{angle: Degrees(-0.2).as_radians(), axis: Vector3{x: 1.0, y: 0.0, z: 0.0}}.to_quaternion();
+		let identity = Quaternion{w: 1.0, x: 0.0, y: 0.0, z: 0.0};
+		AttitudeVoting::average(&vec![a, b, identity]).assert_close(&identity, 0.00001);
+	}
+
+	#[test]
+	// The average should have the sign of the first attitude.
+	fn test_average_sign ( )
+	{
+		let q = around_z(20.0);
+		let negative = Quaternion{w: -q.w, x: -q.x, y: -q.y, z: -q.z};
+		AttitudeVoting::average(&vec![negative, q]).assert_close(&negative, 0.00001);
+	}
+
+//										~ angle ~												 //
+	#[test]
+	fn test_angle ( )
+	{
+		AttitudeVoting::angle(around_z(10.0), around_z(25.0)).assert_close(&Degrees(15.0).as_radians(), 0.00001);
+		AttitudeVoting::angle(around_z(10.0), around_z(10.0)).assert_close(&Degrees(0.0).as_radians(), 0.00001);
+	}
+
+//										~ to_array ~											 //
+	#[test]
+	fn test_to_array ( )
+	{
+		assert_eq!(AttitudeVoting::to_array(Quaternion{w: 1.0, x: 2.0, y: 3.0, z: 4.0}), [1.0, 2.0, 3.0, 4.0]);
+	}
+}
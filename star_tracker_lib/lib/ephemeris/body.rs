@@ -0,0 +1,425 @@
+//! Implementation of [Body](crate::ephemeris::Body) and [OrbitalElements](crate::ephemeris::OrbitalElements)
+use crate::ephemeris::Body;
+use crate::ephemeris::Epoch;
+use crate::ephemeris::OrbitalElements;
+
+use crate::util::units::Equatorial;
+use crate::util::units::Vector3;
+use crate::util::units::Degrees;
+use crate::util::units::Radians;
+use crate::util::aliases::Decimal;
+use crate::util::aliases::M_PI;
+
+use crate::util::Maths;
+
+/// Creates orbital elements from the order of the JPL table (a, e, I, L, long.peri, long.node).
+const fn elements ( a: Decimal, e: Decimal, i: Decimal, l: Decimal, peri: Decimal, node: Decimal ) -> OrbitalElements
+{
+	return OrbitalElements{semi_major_axis: a, eccentricity: e, inclination: Degrees(i),
+		mean_longitude: Degrees(l), perihelion: Degrees(peri), node: Degrees(node)};
+}
+
+// Standish, Keplerian Elements for Approximate Positions of the Major Planets, Table 1 (1800 AD - 2050 AD).
+// The first is the value at J2000, the second is the rate per century.
+const MERCURY : [OrbitalElements; 2] = [
+	elements( 0.38709927, 0.20563593,  7.00497902, 252.25032350,  77.45779628,  48.33076593),
+	elements( 0.00000037, 0.00001906, -0.00594749, 149472.67411175, 0.16047689, -0.12534081)];
+const VENUS   : [OrbitalElements; 2] = [
+	elements( 0.72333566, 0.00677672,  3.39467605, 181.97909950, 131.60246718,  76.67984255),
+	elements( 0.00000390,-0.00004107, -0.00078890, 58517.81538729, 0.00268329, -0.27769418)];
+const EARTH   : [OrbitalElements; 2] = [ // Earth-Moon barycenter.
+	elements( 1.00000261, 0.01671123, -0.00001531, 100.46457166, 102.93768193,   0.0),
+	elements( 0.00000562,-0.00004392, -0.01294668, 35999.37244981, 0.32327364,   0.0)];
+const MARS    : [OrbitalElements; 2] = [
+	elements( 1.52371034, 0.09339410,  1.84969142,  -4.55343205, -23.94362959,  49.55953891),
+	elements( 0.00001847, 0.00007882, -0.00813131, 19140.30268499, 0.44441088, -0.29257343)];
+const JUPITER : [OrbitalElements; 2] = [
+	elements( 5.20288700, 0.04838624,  1.30439695,  34.39644051,  14.72847983, 100.47390909),
+	elements(-0.00011607,-0.00013253, -0.00183714, 3034.74612775, 0.21252668,   0.20469106)];
+const SATURN  : [OrbitalElements; 2] = [
+	elements( 9.53667594, 0.05386179,  2.48599187,  49.95424423,  92.59887831, 113.66242448),
+	elements(-0.00125060,-0.00050991,  0.00193609, 1222.49362201,-0.41897216,  -0.28867794)];
+const URANUS  : [OrbitalElements; 2] = [
+	elements(19.18916464, 0.04725744,  0.77263783, 313.23810451, 170.95427630,  74.01692503),
+	elements(-0.00196176,-0.00004397, -0.00242939, 428.48202785,  0.40805281,   0.04240589)];
+const NEPTUNE : [OrbitalElements; 2] = [
+	elements(30.06992276, 0.00859048,  1.77004347, -55.12002969,  44.96476227, 131.78422574),
+	elements( 0.00026291, 0.00005105,  0.00035372, 218.45945325, -0.32241464,  -0.00508664)];
+
+
+impl Body
+{
+	/// The number of bodies.
+	pub const COUNT : usize = 9;
+
+	/// Every body, the index of a body is `body as usize`.
+	pub const ALL : [Body; Body::COUNT] = [Body::Sun, Body::Moon, Body::Mercury, Body::Venus,
+		Body::Mars, Body::Jupiter, Body::Saturn, Body::Uranus, Body::Neptune];
+
+	/// The obliquity of the ecliptic at J2000.
+	const OBLIQUITY : Degrees = Degrees(23.43928);
+
+	/// The precession of the equinox in longitude per century.
+	const PRECESSION : Degrees = Degrees(1.3969713);
+
+
+	/// Finds the geocentric position of the body (J2000 equatorial).
+	/// # Arguments
+	/// * `epoch` - The time to find the position.
+	pub fn position ( &self, epoch: Epoch ) -> Equatorial
+	{
+		let t = epoch.centuries();
+		let earth = EARTH[0].at(&EARTH[1], t).heliocentric();
+		let ecliptic = match self
+		{
+			Body::Sun     => -earth,
+			Body::Moon    => Self::moon(t),
+			Body::Mercury => MERCURY[0].at(&MERCURY[1], t).heliocentric() - earth,
+			Body::Venus   => VENUS  [0].at(&VENUS  [1], t).heliocentric() - earth,
+			Body::Mars    => MARS   [0].at(&MARS   [1], t).heliocentric() - earth,
+			Body::Jupiter => JUPITER[0].at(&JUPITER[1], t).heliocentric() - earth,
+			Body::Saturn  => SATURN [0].at(&SATURN [1], t).heliocentric() - earth,
+			Body::Uranus  => URANUS [0].at(&URANUS [1], t).heliocentric() - earth,
+			Body::Neptune => NEPTUNE[0].at(&NEPTUNE[1], t).heliocentric() - earth,
+		};
+		return Self::ecliptic_to_equatorial(ecliptic).to_equatorial();
+	}
+
+
+	/// The max angle from the found position which the body can appear.
+	/// This is the apparent radius of the body, the error of the ephemeris and the parallax of a satellite in low earth orbit.
+	pub fn extent ( &self ) -> Radians
+	{
+		return match self
+		{
+			Body::Sun  => Degrees(0.3).as_radians(),
+			Body::Moon => Degrees(1.6).as_radians(),
+			_          => Degrees(0.05).as_radians(),
+		};
+	}
+
+
+	/// The geocentric position of the Moon (J2000 ecliptic), the distance is ignored.
+	/// This is the low precision formula of the Astronomical Almanac which is relative to the equinox of date.
+	/// The longitude is precessed back to J2000.
+	/// # Arguments
+	/// * `t` - Julian centuries since J2000.
+	fn moon ( t: Decimal ) -> Vector3
+	{
+		let longitude = Degrees(218.32 + 481267.881 * t
+			+ 6.29 * Degrees(135.0 + 477198.87 * t).to_radians().sin()
+			- 1.27 * Degrees(259.3 - 413335.36 * t).to_radians().sin()
+			+ 0.66 * Degrees(235.7 + 890534.22 * t).to_radians().sin()
+			+ 0.21 * Degrees(269.9 + 954397.74 * t).to_radians().sin()
+			- 0.19 * Degrees(357.5 + 35999.05  * t).to_radians().sin()
+			- 0.11 * Degrees(186.5 + 966404.03 * t).to_radians().sin()) - Self::PRECESSION * t;
+
+		let latitude = Degrees(5.13 * Degrees(93.3  + 483202.02 * t).to_radians().sin()
+			+ 0.28 * Degrees(228.2 + 960400.89 * t).to_radians().sin()
+			- 0.28 * Degrees(318.3 + 6003.15   * t).to_radians().sin()
+			- 0.17 * Degrees(217.6 - 407332.21 * t).to_radians().sin());
+
+		return Vector3{
+			x: latitude.to_radians().cos() * longitude.to_radians().cos(),
+			y: latitude.to_radians().cos() * longitude.to_radians().sin(),
+			z: latitude.to_radians().sin()};
+	}
+
+
+	/// Rotates a point from the J2000 ecliptic frame to the J2000 equatorial frame.
+	fn ecliptic_to_equatorial ( point: Vector3 ) -> Vector3
+	{
+		let cos = Self::OBLIQUITY.to_radians().cos();
+		let sin = Self::OBLIQUITY.to_radians().sin();
+		return Vector3{
+			x: point.x,
+			y: point.y * cos - point.z * sin,
+			z: point.y * sin + point.z * cos};
+	}
+}
+
+
+
+impl OrbitalElements
+{
+	/// The number of iterations solving kepler's equation.
+	const ITERATIONS : usize = 10;
+
+
+	/// The elements at the time.
+	/// # Arguments
+	/// * `rate` - The change of each element per century.
+	/// * `t`    - Julian centuries since J2000.
+	pub fn at ( &self, rate: &OrbitalElements, t: Decimal ) -> OrbitalElements
+	{
+		return OrbitalElements
+		{
+			semi_major_axis: self.semi_major_axis + rate.semi_major_axis * t,
+			eccentricity:    self.eccentricity    + rate.eccentricity    * t,
+			inclination:     self.inclination     + rate.inclination     * t,
+			mean_longitude:  self.mean_longitude  + rate.mean_longitude  * t,
+			perihelion:      self.perihelion      + rate.perihelion      * t,
+			node:            self.node            + rate.node            * t,
+		};
+	}
+
+
+	/// The position around the sun (J2000 ecliptic) in au.
+	pub fn heliocentric ( &self ) -> Vector3
+	{
+		let e = self.eccentricity;
+		let argument = self.perihelion - self.node;
+		let anomaly  = Self::eccentric_anomaly((self.mean_longitude - self.perihelion).as_radians(), e);
+
+		// The position in the plane of the orbit, x is towards the perihelion.
+		let x = self.semi_major_axis * (anomaly.cos() - e);
+		let y = self.semi_major_axis * (1.0 - e * e).sqrt() * anomaly.sin();
+
+		let (cos_w, sin_w) = (argument.to_radians().cos(), argument.to_radians().sin());
+		let (cos_o, sin_o) = (self.node.to_radians().cos(), self.node.to_radians().sin());
+		let (cos_i, sin_i) = (self.inclination.to_radians().cos(), self.inclination.to_radians().sin());
+		return Vector3
+		{
+			x: (cos_w * cos_o - sin_w * sin_o * cos_i) * x + (-sin_w * cos_o - cos_w * sin_o * cos_i) * y,
+			y: (cos_w * sin_o + sin_w * cos_o * cos_i) * x + (-sin_w * sin_o + cos_w * cos_o * cos_i) * y,
+			z: (sin_w * sin_i) * x + (cos_w * sin_i) * y,
+		};
+	}
+
+
+	/// Solves kepler's equation (M = E - e sin(E)) with newton's method.
+	/// # Arguments
+	/// * `mean`         - The mean anomaly (M).
+	/// * `eccentricity` - The eccentricity of the orbit (e).
+	/// # Returns
+	/// The eccentric anomaly (E) between -π and π.
+	fn eccentric_anomaly ( mean: Radians, eccentricity: Decimal ) -> Radians
+	{
+		let mean = mean.0 - 2.0 * M_PI * (mean.0 / (2.0 * M_PI)).round();
+		let mut anomaly = mean + eccentricity * mean.sin();
+		for _ in 0..Self::ITERATIONS
+		{
+			anomaly -= (anomaly - eccentricity * anomaly.sin() - mean) / (1.0 - eccentricity * anomaly.cos());
+		}
+		return Radians(anomaly);
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::ephemeris::Body;
+	use crate::ephemeris::Epoch;
+	use crate::ephemeris::OrbitalElements;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::Vector3;
+	use crate::util::units::Degrees;
+	use crate::util::units::Radians;
+	use crate::util::aliases::Decimal;
+	use crate::util::aliases::M_PI;
+	use crate::util::test::TestEqual;
+
+	// The angle between two bodies in degrees.
+	#[coverage(off)]
+	fn elongation ( a: Body, b: Body, epoch: Epoch ) -> Decimal
+	{
+		return a.position(epoch).angle_distance(b.position(epoch)).to_degrees().0;
+	}
+
+	// A circular orbit of 1 au on the ecliptic.
+	#[coverage(off)]
+	fn circular ( longitude: Decimal ) -> OrbitalElements
+	{
+		return OrbitalElements{semi_major_axis: 1.0, eccentricity: 0.0, inclination: Degrees(0.0),
+			mean_longitude: Degrees(longitude), perihelion: Degrees(0.0), node: Degrees(0.0)};
+	}
+
+
+//###############################################################################################//
+//
+//										Body
+//
+// pub fn position          ( &self, Epoch ) -> Equatorial
+// pub fn extent            ( &self ) -> Radians
+// fn moon                  ( Decimal ) -> Vector3
+// fn ecliptic_to_equatorial ( Vector3 ) -> Vector3
+//
+//###############################################################################################//
+//										~ ALL ~													 //
+	#[test]
+	// The index of each body should be its position in ALL.
+	fn test_all ( )
+	{
+		for i in 0..Body::COUNT
+		{
+			assert_eq!(Body::ALL[i] as usize, i);
+		}
+	}
+
+//										~ position ~											 //
+	#[test]
+	// At J2000 the Sun is at 18h 45m, -23 degrees.
+	fn test_position_sun_j2000 ( )
+	{
+		let sun = Body::Sun.position(Epoch{days: 0.0});
+		let expected = Equatorial{ra: Degrees(281.29).as_radians(), dec: Degrees(-23.03).as_radians()};
+		assert!(sun.angle_distance(expected) < Degrees(0.05).as_radians());
+	}
+
+	#[test]
+	// At the march equinox of 2024 the Sun is on the equinox of date.
+	// The J2000 frame is 0.34 degrees of precession behind.
+	fn test_position_sun_equinox ( )
+	{
+		let sun = Body::Sun.position(Epoch::from_utc(2024, 3, 20, 3, 6, 0.0));
+		let expected = Equatorial{ra: Degrees(359.690).as_radians(), dec: Degrees(-0.1345).as_radians()};
+		assert!(sun.angle_distance(expected) < Degrees(0.05).as_radians());
+	}
+
+	#[test]
+	// Around opposition, the planets are opposite the Sun (excluding their ecliptic latitude).
+	fn test_position_opposition ( )
+	{
+		assert!(175.0 < elongation(Body::Sun, Body::Mars,    Epoch::from_utc(2020, 10, 13, 23, 0, 0.0)));
+		assert!(178.0 < elongation(Body::Sun, Body::Jupiter, Epoch::from_utc(2023, 11,  3,  5, 0, 0.0)));
+		assert!(177.0 < elongation(Body::Sun, Body::Saturn,  Epoch::from_utc(2023,  8, 27,  8, 0, 0.0)));
+		assert!(178.0 < elongation(Body::Sun, Body::Uranus,  Epoch::from_utc(2023, 11, 13, 17, 0, 0.0)));
+		assert!(177.0 < elongation(Body::Sun, Body::Neptune, Epoch::from_utc(2023,  9, 19, 12, 0, 0.0)));
+	}
+
+	#[test]
+	// Mercury and Venus never get far from the Sun.
+	fn test_position_greatest_elongation ( )
+	{
+		elongation(Body::Sun, Body::Venus,   Epoch::from_utc(2020, 3, 24, 22, 0, 0.0)).assert_close(&46.1, 0.3);
+		elongation(Body::Sun, Body::Mercury, Epoch::from_utc(2024, 3, 24, 22, 0, 0.0)).assert_close(&18.7, 0.3);
+	}
+
+	#[test]
+	// During a solar eclipse the Moon covers the Sun, during a lunar eclipse it is opposite.
+	fn test_position_moon_eclipse ( )
+	{
+		assert!(elongation(Body::Sun, Body::Moon, Epoch::from_utc(2024, 4, 8, 18, 18, 0.0)) < 0.6);
+		assert!(179.0 < elongation(Body::Sun, Body::Moon, Epoch::from_utc(2022, 11, 8, 11, 0, 0.0)));
+	}
+
+//										~ extent ~												 //
+	#[test]
+	// The Moon has parallax, the Sun is large and the planets are points.
+	fn test_extent ( )
+	{
+		assert!(Body::Sun.extent()     < Body::Moon.extent());
+		assert!(Body::Jupiter.extent() < Body::Sun.extent());
+		assert!(Radians(0.0)           < Body::Neptune.extent());
+	}
+
+//										~ moon ~												 //
+	#[test]
+	// The moon should be a unit vector close to the ecliptic.
+	fn test_moon ( )
+	{
+		for i in 0..100
+		{
+			let moon = Body::moon(i as Decimal / 1000.0);
+			moon.magnitude().assert_close(&1.0, 0.00001);
+			assert!(moon.z.abs() < Degrees(5.5).to_radians().sin());
+		}
+	}
+
+//										~ ecliptic_to_equatorial ~								 //
+	#[test]
+	// The equinox is shared, the pole of the ecliptic is tilted by the obliquity.
+	fn test_ecliptic_to_equatorial ( )
+	{
+		let x = Vector3{x: 1.0, y: 0.0, z: 0.0};
+		Body::ecliptic_to_equatorial(x).assert_close(&x, 0.00001);
+
+		let pole = Body::ecliptic_to_equatorial(Vector3{x: 0.0, y: 0.0, z: 1.0}).to_equatorial();
+		pole.dec.to_degrees().assert_close(&Degrees(90.0 - 23.43928), 0.0001);
+		pole.ra.to_degrees().assert_close(&Degrees(270.0), 0.0001);
+	}
+
+
+
+//###############################################################################################//
+//
+//										OrbitalElements
+//
+// pub fn at                ( &self, &OrbitalElements, Decimal ) -> OrbitalElements
+// pub fn heliocentric      ( &self ) -> Vector3
+// fn eccentric_anomaly     ( Radians, Decimal ) -> Radians
+//
+//###############################################################################################//
+//										~ at ~													 //
+	#[test]
+	fn test_at ( )
+	{
+		let rate  = OrbitalElements{semi_major_axis: 1.0, eccentricity: 0.1, inclination: Degrees(2.0),
+			mean_longitude: Degrees(360.0), perihelion: Degrees(3.0), node: Degrees(4.0)};
+		let found = circular(10.0).at(&rate, 0.5);
+		assert_eq!(found.semi_major_axis, 1.5);
+		assert_eq!(found.eccentricity,    0.05);
+		assert_eq!(found.inclination,     Degrees(1.0));
+		assert_eq!(found.mean_longitude,  Degrees(190.0));
+		assert_eq!(found.perihelion,      Degrees(1.5));
+		assert_eq!(found.node,            Degrees(2.0));
+	}
+
+//										~ heliocentric ~										 //
+	#[test]
+	// A circular orbit is at the mean longitude.
+	fn test_heliocentric_circular ( )
+	{
+		circular(0.0).heliocentric().assert_close(&Vector3{x: 1.0, y: 0.0, z: 0.0}, 0.00001);
+		circular(90.0).heliocentric().assert_close(&Vector3{x: 0.0, y: 1.0, z: 0.0}, 0.00001);
+	}
+
+	#[test]
+	// An eccentric orbit is closest to the sun at the perihelion.
+	fn test_heliocentric_perihelion ( )
+	{
+		let mut orbit = circular(90.0);
+		orbit.eccentricity = 0.5;
+		orbit.perihelion   = Degrees(90.0);
+		orbit.heliocentric().assert_close(&Vector3{x: 0.0, y: 0.5, z: 0.0}, 0.00001);
+		orbit.mean_longitude = Degrees(270.0);
+		orbit.heliocentric().assert_close(&Vector3{x: 0.0, y: -1.5, z: 0.0}, 0.00001);
+	}
+
+	#[test]
+	// The orbit is tilted around the node.
+	fn test_heliocentric_inclined ( )
+	{
+		let mut orbit = circular(90.0);
+		orbit.inclination = Degrees(30.0);
+		let found = orbit.heliocentric();
+		found.assert_close(&Vector3{x: 0.0, y: Degrees(30.0).to_radians().cos(), z: Degrees(30.0).to_radians().sin()}, 0.00001);
+	}
+
+//										~ eccentric_anomaly ~									 //
+	#[test]
+	fn test_eccentric_anomaly ( )
+	{
+		// A circular orbit has the same anomaly.
+		OrbitalElements::eccentric_anomaly(Radians(1.0), 0.0).assert_close(&Radians(1.0), 0.00001);
+		// The mean anomaly is wrapped.
+		OrbitalElements::eccentric_anomaly(Radians(1.0 + 4.0 * M_PI), 0.0).assert_close(&Radians(1.0), 0.00001);
+
+		// Kepler's equation should be satisfied.
+		let e = 0.2;
+		let found = OrbitalElements::eccentric_anomaly(Radians(2.0), e).0;
+		(found - e * found.sin()).assert_close(&2.0, 0.00001);
+	}
+}
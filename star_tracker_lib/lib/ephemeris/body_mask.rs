@@ -0,0 +1,187 @@
+//! Implementation of [BodyMask](crate::ephemeris::BodyMask)
+use crate::core_include::*;
+
+use crate::ephemeris::BodyMask;
+use crate::ephemeris::Body;
+use crate::ephemeris::Epoch;
+
+use crate::util::units::Equatorial;
+use crate::util::units::Quaternion;
+use crate::util::units::Radians;
+use crate::util::list::List;
+
+impl BodyMask
+{
+	/// Finds the position of every body at the epoch.
+	/// # Arguments
+	/// * `epoch`  - The time of the image or the catalogue.
+	/// * `margin` - The angle added to the extent of each body.
+	pub fn new ( epoch: Epoch, margin: Radians ) -> Self
+	{
+		let mut positions = [Equatorial{ra: Radians(0.0), dec: Radians(0.0)}; Body::COUNT];
+		for i in 0..Body::COUNT
+		{
+			positions[i] = Body::ALL[i].position(epoch);
+		}
+		return Self{margin: margin, positions: positions};
+	}
+
+
+	/// Finds the body which covers a point.
+	/// # Arguments
+	/// * `point` - The position to check (J2000 equatorial).
+	/// # Returns
+	/// The first body in [Body::ALL](crate::ephemeris::Body::ALL) within its extent and the margin.
+	pub fn find ( &self, point: Equatorial ) -> Option<Body>
+	{
+		for i in 0..Body::COUNT
+		{
+			let body = Body::ALL[i];
+			if point.angle_distance(self.positions[i]) <= body.extent() + self.margin
+			{
+				return Some(body);
+			}
+		}
+		return None;
+	}
+
+
+	/// Flags the observed stars which are covered by a body.
+	/// # Arguments
+	/// * `attitude` - The coarse rotation from inertial to camera.
+	/// * `stars`    - The observed stars (camera frame).
+	/// * `flags`    - The output, the body covering each star (same index as `stars`), cleared before use.
+	/// # Returns
+	/// The number of stars covered by a body.
+	pub fn flag ( &self, attitude: Quaternion, stars: &dyn List<Equatorial>, flags: &mut dyn List<Option<Body>> ) -> usize
+	{
+		flags.clear();
+		let to_world  = attitude.conjugate();
+		let mut count = 0;
+		for i in 0..stars.size()
+		{
+			let world = to_world.rotate_point(stars.get(i).to_vector3()).to_equatorial();
+			let body  = self.find(world);
+			if flags.push_back(body).is_err() { break; }
+			if body.is_some() { count += 1; }
+		}
+		return count;
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::ephemeris::BodyMask;
+	use crate::ephemeris::Body;
+	use crate::ephemeris::Epoch;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::Quaternion;
+	use crate::util::units::AngleAxis;
+	use crate::util::units::Vector3;
+	use crate::util::units::Degrees;
+	use crate::util::units::Radians;
+	use crate::util::aliases::Decimal;
+	use crate::util::list::ArrayList;
+	use crate::util::list::List;
+
+	// Every body is at ra = 0, except the one specified.
+	#[coverage(off)]
+	fn mask ( body: Body, position: Equatorial ) -> BodyMask
+	{
+		let far = Equatorial{ra: Radians(0.0), dec: Degrees(-80.0).as_radians()};
+		let mut positions = [far; Body::COUNT];
+		positions[body as usize] = position;
+		return BodyMask{margin: Degrees(0.5).as_radians(), positions: positions};
+	}
+
+	#[coverage(off)]
+	fn eq ( ra: Decimal, dec: Decimal ) -> Equatorial
+	{
+		return Equatorial{ra: Degrees(ra).as_radians(), dec: Degrees(dec).as_radians()};
+	}
+
+//###############################################################################################//
+//
+//										BodyMask
+//
+// pub fn new  ( Epoch, Radians ) -> Self
+// pub fn find ( &self, Equatorial ) -> Option<Body>
+// pub fn flag ( &self, Quaternion, &dyn List<Equatorial>, &mut dyn List<Option<Body>> ) -> usize
+//
+//###############################################################################################//
+//										~ new ~													 //
+	#[test]
+	// Each position should be the position of the body with the same index.
+	fn test_new ( )
+	{
+		let epoch = Epoch::from_utc(2024, 1, 1, 0, 0, 0.0);
+		let mask  = BodyMask::new(epoch, Degrees(1.0).as_radians());
+		assert_eq!(mask.margin, Degrees(1.0).as_radians());
+		for i in 0..Body::COUNT
+		{
+			assert_eq!(mask.positions[i], Body::ALL[i].position(epoch));
+		}
+	}
+
+//										~ find ~												 //
+	#[test]
+	// A point within the extent and margin is covered.
+	fn test_find ( )
+	{
+		let mask = mask(Body::Venus, eq(10.0, 10.0));
+		assert_eq!(mask.find(eq(10.0, 10.0)),  Some(Body::Venus));
+		assert_eq!(mask.find(eq(10.0, 10.54)), Some(Body::Venus));
+		assert_eq!(mask.find(eq(10.0, 10.56)), None);
+	}
+
+	#[test]
+	// The extent of the Moon is larger than a planet.
+	fn test_find_extent ( )
+	{
+		let mask = mask(Body::Moon, eq(10.0, 10.0));
+		assert_eq!(mask.find(eq(10.0, 12.0)), Some(Body::Moon));
+		assert_eq!(mask.find(eq(10.0, 12.2)), None);
+	}
+
+//										~ flag ~												 //
+	#[test]
+	// The stars are rotated into the world frame before checking.
+	fn test_flag ( )
+	{
+		let mask = mask(Body::Jupiter, eq(90.0, 0.0));
+		let attitude : Quaternion =
+			AngleAxis{angle: Degrees(30.0).as_radians(), axis: Vector3{x: 0.0, y: 1.0, z: 0.0}}.to_quaternion();
+		let to_camera = | point: Equatorial | attitude.rotate_point(point.to_vector3()).to_equatorial();
+
+		let stars = vec![to_camera(eq(90.0, 0.0)), to_camera(eq(0.0, 0.0)), to_camera(eq(90.0, 0.1))];
+		let mut flags : Vec<Option<Body>> = vec![Some(Body::Sun)];
+		assert_eq!(mask.flag(attitude, &stars, &mut flags), 2);
+		assert_eq!(flags, vec![Some(Body::Jupiter), None, Some(Body::Jupiter)]);
+	}
+
+	#[test]
+	// If the output is full, the remaining stars are not flagged.
+	fn test_flag_full ( )
+	{
+		let mask = mask(Body::Jupiter, eq(0.0, 0.0));
+		let identity = Quaternion{w: 1.0, x: 0.0, y: 0.0, z: 0.0};
+		let stars = vec![eq(10.0, 0.0), eq(0.0, 0.0), eq(0.0, 0.0)];
+		let mut flags : ArrayList<Option<Body>, 2> = ArrayList::new();
+		assert_eq!(mask.flag(identity, &stars, &mut flags), 1);
+		assert_eq!(flags.size(), 2);
+		assert_eq!(flags.get(0), None);
+	}
+}
@@ -0,0 +1,169 @@
+//! Implementation of [Epoch](crate::ephemeris::Epoch)
+use crate::ephemeris::Epoch;
+
+use crate::util::aliases::Decimal;
+
+impl Epoch
+{
+	/// The unix time of J2000.0 (2000-01-01 12:00:00 UTC).
+	pub const J2000_UNIX : i64 = 946_728_000;
+
+	/// The number of seconds in a day.
+	const SECONDS_PER_DAY : i64 = 86_400;
+
+	/// The number of days in a julian century.
+	const DAYS_PER_CENTURY : Decimal = 36525.0;
+
+
+	/// Creates an epoch from a UTC date and time.
+	/// The date is on the gregorian calendar.
+	/// # Arguments
+	/// * `year`   - The year (e.g. 2024).
+	/// * `month`  - The month (1 to 12).
+	/// * `day`    - The day of the month (1 to 31).
+	/// * `hour`   - The hour (0 to 23).
+	/// * `minute` - The minute (0 to 59).
+	/// * `second` - The second (0 to 60).
+	pub fn from_utc ( year: i32, month: u32, day: u32, hour: u32, minute: u32, second: Decimal ) -> Self
+	{
+		let days    = Self::days_from_civil(year as i64, month as i64, day as i64);
+		let seconds = days * Self::SECONDS_PER_DAY + hour as i64 * 3600 + minute as i64 * 60;
+		let mut epoch = Self::from_unix(seconds);
+		epoch.days += second / Self::SECONDS_PER_DAY as Decimal;
+		return epoch;
+	}
+
+
+	/// Creates an epoch from the unix time (seconds since 1970-01-01 00:00:00 UTC).
+	/// The whole days and the remaining seconds are converted separately so an f32 is accurate.
+	/// # Arguments
+	/// * `seconds` - The seconds since 1970 (leap seconds are ignored).
+	pub fn from_unix ( seconds: i64 ) -> Self
+	{
+		let since = seconds - Self::J2000_UNIX;
+		let days  = since.div_euclid(Self::SECONDS_PER_DAY) as Decimal;
+		let rem   = since.rem_euclid(Self::SECONDS_PER_DAY) as Decimal / Self::SECONDS_PER_DAY as Decimal;
+		return Self{days: days + rem};
+	}
+
+
+	/// The number of julian centuries since J2000.0.
+	pub fn centuries ( &self ) -> Decimal
+	{
+		return self.days / Self::DAYS_PER_CENTURY;
+	}
+
+
+	/// The number of days from 1970-01-01 to the date (Howard Hinnant's *days_from_civil*).
+	/// # Arguments
+	/// * `year`  - The year.
+	/// * `month` - The month (1 to 12).
+	/// * `day`   - The day of the month (1 to 31).
+	fn days_from_civil ( year: i64, month: i64, day: i64 ) -> i64
+	{
+		// The year starts in March so the leap day is at the end.
+		let year        = if month <= 2 { year - 1 } else { year };
+		let era         = year.div_euclid(400);
+		let year_of_era = year.rem_euclid(400);
+		let month_march = if 2 < month { month - 3 } else { month + 9 };
+		let day_of_year = (153 * month_march + 2) / 5 + day - 1;
+		let day_of_era  = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+		return era * 146_097 + day_of_era - 719_468;
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::ephemeris::Epoch;
+	use crate::util::test::TestEqual;
+
+//###############################################################################################//
+//
+//										Epoch
+//
+// pub fn from_utc        ( i32, u32, u32, u32, u32, Decimal ) -> Self
+// pub fn from_unix       ( i64 ) -> Self
+// pub fn centuries       ( &self ) -> Decimal
+// fn days_from_civil     ( i64, i64, i64 ) -> i64
+//
+//###############################################################################################//
+//										~ from_utc ~											 //
+	#[test]
+	// J2000 is midday on the first of January 2000.
+	fn test_from_utc_j2000 ( )
+	{
+		assert_eq!(Epoch::from_utc(2000, 1, 1, 12, 0, 0.0).days, 0.0);
+		assert_eq!(Epoch::from_utc(2000, 1, 1, 0,  0, 0.0).days, -0.5);
+	}
+
+	#[test]
+	// The time of day should be a fraction of a day.
+	fn test_from_utc_time ( )
+	{
+		Epoch::from_utc(2000, 1, 2, 18, 0, 0.0).days.assert_close(&1.25, 0.000001);
+		Epoch::from_utc(2000, 1, 1, 12, 1, 30.0).days.assert_close(&(90.0 / 86400.0), 0.000001);
+	}
+
+	#[test]
+	// 2000 and 2024 are leap years, 2100 is not.
+	fn test_from_utc_leap_year ( )
+	{
+		let feb = Epoch::from_utc(2024, 2, 28, 12, 0, 0.0).days;
+		let mar = Epoch::from_utc(2024, 3, 1,  12, 0, 0.0).days;
+		assert_eq!(mar - feb, 2.0);
+		assert_eq!(Epoch::from_utc(2001, 1, 1, 12, 0, 0.0).days, 366.0);
+
+		let feb = Epoch::from_utc(2100, 2, 28, 12, 0, 0.0).days;
+		let mar = Epoch::from_utc(2100, 3, 1,  12, 0, 0.0).days;
+		assert_eq!(mar - feb, 1.0);
+	}
+
+	#[test]
+	// Before 2000, the days are negative.
+	fn test_from_utc_before ( )
+	{
+		assert_eq!(Epoch::from_utc(1999, 12, 31, 12, 0, 0.0).days, -1.0);
+		assert_eq!(Epoch::from_utc(1970, 1, 1, 0, 0, 0.0), Epoch::from_unix(0));
+	}
+
+//										~ from_unix ~											 //
+	#[test]
+	fn test_from_unix ( )
+	{
+		assert_eq!(Epoch::from_unix(Epoch::J2000_UNIX).days, 0.0);
+		assert_eq!(Epoch::from_unix(Epoch::J2000_UNIX + 43200).days, 0.5);
+		assert_eq!(Epoch::from_unix(Epoch::J2000_UNIX - 43200).days, -0.5);
+		// 2024-01-01 00:00:00 UTC
+		assert_eq!(Epoch::from_unix(1_704_067_200), Epoch::from_utc(2024, 1, 1, 0, 0, 0.0));
+	}
+
+//										~ centuries ~											 //
+	#[test]
+	fn test_centuries ( )
+	{
+		assert_eq!(Epoch{days: 36525.0}.centuries(), 1.0);
+		assert_eq!(Epoch{days: -36525.0 / 2.0}.centuries(), -0.5);
+	}
+
+//										~ days_from_civil ~										 //
+	#[test]
+	fn test_days_from_civil ( )
+	{
+		assert_eq!(Epoch::days_from_civil(1970, 1, 1), 0);
+		assert_eq!(Epoch::days_from_civil(1970, 3, 1), 59);
+		assert_eq!(Epoch::days_from_civil(1969, 12, 31), -1);
+		assert_eq!(Epoch::days_from_civil(2000, 3, 1), 11_017);
+	}
+}
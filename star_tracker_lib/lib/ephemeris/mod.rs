@@ -0,0 +1,122 @@
+//! `ephemeris` finds the position of the Sun, Moon and planets so they are not confused with stars.
+//!
+//! The planets appear as bright point sources which do not match any catalogue star.
+//! Venus and Jupiter are often the brightest blob in the image, so they are the first star used by the lost-in-space search.
+//! Given the time and a coarse attitude, the blobs which are close to a solar system body can be removed.
+//! When generating the database, the stars which are covered by a body can also be excluded.
+//!
+//! # Accuracy
+//! This is a low precision ephemeris (suitable for a microcontroller), it is NOT suitable for navigation:
+//! - **Planets**: Keplerian elements with linear rates (Standish, *Keplerian Elements for Approximate Positions of the Major Planets*, JPL).
+//!   Valid from 1800 to 2050, the error is in the order of arcminutes.
+//! - **Sun**: The opposite of the Earth-Moon barycenter from the same elements.
+//! - **Moon**: The low precision formula of the *Astronomical Almanac*, the error is up to 0.3 degrees.
+//!   The position is geocentric, a satellite in low earth orbit sees the Moon up to 1 degree away (parallax).
+//!
+//! The positions are geocentric in the J2000 equatorial frame (the same as the catalogue).
+//! The difference between UTC and terrestrial time (about a minute) is ignored.
+//! [extent](crate::ephemeris::Body::extent) includes the size of the body, the error of the ephemeris and the parallax.
+//!
+//! # Example
+//! ```
+//! use star_tracker_lib::ephemeris::Epoch;
+//! use star_tracker_lib::ephemeris::Body;
+//! use star_tracker_lib::ephemeris::BodyMask;
+//! use star_tracker_lib::util::units::Degrees;
+//!
+//! // Jupiter was in opposition on the 3rd of November 2023.
+//! let epoch   = Epoch::from_utc(2023, 11, 3, 5, 0, 0.0);
+//! let sun     = Body::Sun.position(epoch);
+//! let jupiter = Body::Jupiter.position(epoch);
+//! assert!(Degrees(178.0).as_radians() < sun.angle_distance(jupiter));
+//!
+//! // Anything within 0.5 degrees of the edge of a body is masked.
+//! let mask = BodyMask::new(epoch, Degrees(0.5).as_radians());
+//! assert_eq!(mask.find(jupiter), Some(Body::Jupiter));
+//! ```
+
+use crate::core_include::*;
+
+use crate::util::units::Equatorial;
+use crate::util::units::Degrees;
+use crate::util::units::Radians;
+use crate::util::aliases::Decimal;
+
+pub mod epoch;
+pub mod body;
+pub mod body_mask;
+
+
+
+//###############################################################################################//
+//
+//										Structs
+//
+//###############################################################################################//
+
+/// A moment in time as the number of days since J2000.0 (2000-01-01 12:00:00 UTC).
+///
+/// Days since J2000 are used instead of a julian date as a julian date does not fit in an f32 with enough precision.
+/// Use [from_utc](crate::ephemeris::Epoch::from_utc) or [from_unix](crate::ephemeris::Epoch::from_unix).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Epoch
+{
+	/// The days since J2000.0, this is negative before 2000.
+	pub days: Decimal,
+}
+
+
+/// The Keplerian elements of an orbit around the Sun (J2000 ecliptic frame).
+/// The rate of change of each element is also stored as an `OrbitalElements`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct OrbitalElements
+{
+	/// The semi major axis (au).
+	pub semi_major_axis : Decimal,
+	/// The eccentricity.
+	pub eccentricity    : Decimal,
+	/// The inclination to the ecliptic.
+	pub inclination     : Degrees,
+	/// The mean longitude.
+	pub mean_longitude  : Degrees,
+	/// The longitude of the perihelion.
+	pub perihelion      : Degrees,
+	/// The longitude of the ascending node.
+	pub node            : Degrees,
+}
+
+
+/// The positions of the solar system bodies at an epoch.
+/// Use this to find if an observed star or catalogue star is a solar system body.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BodyMask
+{
+	/// The angle added to the [extent](crate::ephemeris::Body::extent) of each body.
+	/// This should include the error of the attitude and the size of the blob of a bright body.
+	pub margin    : Radians,
+	/// The position of each body (J2000 equatorial), the index is the order of [Body::ALL](crate::ephemeris::Body::ALL).
+	pub positions : [Equatorial; Body::COUNT],
+}
+
+
+
+//###############################################################################################//
+//
+//										Enums
+//
+//###############################################################################################//
+
+/// A solar system body which can be seen by a star tracker.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Body
+{
+	Sun,
+	Moon,
+	Mercury,
+	Venus,
+	Mars,
+	Jupiter,
+	Saturn,
+	Uranus,
+	Neptune,
+}
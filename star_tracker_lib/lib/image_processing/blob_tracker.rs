@@ -0,0 +1,576 @@
+//! Implementation of [BlobTracker](crate::image_processing::BlobTracker), [BlobTrack](crate::image_processing::BlobTrack) and [BlobPrediction](crate::image_processing::BlobPrediction).
+use crate::core_include::*;
+
+use crate::image_processing::Blob;
+use crate::image_processing::BlobTracker;
+use crate::image_processing::BlobTrack;
+use crate::image_processing::BlobPrediction;
+use crate::image_processing::BlobPredictionShift;
+use crate::image_processing::BlobPredictionRotation;
+
+use crate::projection::SpaceImage;
+use crate::projection::SpaceCamera;
+
+use crate::util::units::Vector2;
+use crate::util::aliases::Decimal;
+use crate::util::list::ArrayList;
+use crate::util::list::List;
+
+impl <const N_TRACKS: usize, const N_HISTORY: usize> BlobTracker <N_TRACKS, N_HISTORY>
+{
+	/// Creates a tracker with no tracks.
+	/// # Arguments
+	/// * `gate`       - The max distance (pixels) between the predicted track and a blob.
+	/// * `max_missed` - The number of frames a track can be missed before it is removed.
+	pub fn new ( gate: Decimal, max_missed: usize ) -> Self
+	{
+		return Self{gate: gate, max_missed: max_missed, tracks: ArrayList::new(), next_id: 0};
+	}
+
+
+	/// Associates the blobs of a new frame with the tracks.
+	///
+	/// * Every track is moved by `prediction`.
+	/// * Associated tracks record the blob.
+	/// * Tracks which were not associated are counted as missed, if missed more than `max_missed` they are removed.
+	/// * Blobs which were not associated and are outside the gate of every track start a new track (if there is space).  
+	///   A blob inside the gate which lost to a closer blob or track is left unassigned for this frame, it is likely a track which was not resolved.
+	///
+	/// # Arguments
+	/// * `blobs`      - The blobs of the new frame.
+	/// * `prediction` - Moves the tracks from the previous frame into the new frame.
+	pub fn update ( &mut self, blobs: &dyn List<Blob>, prediction: &dyn BlobPrediction )
+	{
+		for i in 0..self.tracks.size()
+		{
+			let mut track = self.tracks.get(i);
+			track.position = prediction.predict(track.position);
+			let _ = self.tracks.set(i, track);
+		}
+
+		// The associations must be found before any track is changed.
+		let mut associated : ArrayList<Option<usize>, N_TRACKS> = ArrayList::new();
+		let mut predicted  : ArrayList<Vector2, N_TRACKS>       = ArrayList::new();
+		for i in 0..self.tracks.size()
+		{
+			let _ = associated.push_back(self.associate(i, blobs));
+			let _ = predicted.push_back(self.tracks.get(i).position);
+		}
+
+		let mut i = self.tracks.size();
+		while 0 < i
+		{
+			i -= 1;
+			let mut track = self.tracks.get(i);
+			if let Some(blob) = associated.get(i)
+			{
+				track.observe(&blobs.get(blob));
+			}
+			else
+			{
+				track.missed += 1;
+			}
+
+			if self.max_missed < track.missed
+			{
+				let _ = self.tracks.pop(i);
+			}
+			else
+			{
+				let _ = self.tracks.set(i, track);
+			}
+		}
+
+		for i in 0..blobs.size()
+		{
+			let mut used = false;
+			for j in 0..associated.size()
+			{
+				used |= associated.get(j) == Some(i);
+				used |= (blobs.get(i).centroid - predicted.get(j)).magnitude() <= self.gate;
+			}
+
+			if !used && !self.tracks.is_full()
+			{
+				let _ = self.tracks.push_back(BlobTrack::new(self.next_id, &blobs.get(i)));
+				self.next_id += 1;
+			}
+		}
+	}
+
+
+	/// Sets the catalogue id of a track so it does not need to be identified again.
+	/// # Arguments
+	/// * `id`        - The id of the track.
+	/// * `catalogue` - The id of the star in the catalogue.
+	/// # Returns
+	/// False if there is no track with the id.
+	pub fn identify ( &mut self, id: usize, catalogue: usize ) -> bool
+	{
+		for i in 0..self.tracks.size()
+		{
+			let mut track = self.tracks.get(i);
+			if track.id == id
+			{
+				track.catalogue = Some(catalogue);
+				let _ = self.tracks.set(i, track);
+				return true;
+			}
+		}
+		return false;
+	}
+
+
+	/// Finds the blob which should be associated with a track.
+	/// # Returns
+	/// The index of the closest blob to the track if it is within `gate` and the track is the closest track to the blob.
+	fn associate ( &self, track: usize, blobs: &dyn List<Blob> ) -> Option<usize>
+	{
+		let position = self.tracks.get(track).position;
+		let mut best          : Option<usize> = None;
+		let mut best_distance = self.gate;
+		for i in 0..blobs.size()
+		{
+			let distance = (blobs.get(i).centroid - position).magnitude();
+			if distance <= best_distance
+			{
+				best          = Some(i);
+				best_distance = distance;
+			}
+		}
+
+		// If another track is closer, the blob belongs to that track (if equal, the first track).
+		let blob = best?;
+		let centroid = blobs.get(blob).centroid;
+		for i in 0..self.tracks.size()
+		{
+			let distance = (self.tracks.get(i).position - centroid).magnitude();
+			if i != track && (distance < best_distance || (distance == best_distance && i < track))
+			{
+				return None;
+			}
+		}
+		return Some(blob);
+	}
+}
+
+
+
+impl <const N_HISTORY: usize> BlobTrack <N_HISTORY>
+{
+	/// Creates a track from a blob.
+	/// # Arguments
+	/// * `id`   - The unique id of the track.
+	/// * `blob` - The first observation.
+	pub fn new ( id: usize, blob: &Blob ) -> Self
+	{
+		let mut track = Self
+		{
+			id:          id,
+			catalogue:   None,
+			position:    blob.centroid,
+			missed:      0,
+			centroids:   ArrayList::new(),
+			intensities: ArrayList::new(),
+		};
+		track.observe(blob);
+		return track;
+	}
+
+
+	/// Records the blob as the latest observation, if the history is full, the oldest is removed.
+	pub fn observe ( &mut self, blob: &Blob )
+	{
+		if self.centroids.is_full()
+		{
+			let _ = self.centroids.pop(0);
+			let _ = self.intensities.pop(0);
+		}
+		let _ = self.centroids.push_back(blob.centroid);
+		let _ = self.intensities.push_back(blob.intensity);
+		self.position = blob.centroid;
+		self.missed   = 0;
+	}
+}
+
+
+
+impl BlobPrediction for BlobPredictionShift
+{
+	/// Adds the shift.
+	fn predict ( &self, centroid: Vector2 ) -> Vector2
+	{
+		return centroid + self.shift;
+	}
+}
+
+
+impl BlobPrediction for BlobPredictionRotation
+{
+	/// Projects the centroid into camera space, rotates it and projects it back onto the image.
+	/// If the point is rotated behind the camera, it is not moved.
+	fn predict ( &self, centroid: Vector2 ) -> Vector2
+	{
+		let camera  = self.intrinsic.from_image(SpaceImage(centroid));
+		let rotated = self.delta.rotate_point(camera.0);
+		if rotated.z <= 0.0
+		{
+			return centroid;
+		}
+		return self.intrinsic.to_image(SpaceCamera(rotated)).0;
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::image_processing::Blob;
+	use crate::image_processing::BlobTracker;
+	use crate::image_processing::BlobTrack;
+	use crate::image_processing::BlobPrediction;
+	use crate::image_processing::BlobPredictionShift;
+	use crate::image_processing::BlobPredictionRotation;
+	use crate::image_processing::MockBlobPrediction;
+
+	use crate::projection::IntrinsicParameters;
+	use crate::projection::SpaceImage;
+	use crate::projection::SpaceCamera;
+
+	use crate::util::units::Quaternion;
+	use crate::util::units::AngleAxis;
+	use crate::util::units::Vector2;
+	use crate::util::units::Vector3;
+	use crate::util::units::Degrees;
+	use crate::util::aliases::Decimal;
+	use crate::util::aliases::UInt;
+	use crate::util::list::List;
+	use crate::util::test::TestEqual;
+
+	#[coverage(off)]
+	fn blob ( x: Decimal, y: Decimal, intensity: UInt ) -> Blob
+	{
+		return Blob{size: 4, intensity: intensity, centroid: Vector2{x: x, y: y}};
+	}
+
+	#[coverage(off)]
+	fn still ( ) -> BlobPredictionShift
+	{
+		return BlobPredictionShift{shift: Vector2{x: 0.0, y: 0.0}};
+	}
+
+	// A tracker with a track at (10, 10) and (50, 20).
+	#[coverage(off)]
+	fn tracker ( ) -> BlobTracker<4, 3>
+	{
+		let mut tracker = BlobTracker::new(3.0, 1);
+		tracker.update(&vec![blob(10.0, 10.0, 100), blob(50.0, 20.0, 200)], &still());
+		return tracker;
+	}
+
+
+//###############################################################################################//
+//
+//										BlobTracker
+//
+// pub fn new      ( Decimal, usize ) -> Self
+// pub fn update   ( &mut self, &dyn List<Blob>, &dyn BlobPrediction )
+// pub fn identify ( &mut self, usize, usize ) -> bool
+// fn associate    ( &self, usize, &dyn List<Blob> ) -> Option<usize>
+//
+//###############################################################################################//
+//										~ new ~													 //
+	#[test]
+	fn test_new ( )
+	{
+		let tracker : BlobTracker<4, 3> = BlobTracker::new(3.0, 2);
+		assert_eq!(tracker.gate, 3.0);
+		assert_eq!(tracker.max_missed, 2);
+		assert_eq!(tracker.tracks.size(), 0);
+		assert_eq!(tracker.next_id, 0);
+	}
+
+//										~ update ~												 //
+	#[test]
+	// Each blob of the first frame should start a track.
+	fn test_update_first ( )
+	{
+		let tracker = tracker();
+		assert_eq!(tracker.tracks.size(), 2);
+		assert_eq!(tracker.tracks.get(0).id, 0);
+		assert_eq!(tracker.tracks.get(1).id, 1);
+		assert_eq!(tracker.tracks.get(1).position, Vector2{x: 50.0, y: 20.0});
+		assert_eq!(tracker.next_id, 2);
+	}
+
+	#[test]
+	// The same stars should keep their id when they move and change order.
+	fn test_update_associate ( )
+	{
+		let mut tracker = tracker();
+		tracker.update(&vec![blob(51.0, 21.0, 210), blob(11.0, 9.0, 110)], &still());
+		assert_eq!(tracker.tracks.size(), 2);
+		let track = tracker.tracks.get(0);
+		assert_eq!(track.id, 0);
+		assert_eq!(track.position, Vector2{x: 11.0, y: 9.0});
+		assert_eq!(track.centroids.size(), 2);
+		assert_eq!(track.centroids.get(0), Vector2{x: 10.0, y: 10.0});
+		assert_eq!(track.intensities.get(1), 110);
+		assert_eq!(tracker.tracks.get(1).position, Vector2{x: 51.0, y: 21.0});
+		assert_eq!(tracker.next_id, 2);
+	}
+
+	#[test]
+	// Blobs outside the gate should start new tracks.
+	fn test_update_gate ( )
+	{
+		let mut tracker = tracker();
+		tracker.update(&vec![blob(15.0, 10.0, 100)], &still());
+		assert_eq!(tracker.tracks.size(), 3);
+		assert_eq!(tracker.tracks.get(0).missed, 1);
+		assert_eq!(tracker.tracks.get(1).missed, 1);
+		assert_eq!(tracker.tracks.get(2).id, 2);
+	}
+
+	#[test]
+	// A blob inside the gate which is not associated should not start a new track.
+	fn test_update_gate_conflict ( )
+	{
+		let mut tracker = tracker();
+		// Both blobs are in the gate of track 0, the closer one is associated.
+		tracker.update(&vec![blob(12.0, 10.0, 100), blob(11.0, 10.0, 100)], &still());
+		assert_eq!(tracker.tracks.size(), 2);
+		assert_eq!(tracker.tracks.get(0).position, Vector2{x: 11.0, y: 10.0});
+		assert_eq!(tracker.next_id, 2);
+	}
+
+	#[test]
+	// A track which loses its closest blob to another track should not create a track from its other blob.
+	fn test_update_gate_conflict_track ( )
+	{
+		let mut tracker : BlobTracker<4, 3> = BlobTracker::new(3.0, 1);
+		tracker.update(&vec![blob(10.0, 10.0, 1), blob(13.0, 10.0, 1)], &still());
+		// Track 0 is closest to (12, 10) which belongs to track 1, (7.5, 10) is in the gate of track 0.
+		tracker.update(&vec![blob(12.0, 10.0, 1), blob(7.5, 10.0, 1)], &still());
+		assert_eq!(tracker.tracks.size(), 2);
+		assert_eq!(tracker.tracks.get(0).missed, 1);
+		assert_eq!(tracker.tracks.get(1).position, Vector2{x: 12.0, y: 10.0});
+		assert_eq!(tracker.next_id, 2);
+	}
+
+	#[test]
+	// With a prediction, blobs which moved further than the gate should be associated.
+	fn test_update_prediction ( )
+	{
+		let mut tracker = tracker();
+		let shift = BlobPredictionShift{shift: Vector2{x: 5.0, y: 0.0}};
+		tracker.update(&vec![blob(15.0, 10.0, 100), blob(55.5, 20.0, 100)], &shift);
+		assert_eq!(tracker.tracks.size(), 2);
+		assert_eq!(tracker.tracks.get(0).position, Vector2{x: 15.0, y: 10.0});
+		assert_eq!(tracker.tracks.get(1).position, Vector2{x: 55.5, y: 20.0});
+	}
+
+	#[test]
+	// A missed track should move with the prediction and be removed after max_missed frames.
+	fn test_update_missed ( )
+	{
+		let mut tracker = tracker();
+		let shift = BlobPredictionShift{shift: Vector2{x: 1.0, y: 0.0}};
+		tracker.update(&vec![blob(11.0, 10.0, 100)], &shift);
+		assert_eq!(tracker.tracks.size(), 2);
+		assert_eq!(tracker.tracks.get(1).missed, 1);
+		assert_eq!(tracker.tracks.get(1).position, Vector2{x: 51.0, y: 20.0});
+		assert_eq!(tracker.tracks.get(1).centroids.size(), 1);
+
+		tracker.update(&vec![blob(12.0, 10.0, 100)], &shift);
+		assert_eq!(tracker.tracks.size(), 1);
+		assert_eq!(tracker.tracks.get(0).id, 0);
+	}
+
+	#[test]
+	// A track which is observed again should reset the missed count.
+	fn test_update_found_again ( )
+	{
+		let mut tracker = tracker();
+		tracker.update(&vec![blob(10.0, 10.0, 100)], &still());
+		tracker.update(&vec![blob(10.0, 10.0, 100), blob(50.0, 20.0, 100)], &still());
+		assert_eq!(tracker.tracks.size(), 2);
+		assert_eq!(tracker.tracks.get(1).missed, 0);
+		assert_eq!(tracker.tracks.get(1).id, 1);
+	}
+
+	#[test]
+	// If there are too many tracks, new blobs are ignored.
+	fn test_update_full ( )
+	{
+		let mut tracker : BlobTracker<2, 3> = BlobTracker::new(3.0, 1);
+		tracker.update(&vec![blob(0.0, 0.0, 1), blob(10.0, 0.0, 1), blob(20.0, 0.0, 1)], &still());
+		assert_eq!(tracker.tracks.size(), 2);
+		assert_eq!(tracker.next_id, 2);
+	}
+
+	#[test]
+	// The catalogue id should be carried forward.
+	fn test_update_catalogue ( )
+	{
+		let mut tracker = tracker();
+		tracker.identify(1, 42);
+		tracker.update(&vec![blob(50.5, 20.0, 100)], &still());
+		assert_eq!(tracker.tracks.get(1).catalogue, Some(42));
+		assert_eq!(tracker.tracks.get(0).catalogue, None);
+	}
+
+	#[test]
+	// Every track should be predicted once per frame.
+	fn test_update_predict_calls ( )
+	{
+		let mut tracker = tracker();
+		let mut prediction = MockBlobPrediction::new();
+		prediction.expect_predict().times(2).returning(|c| c);
+		tracker.update(&Vec::new(), &prediction);
+	}
+
+//										~ identify ~											 //
+	#[test]
+	fn test_identify ( )
+	{
+		let mut tracker = tracker();
+		assert!(tracker.identify(0, 7));
+		assert_eq!(tracker.tracks.get(0).catalogue, Some(7));
+		assert!(!tracker.identify(5, 7));
+	}
+
+//										~ associate ~											 //
+	#[test]
+	// The closest blob within the gate should be associated.
+	fn test_associate ( )
+	{
+		let tracker = tracker();
+		let blobs = vec![blob(12.5, 10.0, 1), blob(11.0, 10.0, 1), blob(30.0, 10.0, 1)];
+		assert_eq!(tracker.associate(0, &blobs), Some(1));
+		assert_eq!(tracker.associate(1, &blobs), None);
+	}
+
+	#[test]
+	// If the blob is closer to another track, it should not be associated.
+	fn test_associate_closer_track ( )
+	{
+		let mut tracker : BlobTracker<4, 3> = BlobTracker::new(3.0, 1);
+		tracker.update(&vec![blob(10.0, 10.0, 1), blob(13.0, 10.0, 1)], &still());
+		let blobs = vec![blob(12.0, 10.0, 1)];
+		assert_eq!(tracker.associate(0, &blobs), None);
+		assert_eq!(tracker.associate(1, &blobs), Some(0));
+	}
+
+	#[test]
+	// If two tracks are the same distance from the blob, the first track gets the blob.
+	fn test_associate_equal ( )
+	{
+		let mut tracker : BlobTracker<4, 3> = BlobTracker::new(3.0, 1);
+		tracker.update(&vec![blob(10.0, 10.0, 1), blob(14.0, 10.0, 1)], &still());
+		let blobs = vec![blob(12.0, 10.0, 1)];
+		assert_eq!(tracker.associate(0, &blobs), Some(0));
+		assert_eq!(tracker.associate(1, &blobs), None);
+	}
+
+
+
+//###############################################################################################//
+//
+//										BlobTrack
+//
+// pub fn new     ( usize, &Blob ) -> Self
+// pub fn observe ( &mut self, &Blob )
+//
+//###############################################################################################//
+//										~ new ~													 //
+	#[test]
+	fn test_track_new ( )
+	{
+		let track : BlobTrack<3> = BlobTrack::new(4, &blob(1.0, 2.0, 3));
+		assert_eq!(track.id, 4);
+		assert_eq!(track.catalogue, None);
+		assert_eq!(track.position, Vector2{x: 1.0, y: 2.0});
+		assert_eq!(track.missed, 0);
+		assert_eq!(track.centroids.size(), 1);
+		assert_eq!(track.intensities.get(0), 3);
+	}
+
+//										~ observe ~												 //
+	#[test]
+	// The history should be bounded, the oldest observation is removed.
+	fn test_track_observe ( )
+	{
+		let mut track : BlobTrack<2> = BlobTrack::new(0, &blob(1.0, 0.0, 1));
+		track.missed = 3;
+		track.observe(&blob(2.0, 0.0, 2));
+		track.observe(&blob(3.0, 0.0, 3));
+		assert_eq!(track.missed, 0);
+		assert_eq!(track.position, Vector2{x: 3.0, y: 0.0});
+		assert_eq!(track.centroids.size(), 2);
+		assert_eq!(track.centroids.get(0), Vector2{x: 2.0, y: 0.0});
+		assert_eq!(track.centroids.get(1), Vector2{x: 3.0, y: 0.0});
+		assert_eq!(track.intensities.get(0), 2);
+		assert_eq!(track.intensities.get(1), 3);
+	}
+
+
+
+//###############################################################################################//
+//
+//										BlobPrediction
+//
+// fn BlobPredictionShift::predict    ( &self, Vector2 ) -> Vector2
+// fn BlobPredictionRotation::predict ( &self, Vector2 ) -> Vector2
+//
+//###############################################################################################//
+	#[test]
+	fn test_shift_predict ( )
+	{
+		let shift = BlobPredictionShift{shift: Vector2{x: 1.0, y: -2.0}};
+		assert_eq!(shift.predict(Vector2{x: 3.0, y: 3.0}), Vector2{x: 4.0, y: 1.0});
+	}
+
+	#[test]
+	// The centroid should move to where the rotated star is projected.
+	fn test_rotation_predict ( )
+	{
+		let intrinsic = IntrinsicParameters::from_fov(Degrees(20.0).as_radians(), 100.0, Vector2{x: 50.0, y: 50.0});
+		let delta = AngleAxis{angle: Degrees(1.0).as_radians(), axis: Vector3{x: 0.0, y: 1.0, z: 0.0}}.to_quaternion();
+		let prediction = BlobPredictionRotation{delta: delta, intrinsic: intrinsic};
+
+		let centroid = Vector2{x: 40.0, y: 55.0};
+		let expected = intrinsic.to_image(SpaceCamera(delta.rotate_point(intrinsic.from_image(SpaceImage(centroid)).0))).0;
+		prediction.predict(centroid).assert_close(&expected, 0.0001);
+		assert!(0.1 < (prediction.predict(centroid) - centroid).magnitude());
+	}
+
+	#[test]
+	// A point rotated behind the camera should not move.
+	fn test_rotation_predict_behind ( )
+	{
+		let intrinsic = IntrinsicParameters::from_fov(Degrees(20.0).as_radians(), 100.0, Vector2{x: 50.0, y: 50.0});
+		let delta = AngleAxis{angle: Degrees(180.0).as_radians(), axis: Vector3{x: 0.0, y: 1.0, z: 0.0}}.to_quaternion();
+		let prediction = BlobPredictionRotation{delta: delta, intrinsic: intrinsic};
+		assert_eq!(prediction.predict(Vector2{x: 40.0, y: 55.0}), Vector2{x: 40.0, y: 55.0});
+	}
+
+	#[test]
+	// No rotation, no movement.
+	fn test_rotation_predict_identity ( )
+	{
+		let intrinsic = IntrinsicParameters::from_fov(Degrees(20.0).as_radians(), 100.0, Vector2{x: 50.0, y: 50.0});
+		let prediction = BlobPredictionRotation{delta: Quaternion{w: 1.0, x: 0.0, y: 0.0, z: 0.0}, intrinsic: intrinsic};
+		prediction.predict(Vector2{x: 40.0, y: 55.0}).assert_close(&Vector2{x: 40.0, y: 55.0}, 0.0001);
+	}
+}
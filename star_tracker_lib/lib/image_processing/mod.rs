@@ -70,6 +70,45 @@
 //! let mut stars_2d : Vec<Vector2> = Vec::new();
 //! Blob::to_vector2(&blobs, &mut stars_2d);
 //! ```
+//!
+//! # Tracking Blobs Between Frames
+//! When tracking, the same star must be found in each frame without identifying it again.  
+//! `BlobTracker` associates each blob with the closest track (after the track has been moved by a `BlobPrediction`).  
+//! A blob is only associated if it is within `gate` pixels and the blob and track are each others closest.  
+//! A blob within the gate of a track which is not associated is ignored for that frame instead of starting a new track.  
+//! Each track has a persistent id, a history of centroids and intensities and the catalogue id once it is identified.
+//! ```
+//! use star_tracker_lib::image_processing::Blob;
+//! use star_tracker_lib::image_processing::BlobTracker;
+//! use star_tracker_lib::image_processing::BlobPredictionShift;
+//! use star_tracker_lib::util::units::Vector2;
+//! use star_tracker_lib::util::list::List;
+//!
+//! // Up to 20 tracks, each remembers the last 5 frames.
+//! // Blobs can be 3 pixels from the prediction, a track is removed if it is missed for 2 frames.
+//! let mut tracker : BlobTracker<20, 5> = BlobTracker::new(3.0, 2);
+//!
+//! let frame_1 = vec![
+//! 	Blob{size: 4, intensity: 100, centroid: Vector2{x: 10.0, y: 10.0}},
+//! 	Blob{size: 4, intensity: 200, centroid: Vector2{x: 50.0, y: 20.0}}];
+//! tracker.update(&frame_1, &BlobPredictionShift{shift: Vector2{x: 0.0, y: 0.0}});
+//!
+//! // Blob 1 was identified as catalogue star 42.
+//! let id = tracker.tracks.get(1).id;
+//! tracker.identify(id, 42);
+//!
+//! // The camera rotated, the stars moved roughly 5 pixels in x and are in a different order.
+//! let frame_2 = vec![
+//! 	Blob{size: 4, intensity: 190, centroid: Vector2{x: 55.5, y: 20.0}},
+//! 	Blob{size: 4, intensity: 110, centroid: Vector2{x: 15.5, y: 10.0}}];
+//! tracker.update(&frame_2, &BlobPredictionShift{shift: Vector2{x: 5.0, y: 0.0}});
+//!
+//! let track = tracker.tracks.get(1);
+//! assert_eq!(track.id, id);
+//! assert_eq!(track.catalogue, Some(42));
+//! assert_eq!(track.centroids.size(), 2);
+//! assert_eq!(track.position, Vector2{x: 55.5, y: 20.0});
+//! ```
 
 pub mod image;
 pub mod image_cropped;
@@ -78,14 +117,23 @@ pub mod image_word;
 pub mod image_c;
 pub mod blob;
 pub mod threshold;
+pub mod blob_tracker;
 
 use crate::core_include::*;
 
 use crate::util::units::Vector2;
+use crate::util::units::Quaternion;
 use crate::util::units::Pixel;
 use crate::util::aliases::Byte;
 use crate::util::aliases::UInt;
+use crate::util::aliases::Decimal;
 use crate::util::word::WordList;
+use crate::util::list::ArrayList;
+
+use crate::projection::IntrinsicParameters;
+
+#[cfg(test)] use mockall::predicate::*;
+#[cfg(test)] use mockall::*;
 
 pub use crate::image_processing::image::Image;
 //###############################################################################################//
@@ -442,6 +490,74 @@ pub struct Blob
 
 
 
+//###############################################################################################//
+//										---	Blob Tracker ---
+//###############################################################################################//
+
+/// Associates the blobs of each frame with the blobs of the previous frames.  
+///
+/// Each track is moved by a [BlobPrediction](crate::image_processing::BlobPrediction),
+/// then the closest blob within `gate` is associated if the track is also the closest track to the blob.  
+/// Blobs which are not associated start a new track, tracks which are missed for more than `max_missed` frames are removed.  
+/// Use [update](crate::image_processing::BlobTracker::update) every frame.
+pub struct BlobTracker <const N_TRACKS: usize, const N_HISTORY: usize>
+{
+	/// The max distance (pixels) between the predicted position of a track and a blob for them to be associated.
+	pub gate       : Decimal,
+	/// The number of frames a track can be missed before it is removed.
+	pub max_missed : usize,
+	/// The current tracks, in the order they were created.
+	pub tracks     : ArrayList<BlobTrack<N_HISTORY>, N_TRACKS>,
+	/// The id of the next track.
+	next_id        : usize,
+}
+
+/// A star which has been followed between frames by a [BlobTracker](crate::image_processing::BlobTracker).
+#[derive(Clone)]
+pub struct BlobTrack <const N_HISTORY: usize>
+{
+	/// A unique id which persists between frames.
+	pub id          : usize,
+	/// The catalogue id of the star once it has been identified.
+	pub catalogue   : Option<usize>,
+	/// The last observed or predicted centroid.
+	pub position    : Vector2,
+	/// The number of consecutive frames the track was not observed.
+	pub missed      : usize,
+	/// The observed centroids, oldest first, only the last `N_HISTORY` are kept.
+	pub centroids   : ArrayList<Vector2, N_HISTORY>,
+	/// The observed intensities, the same index as `centroids`.
+	pub intensities : ArrayList<UInt, N_HISTORY>,
+}
+
+/// Predicts where a blob will be in the next frame.
+#[cfg_attr(test, automock)]
+pub trait BlobPrediction
+{
+	/// Moves a centroid from the previous frame into the current frame.
+	fn predict ( &self, centroid: Vector2 ) -> Vector2;
+}
+
+/// Every blob moves by the same amount (e.g. a rate hint in pixels per frame).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BlobPredictionShift
+{
+	/// The pixels each blob moves between frames.
+	pub shift: Vector2,
+}
+
+/// The blobs move by the rotation of the camera (e.g. the last [RelativeRotation](crate::attitude_determination::RelativeRotation)).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct BlobPredictionRotation
+{
+	/// Rotates a point in the previous camera frame into the current camera frame.
+	pub delta     : Quaternion,
+	/// The projection between the image and camera frame.
+	pub intrinsic : IntrinsicParameters,
+}
+
+
+
 //###############################################################################################//
 //###############################################################################################//
 //
@@ -256,6 +256,15 @@
 //!	let rotate_to_world: Quaternion = rotate_to_cam.conjugate();           // Quaternion to rotate camera space to world space.
 //!	let world_center = rotate_to_world.rotate_point(reference_forward);    // Rotate spacecraft front to world coordinates
 //! ```
+//!
+//! ## All Steps
+//! Instead of calling each step yourself, [StarTracker](crate::pipeline::StarTracker) runs every step for each frame.  
+//! It also remembers the attitude so the next frame can be tracked instead of using lost-in-space.  
+//! Look at [pipeline](crate::pipeline).  
+//!
+//! ## Planets
+//! The planets, Moon and Sun are not in the catalogue and can be mistaken for a star.
+//! Given the time and a coarse attitude, [ephemeris](crate::ephemeris) finds which blobs are solar system bodies.
 //! 
 //! All code in this library has been written Tom Creusot.  
 	
@@ -289,6 +298,8 @@ extern crate core;    // std alternative.
 #[allow(dead_code)] pub mod attitude_determination;
 #[allow(dead_code)] pub mod tracking_mode;
 #[allow(dead_code)] pub mod projection;
+#[allow(dead_code)] pub mod pipeline;
+#[allow(dead_code)] pub mod ephemeris;
 
 /// `core_include` is All the no_std packages.  
 ///
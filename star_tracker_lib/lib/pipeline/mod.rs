@@ -0,0 +1,251 @@
+//! `pipeline` runs the whole star tracker (image processing, projection, tracking mode and attitude determination) for each frame.
+//!
+//! Instead of calling each step by hand, [StarTracker](crate::pipeline::StarTracker) owns the configuration and remembers what happened in the previous frame.
+//! It transitions between three modes:
+//! - **Acquisition**: No attitude is known, a lost-in-space search of the whole sky is used.
+//! - **Tracking**: The attitude of the previous frame is used to identify the stars ([Recursive](crate::tracking_mode::Recursive)).
+//! - **Lost**: Tracking has failed, a lost-in-space search around the last known attitude is used.
+//!   If this fails for `lost_frames` frames, the star tracker returns to acquisition.
+//!
+//! A frame is only valid if the identified stars agree with the found attitude within `max_residual`.
+//! A lost-in-space solution must also pass [Verification](crate::tracking_mode::Verification), the rest of the image is compared with the catalogue.
+//! If the probability of the other stars agreeing by chance is above `max_false_probability`, the pyramid is rejected.
+//! Every other star which agrees with the attitude is then identified ([Expand](crate::tracking_mode::Expand)) and the attitude is found again with all of them.
+//!
+//! The planets, Moon and Sun are not in the catalogue.
+//! If [set_bodies](crate::pipeline::StarTracker::set_bodies) is given the positions at the time of the frame,
+//! the stars covered by a body are removed using the last attitude.
+//! If no attitude is known yet, the attitude found by lost-in-space is used and the covered stars are removed before verifying it.
+//!
+//! If `photometry` is configured and [set_magnitudes](crate::pipeline::StarTracker::set_magnitudes) is given the catalogue magnitudes,
+//! lost-in-space skips any triangle or pilot where the brightness of the blobs does not agree with the catalogue.
+//!
+//! If `voting` is configured, lost-in-space splits the stars into disjoint subsets and searches each subset separately
+//! ([AttitudeVoting](crate::attitude_determination::AttitudeVoting)).
+//! The search only succeeds if `quorum` of the attitudes agree, the stars of the agreeing subsets are then used as the identified stars.
+//!
+//! # Example
+//! ``` ignore
+//! use star_tracker_lib::pipeline::StarTrackerConfig;
+//! use star_tracker_lib::pipeline::StarTracker;
+//! use star_tracker_lib::tracking_mode::Recursive;
+//! use star_tracker_lib::image_processing::ThresholdGrid;
+//! use star_tracker_lib::projection::IntrinsicParameters;
+//! use star_tracker_lib::util::units::Degrees;
+//!
+//! // To construct the database, use the tools in *star_tracker_nix*.
+//! let database = // database goes here.
+//!
+//! let config = StarTrackerConfig
+//! {
+//! 	intrinsic:        IntrinsicParameters::from_fov(database.fov, 1000.0, center),
+//! 	blob_min_size:    2,
+//! 	angle_tolerance:  Degrees(0.05).as_radians(),
+//! 	num_stars:        4..=4,
+//! 	allowed_failures: 10,
+//! 	chunk_step:       Degrees(10.0).as_radians(),
+//! 	lost_area:        database.fov,
+//! 	lost_frames:      5,
+//! 	recursive:        Recursive{gate: 5.0, fov: database.fov, min_stars: 4},
+//! 	max_residual:     Degrees(0.05).as_radians(),
+//! 	max_false_probability: 0.001,
+//! 	photometry:       None,
+//! 	voting:           None,
+//! };
+//!
+//! // A function which returns the time in any unit (e.g. milliseconds).
+//! fn clock ( ) -> u32 { return 0; }
+//!
+//! // Up to 100 stars are used, the triangle search stores up to 1000 matches per star pair.
+//! // Up to 200 catalogue stars in the field of view are compared when verifying.
+//! let mut tracker = StarTracker::<100, 1000, 200>::new(&database, config, clock);
+//!
+//! loop
+//! {
+//! 	let mut image = // Get the image.
+//! 	let threshold = ThresholdGrid::<50, 50>::new(&image, 20, 2);
+//! 	let result    = tracker.frame(&mut image, &threshold);
+//! 	if result.valid
+//! 	{
+//! 		println!("{:?} {:?} {} stars", result.mode, result.attitude, result.stars);
+//! 	}
+//! }
+//! ```
+
+use crate::core_include::*;
+
+use crate::tracking_mode::database::PyramidDatabase;
+use crate::tracking_mode::StarTriangleIterator;
+use crate::tracking_mode::Recursive;
+use crate::tracking_mode::VerificationResult;
+use crate::tracking_mode::Photometry;
+
+use crate::projection::IntrinsicParameters;
+
+use crate::attitude_determination::AttitudeVoting;
+
+use crate::ephemeris::BodyMask;
+
+use crate::util::units::Equatorial;
+use crate::util::units::Quaternion;
+use crate::util::units::Vector2;
+use crate::util::units::Vector3;
+use crate::util::units::Radians;
+use crate::util::units::Match;
+use crate::util::linear_lookup::LinearLookup;
+use crate::util::list::ArrayList;
+use crate::util::aliases::Decimal;
+
+pub mod star_tracker;
+
+
+//###############################################################################################//
+//
+//										Structs
+//
+//###############################################################################################//
+
+/// Runs the star tracker pipeline and transitions between acquisition, tracking and lost.
+///
+/// `N_STARS` is the maximum number of stars (blobs) used from each image.
+/// `N_MATCHES` is the size of the [StarTriangleIterator](crate::tracking_mode::StarTriangleIterator) used for lost-in-space.
+/// `N_REGION` is the maximum number of catalogue stars in the field of view used by [Verification](crate::tracking_mode::Verification).
+/// Nothing is allocated, all the buffers are stored in this struct.
+/// Use [frame](crate::pipeline::StarTracker::frame) for each image.
+pub struct StarTracker <'a, const N_STARS: usize, const N_MATCHES: usize, const N_REGION: usize>
+{
+	/// The configuration of the pipeline, this can be changed between frames.
+	pub config   : StarTrackerConfig,
+	/// The database used for lost-in-space and tracking.
+	database     : &'a PyramidDatabase<'a>,
+	/// Returns the current time, used for the timing of each step.
+	clock        : fn ( ) -> u32,
+
+	/// The mode the next frame will use.
+	mode         : Mode,
+	/// The last valid attitude (inertial to camera).
+	attitude     : Quaternion,
+	/// If `attitude` has been found since the tracker was created or reset.
+	known        : bool,
+	/// The number of frames which have failed since the last valid frame.
+	lost_count   : usize,
+	/// The solar system bodies to remove from the image.
+	bodies       : Option<BodyMask>,
+	/// The magnitude of each catalogue star, used if `config.photometry` is set.
+	magnitude    : Option<&'a dyn LinearLookup<Decimal>>,
+
+	/// The stars in the current image.
+	stars_2d     : ArrayList<Vector2, N_STARS>,
+	/// The stars in the current image projected onto a unit sphere (camera frame).
+	stars_3d     : ArrayList<Equatorial, N_STARS>,
+	/// The intensity of each star in the current image (same index as `stars_2d`), empty if unknown.
+	intensity    : ArrayList<Decimal, N_STARS>,
+	/// The identified stars (input: stars_2d, output: catalogue).
+	matches      : ArrayList<Match<usize>, N_STARS>,
+	/// The identified stars as vectors for attitude determination.
+	vectors      : ArrayList<Match<Vector3>, N_STARS>,
+	/// The residual of each identified star (same index as `matches`).
+	residuals    : ArrayList<Radians, N_STARS>,
+	/// The catalogue stars in the field of view of a lost-in-space solution.
+	region       : ArrayList<usize, N_REGION>,
+	/// The lost-in-space triangle search.
+	triangles    : StarTriangleIterator<N_MATCHES, N_STARS>,
+}
+
+
+/// The configuration of [StarTracker](crate::pipeline::StarTracker).
+#[derive(Debug, Clone, PartialEq)]
+pub struct StarTrackerConfig
+{
+	/// The intrinsic parameters of the camera (update this if the temperature changes).
+	pub intrinsic        : IntrinsicParameters,
+	/// The minimum number of pixels in a blob for it to be a star.
+	pub blob_min_size    : usize,
+	/// The tolerance of a star pair in lost-in-space.
+	pub angle_tolerance  : Radians,
+	/// The min and max (inclusive) number of stars identified by lost-in-space.
+	pub num_stars        : RangeInclusive<usize>,
+	/// The number of failed triangles before lost-in-space gives up.
+	pub allowed_failures : usize,
+	/// The declination step of the lost-in-space search of the whole sky.
+	pub chunk_step       : Radians,
+	/// How far the boresight could have moved from the last attitude when lost.  
+	/// The stars within this plus half the field of view of the last boresight are searched.
+	pub lost_area        : Radians,
+	/// The number of failed frames before searching the whole sky.
+	pub lost_frames      : usize,
+	/// The configuration used when tracking.
+	pub recursive        : Recursive,
+	/// The max angle between an identified star and the catalogue (rotated by the found attitude) for the frame to be valid.
+	pub max_residual     : Radians,
+	/// The max probability of a lost-in-space solution agreeing with the rest of the image by chance.  
+	/// 1 accepts every solution, if every star is used by the pyramid the solution can not be verified and is only accepted by 1.
+	pub max_false_probability : Decimal,
+	/// The brightness calibration used by lost-in-space, None to only use the position of the stars.  
+	/// This is only used if the magnitudes are provided with [set_magnitudes](crate::pipeline::StarTracker::set_magnitudes).
+	pub photometry       : Option<Photometry>,
+	/// Searches disjoint subsets of the stars in lost-in-space and only accepts the attitude if enough subsets agree.  
+	/// None to search all the stars at once.
+	pub voting           : Option<AttitudeVoting>,
+}
+
+
+/// The time each step of a frame took.
+/// The units are the same as the clock provided to [StarTracker](crate::pipeline::StarTracker).
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct Timing
+{
+	/// Finding the stars in the image.
+	pub image    : u32,
+	/// Identifying the stars (lost-in-space or tracking).
+	pub tracking : u32,
+	/// Finding the attitude and checking the residual.
+	pub attitude : u32,
+	/// The whole frame.
+	pub total    : u32,
+}
+
+
+/// The output of a frame from [StarTracker](crate::pipeline::StarTracker).
+#[derive(Debug, Copy, Clone)]
+pub struct StarTrackerResult
+{
+	/// The rotation from inertial to camera.
+	/// If not valid, this is the last valid attitude.
+	pub attitude : Quaternion,
+	/// If the attitude was found in this frame and the stars agree with it.
+	pub valid    : bool,
+	/// The mode used to identify the stars in this frame.
+	pub mode     : Mode,
+	/// The number of stars found in the image (excluding `masked`).
+	pub stars    : usize,
+	/// The number of stars removed as they are covered by a solar system body.
+	pub masked   : usize,
+	/// The number of stars identified.
+	pub matched  : usize,
+	/// The comparison of the whole image with the catalogue.  
+	/// This is only found for lost-in-space solutions.
+	pub verification : Option<VerificationResult>,
+	/// The time taken by each step.
+	pub timing   : Timing,
+}
+
+
+
+//###############################################################################################//
+//
+//										Enums
+//
+//###############################################################################################//
+
+/// The state of [StarTracker](crate::pipeline::StarTracker).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Mode
+{
+	/// No attitude is known, the whole sky is searched.
+	Acquisition,
+	/// The attitude of the previous frame is used to identify the stars.
+	Tracking,
+	/// Tracking failed, the area around the last attitude is searched.
+	Lost,
+}
@@ -0,0 +1,1084 @@
+//! Implementation of [StarTracker](crate::pipeline::StarTracker).
+use crate::core_include::*;
+
+use crate::pipeline::StarTrackerConfig;
+use crate::pipeline::StarTrackerResult;
+use crate::pipeline::StarTracker;
+use crate::pipeline::Timing;
+use crate::pipeline::Mode;
+
+use crate::image_processing::Threshold;
+use crate::image_processing::Image;
+use crate::image_processing::Blob;
+
+use crate::tracking_mode::database::ChunkIteratorDeclination;
+use crate::tracking_mode::database::ChunkIteratorCone;
+use crate::tracking_mode::database::ChunkIterator;
+use crate::tracking_mode::database::PyramidDatabase;
+use crate::tracking_mode::database::PhotometricDatabase;
+use crate::tracking_mode::database::Database;
+use crate::tracking_mode::AbandonSearchFailures;
+use crate::tracking_mode::StarTriangleIterator;
+use crate::tracking_mode::ConstellationResult;
+use crate::tracking_mode::Constellation;
+use crate::tracking_mode::Specularity;
+use crate::tracking_mode::Recursive;
+use crate::tracking_mode::Verification;
+use crate::tracking_mode::Expand;
+use crate::tracking_mode::Photometry;
+
+use crate::attitude_determination::AttitudeDetermination;
+use crate::attitude_determination::AttitudeVoting;
+use crate::attitude_determination::Quest;
+
+use crate::projection::SpaceImage;
+
+use crate::ephemeris::BodyMask;
+use crate::ephemeris::Body;
+
+use crate::util::units::Equatorial;
+use crate::util::units::Quaternion;
+use crate::util::units::Vector2;
+use crate::util::units::Vector3;
+use crate::util::units::Radians;
+use crate::util::units::Pixel;
+use crate::util::units::Match;
+use crate::util::aliases::Decimal;
+use crate::util::list::ArrayList;
+use crate::util::list::List;
+
+impl <'a, const N_STARS: usize, const N_MATCHES: usize, const N_REGION: usize> StarTracker <'a, N_STARS, N_MATCHES, N_REGION>
+{
+	/// Creates a star tracker in acquisition mode.
+	/// # Arguments
+	/// * `database` - The database used for lost-in-space and tracking.
+	/// * `config`   - The configuration of the pipeline.
+	/// * `clock`    - Returns the current time in any unit, used to time each step.
+	pub fn new ( database: &'a PyramidDatabase<'a>, config: StarTrackerConfig, clock: fn ( ) -> u32 ) -> Self
+	{
+		return Self
+		{
+			config:     config,
+			database:   database,
+			clock:      clock,
+			mode:       Mode::Acquisition,
+			attitude:   Quaternion{w: 1.0, x: 0.0, y: 0.0, z: 0.0},
+			known:      false,
+			lost_count: 0,
+			bodies:     None,
+			magnitude:  None,
+			stars_2d:   ArrayList::new(),
+			stars_3d:   ArrayList::new(),
+			intensity:  ArrayList::new(),
+			matches:    ArrayList::new(),
+			vectors:    ArrayList::new(),
+			residuals:  ArrayList::new(),
+			region:     ArrayList::new(),
+			triangles:  StarTriangleIterator::new(),
+		};
+	}
+
+
+	/// The mode the next frame will use.
+	pub fn get_mode ( &self ) -> Mode
+	{
+		return self.mode;
+	}
+
+
+	/// The identified stars from the last frame (input: index of the stars, output: index of the catalogue).
+	pub fn get_matches ( &self ) -> &dyn List<Match<usize>>
+	{
+		return &self.matches;
+	}
+
+
+	/// The residual of each identified star from the last frame (same index as `get_matches`).
+	pub fn get_residuals ( &self ) -> &dyn List<Radians>
+	{
+		return &self.residuals;
+	}
+
+
+	/// Sets the positions of the planets, Moon and Sun at the time of the next frame.
+	/// The positions only change slowly, this does not need to be updated every frame.
+	/// # Arguments
+	/// * `bodies` - The positions of the bodies, None to stop removing stars.
+	pub fn set_bodies ( &mut self, bodies: Option<BodyMask> )
+	{
+		self.bodies = bodies;
+	}
+
+
+	/// Sets the magnitude of each catalogue star so lost-in-space can check the brightness of the stars.
+	/// This is only used if `config.photometry` is set.
+	/// # Arguments
+	/// * `magnitude` - The magnitude of each star in the catalogue (same index as the catalogue), None to only use the position.
+	pub fn set_magnitudes ( &mut self, magnitude: Option<&'a dyn crate::util::linear_lookup::LinearLookup<Decimal>> )
+	{
+		self.magnitude = magnitude;
+	}
+
+
+	/// Forgets the attitude and returns to acquisition.
+	pub fn reset ( &mut self )
+	{
+		self.mode       = Mode::Acquisition;
+		self.known      = false;
+		self.lost_count = 0;
+	}
+
+
+
+	/// Finds the stars in the image and identifies them.
+	/// # Arguments
+	/// * `img`       - The image (this is consumed by the blob detection).
+	/// * `threshold` - The threshold of the image.
+	/// # Returns
+	/// The attitude, validity, mode, star count and timing of the frame.
+	pub fn frame ( &mut self, img: &mut dyn Image, threshold: &dyn Threshold ) -> StarTrackerResult
+	{
+		let start = (self.clock)();
+		let mut blobs : ArrayList<Blob,  N_STARS> = ArrayList::new();
+		let mut stack : ArrayList<Pixel, N_STARS> = ArrayList::new();
+		Blob::find_blobs(self.config.blob_min_size, threshold, img, &mut stack, &mut blobs);
+		blobs.sort_order(Blob::sort_descending_intensity);
+
+		self.stars_2d.clear();
+		self.intensity.clear();
+		Blob::to_vector2(&blobs, &mut self.stars_2d);
+		for i in 0..blobs.size()
+		{
+			let _ = self.intensity.push_back(blobs.get(i).intensity as Decimal);
+		}
+		let image = (self.clock)().wrapping_sub(start);
+
+		let mut result = self.identify();
+		result.timing.image  = image;
+		result.timing.total += image;
+		return result;
+	}
+
+
+	/// Identifies stars which have already been found in the image.
+	/// # Arguments
+	/// * `stars` - The position of each star on the image (pixels), brightest first.
+	/// # Returns
+	/// The attitude, validity, mode, star count and timing of the frame.
+	pub fn frame_stars ( &mut self, stars: &dyn List<Vector2> ) -> StarTrackerResult
+	{
+		let intensity : ArrayList<Decimal, 0> = ArrayList::new();
+		return self.frame_stars_photometric(stars, &intensity);
+	}
+
+
+	/// Identifies stars which have already been found in the image with the brightness of each star.
+	/// # Arguments
+	/// * `stars`     - The position of each star on the image (pixels), brightest first.
+	/// * `intensity` - The intensity of each star (same index as `stars`).
+	/// # Returns
+	/// The attitude, validity, mode, star count and timing of the frame.
+	pub fn frame_stars_photometric ( &mut self, stars: &dyn List<Vector2>, intensity: &dyn List<Decimal> ) -> StarTrackerResult
+	{
+		self.stars_2d.clear();
+		self.intensity.clear();
+		for i in 0..stars.size()
+		{
+			if self.stars_2d.push_back(stars.get(i)).is_err() { break; }
+			if i < intensity.size() { let _ = self.intensity.push_back(intensity.get(i)); }
+		}
+		return self.identify();
+	}
+
+
+
+	/// Identifies the stars in `stars_2d`, finds the attitude and transitions to the next mode.
+	fn identify ( &mut self ) -> StarTrackerResult
+	{
+		let start = (self.clock)();
+
+		self.stars_3d.clear();
+		for i in 0..self.stars_2d.size()
+		{
+			let camera = self.config.intrinsic.from_image(SpaceImage(self.stars_2d.get(i))).0;
+			let _ = self.stars_3d.push_back(camera.to_equatorial());
+		}
+		// The bodies can only be found in the image if the attitude is known.
+		let mut masked = if self.known { self.mask(self.attitude) } else { 0 };
+
+		let mut mode    = self.mode;
+		let mut found   = false;
+		let mut tracked = false;
+		if mode == Mode::Tracking
+		{
+			found   = self.track();
+			tracked = found;
+			if !found { mode = Mode::Lost; }
+		}
+		if !found
+		{
+			found = self.lost_in_space(mode);
+		}
+		let tracking = (self.clock)().wrapping_sub(start);
+
+		let mut attitude     = self.attitude;
+		let mut valid        = false;
+		let mut verification = None;
+		self.residuals.clear();
+		if found && !self.known && self.bodies.is_some()
+		{
+			// The attitude from lost-in-space is used to remove the bodies, a body cannot be a catalogue star.
+			Recursive::to_vectors(&self.stars_2d, self.database.catalogue,
+				&self.config.intrinsic, &self.matches, &mut self.vectors);
+			masked = self.mask(Quest::estimate(&self.vectors, None));
+			found  = 3 <= self.matches.size();
+		}
+		if found
+		{
+			Recursive::to_vectors(&self.stars_2d, self.database.catalogue,
+				&self.config.intrinsic, &self.matches, &mut self.vectors);
+			attitude = Quest::estimate(&self.vectors, None);
+			valid    = true;
+
+			// A pyramid only checks 4 stars, the rest of the image must also agree.
+			if !tracked
+			{
+				let verify = Verification{fov: self.database.fov, tolerance: self.config.angle_tolerance};
+				verify.region(self.database.catalogue, attitude, &mut self.region);
+				let result = verify.verify(&self.stars_3d, &self.matches, self.database.catalogue, &self.region, attitude);
+				valid &= result.false_probability <= self.config.max_false_probability;
+				verification = Some(result);
+			}
+
+			// Every star which agrees with the attitude is used to find a more accurate attitude.
+			let expand = Expand{tolerance: self.config.angle_tolerance};
+			if 0 < expand.expand(&self.stars_3d, self.database.catalogue, attitude, &mut self.matches)
+			{
+				Recursive::to_vectors(&self.stars_2d, self.database.catalogue,
+					&self.config.intrinsic, &self.matches, &mut self.vectors);
+				attitude = Quest::estimate(&self.vectors, None);
+			}
+			valid &= self.residual(attitude) <= self.config.max_residual;
+		}
+
+		if valid
+		{
+			self.mode       = Mode::Tracking;
+			self.attitude   = attitude;
+			self.known      = true;
+			self.lost_count = 0;
+		}
+		else
+		{
+			attitude = self.attitude;
+			if mode != Mode::Acquisition
+			{
+				self.lost_count += 1;
+				self.mode = if self.config.lost_frames <= self.lost_count { Mode::Acquisition } else { Mode::Lost };
+			}
+		}
+
+		let total = (self.clock)().wrapping_sub(start);
+		return StarTrackerResult
+		{
+			attitude: attitude,
+			valid:    valid,
+			mode:     mode,
+			stars:    self.stars_2d.size(),
+			masked:   masked,
+			matched:  self.matches.size(),
+			verification: verification,
+			timing:   Timing{image: 0, tracking: tracking, attitude: total - tracking, total: total},
+		};
+	}
+
+
+	/// Removes the stars which are covered by a solar system body.
+	/// Any match of a removed star is also removed and the other matches are moved to the new index.
+	/// Returns the number of stars removed.
+	/// # Arguments
+	/// * `attitude` - The rotation from inertial to camera used to find the bodies.
+	fn mask ( &mut self, attitude: Quaternion ) -> usize
+	{
+		let bodies = match self.bodies
+		{
+			Some(bodies) => bodies,
+			None => return 0,
+		};
+
+		let mut flags : ArrayList<Option<Body>, N_STARS> = ArrayList::new();
+		let masked = bodies.flag(attitude, &self.stars_3d, &mut flags);
+		let mut i = flags.size();
+		while 0 < i
+		{
+			i -= 1;
+			if flags.get(i).is_some()
+			{
+				let _ = self.stars_2d.pop(i);
+				let _ = self.stars_3d.pop(i);
+				if i < self.intensity.size() { let _ = self.intensity.pop(i); }
+
+				let mut j = self.matches.size();
+				while 0 < j
+				{
+					j -= 1;
+					let mut matched = self.matches.get(j);
+					if matched.input == i { let _ = self.matches.pop(j); }
+					else if i < matched.input
+					{
+						matched.input -= 1;
+						let _ = self.matches.set(j, matched);
+					}
+				}
+			}
+		}
+		return masked;
+	}
+
+
+	/// Identifies the stars with the previous attitude.
+	/// Returns true if enough stars were associated.
+	fn track ( &mut self ) -> bool
+	{
+		let recursive = self.config.recursive;
+		recursive.associate(&self.stars_2d, self.database.catalogue,
+			&self.config.intrinsic, self.attitude, &mut self.matches);
+		return recursive.min_stars <= self.matches.size() && !self.matches.is_empty();
+	}
+
+
+	/// Identifies the stars with a lost-in-space search.
+	/// In Lost mode, only the area around the last attitude is searched.
+	/// If `config.voting` is set, each subset of the stars is searched and the subsets which agree are used.
+	/// Returns true if the search succeeded.
+	fn lost_in_space ( &mut self, mode: Mode ) -> bool
+	{
+		self.matches.clear();
+
+		// The magnitudes are only needed if the brightness is checked.
+		let photometry  = self.config.photometry.filter(|_| self.magnitude.is_some());
+		let photometric = self.magnitude.map(|magnitude| PhotometricDatabase{database: self.database, magnitude: magnitude});
+		let database : &dyn Database = match &photometric
+		{
+			Some(photometric) if photometry.is_some() => photometric,
+			_ => self.database,
+		};
+
+		let forward   = Vector3{x: 0.0, y: 0.0, z: 1.0};
+		let boresight = self.attitude.conjugate().rotate_point(forward).to_equatorial();
+		let boresight = if mode == Mode::Lost { Some(boresight) } else { None };
+
+		let voting = match self.config.voting
+		{
+			Some(voting) => voting,
+			None => return Self::search(database, &self.config, photometry, boresight,
+				&mut self.triangles, &self.stars_3d, &self.intensity, &mut self.matches),
+		};
+
+		let mut indices : ArrayList<usize, N_STARS> = ArrayList::new();
+		for i in 0..self.stars_3d.size() { let _ = indices.push_back(i); }
+
+		// Each subset is searched on its own, the matches are stored with the index of the attitude they found.
+		let mut attitudes : ArrayList<Quaternion, N_STARS>           = ArrayList::new();
+		let mut found     : ArrayList<(usize, Match<usize>), N_STARS> = ArrayList::new();
+		for index in 0..voting.subsets
+		{
+			let mut subset    : ArrayList<usize, N_STARS>        = ArrayList::new();
+			let mut stars     : ArrayList<Equatorial, N_STARS>   = ArrayList::new();
+			let mut intensity : ArrayList<Decimal, N_STARS>      = ArrayList::new();
+			let mut matches   : ArrayList<Match<usize>, N_STARS> = ArrayList::new();
+			AttitudeVoting::subset(&indices, voting.subsets, index, &mut subset);
+			for i in 0..subset.size()
+			{
+				let _ = stars.push_back(self.stars_3d.get(subset.get(i)));
+				if subset.get(i) < self.intensity.size() { let _ = intensity.push_back(self.intensity.get(subset.get(i))); }
+			}
+
+			if !Self::search(database, &self.config, photometry, boresight,
+				&mut self.triangles, &stars, &intensity, &mut matches)
+			{
+				continue;
+			}
+
+			// The matches refer to the subset, they must refer to the image.
+			for i in 0..matches.size()
+			{
+				let mut matched = matches.get(i);
+				matched.input = subset.get(matched.input);
+				let _ = matches.set(i, matched);
+			}
+			Recursive::to_vectors(&self.stars_2d, self.database.catalogue,
+				&self.config.intrinsic, &matches, &mut self.vectors);
+			if attitudes.push_back(Quest::estimate(&self.vectors, None)).is_err() { break; }
+			for i in 0..matches.size() { let _ = found.push_back((attitudes.size() - 1, matches.get(i))); }
+		}
+
+		let mut agreed : ArrayList<Quaternion, N_STARS> = ArrayList::new();
+		if voting.vote(&attitudes, &mut agreed).is_none() { return false; }
+
+		for i in 0..found.size()
+		{
+			let (attitude, matched) = found.get(i);
+			for j in 0..agreed.size()
+			{
+				if agreed.get(j) == attitudes.get(attitude) { let _ = self.matches.push_back(matched); break; }
+			}
+		}
+		return true;
+	}
+
+
+	/// Runs a single lost-in-space search on `stars`.
+	/// # Arguments
+	/// * `database`   - The database to search (with magnitudes if `photometry` is set).
+	/// * `config`     - The configuration of the search.
+	/// * `photometry` - The brightness calibration, None to only use the position of the stars.
+	/// * `boresight`  - The last boresight to search around, None to search the whole sky.
+	/// * `triangles`  - The triangle search.
+	/// * `stars`      - The stars to identify.
+	/// * `intensity`  - The intensity of each star (same index as `stars`).
+	/// * `matches`    - The output, the identified stars (input: stars).
+	/// Returns true if the search succeeded.
+	fn search (
+		database  : &dyn Database,
+		config    : &StarTrackerConfig,
+		photometry: Option<Photometry>,
+		boresight : Option<Equatorial>,
+		triangles : &mut StarTriangleIterator<N_MATCHES, N_STARS>,
+		stars     : &dyn List<Equatorial>,
+		intensity : &dyn List<Decimal>,
+		matches   : &mut dyn List<Match<usize>> ) -> bool
+	{
+		let mut declination;
+		let mut cone;
+		let chunks : &mut dyn ChunkIterator = match boresight
+		{
+			Some(boresight) =>
+			{
+				cone = ChunkIteratorCone::from_prior(database, boresight, config.lost_area, database.get_fov());
+				&mut cone
+			}
+			None =>
+			{
+				declination = ChunkIteratorDeclination::new(database, config.chunk_step,
+					1.0, ChunkIteratorDeclination::randomise_parity);
+				&mut declination
+			}
+		};
+
+		let mut abort = AbandonSearchFailures::new(config.allowed_failures);
+		let result = match photometry
+		{
+			Some(photometry) => Constellation::find_photometric(
+				stars, intensity, photometry, chunks,
+				triangles,
+				&mut Specularity::default(),
+				&mut abort,
+				config.angle_tolerance,
+				config.num_stars.clone(),
+				matches),
+
+			None => Constellation::find(
+				stars, chunks,
+				triangles,
+				&mut Specularity::default(),
+				&mut abort,
+				config.angle_tolerance,
+				config.num_stars.clone(),
+				matches),
+		};
+
+		return matches!(result, ConstellationResult::Success{..});
+	}
+
+
+	/// The largest angle between an identified star and its catalogue star rotated by `attitude`.
+	/// The angle of every star is stored in `residuals`.
+	fn residual ( &mut self, attitude: Quaternion ) -> Radians
+	{
+		self.residuals.clear();
+		let mut residual = Radians(0.0);
+		for i in 0..self.vectors.size()
+		{
+			let pair  = self.vectors.get(i);
+			let error = pair.input.angle_distance(attitude.rotate_point(pair.output));
+			let _ = self.residuals.push_back(error);
+			if residual < error { residual = error; }
+		}
+		return residual;
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use std::sync::atomic::AtomicU32;
+	use std::sync::atomic::Ordering;
+
+	use crate::pipeline::StarTrackerConfig;
+	use crate::pipeline::StarTracker;
+	use crate::pipeline::Mode;
+
+	use crate::image_processing::ThresholdPercent;
+	use crate::image_processing::ImageBasic;
+
+	use crate::tracking_mode::database::PyramidDatabase;
+	use crate::tracking_mode::database::KVector;
+	use crate::tracking_mode::StarPair;
+	use crate::tracking_mode::Recursive;
+	use crate::tracking_mode::Photometry;
+
+	use crate::attitude_determination::AttitudeVoting;
+
+	use crate::projection::IntrinsicParameters;
+	use crate::projection::SpaceCamera;
+
+	use crate::ephemeris::BodyMask;
+	use crate::ephemeris::Body;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::Quaternion;
+	use crate::util::units::AngleAxis;
+	use crate::util::units::Degrees;
+	use crate::util::units::Radians;
+	use crate::util::units::Vector2;
+	use crate::util::units::Vector3;
+	use crate::util::aliases::Decimal;
+	use crate::util::list::ArrayList;
+	use crate::util::list::List;
+	use crate::util::test::TestEqual;
+	use crate::util::test::star_patch;
+	use crate::util::test::star_pairs;
+	use crate::util::test::point_at;
+
+	/// The center of the catalogue.
+	const CENTER : Equatorial = Equatorial{ra: Degrees(30.0).as_radians(), dec: Degrees(20.0).as_radians()};
+
+	#[coverage(off)]
+	fn clock ( ) -> u32 { return 0; }
+
+	// The catalogue is a set of stars around CENTER with unique separations.
+	#[coverage(off)]
+	fn catalogue ( ) -> Vec<Equatorial>
+	{
+		return star_patch((30.0, 20.0), 7);
+	}
+
+	#[coverage(off)]
+	fn intrinsic ( ) -> IntrinsicParameters
+	{
+		return IntrinsicParameters::from_fov(Degrees(20.0).as_radians(), 1000.0, Vector2{x: 500.0, y: 500.0});
+	}
+
+	#[coverage(off)]
+	fn config ( ) -> StarTrackerConfig
+	{
+		return StarTrackerConfig
+		{
+			intrinsic:        intrinsic(),
+			blob_min_size:    1,
+			angle_tolerance:  Degrees(0.01).as_radians(),
+			num_stars:        4..=4,
+			allowed_failures: 100,
+			chunk_step:       Degrees(10.0).as_radians(),
+			lost_area:        Degrees(20.0).as_radians(),
+			lost_frames:      2,
+			recursive:        Recursive{gate: 5.0, fov: Degrees(20.0).as_radians(), min_stars: 4},
+			max_residual:     Degrees(0.01).as_radians(),
+			max_false_probability: 1.0,
+			photometry:       None,
+			voting:           None,
+		};
+	}
+
+	// Projects the catalogue onto the image.
+	#[coverage(off)]
+	fn project ( attitude: Quaternion, catalogue: &Vec<Equatorial> ) -> ArrayList<Vector2, 20>
+	{
+		let mut stars = ArrayList::new();
+		for i in 0..catalogue.len()
+		{
+			let point = attitude.rotate_point(catalogue[i].to_vector3());
+			stars.push_back(intrinsic().to_image(SpaceCamera(point)).0);
+		}
+		return stars;
+	}
+
+	// Jupiter is between the stars of the catalogue, every other body is far away.
+	#[coverage(off)]
+	fn jupiter ( ) -> Equatorial
+	{
+		return Equatorial{ra: CENTER.ra + Degrees(1.0).as_radians(), dec: CENTER.dec - Degrees(0.5).as_radians()};
+	}
+
+	#[coverage(off)]
+	fn bodies ( ) -> BodyMask
+	{
+		let far = Equatorial{ra: CENTER.ra + Degrees(180.0).as_radians(), dec: Radians(0.0)};
+		let mut positions = [far; Body::COUNT];
+		positions[Body::Jupiter as usize] = jupiter();
+		return BodyMask{margin: Degrees(0.1).as_radians(), positions: positions};
+	}
+
+	// Checks the camera is looking at `center`.
+	#[coverage(off)]
+	fn assert_looking_at ( attitude: Quaternion, center: Equatorial )
+	{
+		let forward = Vector3{x: 0.0, y: 0.0, z: 1.0};
+		let found = attitude.conjugate().rotate_point(forward);
+		found.angle_distance(center.to_vector3()).0.assert_close(&0.0, 0.00001);
+	}
+
+
+//###############################################################################################//
+//
+//										StarTracker
+//
+// pub fn new           ( &PyramidDatabase, StarTrackerConfig, fn ( ) -> u32 ) -> Self
+// pub fn get_mode      ( &self ) -> Mode
+// pub fn get_matches   ( &self ) -> &dyn List<Match<usize>>
+// pub fn get_residuals ( &self ) -> &dyn List<Radians>
+// pub fn set_bodies    ( &mut self, Option<BodyMask> )
+// pub fn set_magnitudes ( &mut self, Option<&dyn LinearLookup<Decimal>> )
+// pub fn reset         ( &mut self )
+// pub fn frame         ( &mut self, &mut dyn Image, &dyn Threshold ) -> StarTrackerResult
+// pub fn frame_stars   ( &mut self, &dyn List<Vector2> ) -> StarTrackerResult
+// pub fn frame_stars_photometric ( &mut self, &dyn List<Vector2>, &dyn List<Decimal> ) -> StarTrackerResult
+//
+//###############################################################################################//
+//										~ new ~													 //
+	#[test]
+	// The star tracker should start in acquisition.
+	fn test_new ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+
+		let tracker = StarTracker::<20, 100, 20>::new(&database, config(), clock);
+		assert_eq!(tracker.get_mode(), Mode::Acquisition);
+		assert_eq!(tracker.get_matches().size(), 0);
+	}
+
+
+//										~ frame_stars ~											 //
+	#[test]
+	// The star tracker should acquire the attitude, then track it as it moves.
+	fn test_frame_stars_acquire_track ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let mut tracker = StarTracker::<20, 100, 20>::new(&database, config(), clock);
+
+		let attitude = point_at(CENTER);
+		let result = tracker.frame_stars(&project(attitude, &catalogue));
+		assert!(result.valid);
+		assert_eq!(result.mode, Mode::Acquisition);
+		assert_eq!(result.stars, 7);
+		assert_eq!(result.verification.unwrap().matched, 3);
+		// The 3 stars not in the pyramid should be identified with the pyramid attitude.
+		assert_eq!(result.matched, 7);
+		assert_eq!(tracker.get_residuals().size(), 7);
+		for i in 0..7 { assert!(tracker.get_residuals().get(i) < Degrees(0.001).as_radians()); }
+		assert_looking_at(result.attitude, CENTER);
+		assert_eq!(tracker.get_mode(), Mode::Tracking);
+
+		// Tracking should identify every star in the image.
+		let moved = Equatorial{ra: CENTER.ra + Degrees(0.05).as_radians(), dec: CENTER.dec};
+		let result = tracker.frame_stars(&project(point_at(moved), &catalogue));
+		assert!(result.valid);
+		assert_eq!(result.mode, Mode::Tracking);
+		assert_eq!(result.matched, 7);
+		assert!(result.verification.is_none());
+		assert_looking_at(result.attitude, moved);
+		assert_eq!(tracker.get_mode(), Mode::Tracking);
+	}
+
+	#[test]
+	// If tracking fails, the area around the last attitude should be searched in the same frame.
+	fn test_frame_stars_lost_recover ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let mut tracker = StarTracker::<20, 100, 20>::new(&database, config(), clock);
+		tracker.frame_stars(&project(point_at(CENTER), &catalogue));
+
+		// Moved too far to track.
+		let moved = Equatorial{ra: CENTER.ra + Degrees(1.0).as_radians(), dec: CENTER.dec};
+		let result = tracker.frame_stars(&project(point_at(moved), &catalogue));
+		assert!(result.valid);
+		assert_eq!(result.mode, Mode::Lost);
+		assert_looking_at(result.attitude, moved);
+		assert_eq!(tracker.get_mode(), Mode::Tracking);
+	}
+
+	#[test]
+	// The area searched when lost should be a cone so it works at the pole.
+	fn test_frame_stars_lost_recover_pole ( )
+	{
+		// Rotates the catalogue so the stars surround the north pole.
+		let to_pole   = point_at(CENTER);
+		let catalogue : Vec<Equatorial> = catalogue().iter()
+			.map(|star| to_pole.rotate_point(star.to_vector3()).to_equatorial()).collect();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let mut tracker = StarTracker::<20, 100, 20>::new(&database, config(), clock);
+		let start = Equatorial{ra: Radians(0.0), dec: Degrees(89.99).as_radians()};
+		let result = tracker.frame_stars(&project(point_at(start), &catalogue));
+		assert!(result.valid);
+
+		let moved = Equatorial{ra: Degrees(120.0).as_radians(), dec: Degrees(89.0).as_radians()};
+		let result = tracker.frame_stars(&project(point_at(moved), &catalogue));
+		assert!(result.valid);
+		assert_eq!(result.mode, Mode::Lost);
+		assert_looking_at(result.attitude, moved);
+	}
+
+	#[test]
+	// With voting, each half of the stars should be identified on its own and the attitudes must agree.
+	fn test_frame_stars_voting ( )
+	{
+		let catalogue = star_patch((30.0, 20.0), 8);
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let mut config = config();
+		config.voting = Some(AttitudeVoting{tolerance: Degrees(0.01).as_radians(), quorum: 2, subsets: 2});
+		let mut tracker = StarTracker::<20, 100, 20>::new(&database, config, clock);
+
+		let stars = project(point_at(CENTER), &catalogue);
+		let result = tracker.frame_stars(&stars);
+		assert!(result.valid);
+		assert_eq!(result.matched, 8);
+		assert_looking_at(result.attitude, CENTER);
+		tracker.reset();
+
+		// There are only 2 subsets, 3 cannot agree.
+		tracker.config.voting = Some(AttitudeVoting{tolerance: Degrees(0.01).as_radians(), quorum: 3, subsets: 2});
+		assert!(!tracker.frame_stars(&stars).valid);
+	}
+
+	#[test]
+	// If a subset cannot be identified, there is no quorum even though the other subset is correct.
+	fn test_frame_stars_voting_disagree ( )
+	{
+		let catalogue = star_patch((30.0, 20.0), 8);
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let mut config = config();
+		config.voting = Some(AttitudeVoting{tolerance: Degrees(0.01).as_radians(), quorum: 2, subsets: 2});
+		let mut tracker = StarTracker::<20, 100, 20>::new(&database, config, clock);
+
+		// The odd stars are the second subset.
+		let mut stars = project(point_at(CENTER), &catalogue);
+		for i in [1, 3] { stars.set(i, stars.get(i) + Vector2{x: 50.0, y: 0.0}); }
+		assert!(!tracker.frame_stars(&stars).valid);
+		assert_eq!(tracker.get_mode(), Mode::Acquisition);
+
+		// With a quorum of 1, only the stars of the first subset are identified.
+		tracker.config.voting = Some(AttitudeVoting{tolerance: Degrees(0.01).as_radians(), quorum: 1, subsets: 2});
+		let result = tracker.frame_stars(&stars);
+		assert!(result.valid);
+		assert_eq!(result.matched, 6);
+		assert_looking_at(result.attitude, CENTER);
+	}
+
+	#[test]
+	// If the stars cannot be identified, it should become lost, then return to acquisition.
+	fn test_frame_stars_lost_acquisition ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let mut tracker = StarTracker::<20, 100, 20>::new(&database, config(), clock);
+		let result = tracker.frame_stars(&project(point_at(CENTER), &catalogue));
+		let attitude = result.attitude;
+
+		let none : ArrayList<Vector2, 1> = ArrayList::new();
+		let result = tracker.frame_stars(&none);
+		assert!(!result.valid);
+		assert_eq!(result.mode, Mode::Lost);
+		assert_looking_at(result.attitude, CENTER);
+		assert_eq!(tracker.get_mode(), Mode::Lost);
+
+		let result = tracker.frame_stars(&none);
+		assert!(!result.valid);
+		assert_eq!(result.mode, Mode::Lost);
+		assert_eq!(tracker.get_mode(), Mode::Acquisition);
+
+		let result = tracker.frame_stars(&none);
+		assert_eq!(result.mode, Mode::Acquisition);
+		assert_eq!(tracker.get_mode(), Mode::Acquisition);
+		assert_looking_at(attitude, CENTER);
+	}
+
+	#[test]
+	// If the stars do not agree with the attitude, the frame should not be valid.
+	fn test_frame_stars_residual ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let mut config = config();
+		config.max_residual = Radians(0.0);
+		config.angle_tolerance = Degrees(0.1).as_radians();
+		let mut tracker = StarTracker::<20, 100, 20>::new(&database, config, clock);
+
+		let mut stars = project(point_at(CENTER), &catalogue);
+		stars.set(0, stars.get(0) + Vector2{x: 1.0, y: 0.0});
+		let result = tracker.frame_stars(&stars);
+		assert!(!result.valid);
+		assert_eq!(tracker.get_mode(), Mode::Acquisition);
+	}
+
+	#[test]
+	// If the rest of the image does not agree with the pyramid, the frame should not be valid.
+	fn test_frame_stars_verification ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let mut config = config();
+		config.max_false_probability = 0.001;
+		let mut tracker = StarTracker::<20, 100, 20>::new(&database, config, clock);
+
+		// Every star agrees.
+		let result = tracker.frame_stars(&project(point_at(CENTER), &catalogue));
+		assert!(result.valid);
+		assert!(result.verification.unwrap().false_probability < 0.001);
+		tracker.reset();
+
+		// The stars not in the pyramid do not agree.
+		let mut stars = project(point_at(CENTER), &catalogue);
+		for i in 4..7 { stars.set(i, stars.get(i) + Vector2{x: 50.0, y: 0.0}); }
+		let result = tracker.frame_stars(&stars);
+		let verification = result.verification.unwrap();
+		assert!(!result.valid);
+		assert_eq!(verification.tested,  3);
+		assert_eq!(verification.matched, 0);
+		assert_eq!(verification.false_probability, 1.0);
+	}
+
+
+//										~ frame ~												 //
+	#[test]
+	// The time of each step should be recorded.
+	fn test_frame_timing ( )
+	{
+		static TIME : AtomicU32 = AtomicU32::new(0);
+		#[coverage(off)]
+		fn counter ( ) -> u32 { return TIME.fetch_add(1, Ordering::Relaxed); }
+
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let mut tracker = StarTracker::<20, 100, 20>::new(&database, config(), counter);
+
+		let mut img_array = [[0; 10]; 10];
+		img_array[3][3] = 100;
+		let mut img = ImageBasic::new(&mut img_array);
+		let threshold = ThresholdPercent::new(&img, 0.5);
+		let result = tracker.frame(&mut img, &threshold);
+
+		assert!(!result.valid);
+		assert_eq!(result.stars, 1);
+		assert_eq!(result.timing.image,    1);
+		assert_eq!(result.timing.tracking, 1);
+		assert_eq!(result.timing.attitude, 1);
+		assert_eq!(result.timing.total,    3);
+	}
+
+
+//										~ set_bodies ~											 //
+	#[test]
+	// When tracking, the star on Jupiter should be removed before identifying the stars.
+	fn test_set_bodies ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let mut tracker = StarTracker::<20, 100, 20>::new(&database, config(), clock);
+		tracker.set_bodies(Some(bodies()));
+		tracker.frame_stars(&project(point_at(CENTER), &catalogue));
+
+		let mut sky = vec![jupiter()];
+		sky.append(&mut catalogue.clone());
+		let result = tracker.frame_stars(&project(point_at(CENTER), &sky));
+		assert!(result.valid);
+		assert_eq!(result.mode,    Mode::Tracking);
+		assert_eq!(result.masked,  1);
+		assert_eq!(result.stars,   7);
+		assert_eq!(result.matched, 7);
+
+		// Without the bodies, nothing is removed.
+		tracker.set_bodies(None);
+		let result = tracker.frame_stars(&project(point_at(CENTER), &sky));
+		assert_eq!(result.masked, 0);
+		assert_eq!(result.stars,  8);
+	}
+
+	#[test]
+	// In acquisition, the attitude from lost-in-space is used to remove the star on Jupiter.
+	fn test_set_bodies_acquisition ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let mut tracker = StarTracker::<20, 100, 20>::new(&database, config(), clock);
+		tracker.set_bodies(Some(bodies()));
+
+		let mut sky = vec![jupiter()];
+		sky.append(&mut catalogue.clone());
+		let result = tracker.frame_stars(&project(point_at(CENTER), &sky));
+		assert!(result.valid);
+		assert_eq!(result.mode,    Mode::Acquisition);
+		assert_eq!(result.masked,  1);
+		assert_eq!(result.stars,   7);
+		assert_eq!(result.matched, 7);
+		assert_looking_at(result.attitude, CENTER);
+
+		// The matches are moved to the index of the star after Jupiter is removed.
+		let matches = tracker.get_matches();
+		for i in 0..matches.size()
+		{
+			assert_eq!(matches.get(i).input, matches.get(i).output);
+		}
+	}
+
+	#[test]
+	// After returning to acquisition, the last attitude is used to remove the bodies before searching.
+	fn test_set_bodies_acquisition_prior ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let mut tracker = StarTracker::<20, 100, 20>::new(&database, config(), clock);
+		tracker.frame_stars(&project(point_at(CENTER), &catalogue));
+		let empty : Vec<Vector2> = Vec::new();
+		tracker.frame_stars(&empty);
+		tracker.frame_stars(&empty);
+		assert_eq!(tracker.get_mode(), Mode::Acquisition);
+
+		// Only Jupiter is visible, it is removed even though the search fails.
+		tracker.set_bodies(Some(bodies()));
+		let result = tracker.frame_stars(&project(point_at(CENTER), &vec![jupiter()]));
+		assert!(!result.valid);
+		assert_eq!(result.mode,   Mode::Acquisition);
+		assert_eq!(result.masked, 1);
+		assert_eq!(result.stars,  0);
+	}
+
+
+//										~ set_magnitudes ~										 //
+	#[test]
+	// With photometry, lost-in-space should only accept stars as bright as the catalogue.
+	fn test_set_magnitudes ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let magnitude : Vec<Decimal> = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+		let photometry = Photometry{zero_point: 10000.0, tolerance: 0.5};
+		let mut config = config();
+		config.photometry = Some(photometry);
+		let mut tracker = StarTracker::<20, 100, 20>::new(&database, config, clock);
+		tracker.set_magnitudes(Some(&magnitude));
+
+		let stars = project(point_at(CENTER), &catalogue);
+		let mut intensity : Vec<Decimal> = Vec::new();
+		for i in 0..magnitude.len() { intensity.push(photometry.expected_intensity(magnitude[i])); }
+		let result = tracker.frame_stars_photometric(&stars, &intensity);
+		assert!(result.valid);
+		assert_eq!(result.matched, 7);
+		tracker.reset();
+
+		// Every star is too bright, every triangle should be pruned.
+		let bright = vec![photometry.expected_intensity(0.0); 7];
+		let result = tracker.frame_stars_photometric(&stars, &bright);
+		assert!(!result.valid);
+		assert_eq!(tracker.get_mode(), Mode::Acquisition);
+
+		// Without the magnitudes, the brightness is ignored.
+		tracker.set_magnitudes(None);
+		let result = tracker.frame_stars_photometric(&stars, &bright);
+		assert!(result.valid);
+	}
+
+	#[test]
+	// Without the photometry config, the magnitudes are ignored.
+	fn test_set_magnitudes_no_photometry ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let magnitude : Vec<Decimal> = vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+		let mut tracker = StarTracker::<20, 100, 20>::new(&database, config(), clock);
+		tracker.set_magnitudes(Some(&magnitude));
+
+		let bright = vec![10000.0; 7];
+		let result = tracker.frame_stars_photometric(&project(point_at(CENTER), &catalogue), &bright);
+		assert!(result.valid);
+	}
+
+
+//										~ reset ~												 //
+	#[test]
+	// Reset should return to acquisition and forget the attitude.
+	fn test_reset ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let mut tracker = StarTracker::<20, 100, 20>::new(&database, config(), clock);
+		tracker.frame_stars(&project(point_at(CENTER), &catalogue));
+		assert_eq!(tracker.get_mode(), Mode::Tracking);
+		assert!(tracker.known);
+		tracker.reset();
+		assert_eq!(tracker.get_mode(), Mode::Acquisition);
+		assert!(!tracker.known);
+	}
+}
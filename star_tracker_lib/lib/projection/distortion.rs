@@ -0,0 +1,163 @@
+//! Implementation of [Distortion](crate::projection::Distortion)
+use super::Distortion;
+
+use util::units::Vector2;
+use util::aliases::Decimal;
+
+impl Distortion
+{
+	/// The number of iterations used by `undistort` to reverse the distortion.
+	/// This is the same amount opencv uses in `undistortPoints`.
+	pub const UNDISTORT_ITERATIONS: usize = 20;
+
+	/// A rectilinear lens (no distortion).
+	pub const fn none ( ) -> Self
+	{
+		return Self{radial: [0.0, 0.0, 0.0], tangential: [0.0, 0.0]};
+	}
+
+
+	/// Applies the distortion to a point on the normalized image plane (x/z, y/z).
+	/// # Arguments
+	/// * `point` - The undistorted point on the normalized image plane.
+	/// # Returns
+	/// Where the point will appear after passing through the lens.
+	pub fn distort ( &self, point: Vector2 ) -> Vector2
+	{
+		let r_2 = point.x * point.x + point.y * point.y;
+		let r_4 = r_2 * r_2;
+		let r_6 = r_4 * r_2;
+
+		let radial = 1.0 + self.radial[0] * r_2 + self.radial[1] * r_4 + self.radial[2] * r_6;
+		return self.tangential(point) + point * radial;
+	}
+
+
+	/// Reverses `distort` with a fixed point iteration.
+	/// The distortion model has no closed form inverse, for reasonable lenses this converges quickly.
+	/// # Arguments
+	/// * `point` - The distorted point on the normalized image plane.
+	/// # Returns
+	/// Where the point would be if the lens was rectilinear.
+	pub fn undistort ( &self, point: Vector2 ) -> Vector2
+	{
+		let mut undistorted = point;
+		for _ in 0..Self::UNDISTORT_ITERATIONS
+		{
+			let r_2 = undistorted.x * undistorted.x + undistorted.y * undistorted.y;
+			let r_4 = r_2 * r_2;
+			let r_6 = r_4 * r_2;
+
+			let radial: Decimal =
+				1.0 + self.radial[0] * r_2 + self.radial[1] * r_4 + self.radial[2] * r_6;
+			undistorted = (point - self.tangential(undistorted)) / radial;
+		}
+		return undistorted;
+	}
+
+
+	/// The offset caused by the tangential (decentering) coefficients.
+	fn tangential ( &self, point: Vector2 ) -> Vector2
+	{
+		let r_2 = point.x * point.x + point.y * point.y;
+		let p_1 = self.tangential[0];
+		let p_2 = self.tangential[1];
+
+		let x = 2.0 * p_1 * point.x * point.y + p_2 * (r_2 + 2.0 * point.x * point.x);
+		let y = p_1 * (r_2 + 2.0 * point.y * point.y) + 2.0 * p_2 * point.x * point.y;
+		return Vector2{x: x, y: y};
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+mod test
+{
+	use rand::prelude::*;
+
+	use crate::projection::Distortion;
+
+	use crate::util::units::Vector2;
+
+	use crate::util::test::TestEqual;
+
+//###############################################################################################//
+//
+//										Distortion
+//
+// pub const fn none ( ) -> Self
+// pub fn distort    ( &self, Vector2 ) -> Vector2
+// pub fn undistort  ( &self, Vector2 ) -> Vector2
+//
+//###############################################################################################//
+//										~ none ~												 //
+	#[test]
+	// No distortion should not move the point in either direction.
+	fn test_none ( )
+	{
+		let distortion = Distortion::none();
+		let point = Vector2{x: 0.3, y: -0.2};
+		assert_eq!(distortion.distort(point), point);
+		assert_eq!(distortion.undistort(point), point);
+	}
+
+//										~ distort ~												 //
+	#[test]
+	// The center of the lens is never distorted.
+	fn test_distort_center ( )
+	{
+		let distortion = Distortion{radial: [0.1, 0.2, 0.3], tangential: [0.01, 0.02]};
+		let point = Vector2{x: 0.0, y: 0.0};
+		assert_eq!(distortion.distort(point), point);
+	}
+
+	#[test]
+	// Barrel distortion (negative k1) should pull the point towards the center.
+	fn test_distort_radial ( )
+	{
+		let distortion = Distortion{radial: [-0.1, 0.0, 0.0], tangential: [0.0, 0.0]};
+		let point = Vector2{x: 0.5, y: 0.0};
+		let distorted = distortion.distort(point);
+		distorted.x.test_equal(&(0.5 * (1.0 - 0.1 * 0.25)));
+		distorted.y.test_equal(&0.0);
+	}
+
+	#[test]
+	// Tangential distortion should offset the point even along an axis.
+	fn test_distort_tangential ( )
+	{
+		let distortion = Distortion{radial: [0.0, 0.0, 0.0], tangential: [0.01, 0.02]};
+		let point = Vector2{x: 0.5, y: 0.0};
+		let distorted = distortion.distort(point);
+		distorted.x.test_equal(&(0.5 + 0.02 * (0.25 + 2.0 * 0.25)));
+		distorted.y.test_equal(&(0.01 * 0.25));
+	}
+
+//										~ undistort ~											 //
+	#[test]
+	// undistort() should reverse distort() for reasonable lenses.
+	fn test_undistort ( )
+	{
+		let mut rng = rand::thread_rng();
+		for _ in 0..100
+		{
+			let distortion = Distortion{
+				radial:     [rng.gen_range(-0.2..0.2), rng.gen_range(-0.05..0.05), 0.0],
+				tangential: [rng.gen_range(-0.001..0.001), rng.gen_range(-0.001..0.001)]};
+			let point = Vector2{x: rng.gen_range(-0.3..0.3), y: rng.gen_range(-0.3..0.3)};
+
+			let undistorted = distortion.undistort(distortion.distort(point));
+			undistorted.x.assert_close(&point.x, 0.00001);
+			undistorted.y.assert_close(&point.y, 0.00001);
+		}
+	}
+}
@@ -1,10 +1,9 @@
 //! Implementation of [IntrinsicParameters](crate::projection::intrinsic_parameters)
 use super::IntrinsicParameters;
+use super::Distortion;
 use super::SpaceCamera;
 use super::SpaceImage;
 
-use util::units::Matrix;
-use util::units::MatPos;
 use util::units::Radians;
 use util::units::Vector2;
 use util::units::Vector3;
@@ -17,17 +16,12 @@ impl IntrinsicParameters
 	/// Lossy as depth is sacrificed.
 	pub fn to_image ( &self, point: SpaceCamera ) -> SpaceImage
 	{
-		let mut matrix : Matrix<3,3> = Matrix::identity();
-		matrix.set(MatPos{row: 0, col: 0}, self.focal_length.x);
-		matrix.set(MatPos{row: 1, col: 1}, self.focal_length.y);
+		let normalized = Vector2{x: point.0.x / point.0.z, y: point.0.y / point.0.z};
+		let distorted  = self.distortion.distort(normalized);
 
-		matrix.set(MatPos{row: 0, col: 2}, self.principle_point.x);
-		matrix.set(MatPos{row: 1, col: 2}, self.principle_point.y);
-
-		let out_3d = matrix.multiply(point.0);
-		let out_2d = out_3d / out_3d.z;
-
-		return SpaceImage(Vector2{x: out_2d.x, y: out_2d.y});
+		let x = distorted.x * self.focal_length.x + self.principle_point.x;
+		let y = distorted.y * self.focal_length.y + self.principle_point.y;
+		return SpaceImage(Vector2{x: x, y: y});
 	}
 
 
@@ -37,7 +31,8 @@ impl IntrinsicParameters
 	{
 		let x = (point.0.x - self.principle_point.x) / self.focal_length.x;
 		let y = (point.0.y - self.principle_point.y) / self.focal_length.y;
-		return SpaceCamera(Vector3{x: x, y: y, z: 1.0});
+		let undistorted = self.distortion.undistort(Vector2{x: x, y: y});
+		return SpaceCamera(Vector3{x: undistorted.x, y: undistorted.y, z: 1.0});
 	}
 
 
@@ -56,7 +51,8 @@ impl IntrinsicParameters
 		let working_dist = (fov / 2.0).cos(); // The distance of the distance from the plane.
 		let focal_length = sensor_size / unit_fov * working_dist;
 		let focal_lengths = Vector2{x: focal_length, y: focal_length};
-		return Self{focal_length: focal_lengths, principle_point: principle_point};
+		return Self{
+			focal_length: focal_lengths, principle_point: principle_point, distortion: Distortion::none()};
 	}
 }
 
@@ -76,6 +72,7 @@ mod test
 	use rand::prelude::*;
 
 	use crate::projection::IntrinsicParameters;
+	use crate::projection::Distortion;
 	use crate::projection::SpaceImage;
 	use crate::projection::SpaceCamera;
 
@@ -101,7 +98,8 @@ mod test
 		let focal_length    = Vector2{x: 1.0,  y: 1.0};
 		let principle_point = Vector2{x: 11.1, y: 22.2};
 		let param = 
-			IntrinsicParameters{focal_length: focal_length, principle_point: principle_point};
+			IntrinsicParameters{focal_length: focal_length, principle_point: principle_point,
+				distortion: Distortion::none()};
 
 		let initial  = SpaceCamera(Vector3{x: 2.0, y: 3.0, z: 1.0});
 		let expected = SpaceImage(Vector2{x: 2.0 + 11.1, y: 3.0 + 22.2});
@@ -115,7 +113,8 @@ mod test
 		let focal_length    = Vector2{x: 5.0,  y: 6.0};
 		let principle_point = Vector2{x: 0.0, y: 0.0};
 		let param = 
-			IntrinsicParameters{focal_length: focal_length, principle_point: principle_point};
+			IntrinsicParameters{focal_length: focal_length, principle_point: principle_point,
+				distortion: Distortion::none()};
 
 		let initial  = SpaceCamera(Vector3{x: 2.0, y: 3.0, z: 2.0});
 		let expected = SpaceImage(Vector2{x: 2.0 * 5.0 / 2.0, y: 3.0 * 6.0 / 2.0});
@@ -136,7 +135,8 @@ mod test
 			let focal_length    = Vector2{x: 1.0, y: 1.0};
 			let principle_point = Vector2{x: 0.0, y: 0.0};
 			let param = 
-				IntrinsicParameters{focal_length: focal_length, principle_point: principle_point};
+				IntrinsicParameters{focal_length: focal_length, principle_point: principle_point,
+				distortion: Distortion::none()};
 			
 			let mut point = SpaceCamera(Vector3{
 				x: rng.gen_range(0.1..10.0), 
@@ -149,6 +149,30 @@ mod test
 		}
 	}
 
+	#[test]
+	// With distortion, to_image() should still be reversed by from_image().
+	fn test_from_image_distortion ( )
+	{
+		let mut rng = rand::thread_rng();
+		for _ in 0..100
+		{
+			let focal_length    = Vector2{x: 500.0, y: 510.0};
+			let principle_point = Vector2{x: 320.0, y: 240.0};
+			let distortion      = Distortion{radial: [-0.1, 0.01, 0.0], tangential: [0.001, -0.001]};
+			let param = IntrinsicParameters{
+				focal_length: focal_length, principle_point: principle_point, distortion: distortion};
+
+			let point = SpaceCamera(Vector3{
+				x: rng.gen_range(-0.3..0.3),
+				y: rng.gen_range(-0.3..0.3),
+				z: 1.0});
+
+			let projected = param.from_image(param.to_image(point));
+			projected.0.x.assert_close(&point.0.x, 0.00001);
+			projected.0.y.assert_close(&point.0.y, 0.00001);
+		}
+	}
+
 
 
 
@@ -5,6 +5,9 @@
 //! # Parameters
 //! * `Extrinsic Parameters` - Rotating and moving world space so that the point with (0, 0, +x) is in front of the camera.
 //! * `Intrinsic Parameters` - Projecting the camera space onto the image plane with scale and translation.
+//! * `Distortion`           - The radial and tangential lens distortion applied as part of the intrinsic parameters.
+//! * `ThermalModel`         - Generates the intrinsic parameters for the temperature of the lens.
+//! * `Uncertainty`          - How accurately a star can be projected, used as the tolerance when searching the database.
 //! 
 //! # Spaces
 //! * `SpaceWorld ` - The location of the objects in a 3D environment relative to \[0,0,0\], 0 rotation.
@@ -101,11 +104,15 @@ use util::units::Matrix;
 use util::units::Vector3;
 use util::units::Vector2;
 use util::units::Pixel;
+use util::aliases::Decimal;
 
 
 
 pub mod extrinsic_parameters;
 pub mod intrinsic_parameters;
+pub mod distortion;
+pub mod thermal_model;
+pub mod uncertainty;
 
 /// The extrinsic (world transformation) parameters.  
 /// These are rotations and translations to convert world coordinates into the camera coordinates.
@@ -132,10 +139,95 @@ pub struct IntrinsicParameters
 	/// The center point of the image.
 	pub principle_point : Vector2,
 
+	/// The lens distortion (use `Distortion::none()` for a rectilinear lens).
+	pub distortion      : Distortion,
+
 	// / How skewed the image is.
 	// pub shear           : Decimal,
 }
 
+/// The Brown-Conrady lens distortion coefficients.  
+/// This is the same model (and coefficient order) used by opencv's `calibrateCamera`.  
+/// The coefficients are applied to the normalized image plane (x/z, y/z) before the focal length is.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Distortion
+{
+	/// The radial coefficients \[k1, k2, k3\].
+	pub radial     : [Decimal; 3],
+	/// The tangential coefficients \[p1, p2\].
+	pub tangential : [Decimal; 2],
+}
+
+
+/// Generates [IntrinsicParameters](crate::projection::IntrinsicParameters) for a given temperature.  
+///
+/// As the lens and mount expand and contract, the focal length, principle point and distortion drift.  
+/// Each parameter is modeled as a polynomial of the difference from the reference temperature:  
+/// `parameter(t) = reference + coefficients[0] * dt + coefficients[1] * dt^2 + ...` where `dt = t - reference_temperature`.  
+/// Use N = 1 for a linear model.
+///
+/// # Example
+/// ```
+/// use star_tracker_lib::projection::IntrinsicParameters;
+/// use star_tracker_lib::projection::ThermalModel;
+/// use star_tracker_lib::util::units::Degrees;
+/// use star_tracker_lib::util::units::Vector2;
+///
+/// let reference = IntrinsicParameters::from_fov(Degrees(20.0).as_radians(), 1000.0, Vector2{x: 400.0, y: 300.0});
+/// // The focal length grows by 5 parts per million for every degree above 20 degrees celsius.
+/// let model = ThermalModel::linear_ppm(reference, 20.0, 5.0);
+///
+/// let hot = model.at_temperature(30.0);
+/// assert!(reference.focal_length.x < hot.focal_length.x);
+/// assert_eq!(model.at_temperature(20.0), reference);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ThermalModel <const N: usize>
+{
+	/// The parameters at the reference temperature.
+	pub reference             : IntrinsicParameters,
+	/// The temperature the reference was measured at (degrees celsius).
+	pub reference_temperature : Decimal,
+	/// The coefficient of each power of the temperature difference (starting at dt^1).
+	pub coefficients          : [IntrinsicParameters; N],
+}
+
+
+/// Models the angular uncertainty of a projected star.  
+///
+/// The error of a star grows towards the edge of the image (residual distortion) and as the star gets dimmer (centroid noise).  
+/// The uncertainty of each star can be provided to [Constellation::find_uncertain](crate::tracking_mode::Constellation::find_uncertain)
+/// so each star pair is searched with its own tolerance.  
+/// The pixel error is `sigma * sqrt((centroid / snr)^2 + residual(r)^2)` where r is the distance from the principle point divided by the focal length.
+///
+/// # Example
+/// ```
+/// use star_tracker_lib::projection::IntrinsicParameters;
+/// use star_tracker_lib::projection::Uncertainty;
+/// use star_tracker_lib::projection::SpaceImage;
+/// use star_tracker_lib::util::units::Degrees;
+/// use star_tracker_lib::util::units::Vector2;
+///
+/// let intrinsic = IntrinsicParameters::from_fov(Degrees(20.0).as_radians(), 1000.0, Vector2{x: 400.0, y: 300.0});
+/// // Use corr_analyser to find the residual.
+/// let model = Uncertainty{centroid: 1.0, residual: [0.1, 0.0, 2.0], sigma: 3.0};
+///
+/// let center = model.find(&intrinsic, SpaceImage(Vector2{x: 400.0, y: 300.0}), 10.0);
+/// let edge   = model.find(&intrinsic, SpaceImage(Vector2{x: 800.0, y: 600.0}), 10.0);
+/// assert!(center < edge);
+/// ```
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Uncertainty
+{
+	/// The centroid error (pixels, 1 standard deviation) of a star with a signal to noise ratio of 1.
+	pub centroid : Decimal,
+	/// The calibration residual (pixels, 1 standard deviation) as a polynomial of the normalized radius.  
+	/// `residual[0] + residual[1] * r + residual[2] * r^2`.
+	pub residual : [Decimal; 3],
+	/// The number of standard deviations the tolerance should cover (3 is recommended).
+	pub sigma    : Decimal,
+}
+
 
 /// A 3D point in a scene with no relation to the camera.
 #[derive(PartialEq, Debug, Clone, Copy)] 
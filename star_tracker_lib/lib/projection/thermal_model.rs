@@ -0,0 +1,151 @@
+//! Implementation of [ThermalModel](crate::projection::ThermalModel)
+use super::IntrinsicParameters;
+use super::ThermalModel;
+use super::Distortion;
+
+use util::units::Vector2;
+use util::aliases::Decimal;
+
+impl <const N: usize> ThermalModel <N>
+{
+	/// Finds the intrinsic parameters at the given temperature.
+	/// # Arguments
+	/// * `temperature` - The temperature of the lens (degrees celsius).
+	pub fn at_temperature ( &self, temperature: Decimal ) -> IntrinsicParameters
+	{
+		let dt = temperature - self.reference_temperature;
+		let mut result = self.reference;
+		let mut power  = 1.0;
+		for i in 0..N
+		{
+			power *= dt;
+			let coeff = &self.coefficients[i];
+			result.focal_length    = result.focal_length    + coeff.focal_length    * power;
+			result.principle_point = result.principle_point + coeff.principle_point * power;
+
+			for j in 0..result.distortion.radial.len()
+			{
+				result.distortion.radial[j] += coeff.distortion.radial[j] * power;
+			}
+			for j in 0..result.distortion.tangential.len()
+			{
+				result.distortion.tangential[j] += coeff.distortion.tangential[j] * power;
+			}
+		}
+		return result;
+	}
+}
+
+
+impl ThermalModel <1>
+{
+	/// Creates a linear model where only the focal length changes.
+	/// This is the most common specification of a lens (ppm/°C).
+	/// # Arguments
+	/// * `reference`             - The parameters at the reference temperature.
+	/// * `reference_temperature` - The temperature `reference` was measured at (degrees celsius).
+	/// * `focal_ppm`             - The change in focal length per degree in parts per million.
+	pub fn linear_ppm (
+		reference: IntrinsicParameters, reference_temperature: Decimal, focal_ppm: Decimal ) -> Self
+	{
+		let coefficient = IntrinsicParameters{
+			focal_length:    reference.focal_length * (focal_ppm / 1_000_000.0),
+			principle_point: Vector2{x: 0.0, y: 0.0},
+			distortion:      Distortion::none()};
+		return Self{
+			reference: reference, reference_temperature: reference_temperature, coefficients: [coefficient]};
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+mod test
+{
+	use crate::projection::IntrinsicParameters;
+	use crate::projection::ThermalModel;
+	use crate::projection::Distortion;
+
+	use crate::util::units::Vector2;
+	use crate::util::test::TestEqual;
+
+	#[coverage(off)]
+	fn reference ( ) -> IntrinsicParameters
+	{
+		return IntrinsicParameters{
+			focal_length:    Vector2{x: 1000.0, y: 1000.0},
+			principle_point: Vector2{x: 400.0, y: 300.0},
+			distortion:      Distortion{radial: [0.1, 0.01, 0.001], tangential: [0.002, 0.003]}};
+	}
+
+//###############################################################################################//
+//
+//										ThermalModel
+//
+// pub fn at_temperature ( &self, Decimal ) -> IntrinsicParameters
+// pub fn linear_ppm     ( IntrinsicParameters, Decimal, Decimal ) -> ThermalModel<1>
+//
+//###############################################################################################//
+//										~ at_temperature ~										 //
+	#[test]
+	// At the reference temperature, the parameters should not change.
+	fn test_at_temperature_reference ( )
+	{
+		let coeff = IntrinsicParameters{
+			focal_length: Vector2{x: 1.0, y: 2.0}, principle_point: Vector2{x: 3.0, y: 4.0},
+			distortion: Distortion{radial: [1.0, 1.0, 1.0], tangential: [1.0, 1.0]}};
+		let model = ThermalModel{reference: reference(), reference_temperature: 20.0, coefficients: [coeff, coeff]};
+		assert_eq!(model.at_temperature(20.0), reference());
+	}
+
+	#[test]
+	// With no coefficients, the temperature should not change anything.
+	fn test_at_temperature_constant ( )
+	{
+		let model = ThermalModel::<0>{reference: reference(), reference_temperature: 20.0, coefficients: []};
+		assert_eq!(model.at_temperature(-40.0), reference());
+	}
+
+	#[test]
+	// Each coefficient should be multiplied by the power of the temperature difference.
+	fn test_at_temperature_polynomial ( )
+	{
+		let linear = IntrinsicParameters{
+			focal_length: Vector2{x: 1.0, y: 2.0}, principle_point: Vector2{x: 0.1, y: -0.1},
+			distortion: Distortion{radial: [0.01, 0.0, 0.0], tangential: [0.0, -0.001]}};
+		let quadratic = IntrinsicParameters{
+			focal_length: Vector2{x: 0.5, y: 0.0}, principle_point: Vector2{x: 0.0, y: 0.0},
+			distortion: Distortion::none()};
+		let model = ThermalModel{reference: reference(), reference_temperature: 20.0, coefficients: [linear, quadratic]};
+
+		// dt = -10
+		let found = model.at_temperature(10.0);
+		assert!(found.focal_length.x.test_close(&(1000.0 - 10.0 + 50.0), 0.0001));
+		assert!(found.focal_length.y.test_close(&(1000.0 - 20.0), 0.0001));
+		assert!(found.principle_point.x.test_close(&(400.0 - 1.0), 0.0001));
+		assert!(found.principle_point.y.test_close(&(300.0 + 1.0), 0.0001));
+		assert!(found.distortion.radial[0].test_close(&(0.1 - 0.1), 0.0001));
+		assert!(found.distortion.tangential[1].test_close(&(0.003 + 0.01), 0.0001));
+	}
+
+//										~ linear_ppm ~											 //
+	#[test]
+	// The focal length should change by the ppm for every degree.
+	fn test_linear_ppm ( )
+	{
+		let model = ThermalModel::linear_ppm(reference(), 20.0, 10.0);
+		let found = model.at_temperature(120.0);
+		assert!(found.focal_length.x.test_close(&1001.0, 0.0001));
+		assert!(found.focal_length.y.test_close(&1001.0, 0.0001));
+		assert_eq!(found.principle_point, reference().principle_point);
+		assert_eq!(found.distortion, reference().distortion);
+	}
+}
@@ -0,0 +1,107 @@
+//! Implementation of [Uncertainty](crate::projection::Uncertainty)
+use crate::core_include::*;
+
+use super::IntrinsicParameters;
+use super::Uncertainty;
+use super::SpaceImage;
+
+use util::units::Radians;
+use util::aliases::Decimal;
+
+impl Uncertainty
+{
+	/// Finds the angular uncertainty of a star on the image.
+	/// # Arguments
+	/// * `intrinsic` - The intrinsic parameters used to project the star.
+	/// * `point`     - The position of the star on the image.
+	/// * `snr`       - The signal to noise ratio of the star (values at or below 0 are treated as 1).
+	/// # Returns
+	/// The tolerance of the star in radians.
+	pub fn find ( &self, intrinsic: &IntrinsicParameters, point: SpaceImage, snr: Decimal ) -> Radians
+	{
+		let focal_length = (intrinsic.focal_length.x + intrinsic.focal_length.y) / 2.0;
+		let radius       = (point.0 - intrinsic.principle_point).magnitude() / focal_length;
+
+		let residual = self.residual[0] + self.residual[1] * radius + self.residual[2] * radius * radius;
+		let centroid = if 0.0 < snr { self.centroid / snr } else { self.centroid };
+		let pixels   = (centroid * centroid + residual * residual).sqrt() * self.sigma;
+
+		// Towards the edge, each pixel covers a smaller angle (d atan(r) = dr / (1 + r^2)).
+		return Radians(pixels / focal_length / (1.0 + radius * radius));
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+mod test
+{
+	use crate::projection::IntrinsicParameters;
+	use crate::projection::Uncertainty;
+	use crate::projection::Distortion;
+	use crate::projection::SpaceImage;
+
+	use crate::util::units::Vector2;
+	use crate::util::test::TestEqual;
+
+	#[coverage(off)]
+	fn intrinsic ( ) -> IntrinsicParameters
+	{
+		return IntrinsicParameters{
+			focal_length:    Vector2{x: 1000.0, y: 1000.0},
+			principle_point: Vector2{x: 400.0, y: 300.0},
+			distortion:      Distortion::none()};
+	}
+
+//###############################################################################################//
+//
+//										Uncertainty
+//
+// pub fn find ( &self, &IntrinsicParameters, SpaceImage, Decimal ) -> Radians
+//
+//###############################################################################################//
+//										~ find ~												 //
+	#[test]
+	// At the center, only the constant residual and centroid error apply.
+	fn test_find_center ( )
+	{
+		let model = Uncertainty{centroid: 3.0, residual: [4.0, 100.0, 100.0], sigma: 2.0};
+		let found = model.find(&intrinsic(), SpaceImage(Vector2{x: 400.0, y: 300.0}), 1.0);
+		// sqrt(3^2 + 4^2) * 2 / 1000
+		found.0.assert_close(&0.01, 0.000001);
+	}
+
+	#[test]
+	// A brighter star should have a smaller uncertainty.
+	fn test_find_snr ( )
+	{
+		let model = Uncertainty{centroid: 3.0, residual: [0.0, 0.0, 0.0], sigma: 1.0};
+		let point = SpaceImage(Vector2{x: 400.0, y: 300.0});
+		let dim    = model.find(&intrinsic(), point, 1.0);
+		let bright = model.find(&intrinsic(), point, 3.0);
+		dim.0.assert_close(&0.003, 0.000001);
+		bright.0.assert_close(&0.001, 0.000001);
+
+		// Invalid snr is treated as 1.
+		assert_eq!(model.find(&intrinsic(), point, 0.0), dim);
+	}
+
+	#[test]
+	// The residual polynomial should use the distance from the principle point over the focal length.
+	fn test_find_radial ( )
+	{
+		let model = Uncertainty{centroid: 0.0, residual: [0.0, 1.0, 2.0], sigma: 1.0};
+		// r = 0.5
+		let found = model.find(&intrinsic(), SpaceImage(Vector2{x: 400.0, y: 800.0}), 1.0);
+		let pixels = 0.5 + 2.0 * 0.25;
+		found.0.assert_close(&(pixels / 1000.0 / 1.25), 0.000001);
+	}
+}
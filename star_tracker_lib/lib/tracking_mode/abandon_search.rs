@@ -0,0 +1,209 @@
+//! Implementation of [Clock](crate::tracking_mode::Clock), [AbandonSearchClock](crate::tracking_mode::AbandonSearchClock) and [AbandonSearchClockFailure](crate::tracking_mode::AbandonSearchClockFailure).
+use crate::tracking_mode::AbandonSearch;
+use crate::tracking_mode::AbandonSearchFailures;
+use crate::tracking_mode::AbandonSearchClock;
+use crate::tracking_mode::AbandonSearchClockFailure;
+use crate::tracking_mode::Clock;
+
+impl Clock for fn ( ) -> u32
+{
+	/// Calls the function.
+	fn now ( &self ) -> u32 { return self(); }
+}
+
+
+
+impl <T: Clock> AbandonSearchClock <T>
+{
+	/// Constructs and starts the timer.
+	/// # Arguments
+	/// * `clock`   - The source of time.
+	/// * `timeout` - The number of ticks of `clock` until the search is abandoned.
+	pub fn new ( clock: T, timeout: u32 ) -> Self
+	{
+		let start = clock.now();
+		return Self { clock: clock, start: start, timeout: timeout };
+	}
+
+	/// The number of ticks since the timer started.
+	pub fn elapsed ( &self ) -> u32
+	{
+		return self.clock.now().wrapping_sub(self.start);
+	}
+}
+
+impl <T: Clock> AbandonSearch for AbandonSearchClock <T>
+{
+	/// If the timeout is exceeded, returns true.
+	fn should_abort ( &mut self ) -> bool
+	{
+		return self.timeout < self.elapsed();
+	}
+}
+
+
+
+impl <T: Clock> AbandonSearchClockFailure <T>
+{
+	/// Constructs and starts the timer counter.
+	/// # Arguments
+	/// * `clock`        - The source of time.
+	/// * `timeout`      - The number of ticks of `clock` until the search is abandoned.
+	/// * `max_failures` - The number of times the algorithm could not find a match until the search is abandoned.
+	pub fn new ( clock: T, timeout: u32, max_failures: usize ) -> Self
+	{
+		return Self {
+			timeout: AbandonSearchClock::new(clock, timeout),
+			failure: AbandonSearchFailures::new(max_failures) };
+	}
+}
+
+impl <T: Clock> AbandonSearch for AbandonSearchClockFailure <T>
+{
+	/// If the timeout is exceeded or count has reached max, returns true.
+	fn should_abort ( &mut self ) -> bool
+	{
+		return self.timeout.should_abort() || self.failure.should_abort();
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::AbandonSearch;
+	use crate::tracking_mode::AbandonSearchClock;
+	use crate::tracking_mode::AbandonSearchClockFailure;
+	use crate::tracking_mode::MockClock;
+	use crate::tracking_mode::Clock;
+
+	// A clock which returns each value of `ticks` in order.
+	#[coverage(off)]
+	fn clock ( ticks: Vec<u32> ) -> MockClock
+	{
+		let mut clock = MockClock::new();
+		let mut index = 0;
+		clock.expect_now().times(ticks.len()).returning(move ||
+		{
+			index += 1;
+			return ticks[index - 1];
+		});
+		return clock;
+	}
+
+	#[coverage(off)]
+	fn clock_fn ( ) -> u32 { return 123; }
+
+
+//###############################################################################################//
+//
+//										Clock
+//
+// fn now ( &self ) -> u32 (for fn ( ) -> u32)
+//
+//###############################################################################################//
+//										~ now ~													 //
+	#[test]
+	// A function should be usable as a clock.
+	fn test_now ( )
+	{
+		let clock = clock_fn as fn ( ) -> u32;
+		assert_eq!(clock.now(), 123);
+	}
+
+
+//###############################################################################################//
+//
+//										AbandonSearchClock
+//
+// pub fn new     ( T, u32 ) -> Self
+// pub fn elapsed ( &self ) -> u32
+// fn should_abort ( &mut self ) -> bool
+//
+//###############################################################################################//
+//										~ new ~													 //
+	#[test]
+	// The timer should start when constructed.
+	fn test_clock_new ( )
+	{
+		let abort = AbandonSearchClock::new(clock(vec![10]), 5);
+		assert_eq!(abort.start, 10);
+		assert_eq!(abort.timeout, 5);
+	}
+
+//										~ elapsed ~												 //
+	#[test]
+	// The elapsed time should be correct even if the clock overflows.
+	fn test_clock_elapsed ( )
+	{
+		let abort = AbandonSearchClock::new(clock(vec![10, 15]), 5);
+		assert_eq!(abort.elapsed(), 5);
+
+		let abort = AbandonSearchClock::new(clock(vec![u32::MAX - 1, 3]), 5);
+		assert_eq!(abort.elapsed(), 5);
+	}
+
+//										~ should_abort ~										 //
+	#[test]
+	// Should abort once more than timeout ticks have passed.
+	fn test_clock_should_abort ( )
+	{
+		let mut abort = AbandonSearchClock::new(clock(vec![10, 10, 15, 16, 12]), 5);
+		assert!(!abort.should_abort());
+		assert!(!abort.should_abort());
+		assert!(abort.should_abort());
+		assert!(!abort.should_abort());
+	}
+
+
+//###############################################################################################//
+//
+//										AbandonSearchClockFailure
+//
+// pub fn new      ( T, u32, usize ) -> Self
+// fn should_abort ( &mut self ) -> bool
+//
+//###############################################################################################//
+//										~ new ~													 //
+	#[test]
+	// Both the timer and counter should be started.
+	fn test_clock_failure_new ( )
+	{
+		let abort = AbandonSearchClockFailure::new(clock(vec![10]), 5, 3);
+		assert_eq!(abort.timeout.start, 10);
+		assert_eq!(abort.timeout.timeout, 5);
+		assert_eq!(abort.failure.max, 3);
+		assert_eq!(abort.failure.count, 0);
+	}
+
+//										~ should_abort ~										 //
+	#[test]
+	// Should abort once the failures are exceeded.
+	fn test_clock_failure_should_abort_failures ( )
+	{
+		let mut abort = AbandonSearchClockFailure::new(clock(vec![0, 0, 0, 0]), 5, 2);
+		assert!(!abort.should_abort());
+		assert!(!abort.should_abort());
+		assert!(abort.should_abort());
+	}
+
+	#[test]
+	// Should abort once the timeout is exceeded.
+	fn test_clock_failure_should_abort_timeout ( )
+	{
+		let mut abort = AbandonSearchClockFailure::new(clock(vec![0, 1, 6]), 5, 100);
+		assert!(!abort.should_abort());
+		assert!(abort.should_abort());
+		assert_eq!(abort.failure.count, 1);
+	}
+}
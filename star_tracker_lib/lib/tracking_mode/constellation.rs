@@ -5,16 +5,19 @@ use super::SpecularityConstruct;
 use super::TriangleConstruct;
 use super::AbandonSearch;
 use super::Constellation;
-use super::StarTriangle;
+use super::ConstellationSearch;
+use super::ConstellationStep;
 use super::ConstellationResult;
 use super::AbandonSearchFailures;
+use super::Photometry;
+use super::SearchDiagnostics;
 use crate::tracking_mode::database::ChunkIterator;
 
 use crate::util::units::Equatorial;
 use crate::util::units::Radians;
 use crate::util::units::Match;
+use crate::util::aliases::Decimal;
 use crate::util::list::List;
-use crate::util::err::Error;
 use crate::core_include::RangeInclusive;
 
 impl Constellation
@@ -55,65 +58,126 @@ pub fn find	(
 	matches        : &mut dyn List<Match<usize>>,
 ) -> ConstellationResult
 {
-	let mut result = ConstellationResult::ErrorNoTriangleMatch{fails: 0};
-	let mut should_abort  = abort.should_abort();
-	database.begin();
 	gen_tri.begin(angle_tolerance, stars);
+	return Self::search(stars, database, gen_tri, gen_spec, abort, num_stars, matches, None);
+}
+
+
+
+/// The same as `find` except each star has its own uncertainty.  
+/// Stars near the edge of the lens or dim stars are less accurate, by giving each star its own uncertainty,
+/// the database search will be tighter for accurate stars and looser for inaccurate stars.  
+/// Use [Uncertainty](crate::projection::Uncertainty) to find the uncertainty of each star.
+///
+/// # Arguments
+/// * `stars`           - The observed (image) stars.
+/// * `uncertainty`     - The uncertainty of each star (same index as `stars`).
+/// * `database`        - The database storing reference
+/// * `gen_tri`         - An object for generating a StarTriangle (must support `begin_uncertain`).
+/// * `gen_spec`        - An object for checking Specularity.
+/// * `abort`           - A way of abandoning a search if it takes too long.
+/// * `angle_tolerance` - The tolerance to use for any star without an uncertainty.
+/// * `num_stars`       - The how many stars are required (see `find`).
+/// * `matches`         - The output.
+pub fn find_uncertain (
+	stars          : &dyn List<Equatorial>,
+	uncertainty    : &dyn List<Radians>,
+	database       : &mut dyn ChunkIterator,
+	gen_tri        : &mut dyn TriangleConstruct,
+	gen_spec       : &mut dyn SpecularityConstruct,
+	abort          : &mut dyn AbandonSearch,
+	angle_tolerance: Radians,
+	num_stars      : RangeInclusive<usize>,
+	matches        : &mut dyn List<Match<usize>>,
+) -> ConstellationResult
+{
+	gen_tri.begin_uncertain(angle_tolerance, uncertainty, stars);
+	return Self::search(stars, database, gen_tri, gen_spec, abort, num_stars, matches, None);
+}
+
+
+
+/// The same as `find` except the brightness of each star must agree with the catalogue.  
+/// Any triangle or pilot star where a bright observed star is matched with a dim catalogue star (or the other way around) is skipped.  
+/// Use a [PhotometricDatabase](crate::tracking_mode::database::PhotometricDatabase) so the magnitudes are available.
+///
+/// # Arguments
+/// * `stars`           - The observed (image) stars.
+/// * `intensity`       - The intensity of each star (same index as `stars`).
+/// * `photometry`      - The magnitude to intensity calibration.
+/// * `database`        - The database storing reference
+/// * `gen_tri`         - An object for generating a StarTriangle (must support `begin_photometric`).
+/// * `gen_spec`        - An object for checking Specularity.
+/// * `abort`           - A way of abandoning a search if it takes too long.
+/// * `angle_tolerance` - How much error a star pair can have until it is not considered the same.
+/// * `num_stars`       - The how many stars are required (see `find`).
+/// * `matches`         - The output.
+pub fn find_photometric (
+	stars          : &dyn List<Equatorial>,
+	intensity      : &dyn List<Decimal>,
+	photometry     : Photometry,
+	database       : &mut dyn ChunkIterator,
+	gen_tri        : &mut dyn TriangleConstruct,
+	gen_spec       : &mut dyn SpecularityConstruct,
+	abort          : &mut dyn AbandonSearch,
+	angle_tolerance: Radians,
+	num_stars      : RangeInclusive<usize>,
+	matches        : &mut dyn List<Match<usize>>,
+) -> ConstellationResult
+{
+	gen_tri.begin_photometric(angle_tolerance, photometry, intensity, stars);
+	return Self::search(stars, database, gen_tri, gen_spec, abort, num_stars, matches, None);
+}
 
-	// Loop through every possible combination of star combination using the kernel_iterator.
-	// Using star_triangle_iterator to find triangle matches in the database.
-	while !should_abort && let Some(iter) = gen_tri.next(stars, database)
-	{
-		// input and output both make triangles of the same length.
-		let input  : Error<StarTriangle<Equatorial>> = iter.input.search_list(stars);
-		let output : Error<StarTriangle<Equatorial>> = 
-			iter.output.search_database(database.get_database());
-
-		// ~~~~~~~~~~~~~~~~~~~~~~~~~	 A valid match was found.		~~~~~~~~~~~~~~~~~~~~
-		// If the stars can be found in the database and the observed list (bug if not),
-		// The code can continue.
-		if input.is_ok() && output.is_ok()
-		{
-			let input = input.unwrap();
-			let output = output.unwrap();
 
-			// ~~~~~~~~~~~~~~~~~~~~~	 The specularity in/out match	~~~~~~~~~~~~~~~~~~~~
-			// If the triangles are not flipped, the code can continue.
-			if gen_spec.same(&input.to_vector3(), &output.to_vector3())
-			{
-				result = ConstellationResult::ErrorInsufficientPyramids{fails: *result.get_fails()};
-
-				matches.clear();
-				let _=
-				matches.push_back(Match{input: iter.input.0, output: iter.output.0, weight: 1.0});
-				let _=
-				matches.push_back(Match{input: iter.input.1, output: iter.output.1, weight: 1.0});
-				let _=
-				matches.push_back(Match{input: iter.input.2, output: iter.output.2, weight: 1.0});
-
-				// ~~~~~~~~~~~~~	Searching through pilot stars.		~~~~~~~~~~~~~~~~~~~~
-				while matches.size() < *num_stars.end() && 
-					let Some(pilot) = gen_tri.next_pilot(stars, database) 
-				{
-					let _=
-					matches.push_back(Match{input: pilot.input, output: pilot.output, weight:1.0});
-				}
-				
-				if *num_stars.start() <= matches.size()
-				{ 
-					return ConstellationResult::Success{fails: *result.get_fails()};
-				}
-			}
-		}
 
-		*result.get_fails() += 1;
-		should_abort = abort.should_abort();
+/// Searches for the constellation after `gen_tri` has begun, each step of the search can be reported to `diagnostics`.  
+/// `find`, `find_uncertain` and `find_photometric` begin `gen_tri` and call this without diagnostics.  
+/// To find why a search failed or was slow, begin `gen_tri` yourself and pass a [SearchDiagnosticsRecord](crate::tracking_mode::SearchDiagnosticsRecord)
+/// (`diagnostics` is reset at the start of the search).
+///
+/// # Arguments
+/// * `stars`       - The observed (image) stars.
+/// * `database`    - The database storing reference
+/// * `gen_tri`     - An object for generating a StarTriangle, `begin`, `begin_uncertain` or `begin_photometric` must have been called.
+/// * `gen_spec`    - An object for checking Specularity.
+/// * `abort`       - A way of abandoning a search if it takes too long.
+/// * `num_stars`   - The how many stars are required (see `find`).
+/// * `matches`     - The output.
+/// * `diagnostics` - Where each step of the search is reported, None to not report.
+///
+/// # Example
+/// ``` ignore
+/// let mut record : SearchDiagnosticsRecord<_, 10> = SearchDiagnosticsRecord::new(clock);
+/// gen_tri.begin_uncertain(angle_tolerance, &uncertainty, &stars);
+/// let result = Constellation::search(&stars, &mut database, &mut gen_tri, &mut gen_spec, &mut abort,
+/// 	4..=4, &mut matches, Some(&mut record));
+/// println!("{} triangles, {} pilot sides", record.triangles, record.pilot_sides);
+/// ```
+pub fn search (
+	stars          : &dyn List<Equatorial>,
+	database       : &mut dyn ChunkIterator,
+	gen_tri        : &mut dyn TriangleConstruct,
+	gen_spec       : &mut dyn SpecularityConstruct,
+	abort          : &mut dyn AbandonSearch,
+	num_stars      : RangeInclusive<usize>,
+	matches        : &mut dyn List<Match<usize>>,
+	diagnostics    : Option<&mut dyn SearchDiagnostics>,
+) -> ConstellationResult
+{
+	let mut search =
+		ConstellationSearch::begun(stars, database, gen_tri, gen_spec, abort, num_stars, matches);
+	if let Some(diagnostics) = diagnostics
+	{
+		search = search.with_diagnostics(diagnostics);
 	}
-	if should_abort
+	loop
 	{
-		return ConstellationResult::ErrorAborted{fails: *result.get_fails()};
+		if let ConstellationStep::Done(result) = search.step(usize::MAX)
+		{
+			return result;
+		}
 	}
-	return result;
 }
 }
 
@@ -171,20 +235,30 @@ impl AbandonSearch for AbandonSearchFailures
 mod test
 {
 	use super::Constellation;
-	use super::StarTriangle;
+	use crate::tracking_mode::StarTriangle;
 	use super::Match;
 	use crate::tracking_mode::database::MockDatabase;
 	use crate::tracking_mode::database::MockChunkIterator;
 	use crate::tracking_mode::database::ChunkIteratorNone;
 	use crate::tracking_mode::MockSpecularityConstruct;
 	use crate::tracking_mode::MockTriangleConstruct;
+	use crate::tracking_mode::TriangleConstruct;
 	use crate::tracking_mode::MockAbandonSearch;
 	use crate::tracking_mode::AbandonSearch;
 	use crate::tracking_mode::AbandonSearchFailures;
 	use crate::tracking_mode::ConstellationResult;
+	use crate::tracking_mode::Photometry;
+	use crate::tracking_mode::SearchDiagnosticsRecord;
+	use crate::tracking_mode::StarTriangleIterator;
+	use crate::tracking_mode::Specularity;
+	use crate::tracking_mode::database::PyramidDatabase;
+	use crate::tracking_mode::database::KVector;
 
 	use crate::util::units::Equatorial;
+	use crate::util::units::Degrees;
 	use crate::util::units::Radians;
+	use crate::util::test::star_patch;
+	use crate::util::test::star_pairs;
 	use crate::util::err::Errors;
 
 
@@ -774,4 +848,264 @@ mod test
 		assert!(matches!(passed, ConstellationResult::ErrorAborted{fails: 2}));
 		assert_eq!(matches.len(), 3);
 	}
+
+
+//###############################################################################################//
+//
+//										find_uncertain
+//
+// pub fn find_uncertain (
+// 	stars          : &dyn List<Equatorial>,
+// 	uncertainty    : &dyn List<Radians>,
+// 	database       : &mut dyn ChunkIterator,
+// 	gen_tri        : &mut dyn TriangleConstruct,
+// 	gen_spec       : &mut dyn SpecularityConstruct,
+// 	abort          : &mut dyn AbandonSearch,
+// 	angle_tolerance: Radians,
+// 	num_stars      : RangeInclusive<usize>,
+// 	matches        : &mut dyn List<Match<usize>>,
+// ) -> ConstellationResult
+//
+//###############################################################################################//
+
+	#[test]
+	// The uncertainty should be passed to the triangle constructor, the search is the same as find.
+	fn test_find_uncertain ( )
+	{
+		let stars = vec![Equatorial::north()];
+		let uncertainty = vec![Radians(0.1)];
+
+		let mut mock_d = MockDatabase::new();
+		let mut mock_t = MockTriangleConstruct::new();
+		let mut mock_s = MockSpecularityConstruct::new();
+		let mut abandon = abandon_never();
+		let angle_tolerance = Radians(0.2);
+		let mut matches = Vec::<Match<usize>>::new();
+
+		mock_t.expect_begin_uncertain().times(1)
+			.withf(|tolerance, uncertainty, _| *tolerance == Radians(0.2) && uncertainty.get(0) == Radians(0.1))
+			.returning(|_, _, _| return);
+		mock_t.expect_next()      .times(1).returning(|_, _| 
+			Some(Match{input: StarTriangle(0, 0, 0), output: StarTriangle(1, 2, 3), weight: 0.0}));
+
+		mock_d.expect_find_star() .times(3).returning(|_| return Ok(Equatorial::north()));
+		mock_s.expect_same()      .times(1).returning(|_, _| true);
+		mock_t.expect_next_pilot().times(1).returning(|_, _| 
+			return Some(Match{input: 200, output: 100, weight: 0.0}));
+
+		let mut mock_c = ChunkIteratorNone::new(&mock_d);
+
+		let passed = Constellation::find_uncertain (&stars, &uncertainty, &mut mock_c, &mut mock_t, &mut mock_s, &mut abandon, angle_tolerance, 4..=4, &mut matches);
+		assert!(matches!(passed, ConstellationResult::Success{fails: 0}));
+		assert_eq!(matches.len(), 4);
+	}
+
+	#[test]
+	// The pilot star is further from the catalogue than the tolerance.
+	// It should only be found if its own uncertainty covers the error.
+	fn test_find_uncertain_pilot ( )
+	{
+		let catalogue = star_patch((30.0, 20.0), 4);
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+
+		let mut stars = catalogue.clone();
+		stars[3].dec = stars[3].dec + Degrees(0.1).as_radians();
+		let angle_tolerance = Degrees(0.01).as_radians();
+
+		let mut matches = Vec::<Match<usize>>::new();
+		let passed = Constellation::find(&stars, &mut ChunkIteratorNone::new(&database),
+			&mut StarTriangleIterator::<100, 4>::new(), &mut Specularity::default(), &mut abandon_never(),
+			angle_tolerance, 4..=4, &mut matches);
+		assert!(!matches!(passed, ConstellationResult::Success{fails: _}));
+
+		let uncertainty = vec![angle_tolerance, angle_tolerance, angle_tolerance, Degrees(0.2).as_radians()];
+		let mut matches = Vec::<Match<usize>>::new();
+		let passed = Constellation::find_uncertain(&stars, &uncertainty, &mut ChunkIteratorNone::new(&database),
+			&mut StarTriangleIterator::<100, 4>::new(), &mut Specularity::default(), &mut abandon_never(),
+			angle_tolerance, 4..=4, &mut matches);
+		assert!(matches!(passed, ConstellationResult::Success{fails: _}));
+		assert_eq!(matches.len(), 4);
+		for found in matches { assert_eq!(found.input, found.output); }
+	}
+
+
+
+//###############################################################################################//
+//
+//										find_photometric
+//
+// pub fn find_photometric (
+// 	stars          : &dyn List<Equatorial>,
+// 	intensity      : &dyn List<Decimal>,
+// 	photometry     : Photometry,
+// 	database       : &mut dyn ChunkIterator,
+// 	gen_tri        : &mut dyn TriangleConstruct,
+// 	gen_spec       : &mut dyn SpecularityConstruct,
+// 	abort          : &mut dyn AbandonSearch,
+// 	angle_tolerance: Radians,
+// 	num_stars      : RangeInclusive<usize>,
+// 	matches        : &mut dyn List<Match<usize>>,
+// ) -> ConstellationResult
+//
+//###############################################################################################//
+
+	#[test]
+	// The intensity and calibration should be passed to the triangle constructor, the search is the same as find.
+	fn test_find_photometric ( )
+	{
+		let stars = vec![Equatorial::north()];
+		let intensity = vec![100.0];
+		let photometry = Photometry{zero_point: 1000.0, tolerance: 0.5};
+
+		let mut mock_d = MockDatabase::new();
+		let mut mock_t = MockTriangleConstruct::new();
+		let mut mock_s = MockSpecularityConstruct::new();
+		let mut abandon = abandon_never();
+		let angle_tolerance = Radians(0.2);
+		let mut matches = Vec::<Match<usize>>::new();
+
+		mock_t.expect_begin_photometric().times(1)
+			.withf(|tolerance, photometry, intensity, _|
+				*tolerance == Radians(0.2) && photometry.zero_point == 1000.0 && intensity.get(0) == 100.0)
+			.returning(|_, _, _, _| return);
+		mock_t.expect_next()      .times(1).returning(|_, _|
+			Some(Match{input: StarTriangle(0, 0, 0), output: StarTriangle(1, 2, 3), weight: 0.0}));
+
+		mock_d.expect_find_star() .times(3).returning(|_| return Ok(Equatorial::north()));
+		mock_s.expect_same()      .times(1).returning(|_, _| true);
+		mock_t.expect_next_pilot().times(1).returning(|_, _|
+			return Some(Match{input: 200, output: 100, weight: 0.0}));
+
+		let mut mock_c = ChunkIteratorNone::new(&mock_d);
+
+		let passed = Constellation::find_photometric (&stars, &intensity, photometry, &mut mock_c, &mut mock_t, &mut mock_s, &mut abandon, angle_tolerance, 4..=4, &mut matches);
+		assert!(matches!(passed, ConstellationResult::Success{fails: 0}));
+		assert_eq!(matches.len(), 4);
+	}
+
+
+
+//###############################################################################################//
+//
+//										search
+//
+// pub fn search (
+// 	stars          : &dyn List<Equatorial>,
+// 	database       : &mut dyn ChunkIterator,
+// 	gen_tri        : &mut dyn TriangleConstruct,
+// 	gen_spec       : &mut dyn SpecularityConstruct,
+// 	abort          : &mut dyn AbandonSearch,
+// 	num_stars      : RangeInclusive<usize>,
+// 	matches        : &mut dyn List<Match<usize>>,
+// 	diagnostics    : Option<&mut dyn SearchDiagnostics>,
+// ) -> ConstellationResult
+//
+//###############################################################################################//
+
+	#[test]
+	// The search is the same as find except gen_tri is not begun and the steps are recorded.
+	fn test_search_diagnostics ( )
+	{
+		let stars = vec![Equatorial::north()];
+
+		let mut mock_d = MockDatabase::new();
+		let mut mock_t = MockTriangleConstruct::new();
+		let mut mock_s = MockSpecularityConstruct::new();
+		let mut abandon = abandon_never();
+		let mut matches = Vec::<Match<usize>>::new();
+		let mut record : SearchDiagnosticsRecord<fn()->u32, 4> = SearchDiagnosticsRecord::new(|| 0);
+
+		mock_t.expect_begin()     .times(0);
+		mock_t.expect_next()      .times(1).returning(|_, _|
+			Some(Match{input: StarTriangle(0, 0, 0), output: StarTriangle(1, 2, 3), weight: 0.0}));
+
+		mock_d.expect_find_star() .times(3).returning(|_| return Ok(Equatorial::north()));
+		mock_s.expect_same()      .times(1).returning(|_, _| true);
+		mock_t.expect_next_pilot().times(1).returning(|_, _|
+			return Some(Match{input: 200, output: 100, weight: 0.0}));
+
+		let mut mock_c = ChunkIteratorNone::new(&mock_d);
+
+		let passed = Constellation::search(&stars, &mut mock_c, &mut mock_t, &mut mock_s, &mut abandon, 4..=4, &mut matches, Some(&mut record));
+		assert!(matches!(passed, ConstellationResult::Success{fails: 0}));
+		assert_eq!(matches.len(), 4);
+		assert_eq!(record.triangles, 1);
+		assert_eq!(record.pilots, 1);
+		assert_eq!(record.pilot_failures, 0);
+		assert_eq!(record.result, Some(passed));
+	}
+
+	#[test]
+	// The database lookups of the pilot should be counted separately to the sides of the triangle.
+	fn test_search_diagnostics_pilot ( )
+	{
+		let catalogue = star_patch((30.0, 20.0), 4);
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+
+		let mut gen_tri = StarTriangleIterator::<100, 4>::new();
+		gen_tri.begin(Degrees(0.01).as_radians(), &catalogue);
+		let mut matches = Vec::<Match<usize>>::new();
+		let mut record : SearchDiagnosticsRecord<fn()->u32, 4> = SearchDiagnosticsRecord::new(|| 0);
+		let passed = Constellation::search(&catalogue, &mut ChunkIteratorNone::new(&database), &mut gen_tri,
+			&mut Specularity::default(), &mut abandon_never(), 4..=4, &mut matches, Some(&mut record));
+		assert!(matches!(passed, ConstellationResult::Success{fails: 0}));
+		assert_eq!(record.candidates, 3);
+		assert_eq!(record.pilot_sides, 3);
+		assert_eq!(record.pilot_candidates, 3);
+		assert_eq!(record.pilots, 1);
+	}
+
+	#[test]
+	// The diagnostics can be used with any way of beginning gen_tri.
+	fn test_search_diagnostics_uncertain ( )
+	{
+		let catalogue = star_patch((30.0, 20.0), 4);
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+
+		let mut stars = catalogue.clone();
+		stars[3].dec = stars[3].dec + Degrees(0.1).as_radians();
+		let angle_tolerance = Degrees(0.01).as_radians();
+		let uncertainty = vec![angle_tolerance, angle_tolerance, angle_tolerance, Degrees(0.2).as_radians()];
+
+		let mut gen_tri = StarTriangleIterator::<100, 4>::new();
+		gen_tri.begin_uncertain(angle_tolerance, &uncertainty, &stars);
+		let mut matches = Vec::<Match<usize>>::new();
+		let mut record : SearchDiagnosticsRecord<fn()->u32, 4> = SearchDiagnosticsRecord::new(|| 0);
+		let passed = Constellation::search(&stars, &mut ChunkIteratorNone::new(&database), &mut gen_tri,
+			&mut Specularity::default(), &mut abandon_never(), 4..=4, &mut matches, Some(&mut record));
+		assert!(matches!(passed, ConstellationResult::Success{fails: _}));
+		assert_eq!(matches.len(), 4);
+		assert_eq!(record.pilots, 1);
+		assert_eq!(record.pilot_failures, 0);
+		assert!(3 <= record.pilot_sides);
+		assert_eq!(record.result, Some(passed));
+	}
+
+	#[test]
+	// Without diagnostics, the search is the same.
+	fn test_search_none ( )
+	{
+		let catalogue = star_patch((30.0, 20.0), 4);
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+
+		let mut gen_tri = StarTriangleIterator::<100, 4>::new();
+		gen_tri.begin(Degrees(0.01).as_radians(), &catalogue);
+		let mut matches = Vec::<Match<usize>>::new();
+		let passed = Constellation::search(&catalogue, &mut ChunkIteratorNone::new(&database), &mut gen_tri,
+			&mut Specularity::default(), &mut abandon_never(), 4..=4, &mut matches, None);
+		assert!(matches!(passed, ConstellationResult::Success{fails: 0}));
+		assert_eq!(matches.len(), 4);
+	}
 }
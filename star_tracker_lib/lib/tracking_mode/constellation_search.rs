@@ -0,0 +1,639 @@
+//! Implementation of [ConstellationSearch](crate::tracking_mode::ConstellationSearch).
+use crate::core_include::*;
+
+use crate::tracking_mode::SpecularityConstruct;
+use crate::tracking_mode::TriangleConstruct;
+use crate::tracking_mode::AbandonSearch;
+use crate::tracking_mode::ConstellationSearch;
+use crate::tracking_mode::ConstellationStep;
+use crate::tracking_mode::ConstellationResult;
+use crate::tracking_mode::StarTriangle;
+use crate::tracking_mode::SearchDiagnostics;
+use crate::tracking_mode::ChunkIteratorDiagnostics;
+use crate::tracking_mode::database::ChunkIterator;
+
+use crate::util::units::Equatorial;
+use crate::util::units::Radians;
+use crate::util::units::Match;
+use crate::util::list::List;
+use crate::util::err::Error;
+
+impl <'a> ConstellationSearch <'a>
+{
+	/// Starts a search, this is the same as calling [find](crate::tracking_mode::Constellation::find) with a budget.
+	/// `gen_tri` and `database` are reset, no triangles are tried until `step` is called.
+	///
+	/// # Arguments
+	/// * `stars`           - The observed (image) stars.
+	/// * `database`        - The database storing reference
+	/// * `gen_tri`         - An object for generating a StarTriangle.
+	/// * `gen_spec`        - An object for checking Specularity.
+	/// * `abort`           - A way of abandoning a search if it takes too long.
+	/// * `angle_tolerance` - How much error a star pair can have until it is not considered the same.
+	/// * `num_stars`       - The how many stars are required (see `find`).
+	/// * `matches`         - The output.
+	pub fn new (
+		stars          : &'a dyn List<Equatorial>,
+		database       : &'a mut dyn ChunkIterator,
+		gen_tri        : &'a mut dyn TriangleConstruct,
+		gen_spec       : &'a mut dyn SpecularityConstruct,
+		abort          : &'a mut dyn AbandonSearch,
+		angle_tolerance: Radians,
+		num_stars      : RangeInclusive<usize>,
+		matches        : &'a mut dyn List<Match<usize>>,
+	) -> Self
+	{
+		gen_tri.begin(angle_tolerance, stars);
+		return Self::begun(stars, database, gen_tri, gen_spec, abort, num_stars, matches);
+	}
+
+
+	/// Starts a search where `gen_tri` has already begun (e.g. with `begin_uncertain`).
+	///
+	/// # Arguments
+	/// Same as `new`.
+	pub fn begun (
+		stars          : &'a dyn List<Equatorial>,
+		database       : &'a mut dyn ChunkIterator,
+		gen_tri        : &'a mut dyn TriangleConstruct,
+		gen_spec       : &'a mut dyn SpecularityConstruct,
+		abort          : &'a mut dyn AbandonSearch,
+		num_stars      : RangeInclusive<usize>,
+		matches        : &'a mut dyn List<Match<usize>>,
+	) -> Self
+	{
+		let should_abort = abort.should_abort();
+		database.begin();
+		return Self
+		{
+			stars:        stars,
+			database:     database,
+			gen_tri:      gen_tri,
+			gen_spec:     gen_spec,
+			abort:        abort,
+			num_stars:    num_stars,
+			matches:      matches,
+			result:       ConstellationResult::ErrorNoTriangleMatch{fails: 0},
+			should_abort: should_abort,
+			pilot:        false,
+			done:         false,
+			diagnostics:  None,
+		};
+	}
+
+
+	/// Reports each step of the search to `diagnostics`, `begin` is called on `diagnostics`.
+	///
+	/// # Arguments
+	/// * `diagnostics` - Where the triangles, database candidates, pilots and result are reported.
+	pub fn with_diagnostics ( mut self, diagnostics: &'a mut dyn SearchDiagnostics ) -> Self
+	{
+		diagnostics.begin();
+		self.diagnostics = Some(diagnostics);
+		return self;
+	}
+
+
+	/// Continues the search.
+	/// Each star triangle and pilot star taken from `gen_tri` uses 1 of the budget.
+	/// Once the search has ended, every call returns the same result without doing anything.
+	///
+	/// # Arguments
+	/// * `budget` - The max number of star triangles and pilot stars to try.
+	///
+	/// # Returns
+	/// InProgress if the budget was used before the search ended.
+	/// Done with the same result as [find](crate::tracking_mode::Constellation::find) if the search ended.
+	pub fn step ( &mut self, budget: usize ) -> ConstellationStep
+	{
+		if self.done
+		{
+			return ConstellationStep::Done(self.result);
+		}
+
+		let mut work = 0;
+		loop
+		{
+			// ~~~~~~~~~~~~~~~~~	Searching through pilot stars.		~~~~~~~~~~~~~~~~~~~~~~~~
+			if self.pilot
+			{
+				if self.matches.size() < *self.num_stars.end()
+				{
+					if budget <= work { return ConstellationStep::InProgress; }
+					work += 1;
+
+					let pilot = match &mut self.diagnostics
+					{
+						Some(diagnostics) =>
+						{
+							let mut database = ChunkIteratorDiagnostics::new_pilot(&mut *self.database, &mut **diagnostics);
+							self.gen_tri.next_pilot(self.stars, &mut database)
+						},
+						None => self.gen_tri.next_pilot(self.stars, self.database),
+					};
+					if let Some(pilot) = pilot
+					{
+						if let Some(diagnostics) = &mut self.diagnostics { diagnostics.pilot(true); }
+						let _=
+						self.matches.push_back(Match{input: pilot.input, output: pilot.output, weight:1.0});
+						continue;
+					}
+				}
+
+				self.pilot = false;
+				if *self.num_stars.start() <= self.matches.size()
+				{
+					let fails = *self.result.get_fails();
+					return self.finish(ConstellationResult::Success{fails: fails});
+				}
+				if let Some(diagnostics) = &mut self.diagnostics { diagnostics.pilot(false); }
+				self.fail();
+			}
+
+			if self.should_abort
+			{
+				let fails = *self.result.get_fails();
+				return self.finish(ConstellationResult::ErrorAborted{fails: fails});
+			}
+
+			// ~~~~~~~~~~~~~~~~~	Searching through star triangles.	~~~~~~~~~~~~~~~~~~~~~~~~
+			if budget <= work { return ConstellationStep::InProgress; }
+			work += 1;
+
+			let next = match &mut self.diagnostics
+			{
+				Some(diagnostics) =>
+				{
+					let mut database = ChunkIteratorDiagnostics::new(&mut *self.database, &mut **diagnostics);
+					self.gen_tri.next(self.stars, &mut database)
+				},
+				None => self.gen_tri.next(self.stars, self.database),
+			};
+
+			let iter = match next
+			{
+				Some(iter) => iter,
+				None       => return self.finish(self.result),
+			};
+			if let Some(diagnostics) = &mut self.diagnostics { diagnostics.triangle(iter); }
+
+			// input and output both make triangles of the same length.
+			let input  : Error<StarTriangle<Equatorial>> = iter.input.search_list(self.stars);
+			let output : Error<StarTriangle<Equatorial>> =
+				iter.output.search_database(self.database.get_database());
+
+			// If the stars can be found and the triangles are not flipped, the pilot can be searched for.
+			let found = input.is_ok() && output.is_ok();
+			let same  = found && self.gen_spec.same(&input.unwrap().to_vector3(), &output.unwrap().to_vector3());
+
+			if found && !same
+			{
+				if let Some(diagnostics) = &mut self.diagnostics { diagnostics.specularity(); }
+			}
+
+			if same
+			{
+				self.result = ConstellationResult::ErrorInsufficientPyramids{fails: *self.result.get_fails()};
+
+				self.matches.clear();
+				let _=
+				self.matches.push_back(Match{input: iter.input.0, output: iter.output.0, weight: 1.0});
+				let _=
+				self.matches.push_back(Match{input: iter.input.1, output: iter.output.1, weight: 1.0});
+				let _=
+				self.matches.push_back(Match{input: iter.input.2, output: iter.output.2, weight: 1.0});
+				self.pilot = true;
+			}
+			else
+			{
+				self.fail();
+			}
+		}
+	}
+
+
+	/// Records a failed star triangle and checks if the search should be abandoned.
+	fn fail ( &mut self )
+	{
+		*self.result.get_fails() += 1;
+		self.should_abort = self.abort.should_abort();
+	}
+
+
+	/// Ends the search with `result`.
+	fn finish ( &mut self, result: ConstellationResult ) -> ConstellationStep
+	{
+		self.result = result;
+		self.done   = true;
+		if let Some(diagnostics) = &mut self.diagnostics { diagnostics.end(result); }
+		return ConstellationStep::Done(result);
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::ConstellationSearch;
+	use crate::tracking_mode::ConstellationStep;
+	use crate::tracking_mode::ConstellationResult;
+	use crate::tracking_mode::StarTriangle;
+	use crate::tracking_mode::database::MockDatabase;
+	use crate::tracking_mode::database::MockChunkIterator;
+	use crate::tracking_mode::database::ChunkIteratorNone;
+	use crate::tracking_mode::MockSpecularityConstruct;
+	use crate::tracking_mode::MockTriangleConstruct;
+	use crate::tracking_mode::MockAbandonSearch;
+	use crate::tracking_mode::SearchDiagnosticsRecord;
+	use crate::tracking_mode::MockSearchDiagnostics;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::Radians;
+	use crate::util::units::Match;
+
+	// Returns an AbandonSearch which is mocked and will return false every time it is called.
+	#[coverage(off)]
+	fn abandon_never ( ) -> MockAbandonSearch
+	{
+		let mut abandon = MockAbandonSearch::new();
+		abandon.expect_should_abort().returning(||false);
+		return abandon;
+	}
+
+	// A database where every star exists.
+	#[coverage(off)]
+	fn database ( ) -> MockDatabase
+	{
+		let mut database = MockDatabase::new();
+		database.expect_find_star().returning(|_| Ok(Equatorial::north()));
+		return database;
+	}
+
+	#[coverage(off)]
+	fn stars ( ) -> Vec<Equatorial>
+	{
+		return vec![Equatorial::north(), Equatorial::south(), Equatorial::zero()];
+	}
+
+	// A triangle generator which provides `triangles` triangles then none.
+	#[coverage(off)]
+	fn triangles ( triangles: usize ) -> MockTriangleConstruct
+	{
+		let mut gen_tri = MockTriangleConstruct::new();
+		let mut count   = 0;
+		gen_tri.expect_begin().times(1).returning(|_, _| return);
+		gen_tri.expect_next().times(triangles + 1).returning(move |_, _|
+		{
+			count += 1;
+			if triangles < count { return None; }
+			return Some(Match{input: StarTriangle(0, 1, 2), output: StarTriangle(0, 1, 2), weight: 1.0});
+		});
+		return gen_tri;
+	}
+
+	#[coverage(off)]
+	fn specularity ( same: bool ) -> MockSpecularityConstruct
+	{
+		let mut gen_spec = MockSpecularityConstruct::new();
+		gen_spec.expect_same().returning(move |_, _| same);
+		return gen_spec;
+	}
+
+
+//###############################################################################################//
+//
+//										ConstellationSearch
+//
+// pub fn new    ( &dyn List<Equatorial>, &mut dyn ChunkIterator, &mut dyn TriangleConstruct,
+//                 &mut dyn SpecularityConstruct, &mut dyn AbandonSearch, Radians,
+//                 RangeInclusive<usize>, &mut dyn List<Match<usize>> ) -> Self
+// pub fn begun  ( &dyn List<Equatorial>, &mut dyn ChunkIterator, &mut dyn TriangleConstruct,
+//                 &mut dyn SpecularityConstruct, &mut dyn AbandonSearch,
+//                 RangeInclusive<usize>, &mut dyn List<Match<usize>> ) -> Self
+// pub fn with_diagnostics ( self, &mut dyn SearchDiagnostics ) -> Self
+// pub fn step   ( &mut self, usize ) -> ConstellationStep
+// fn fail       ( &mut self )
+// fn finish     ( &mut self, ConstellationResult ) -> ConstellationStep
+//
+//###############################################################################################//
+//										~ new ~													 //
+	#[test]
+	// The triangle generator and database should be reset but nothing should be searched.
+	fn test_new ( )
+	{
+		let stars = stars();
+		let mut gen_tri  = MockTriangleConstruct::new();
+		let mut gen_spec = MockSpecularityConstruct::new();
+		let mut chunk    = MockChunkIterator::new();
+		let mut abort    = abandon_never();
+		let mut matches  : Vec<Match<usize>> = Vec::new();
+
+		gen_tri.expect_begin().times(1).returning(|_, _| return);
+		chunk.expect_begin().times(1).returning(|| return);
+
+		let search = ConstellationSearch::new(&stars, &mut chunk, &mut gen_tri, &mut gen_spec, &mut abort,
+			Radians(0.1), 4..=4, &mut matches);
+		assert!(!search.pilot);
+		assert!(!search.done);
+		assert_eq!(search.result, ConstellationResult::ErrorNoTriangleMatch{fails: 0});
+	}
+
+//										~ begun ~												 //
+	#[test]
+	// The triangle generator should not be reset.
+	fn test_begun ( )
+	{
+		let stars = stars();
+		let mut gen_tri  = MockTriangleConstruct::new();
+		let mut gen_spec = MockSpecularityConstruct::new();
+		let mut chunk    = MockChunkIterator::new();
+		let mut abort    = MockAbandonSearch::new();
+		let mut matches  : Vec<Match<usize>> = Vec::new();
+
+		gen_tri.expect_begin().times(0);
+		chunk.expect_begin().times(1).returning(|| return);
+		abort.expect_should_abort().times(1).returning(|| true);
+
+		let search = ConstellationSearch::begun(&stars, &mut chunk, &mut gen_tri, &mut gen_spec, &mut abort,
+			4..=4, &mut matches);
+		assert!(search.should_abort);
+	}
+
+//										~ with_diagnostics ~									 //
+	#[test]
+	// The diagnostics should be reset.
+	fn test_with_diagnostics ( )
+	{
+		let stars = stars();
+		let mut gen_tri     = MockTriangleConstruct::new();
+		let mut gen_spec    = MockSpecularityConstruct::new();
+		let mut chunk       = MockChunkIterator::new();
+		let mut abort       = abandon_never();
+		let mut matches     : Vec<Match<usize>> = Vec::new();
+		let mut diagnostics = MockSearchDiagnostics::new();
+		gen_tri.expect_begin().times(1).returning(|_, _| return);
+		chunk.expect_begin().times(1).returning(|| return);
+		diagnostics.expect_begin().times(1).returning(|| return);
+
+		let search = ConstellationSearch::new(&stars, &mut chunk, &mut gen_tri, &mut gen_spec, &mut abort,
+			Radians(0.1), 4..=4, &mut matches).with_diagnostics(&mut diagnostics);
+		assert!(search.diagnostics.is_some());
+	}
+
+//										~ step ~												 //
+	#[test]
+	// Each call should try at most `budget` triangles.
+	fn test_step_budget ( )
+	{
+		let stars = stars();
+		let database = database();
+		let mut chunk    = ChunkIteratorNone::new(&database);
+		let mut gen_tri  = triangles(5);
+		let mut gen_spec = specularity(false);
+		let mut abort    = abandon_never();
+		let mut matches  : Vec<Match<usize>> = Vec::new();
+
+		let mut search = ConstellationSearch::new(&stars, &mut chunk, &mut gen_tri, &mut gen_spec, &mut abort,
+			Radians(0.1), 4..=4, &mut matches);
+		assert_eq!(search.step(2), ConstellationStep::InProgress);
+		assert_eq!(search.step(2), ConstellationStep::InProgress);
+		assert_eq!(search.step(2),
+			ConstellationStep::Done(ConstellationResult::ErrorNoTriangleMatch{fails: 5}));
+	}
+
+	#[test]
+	// A budget of 0 should not do anything.
+	fn test_step_no_budget ( )
+	{
+		let stars = stars();
+		let mut gen_tri  = MockTriangleConstruct::new();
+		let mut gen_spec = MockSpecularityConstruct::new();
+		let mut chunk    = MockChunkIterator::new();
+		let mut abort    = abandon_never();
+		let mut matches  : Vec<Match<usize>> = Vec::new();
+
+		gen_tri.expect_begin().times(1).returning(|_, _| return);
+		gen_tri.expect_next().times(0);
+		chunk.expect_begin().times(1).returning(|| return);
+
+		let mut search = ConstellationSearch::new(&stars, &mut chunk, &mut gen_tri, &mut gen_spec, &mut abort,
+			Radians(0.1), 4..=4, &mut matches);
+		assert_eq!(search.step(0), ConstellationStep::InProgress);
+	}
+
+	#[test]
+	// The pilot search should continue from where the last step stopped.
+	fn test_step_pilot ( )
+	{
+		let stars = stars();
+		let database = database();
+		let mut chunk    = ChunkIteratorNone::new(&database);
+		let mut gen_tri  = MockTriangleConstruct::new();
+		let mut gen_spec = specularity(true);
+		let mut abort    = abandon_never();
+		let mut matches  : Vec<Match<usize>> = Vec::new();
+
+		let mut pilot = 10;
+		gen_tri.expect_begin().times(1).returning(|_, _| return);
+		gen_tri.expect_next().times(1).returning(|_, _|
+			Some(Match{input: StarTriangle(0, 1, 2), output: StarTriangle(0, 1, 2), weight: 1.0}));
+		gen_tri.expect_next_pilot().times(3).returning(move |_, _|
+		{
+			pilot += 1;
+			return Some(Match{input: pilot, output: pilot, weight: 1.0});
+		});
+
+		{
+			let mut search = ConstellationSearch::new(&stars, &mut chunk, &mut gen_tri, &mut gen_spec, &mut abort,
+				Radians(0.1), 4..=6, &mut matches);
+			assert_eq!(search.step(2), ConstellationStep::InProgress);
+			assert!(search.pilot);
+			assert_eq!(search.step(2), ConstellationStep::Done(ConstellationResult::Success{fails: 0}));
+		}
+		assert_eq!(matches.len(), 6);
+		assert_eq!(matches[5], Match{input: 13, output: 13, weight: 1.0});
+	}
+
+	#[test]
+	// If not enough pilots are found, the next triangle should be tried.
+	fn test_step_insufficient_pilots ( )
+	{
+		let stars = stars();
+		let database = database();
+		let mut chunk    = ChunkIteratorNone::new(&database);
+		let mut gen_tri  = triangles(2);
+		let mut gen_spec = specularity(true);
+		let mut abort    = abandon_never();
+		let mut matches  : Vec<Match<usize>> = Vec::new();
+
+		gen_tri.expect_next_pilot().times(2).returning(|_, _| None);
+
+		let mut search = ConstellationSearch::new(&stars, &mut chunk, &mut gen_tri, &mut gen_spec, &mut abort,
+			Radians(0.1), 4..=4, &mut matches);
+		assert_eq!(search.step(100),
+			ConstellationStep::Done(ConstellationResult::ErrorInsufficientPyramids{fails: 2}));
+	}
+
+	#[test]
+	// When abort is true, the search should end.
+	fn test_step_abort ( )
+	{
+		let stars = stars();
+		let database = database();
+		let mut chunk    = ChunkIteratorNone::new(&database);
+		let mut gen_tri  = MockTriangleConstruct::new();
+		let mut gen_spec = specularity(false);
+		let mut abort    = MockAbandonSearch::new();
+		let mut matches  : Vec<Match<usize>> = Vec::new();
+
+		let mut calls = 0;
+		abort.expect_should_abort().times(3).returning(move ||
+		{
+			calls += 1;
+			return 3 <= calls;
+		});
+		gen_tri.expect_begin().times(1).returning(|_, _| return);
+		gen_tri.expect_next().times(2).returning(|_, _|
+			Some(Match{input: StarTriangle(0, 1, 2), output: StarTriangle(0, 1, 2), weight: 1.0}));
+
+		let mut search = ConstellationSearch::new(&stars, &mut chunk, &mut gen_tri, &mut gen_spec, &mut abort,
+			Radians(0.1), 4..=4, &mut matches);
+		assert_eq!(search.step(1), ConstellationStep::InProgress);
+		assert_eq!(search.step(5), ConstellationStep::Done(ConstellationResult::ErrorAborted{fails: 2}));
+	}
+
+	#[test]
+	// Once done, the search should not continue.
+	fn test_step_done ( )
+	{
+		let stars = stars();
+		let database = database();
+		let mut chunk    = ChunkIteratorNone::new(&database);
+		let mut gen_tri  = triangles(0);
+		let mut gen_spec = specularity(false);
+		let mut abort    = abandon_never();
+		let mut matches  : Vec<Match<usize>> = Vec::new();
+
+		let mut search = ConstellationSearch::new(&stars, &mut chunk, &mut gen_tri, &mut gen_spec, &mut abort,
+			Radians(0.1), 4..=4, &mut matches);
+		let done = ConstellationStep::Done(ConstellationResult::ErrorNoTriangleMatch{fails: 0});
+		assert_eq!(search.step(10), done);
+		assert_eq!(search.step(10), done);
+	}
+
+	#[test]
+	// Every triangle, flipped triangle, pilot and the result should be reported.
+	fn test_step_diagnostics ( )
+	{
+		let stars = stars();
+		let database = database();
+		let mut chunk    = ChunkIteratorNone::new(&database);
+		let mut gen_tri  = triangles(3);
+		let mut gen_spec = MockSpecularityConstruct::new();
+		let mut abort    = abandon_never();
+		let mut matches  : Vec<Match<usize>> = Vec::new();
+		let mut record   : SearchDiagnosticsRecord<fn()->u32, 4> = SearchDiagnosticsRecord::new(|| 0);
+
+		// The first triangle is flipped, the others are not.
+		let mut calls = 0;
+		gen_spec.expect_same().returning(move |_, _| { calls += 1; return 1 < calls; });
+		// The second triangle finds 1 pilot, the third finds none.
+		let mut pilots = 0;
+		gen_tri.expect_next_pilot().times(3).returning(move |_, _|
+		{
+			pilots += 1;
+			if pilots == 1 { return Some(Match{input: 3, output: 3, weight: 1.0}); }
+			return None;
+		});
+
+		{
+			let mut search = ConstellationSearch::new(&stars, &mut chunk, &mut gen_tri, &mut gen_spec, &mut abort,
+				Radians(0.1), 5..=5, &mut matches).with_diagnostics(&mut record);
+			search.step(100);
+		}
+		assert_eq!(record.triangles, 3);
+		assert_eq!(record.specularity, 1);
+		assert_eq!(record.pilots, 1);
+		assert_eq!(record.pilot_failures, 2);
+		assert_eq!(record.result, Some(ConstellationResult::ErrorInsufficientPyramids{fails: 3}));
+	}
+
+	#[test]
+	// The database given to the triangle generator should report the chunks and candidates.
+	fn test_step_diagnostics_database ( )
+	{
+		let stars = stars();
+		let mut gen_tri     = MockTriangleConstruct::new();
+		let mut gen_spec    = MockSpecularityConstruct::new();
+		let mut chunk       = MockChunkIterator::new();
+		let mut abort       = abandon_never();
+		let mut matches     : Vec<Match<usize>> = Vec::new();
+		let mut diagnostics = MockSearchDiagnostics::new();
+
+		gen_tri.expect_begin().times(1).returning(|_, _| return);
+		gen_tri.expect_next().times(1).returning(|_, database| { database.next(); return None; });
+		chunk.expect_begin().times(1).returning(|| return);
+		chunk.expect_next().times(1).returning(|| true);
+		diagnostics.expect_begin().times(1).returning(|| return);
+		diagnostics.expect_chunk().times(1).returning(|| return);
+		diagnostics.expect_end().times(1).returning(|_| return);
+
+		let mut search = ConstellationSearch::new(&stars, &mut chunk, &mut gen_tri, &mut gen_spec, &mut abort,
+			Radians(0.1), 4..=4, &mut matches).with_diagnostics(&mut diagnostics);
+		search.step(1);
+	}
+
+//										~ fail ~												 //
+	#[test]
+	// A failure should be counted and abort should be checked.
+	fn test_fail ( )
+	{
+		let stars = stars();
+		let mut gen_tri  = MockTriangleConstruct::new();
+		let mut gen_spec = MockSpecularityConstruct::new();
+		let mut chunk    = MockChunkIterator::new();
+		let mut abort    = MockAbandonSearch::new();
+		let mut matches  : Vec<Match<usize>> = Vec::new();
+
+		let mut calls = 0;
+		abort.expect_should_abort().times(2).returning(move || { calls += 1; return calls == 2; });
+		chunk.expect_begin().times(1).returning(|| return);
+
+		let mut search = ConstellationSearch::begun(&stars, &mut chunk, &mut gen_tri, &mut gen_spec, &mut abort,
+			4..=4, &mut matches);
+		search.fail();
+		assert_eq!(search.result, ConstellationResult::ErrorNoTriangleMatch{fails: 1});
+		assert!(search.should_abort);
+	}
+
+//										~ finish ~												 //
+	#[test]
+	// The result should be stored.
+	fn test_finish ( )
+	{
+		let stars = stars();
+		let mut gen_tri  = MockTriangleConstruct::new();
+		let mut gen_spec = MockSpecularityConstruct::new();
+		let mut chunk    = MockChunkIterator::new();
+		let mut abort    = abandon_never();
+		let mut matches  : Vec<Match<usize>> = Vec::new();
+		chunk.expect_begin().times(1).returning(|| return);
+
+		let mut search = ConstellationSearch::begun(&stars, &mut chunk, &mut gen_tri, &mut gen_spec, &mut abort,
+			4..=4, &mut matches);
+		let result = ConstellationResult::Success{fails: 3};
+		assert_eq!(search.finish(result), ConstellationStep::Done(result));
+		assert!(search.done);
+		assert_eq!(search.result, result);
+	}
+}
@@ -0,0 +1,106 @@
+//! Implementation of [AttitudePrior](crate::tracking_mode::database::AttitudePrior) and [PriorBoresight](crate::tracking_mode::database::PriorBoresight).
+use crate::tracking_mode::database::AttitudePrior;
+use crate::tracking_mode::database::PriorBoresight;
+
+use crate::util::units::Equatorial;
+use crate::util::aliases::Decimal;
+
+impl AttitudePrior for fn ( Equatorial ) -> Decimal
+{
+	/// Calls the function.
+	fn probability ( &self, point: Equatorial ) -> Decimal { return self(point); }
+}
+
+
+
+impl AttitudePrior for PriorBoresight
+{
+	/// 1 at `boresight`, 1/2 at `uncertainty` and approaching 0 on the other side of the sphere.
+	/// If `uncertainty` is 0 or less, only the boresight is likely (1) and everywhere else is 0.
+	fn probability ( &self, point: Equatorial ) -> Decimal
+	{
+		let distance = self.boresight.angle_distance(point).0;
+		if self.uncertainty.0 <= 0.0
+		{
+			return if distance <= 0.0 { 1.0 } else { 0.0 };
+		}
+		let ratio = distance / self.uncertainty.0;
+		return 1.0 / (1.0 + ratio * ratio);
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::database::AttitudePrior;
+	use crate::tracking_mode::database::PriorBoresight;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::Degrees;
+	use crate::util::aliases::Decimal;
+	use crate::util::test::TestEqual;
+
+	#[coverage(off)]
+	fn prior_fn ( point: Equatorial ) -> Decimal { return point.dec.0; }
+
+
+//###############################################################################################//
+//
+//										AttitudePrior
+//
+// fn probability ( &self, Equatorial ) -> Decimal (for fn ( Equatorial ) -> Decimal)
+// fn probability ( &self, Equatorial ) -> Decimal (for PriorBoresight)
+//
+//###############################################################################################//
+//										~ probability ~											 //
+	#[test]
+	// A function should be usable as a prior.
+	fn test_probability_fn ( )
+	{
+		let prior = prior_fn as fn ( Equatorial ) -> Decimal;
+		let point = Equatorial{ra: Degrees(10.0).as_radians(), dec: Degrees(20.0).as_radians()};
+		prior.probability(point).assert_close(&Degrees(20.0).as_radians().0, 0.0001);
+	}
+
+	#[test]
+	// The probability should be 1 at the boresight, 1/2 at the uncertainty and decrease with distance.
+	fn test_probability_boresight ( )
+	{
+		let boresight = Equatorial{ra: Degrees(350.0).as_radians(), dec: Degrees(80.0).as_radians()};
+		let prior = PriorBoresight{boresight: boresight, uncertainty: Degrees(10.0).as_radians()};
+
+		prior.probability(boresight).assert_close(&1.0, 0.0001);
+
+		// Over the pole.
+		let point = Equatorial{ra: Degrees(170.0).as_radians(), dec: Degrees(90.0).as_radians()};
+		prior.probability(point).assert_close(&0.5, 0.0001);
+
+		let near = Equatorial{ra: Degrees(10.0).as_radians(), dec: Degrees(80.0).as_radians()};
+		let far  = Equatorial{ra: Degrees(10.0).as_radians(), dec: Degrees(-80.0).as_radians()};
+		assert!(prior.probability(far) < prior.probability(near));
+		assert!(prior.probability(near) < 1.0);
+		assert!(0.0 < prior.probability(far));
+	}
+
+	#[test]
+	// An uncertainty of 0 should not divide by 0.
+	fn test_probability_boresight_zero_uncertainty ( )
+	{
+		let boresight = Equatorial{ra: Degrees(10.0).as_radians(), dec: Degrees(20.0).as_radians()};
+		let point     = Equatorial{ra: Degrees(10.0).as_radians(), dec: Degrees(21.0).as_radians()};
+		let prior = PriorBoresight{boresight: boresight, uncertainty: Degrees(0.0).as_radians()};
+		assert_eq!(prior.probability(boresight), 1.0);
+		assert_eq!(prior.probability(point), 0.0);
+	}
+}
@@ -8,6 +8,11 @@ use crate::core_include::*;
 use crate::tracking_mode::database::Database;
 use crate::tracking_mode::database::SearchResult;
 use crate::tracking_mode::database::ChunkAreaSearch;
+use crate::tracking_mode::database::ChunkIteratorCone;
+use crate::tracking_mode::database::ChunkIteratorTiled;
+use crate::tracking_mode::database::ChunkIteratorPrior;
+use crate::tracking_mode::database::AttitudePrior;
+use crate::tracking_mode::database::TiledDatabase;
 use crate::tracking_mode::database::ChunkIteratorNone;
 use crate::tracking_mode::database::ChunkIteratorEquatorial;
 use crate::tracking_mode::database::ChunkIteratorDeclination;
@@ -17,6 +22,7 @@ use crate::util::aliases::M_PI;
 use crate::util::units::Equatorial;
 use crate::util::units::Radians;
 use crate::util::units::Degrees;
+use crate::util::list::ArrayList;
 use crate::util::list::List;
 
 use crate::util::Maths;
@@ -499,6 +505,224 @@ impl<'a> ChunkIterator for ChunkAreaSearch <'a>
 
 
 
+impl <'a> ChunkIteratorCone <'a>
+{
+	/// Uses the given cone as the search area.
+	/// # Arguments
+	/// * `database` - The database to search.
+	/// * `center`   - The center of the cone.
+	/// * `radius`   - The angle from the center to the edge of the cone.
+	pub fn new ( database: &'a dyn Database, center: Equatorial, radius: Radians ) -> Self
+	{
+		return Self { database: database, center: center, radius: radius, started: false };
+	}
+
+	/// Constructs the search area from an estimate of where the camera is pointing.
+	/// The radius is `uncertainty` + 1/2 `fov` so every star in the image is in the cone if the boresight is within `uncertainty`.
+	/// # Arguments
+	/// * `database`    - The database to search.
+	/// * `boresight`   - The estimated center of the image.
+	/// * `uncertainty` - How far the actual boresight could be from `boresight`.
+	/// * `fov`         - The diagonal field of view of the sensor.
+	pub fn from_prior ( database: &'a dyn Database, boresight: Equatorial, uncertainty: Radians, fov: Radians ) -> Self
+	{
+		return Self::new(database, boresight, uncertainty + fov / 2.0);
+	}
+}
+
+
+
+impl <'a> ChunkIterator for ChunkIteratorCone <'a>
+{
+	/// Allows you to use `next`.
+	fn begin ( &mut self ) { self.started = false; }
+
+	/// Returns true then always false until begin is returned again.
+	fn next ( &mut self ) -> bool
+	{
+		let val = self.started;
+		self.started = true;
+		return !val;
+	}
+
+	/// Returns the database this iterator holds.
+	fn get_database ( &self ) -> &dyn Database { return self.database; }
+
+
+	/// Returns true if either star of the pair at the provided index is within the cone.
+	fn same_region ( &self, pair_index: usize ) -> bool
+	{
+		let pair = self.database.get_pairs(pair_index);
+		let p1 = self.database.find_star(pair.0);
+		let p2 = self.database.find_star(pair.1);
+
+		if p1.is_err() || p2.is_err()
+		{
+			return false;
+		}
+		return self.center.angle_distance(p1.unwrap()) <= self.radius ||
+			self.center.angle_distance(p2.unwrap()) <= self.radius;
+	}
+}
+
+
+
+
+impl <'a> ChunkIteratorTiled <'a>
+{
+	/// Creates a new iterator.
+	/// Use `begin` to start iterating then use `next` to step.
+	/// # Arguments
+	/// * `database`   - The tiled database to search.
+	/// * `randomiser` - fn randomiser ( current step, number of steps ) -> index of tile.
+	///                  Use `ChunkIteratorDeclination::randomise_parity` so neighbouring tiles are not searched one after another.
+	pub fn new ( database: TiledDatabase<'a>, randomiser: fn (usize, usize) -> usize ) -> Self
+	{
+		return Self { database: database, randomiser: randomiser, index: 0, tile: None };
+	}
+}
+
+
+
+impl <'a> ChunkIterator for ChunkIteratorTiled <'a>
+{
+	/// Resets this iterator.
+	/// Call this to return to the start, then call next.
+	fn begin ( &mut self )
+	{
+		self.index = 0;
+		self.tile  = None;
+	}
+
+	/// Steps to the next tile.
+	/// If there is no more tiles, next will return false.
+	fn next ( &mut self ) -> bool
+	{
+		let num = self.database.num_tiles();
+		if num <= self.index { return false; }
+		self.tile   = Some((self.randomiser)(self.index, num));
+		self.index += 1;
+		return true;
+	}
+
+	/// Only the pairs stored in the current tile are searched.
+	fn find_close_ref_region ( &self, find : Radians, tolerance: Radians, found : &mut dyn List<SearchResult> )
+	{
+		let tile = match self.tile { Some(tile) => tile, None => return };
+		let range = self.database.database.find_close_ref_range(find, tolerance);
+
+		let end = self.database.tile_range(tile).end;
+		let mut i = self.database.lower_bound(tile, range.start);
+		while i < end && !found.is_full()
+		{
+			let pair_index = self.database.tile_pairs.get(i);
+			if range.end <= pair_index { break; }
+
+			let result = SearchResult{result: self.database.database.get_pairs(pair_index), error: 1.0};
+			let _ = found.push_back(result);
+			i += 1;
+		}
+	}
+
+	/// Returns the database the tiles point to.
+	fn get_database ( &self ) -> &dyn Database { return self.database.database; }
+
+	/// Returns true if the pair is stored in the current tile.
+	fn same_region ( &self, pair_index: usize ) -> bool
+	{
+		match self.tile
+		{
+			Some(tile) => return self.database.contains(tile, pair_index),
+			None       => return false,
+		}
+	}
+}
+
+
+
+
+impl <'a, const N: usize> ChunkIteratorPrior <'a, N>
+{
+	/// Creates a new iterator and sorts the tiles.
+	/// Use `next` to step.
+	/// # Arguments
+	/// * `database` - The tiled database to search.
+	/// * `prior`    - The probability of the boresight at each tile.
+	pub fn new ( database: TiledDatabase<'a>, prior: &'a dyn AttitudePrior ) -> Self
+	{
+		let mut iter = Self
+		{
+			tiled: ChunkIteratorTiled::new(database, ChunkIteratorDeclination::randomise_none),
+			prior: prior,
+			order: ArrayList::new(),
+			index: None,
+		};
+		iter.begin();
+		return iter;
+	}
+
+
+	/// The most likely tile first, if the probability is the same, the lowest index first.
+	fn sort_probability ( a: &(Decimal, usize), b: &(Decimal, usize) ) -> bool
+	{
+		return b.0 < a.0;
+	}
+}
+
+
+
+impl <'a, const N: usize> ChunkIterator for ChunkIteratorPrior <'a, N>
+{
+	/// Resets this iterator and sorts the tiles by the prior.
+	/// Call this to return to the start, then call next.
+	fn begin ( &mut self )
+	{
+		self.tiled.begin();
+		self.index = None;
+		self.order.clear();
+		for i in 0..self.tiled.database.num_tiles()
+		{
+			let mut probability = self.prior.probability(self.tiled.database.tiles.get(i));
+			// NaN cannot be compared, it is searched last.
+			if probability.is_nan() { probability = Decimal::NEG_INFINITY; }
+			if self.order.push_back((probability, i)).is_err() { break; }
+		}
+		self.order.sort_order(Self::sort_probability);
+	}
+
+	/// Steps to the most likely tile which has not been searched.
+	/// If every tile has been searched, next will return false.
+	fn next ( &mut self ) -> bool
+	{
+		let index = match self.index { Some(index) => index + 1, None => 0 };
+		if self.tiled.database.num_tiles() <= index
+		{
+			self.index = Some(self.tiled.database.num_tiles());
+			return false;
+		}
+
+		self.index = Some(index);
+		// Tiles which did not fit in `order` are searched last in order.
+		self.tiled.tile = if index < self.order.size() { Some(self.order.get(index).1 ) } else { Some(index) };
+		return true;
+	}
+
+	/// Only the pairs stored in the current tile are searched.
+	fn find_close_ref_region ( &self, find : Radians, tolerance: Radians, found : &mut dyn List<SearchResult> )
+	{
+		self.tiled.find_close_ref_region(find, tolerance, found);
+	}
+
+	/// Returns the database the tiles point to.
+	fn get_database ( &self ) -> &dyn Database { return self.tiled.get_database(); }
+
+	/// Returns true if the pair is stored in the current tile.
+	fn same_region ( &self, pair_index: usize ) -> bool { return self.tiled.same_region(pair_index); }
+}
+
+
+
+
 
 
 
@@ -541,6 +765,12 @@ mod test
 	
 	use crate::tracking_mode::database::ChunkIterator;
 	use crate::tracking_mode::database::ChunkAreaSearch;
+	use crate::tracking_mode::database::ChunkIteratorCone;
+	use crate::tracking_mode::database::ChunkIteratorTiled;
+	use crate::tracking_mode::database::TiledDatabase;
+	use crate::tracking_mode::database::ChunkIteratorPrior;
+	use crate::tracking_mode::database::PriorBoresight;
+	use crate::tracking_mode::database::SearchResult;
 	use crate::tracking_mode::database::ChunkIteratorNone;
 	use crate::tracking_mode::database::ChunkIteratorEquatorial;
 	use crate::tracking_mode::database::ChunkIteratorDeclination;
@@ -554,6 +784,8 @@ mod test
 	use crate::util::aliases::Decimal;
 	use crate::util::test::TestEqual;
 	use crate::util::err::Errors;
+	use crate::util::list::ArrayList;
+	use crate::util::list::List;
 
 
 
@@ -1973,4 +2205,511 @@ mod test
 		assert!(!iter.same_region(0), "Outside range");
 		assert!(!iter.same_region(1), "Outside range");
 	}
+
+
+
+//###############################################################################################//
+//
+//										ChunkIteratorCone
+//
+// pub fn new          ( &'a dyn Database, Equatorial, Radians ) -> Self
+// pub fn from_prior   ( &'a dyn Database, Equatorial, Radians, Radians ) -> Self
+//
+// pub fn begin        ( &mut self );
+// pub fn next         ( &mut self );
+// pub fn get_database ( &self );
+// pub fn same_region  ( &self, usize );
+//
+//###############################################################################################//
+//										~ new ~													 //
+	#[test]
+	fn test_cone_new ( )
+	{
+		let db = MockDatabase::new();
+		let center = Equatorial{ra: Degrees(30.0).as_radians(), dec: Degrees(10.0).as_radians()};
+		let iter = ChunkIteratorCone::new(&db, center, Degrees(5.0).as_radians());
+		assert_eq!(iter.center, center);
+		iter.radius.test_close(&Degrees(5.0).as_radians(), 0.0001);
+		assert_eq!(iter.started, false);
+	}
+
+//										~ from_prior ~											 //
+	#[test]
+	// The cone should fit the whole image if the boresight is at the edge of the uncertainty.
+	fn test_cone_from_prior ( )
+	{
+		let db = MockDatabase::new();
+		let center = Equatorial{ra: Degrees(30.0).as_radians(), dec: Degrees(10.0).as_radians()};
+		let iter = ChunkIteratorCone::from_prior(&db, center, Degrees(5.0).as_radians(), Degrees(20.0).as_radians());
+		assert_eq!(iter.center, center);
+		iter.radius.test_close(&Degrees(15.0).as_radians(), 0.0001);
+		assert_eq!(iter.started, false);
+	}
+
+//										~ begin ~												 //
+	#[test]
+	fn test_cone_begin ( )
+	{
+		let db = MockDatabase::new();
+		let mut iter = ChunkIteratorCone::new(&db, Equatorial::zero(), Radians(1.0));
+		iter.started = true;
+		iter.begin();
+		assert_eq!(iter.started, false);
+	}
+
+//										~ next ~												 //
+	#[test]
+	// There is only one region.
+	fn test_cone_next ( )
+	{
+		let db = MockDatabase::new();
+		let mut iter = ChunkIteratorCone::new(&db, Equatorial::zero(), Radians(1.0));
+		assert!(iter.next());
+		assert!(!iter.next());
+		assert!(!iter.next());
+		iter.begin();
+		assert!(iter.next());
+	}
+
+//										~ get_database ~										 //
+	#[test]
+	fn test_cone_get_database ( )
+	{
+		let mut db = MockDatabase::new();
+		db.expect_find_star().times(1).returning(|_| return Ok(Equatorial::north()));
+		let iter = ChunkIteratorCone::new(&db, Equatorial::zero(), Radians(1.0));
+		assert_eq!(iter.get_database().find_star(0), Ok(Equatorial::north()));
+	}
+
+//										~ same_region ~											 //
+	#[test]
+	// A pair is inside if either star is within the radius.
+	fn test_cone_same_region ( )
+	{
+		let mut db = MockDatabase::new();
+		db.expect_find_star().returning(|a|
+			{
+				match a
+				{
+					0 => return Ok(Equatorial{ra: Degrees(30.0).to_radians(), dec: Degrees(10.0).to_radians()}),
+					1 => return Ok(Equatorial{ra: Degrees(30.0).to_radians(), dec: Degrees(14.9).to_radians()}),
+					2 => return Ok(Equatorial{ra: Degrees(30.0).to_radians(), dec: Degrees(15.1).to_radians()}),
+					_ => return Ok(Equatorial{ra: Degrees(90.0).to_radians(), dec: Degrees(10.0).to_radians()}),
+				}
+			});
+		db.expect_get_pairs().returning(|a|
+			{
+				match a
+				{
+					0 => return StarPair(0, 3),
+					1 => return StarPair(3, 1),
+					2 => return StarPair(2, 3),
+					_ => return StarPair(3, 3),
+				}
+			});
+
+		let center = Equatorial{ra: Degrees(30.0).to_radians(), dec: Degrees(10.0).to_radians()};
+		let iter = ChunkIteratorCone::new(&db, center, Degrees(5.0).to_radians());
+		assert!(iter.same_region(0));
+		assert!(iter.same_region(1));
+		assert!(!iter.same_region(2));
+		assert!(!iter.same_region(3));
+	}
+
+	#[test]
+	// The cone should wrap around 0/360 degrees right ascension.
+	fn test_cone_same_region_wrap ( )
+	{
+		let mut db = MockDatabase::new();
+		db.expect_find_star().returning(|a|
+			{
+				match a
+				{
+					0 => return Ok(Equatorial{ra: Degrees(355.0).to_radians(), dec: Degrees(0.0).to_radians()}),
+					1 => return Ok(Equatorial{ra: Degrees(  8.0).to_radians(), dec: Degrees(0.0).to_radians()}),
+					_ => return Ok(Equatorial{ra: Degrees(180.0).to_radians(), dec: Degrees(0.0).to_radians()}),
+				}
+			});
+		db.expect_get_pairs().returning(|a| return StarPair(a, a));
+
+		let center = Equatorial{ra: Degrees(2.0).to_radians(), dec: Degrees(0.0).to_radians()};
+		let iter = ChunkIteratorCone::new(&db, center, Degrees(10.0).to_radians());
+		assert!(iter.same_region(0));
+		assert!(iter.same_region(1));
+		assert!(!iter.same_region(2));
+	}
+
+	#[test]
+	// Near the pole, every right ascension within the radius is inside.
+	fn test_cone_same_region_pole ( )
+	{
+		let mut db = MockDatabase::new();
+		db.expect_find_star().returning(|a|
+			{
+				match a
+				{
+					0 => return Ok(Equatorial{ra: Degrees(  0.0).to_radians(), dec: Degrees(85.0).to_radians()}),
+					1 => return Ok(Equatorial{ra: Degrees(180.0).to_radians(), dec: Degrees(85.0).to_radians()}),
+					2 => return Ok(Equatorial{ra: Degrees(270.0).to_radians(), dec: Degrees(89.0).to_radians()}),
+					_ => return Ok(Equatorial{ra: Degrees(180.0).to_radians(), dec: Degrees(75.0).to_radians()}),
+				}
+			});
+		db.expect_get_pairs().returning(|a| return StarPair(a, a));
+
+		let center = Equatorial{ra: Degrees(0.0).to_radians(), dec: Degrees(88.0).to_radians()};
+		let iter = ChunkIteratorCone::new(&db, center, Degrees(10.0).to_radians());
+		assert!(iter.same_region(0));
+		assert!(iter.same_region(1));  // 7 degrees over the pole.
+		assert!(iter.same_region(2));
+		assert!(!iter.same_region(3)); // 17 degrees over the pole.
+	}
+
+	#[test]
+	// If an invalid star pair is entered (which wont happen), false will be returned.
+	fn test_cone_same_region_error ( )
+	{
+		let mut db = MockDatabase::new();
+		db.expect_get_pairs().returning(|_| return StarPair(0, 1));
+		db.expect_find_star().returning(|a| if a == 0 { Ok(Equatorial::zero()) } else { Err(Errors::NoMatch) });
+
+		let iter = ChunkIteratorCone::new(&db, Equatorial::zero(), Radians(1.0));
+		assert!(!iter.same_region(0));
+	}
+
+
+
+//###############################################################################################//
+//
+//										ChunkIteratorTiled
+//
+// pub fn new                ( TiledDatabase, fn (usize, usize) -> usize ) -> Self
+//
+// pub fn begin                 ( &mut self );
+// pub fn next                  ( &mut self );
+// pub fn find_close_ref_region ( &self, Radians, Radians, &mut dyn List<SearchResult> );
+// pub fn get_database          ( &self );
+// pub fn same_region           ( &self, usize );
+//
+//###############################################################################################//
+
+	// A mock database where find_close_ref_range returns 2..7 and each pair is (index, index).
+	#[coverage(off)]
+	fn tiled_mock ( ) -> MockDatabase
+	{
+		let mut db = MockDatabase::new();
+		db.expect_find_close_ref_range().returning(|_, _| return 2..7);
+		db.expect_get_pairs().returning(|a| return StarPair(a, a));
+		return db;
+	}
+
+	// 3 tiles, the middle tile is empty.
+	#[coverage(off)]
+	fn tiled_database ( database: &MockDatabase ) -> TiledDatabase
+	{
+		static TILES      : [Equatorial; 3] = [Equatorial{ra: Radians(0.0), dec: Radians(0.0)}; 3];
+		static TILE_START : [usize; 4]      = [0, 5, 5, 8];
+		static TILE_PAIRS : [usize; 8]      = [1, 2, 4, 7, 9, 0, 3, 6];
+		return TiledDatabase
+		{
+			database:   database,
+			reach:      Radians(0.1),
+			tiles:      &TILES,
+			tile_start: &TILE_START,
+			tile_pairs: &TILE_PAIRS,
+		};
+	}
+
+//										~ new ~													 //
+	#[test]
+	fn test_tiled_new ( )
+	{
+		let db = tiled_mock();
+		let iter = ChunkIteratorTiled::new(tiled_database(&db), ChunkIteratorDeclination::randomise_parity);
+		assert_eq!(iter.index, 0);
+		assert_eq!(iter.tile, None);
+	}
+
+//										~ begin ~												 //
+	#[test]
+	fn test_tiled_begin ( )
+	{
+		let db = tiled_mock();
+		let mut iter = ChunkIteratorTiled::new(tiled_database(&db), ChunkIteratorDeclination::randomise_parity);
+		iter.next();
+		iter.next();
+		iter.begin();
+		assert_eq!(iter.index, 0);
+		assert_eq!(iter.tile, None);
+	}
+
+//										~ next ~												 //
+	#[test]
+	// Every tile should be visited in the order of the randomiser.
+	fn test_tiled_next ( )
+	{
+		let db = tiled_mock();
+		let mut iter = ChunkIteratorTiled::new(tiled_database(&db), ChunkIteratorDeclination::randomise_parity);
+		assert!(iter.next());
+		assert_eq!(iter.tile, Some(0));
+		assert!(iter.next());
+		assert_eq!(iter.tile, Some(2));
+		assert!(iter.next());
+		assert_eq!(iter.tile, Some(1));
+		assert!(!iter.next());
+	}
+
+//								~ find_close_ref_region ~										 //
+	#[test]
+	// Only the pairs of the tile in the k-vector range should be found.
+	fn test_tiled_find_close_ref_region ( )
+	{
+		let db = tiled_mock();
+		let mut iter = ChunkIteratorTiled::new(tiled_database(&db), ChunkIteratorDeclination::randomise_none);
+		let mut found : Vec<SearchResult> = Vec::new();
+
+		iter.next();
+		iter.find_close_ref_region(Radians(0.0), Radians(0.0), &mut found);
+		assert_eq!(found.len(), 2);
+		assert_eq!(found[0].result, StarPair(2, 2));
+		assert_eq!(found[1].result, StarPair(4, 4));
+
+		found.clear();
+		iter.next();
+		iter.find_close_ref_region(Radians(0.0), Radians(0.0), &mut found);
+		assert_eq!(found.len(), 0);
+
+		iter.next();
+		iter.find_close_ref_region(Radians(0.0), Radians(0.0), &mut found);
+		assert_eq!(found.len(), 2);
+		assert_eq!(found[0].result, StarPair(3, 3));
+		assert_eq!(found[1].result, StarPair(6, 6));
+	}
+
+	#[test]
+	// Nothing should be found before next and it should stop when the list is full.
+	fn test_tiled_find_close_ref_region_edge ( )
+	{
+		let db = tiled_mock();
+		let mut iter = ChunkIteratorTiled::new(tiled_database(&db), ChunkIteratorDeclination::randomise_none);
+		let mut found : ArrayList<SearchResult, 1> = ArrayList::new();
+
+		iter.find_close_ref_region(Radians(0.0), Radians(0.0), &mut found);
+		assert_eq!(found.size(), 0);
+
+		iter.next();
+		iter.find_close_ref_region(Radians(0.0), Radians(0.0), &mut found);
+		assert_eq!(found.size(), 1);
+		assert_eq!(found.get(0).result, StarPair(2, 2));
+	}
+
+//										~ get_database ~										 //
+	#[test]
+	fn test_tiled_get_database ( )
+	{
+		let db = tiled_mock();
+		let iter = ChunkIteratorTiled::new(tiled_database(&db), ChunkIteratorDeclination::randomise_none);
+		assert_eq!(iter.get_database().get_pairs(5), StarPair(5, 5));
+	}
+
+//										~ same_region ~											 //
+	#[test]
+	// Only pairs in the current tile are in the region.
+	fn test_tiled_same_region ( )
+	{
+		let db = tiled_mock();
+		let mut iter = ChunkIteratorTiled::new(tiled_database(&db), ChunkIteratorDeclination::randomise_none);
+		assert!(!iter.same_region(1));
+
+		iter.next();
+		assert!(iter.same_region(1));
+		assert!(iter.same_region(9));
+		assert!(!iter.same_region(3));
+
+		iter.next();
+		assert!(!iter.same_region(1));
+
+		iter.next();
+		assert!(iter.same_region(3));
+		assert!(!iter.same_region(1));
+	}
+
+
+
+//###############################################################################################//
+//
+//										ChunkIteratorPrior
+//
+// pub fn new                   ( TiledDatabase, &dyn AttitudePrior ) -> Self
+// fn sort_probability          ( &(Decimal, usize), &(Decimal, usize) ) -> bool
+//
+// pub fn begin                 ( &mut self );
+// pub fn next                  ( &mut self );
+// pub fn find_close_ref_region ( &self, Radians, Radians, &mut dyn List<SearchResult> );
+// pub fn get_database          ( &self );
+// pub fn same_region           ( &self, usize );
+//
+//###############################################################################################//
+
+	// 4 tiles along the prime meridian, each tile has the pair of its index.
+	#[coverage(off)]
+	fn prior_database ( database: &MockDatabase ) -> TiledDatabase
+	{
+		static TILES : [Equatorial; 4] = [
+			Equatorial{ra: Radians(0.0), dec: Radians(0.0)},
+			Equatorial{ra: Radians(0.0), dec: Radians(0.1)},
+			Equatorial{ra: Radians(0.0), dec: Radians(0.2)},
+			Equatorial{ra: Radians(0.0), dec: Radians(0.3)}];
+		static TILE_START : [usize; 5] = [0, 1, 2, 3, 4];
+		static TILE_PAIRS : [usize; 4] = [0, 1, 2, 3];
+		return TiledDatabase
+		{
+			database:   database,
+			reach:      Radians(0.1),
+			tiles:      &TILES,
+			tile_start: &TILE_START,
+			tile_pairs: &TILE_PAIRS,
+		};
+	}
+
+	// Tile 1 is the most likely, tile 0 and 2 are the same and tile 3 is the least likely.
+	#[coverage(off)]
+	fn prior_fn ( point: Equatorial ) -> Decimal
+	{
+		return [0.5, 0.9, 0.5, 0.1][(point.dec.0 * 10.0).round() as usize];
+	}
+
+//										~ new ~													 //
+	#[test]
+	fn test_prior_new ( )
+	{
+		let db = tiled_mock();
+		let prior = prior_fn as fn ( Equatorial ) -> Decimal;
+		let iter = ChunkIteratorPrior::<4>::new(prior_database(&db), &prior);
+		assert_eq!(iter.index, None);
+		assert_eq!(iter.tiled.tile, None);
+		assert_eq!(iter.order.size(), 4);
+		assert_eq!(iter.order.get(0), (0.9, 1));
+		assert_eq!(iter.order.get(3), (0.1, 3));
+	}
+
+//										~ begin ~												 //
+	#[test]
+	fn test_prior_begin ( )
+	{
+		let db = tiled_mock();
+		let prior = prior_fn as fn ( Equatorial ) -> Decimal;
+		let mut iter = ChunkIteratorPrior::<4>::new(prior_database(&db), &prior);
+		iter.next();
+		iter.begin();
+		assert_eq!(iter.index, None);
+		assert_eq!(iter.tiled.tile, None);
+		assert!(iter.next());
+		assert_eq!(iter.tiled.tile, Some(1));
+	}
+
+//										~ next ~												 //
+	#[test]
+	// The tiles should be visited from the most to least likely, equal tiles in order of index.
+	fn test_prior_next ( )
+	{
+		let db = tiled_mock();
+		let prior = prior_fn as fn ( Equatorial ) -> Decimal;
+		let mut iter = ChunkIteratorPrior::<4>::new(prior_database(&db), &prior);
+		assert!(iter.next());
+		assert_eq!(iter.tiled.tile, Some(1));
+		assert!(iter.next());
+		assert_eq!(iter.tiled.tile, Some(0));
+		assert!(iter.next());
+		assert_eq!(iter.tiled.tile, Some(2));
+		assert!(iter.next());
+		assert_eq!(iter.tiled.tile, Some(3));
+		assert!(!iter.next());
+		assert!(!iter.next());
+	}
+
+	#[test]
+	// A boresight prior should search the closest tile first.
+	fn test_prior_next_boresight ( )
+	{
+		let db = tiled_mock();
+		let prior = PriorBoresight{boresight: Equatorial{ra: Radians(0.0), dec: Radians(0.22)}, uncertainty: Radians(0.1)};
+		let mut iter = ChunkIteratorPrior::<4>::new(prior_database(&db), &prior);
+		let mut order = Vec::new();
+		while iter.next() { order.push(iter.tiled.tile.unwrap()); }
+		assert_eq!(order, vec![2, 3, 1, 0]);
+	}
+
+	#[test]
+	// A NaN probability should be searched last and only once.
+	fn test_prior_next_nan ( )
+	{
+		#[coverage(off)]
+		fn prior_nan ( point: Equatorial ) -> Decimal
+		{
+			return [0.5, Decimal::NAN, 0.9, 0.1][(point.dec.0 * 10.0).round() as usize];
+		}
+		let db = tiled_mock();
+		let prior = prior_nan as fn ( Equatorial ) -> Decimal;
+		let mut iter = ChunkIteratorPrior::<4>::new(prior_database(&db), &prior);
+		let mut order = Vec::new();
+		while iter.next() { order.push(iter.tiled.tile.unwrap()); }
+		assert_eq!(order, vec![2, 0, 3, 1]);
+	}
+
+	#[test]
+	// If there are more tiles than `N`, the remaining tiles are searched last in order.
+	fn test_prior_next_overflow ( )
+	{
+		let db = tiled_mock();
+		let prior = prior_fn as fn ( Equatorial ) -> Decimal;
+		let mut iter = ChunkIteratorPrior::<2>::new(prior_database(&db), &prior);
+		let mut order = Vec::new();
+		while iter.next() { order.push(iter.tiled.tile.unwrap()); }
+		assert_eq!(order, vec![1, 0, 2, 3]);
+	}
+
+//								~ find_close_ref_region ~										 //
+	#[test]
+	// Only the pairs of the current tile should be found.
+	fn test_prior_find_close_ref_region ( )
+	{
+		let db = tiled_mock();
+		let prior = prior_fn as fn ( Equatorial ) -> Decimal;
+		let mut iter = ChunkIteratorPrior::<4>::new(prior_database(&db), &prior);
+		let mut found : Vec<SearchResult> = Vec::new();
+
+		iter.next(); // Tile 1, outside of 2..7.
+		iter.find_close_ref_region(Radians(0.0), Radians(0.0), &mut found);
+		assert_eq!(found.len(), 0);
+
+		iter.next(); // Tile 0
+		iter.next(); // Tile 2
+		iter.find_close_ref_region(Radians(0.0), Radians(0.0), &mut found);
+		assert_eq!(found.len(), 1);
+		assert_eq!(found[0].result, StarPair(2, 2));
+	}
+
+//										~ get_database ~										 //
+	#[test]
+	fn test_prior_get_database ( )
+	{
+		let db = tiled_mock();
+		let prior = prior_fn as fn ( Equatorial ) -> Decimal;
+		let iter = ChunkIteratorPrior::<4>::new(prior_database(&db), &prior);
+		assert_eq!(iter.get_database().get_pairs(5), StarPair(5, 5));
+	}
+
+//										~ same_region ~											 //
+	#[test]
+	// Only pairs in the current tile are in the region.
+	fn test_prior_same_region ( )
+	{
+		let db = tiled_mock();
+		let prior = prior_fn as fn ( Equatorial ) -> Decimal;
+		let mut iter = ChunkIteratorPrior::<4>::new(prior_database(&db), &prior);
+		assert!(!iter.same_region(1));
+		iter.next();
+		assert!(iter.same_region(1));
+		assert!(!iter.same_region(0));
+	}
 }
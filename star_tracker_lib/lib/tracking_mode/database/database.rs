@@ -8,6 +8,7 @@ use crate::core_include::*;
 use crate::util::list::List;
 use crate::util::units::Equatorial;
 use crate::util::units::Radians;
+use crate::util::aliases::Decimal;
 use crate::util::err::Errors;
 use crate::util::err::Error;
 
@@ -107,6 +108,10 @@ pub trait Database
 	/// Gets the field of view the database was created for.
 	fn get_fov            ( &self ) -> Radians;
 
+	/// Gets the magnitude of the catalogue star.
+	/// By default, the magnitude is unknown (see [PhotometricDatabase](crate::tracking_mode::database::PhotometricDatabase)).
+	fn get_magnitude      ( &self, _index: usize ) -> Option<Decimal> { return None; }
+
 
 	/// Trims the range provided by the k-vector so that every value is within the tolerance.
 	/// # Arguments
@@ -0,0 +1,424 @@
+//! Implementation for [GridDatabase](crate::tracking_mode::database::GridDatabase).
+use crate::core_include::*;
+
+use crate::tracking_mode::database::GridDatabase;
+
+use crate::util::units::Equatorial;
+use crate::util::units::Vector3;
+use crate::util::units::Radians;
+use crate::util::aliases::Decimal;
+use crate::util::list::List;
+use crate::util::err::Errors;
+use crate::util::err::Error;
+
+impl <'a> GridDatabase <'a>
+{
+	/// Creates the grid pattern of a star.
+	/// The neighbours are projected onto a plane touching `center` and rotated so the closest neighbour is on the x axis.
+	/// The closest neighbour is always in the middle row, use an odd `grid_size` so it is not on the edge of a cell.
+	/// # Arguments
+	/// * `center`    - The star the pattern is for.
+	/// * `stars`     - The stars around `center` (`center` may be in this list, it is ignored by `buffer`).
+	/// * `radius`    - Stars further than this from `center` are not in the pattern.
+	/// * `buffer`    - Stars closer than this to `center` are not in the pattern (must be above 0).
+	/// * `grid_size` - The number of cells on each side of the grid.
+	/// * `cells`     - The output, the occupied cells in ascending order (row * grid_size + column).
+	/// # Returns
+	/// Err(Errors::InvalidSize) if `cells` is too small for the pattern, a partial pattern would give the wrong match.
+	pub fn pattern (
+		center   : Equatorial,
+		stars    : &dyn List<Equatorial>,
+		radius   : Radians,
+		buffer   : Radians,
+		grid_size: usize,
+		cells    : &mut dyn List<usize> ) -> Error<()>
+	{
+		cells.clear();
+		if grid_size == 0 { return Ok(()); }
+
+		// The closest neighbour sets the orientation.
+		let mut nearest      : Option<Equatorial> = None;
+		let mut nearest_dist = radius;
+		for i in 0..stars.size()
+		{
+			let dist = center.angle_distance(stars.get(i));
+			if buffer < dist && dist < nearest_dist
+			{
+				nearest      = Some(stars.get(i));
+				nearest_dist = dist;
+			}
+		}
+
+		let center_3d = center.to_vector3();
+		let x_axis : Vector3;
+		match nearest
+		{
+			Some(star) =>
+			{
+				let star_3d = star.to_vector3();
+				match (star_3d - center_3d * star_3d.dot(center_3d)).normalized()
+				{
+					Ok(axis) => x_axis = axis,
+					Err(_)   => return Ok(()),
+				}
+			}
+			None => return Ok(()),
+		}
+		let y_axis = center_3d.cross(x_axis);
+
+		// The grid goes from -extent to extent on the plane.
+		let extent = radius.sin() / radius.cos();
+		let scale  = grid_size as Decimal / (2.0 * extent);
+		for i in 0..stars.size()
+		{
+			let dist = center.angle_distance(stars.get(i));
+			if dist <= buffer || radius <= dist { continue; }
+
+			let star_3d = stars.get(i).to_vector3();
+			let depth   = star_3d.dot(center_3d);
+			let x = star_3d.dot(x_axis) / depth;
+			let y = star_3d.dot(y_axis) / depth;
+
+			let column = core::cmp::min(((x + extent) * scale) as usize, grid_size - 1);
+			let row    = core::cmp::min(((y + extent) * scale) as usize, grid_size - 1);
+			let cell   = row * grid_size + column;
+
+			let mut exists = false;
+			for j in 0..cells.size() { exists |= cells.get(j) == cell; }
+			if exists { continue; }
+			// Slotting into a full list drops the last cell.
+			if cells.is_full() { return Err(Errors::InvalidSize); }
+			cells.slot(cell, |a, b| a < b);
+		}
+		return Ok(());
+	}
+
+
+
+	/// Finds the number of cells `cells` shares with the pattern of a catalogue star.
+	/// # Arguments
+	/// * `index` - The index of the catalogue star.
+	/// * `cells` - The pattern to compare in ascending order (use [pattern](crate::tracking_mode::database::GridDatabase::pattern)).
+	pub fn score ( &self, index: usize, cells: &dyn List<usize> ) -> usize
+	{
+		let mut i   = self.pattern_start.get(index);
+		let end     = self.pattern_start.get(index + 1);
+		let mut j   = 0;
+		let mut score = 0;
+		while i < end && j < cells.size()
+		{
+			let stored = self.cells.get(i);
+			let found  = cells.get(j);
+			if stored == found { score += 1; i += 1; j += 1; }
+			else if stored < found { i += 1; }
+			else                   { j += 1; }
+		}
+		return score;
+	}
+
+
+
+	/// Finds the catalogue star with the pattern which shares the most cells with `cells`.
+	/// Only the stars in `cell_stars` of the cells in `cells` are scored.
+	/// # Arguments
+	/// * `cells`     - The pattern to find in ascending order (use [pattern](crate::tracking_mode::database::GridDatabase::pattern)).
+	/// * `min_score` - The minimum number of shared cells.
+	/// # Returns
+	/// (catalogue index, score) if a single star has the highest score and it is at least `min_score`.
+	pub fn find_best ( &self, cells: &dyn List<usize>, min_score: usize ) -> Option<(usize, usize)>
+	{
+		let mut best       = 0;
+		let mut best_score = 0;
+		let mut second     = 0;
+
+		// The stars of each cell are ascending, so the next star to score is the lowest after the last star in any cell.
+		// The score is the number of cells which have that star.
+		let mut last : Option<usize> = None;
+		loop
+		{
+			let mut star  : Option<usize> = None;
+			let mut score = 0;
+			for i in 0..cells.size()
+			{
+				if let Some(next) = self.next_star(cells.get(i), last)
+				{
+					if star.is_none() || next < star.unwrap() { star = Some(next); score = 1; }
+					else if next == star.unwrap()             { score += 1; }
+				}
+			}
+
+			match star
+			{
+				Some(index) => last = Some(index),
+				None        => break,
+			}
+
+			if best_score < score
+			{
+				second     = best_score;
+				best_score = score;
+				best       = last.unwrap();
+			}
+			else if second < score
+			{
+				second = score;
+			}
+		}
+
+		if best_score < min_score || best_score == 0 || best_score == second
+		{
+			return None;
+		}
+		return Some((best, best_score));
+	}
+
+
+
+	/// Finds the lowest catalogue star with `cell` in its pattern which is after `last`.
+	/// # Arguments
+	/// * `cell` - The cell to search.
+	/// * `last` - The star to search after, None for the first star.
+	fn next_star ( &self, cell: usize, last: Option<usize> ) -> Option<usize>
+	{
+		if self.cell_start.size() <= cell + 1 { return None; }
+		let mut low  = self.cell_start.get(cell);
+		let mut high = self.cell_start.get(cell + 1);
+		let end      = high;
+		if let Some(last) = last
+		{
+			while low < high
+			{
+				let mid = (low + high) / 2;
+				if self.cell_stars.get(mid) <= last { low = mid + 1; }
+				else                                { high = mid;    }
+			}
+		}
+		if low < end { return Some(self.cell_stars.get(low)); }
+		return None;
+	}
+
+
+
+	/// Gets the star at the index of the catalogue.
+	pub fn get_catalogue ( &self, index: usize ) -> Equatorial
+	{
+		return self.catalogue.get(index);
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::database::GridDatabase;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::AngleAxis;
+	use crate::util::units::Degrees;
+	use crate::util::units::Radians;
+	use crate::util::aliases::Decimal;
+	use crate::util::list::ArrayList;
+	use crate::util::list::List;
+	use crate::util::err::Errors;
+
+	// Star 0 has the pattern [1, 5, 7], star 1 has [5, 7], star 2 has [2, 5, 8].
+	static PATTERN_START: [usize; 4]  = [0, 3, 5, 8];
+	static CELLS:         [usize; 8]  = [1, 5, 7, 5, 7, 2, 5, 8];
+	// Cell 1 has [0], cell 2 has [2], cell 5 has [0, 1, 2], cell 7 has [0, 1], cell 8 has [2].
+	static CELL_START:    [usize; 10] = [0, 0, 1, 2, 2, 2, 5, 5, 7, 8];
+	static CELL_STARS:    [usize; 8]  = [0, 2, 0, 1, 2, 0, 1, 2];
+	static CATALOGUE:     [Equatorial; 3] =
+	[
+		Equatorial{ra: Radians(0.0), dec: Radians(0.0)},
+		Equatorial{ra: Radians(0.1), dec: Radians(0.0)},
+		Equatorial{ra: Radians(0.2), dec: Radians(0.0)},
+	];
+
+	#[coverage(off)]
+	fn create_database ( ) -> GridDatabase<'static>
+	{
+		return GridDatabase
+		{
+			radius:        Degrees(5.5).as_radians(),
+			buffer:        Degrees(0.1).as_radians(),
+			grid_size:     3,
+			pattern_start: &PATTERN_START,
+			cells:         &CELLS,
+			cell_start:    &CELL_START,
+			cell_stars:    &CELL_STARS,
+			catalogue:     &CATALOGUE,
+		};
+	}
+
+	#[coverage(off)]
+	fn eq ( ra: Decimal, dec: Decimal ) -> Equatorial
+	{
+		return Equatorial{ra: Degrees(ra).as_radians(), dec: Degrees(dec).as_radians()};
+	}
+
+
+//###############################################################################################//
+//
+//										GridDatabase
+//
+// pub fn pattern       ( Equatorial, &dyn List<Equatorial>, Radians, Radians, usize, &mut dyn List<usize> ) -> Error<()>
+// pub fn score         ( &self, usize, &dyn List<usize> ) -> usize
+// pub fn find_best     ( &self, &dyn List<usize>, usize ) -> Option<(usize, usize)>
+// fn next_star         ( &self, usize, Option<usize> ) -> Option<usize>
+// pub fn get_catalogue ( &self, usize ) -> Equatorial
+//
+//###############################################################################################//
+//										~ pattern ~												 //
+	#[test]
+	// The closest star should be on the x axis and the cells should be in ascending order.
+	// The grid is 11 cells from -tan(5.5) to tan(5.5), each cell is about 1 degree.
+	fn test_pattern ( )
+	{
+		let stars = vec![
+			eq(0.0, 0.0),   // The center is ignored.
+			eq(0.05, 0.0),  // Within the buffer.
+			eq(2.0, 0.0),   // Closest (x axis): row 5, column 7.
+			eq(0.0, 3.0),   // y axis: row 8, column 5.
+			eq(-3.0, 0.0),  // -x axis: row 5, column 2.
+			eq(6.0, 0.0)];  // Outside the radius.
+		let mut cells : Vec<usize> = Vec::new();
+		assert_eq!(GridDatabase::pattern(eq(0.0, 0.0), &stars,
+			Degrees(5.5).as_radians(), Degrees(0.1).as_radians(), 11, &mut cells), Ok(()));
+		assert_eq!(cells, vec![57, 62, 93]);
+	}
+
+	#[test]
+	// Rotating the sky should not change the pattern.
+	fn test_pattern_rotation ( )
+	{
+		let center = eq(40.0, -30.0);
+		let stars  = vec![center, eq(41.0, -29.0), eq(38.0, -31.5), eq(42.5, -33.0), eq(37.0, -27.0), eq(40.2, -26.0)];
+		let mut expected : Vec<usize> = Vec::new();
+		GridDatabase::pattern(center, &stars, Degrees(5.5).as_radians(), Degrees(0.1).as_radians(), 11, &mut expected);
+		assert_eq!(expected.size(), 5);
+
+		let rotation = AngleAxis{angle: Degrees(123.0).as_radians(),
+			axis: eq(10.0, 20.0).to_vector3()}.to_quaternion();
+		let rotated : Vec<Equatorial> =
+			stars.iter().map(|s| rotation.rotate_point(s.to_vector3()).to_equatorial()).collect();
+		let mut cells : Vec<usize> = Vec::new();
+		GridDatabase::pattern(rotated[0], &rotated,
+			Degrees(5.5).as_radians(), Degrees(0.1).as_radians(), 11, &mut cells);
+		assert_eq!(cells, expected);
+	}
+
+	#[test]
+	// Duplicate cells should only be added once.
+	fn test_pattern_duplicate ( )
+	{
+		let stars = vec![eq(2.0, 0.0), eq(2.1, 0.0)];
+		let mut cells : Vec<usize> = Vec::new();
+		GridDatabase::pattern(eq(0.0, 0.0), &stars, Degrees(5.5).as_radians(), Degrees(0.1).as_radians(), 11, &mut cells);
+		assert_eq!(cells, vec![62]);
+	}
+
+	#[test]
+	// If there are no neighbours, the pattern is empty.
+	fn test_pattern_empty ( )
+	{
+		let stars = vec![eq(0.0, 0.0), eq(10.0, 0.0)];
+		let mut cells : Vec<usize> = vec![1];
+		GridDatabase::pattern(eq(0.0, 0.0), &stars, Degrees(5.5).as_radians(), Degrees(0.1).as_radians(), 11, &mut cells);
+		assert_eq!(cells.size(), 0);
+
+		GridDatabase::pattern(eq(0.0, 0.0), &stars, Degrees(15.0).as_radians(), Degrees(0.1).as_radians(), 0, &mut cells);
+		assert_eq!(cells.size(), 0);
+	}
+
+	#[test]
+	// If the output is too small, an error is returned instead of a partial pattern.
+	fn test_pattern_full ( )
+	{
+		let stars = vec![eq(2.0, 0.0), eq(0.0, 3.0), eq(-3.0, 0.0)];
+		let mut cells : ArrayList<usize, 2> = ArrayList::new();
+		assert_eq!(GridDatabase::pattern(eq(0.0, 0.0), &stars,
+			Degrees(5.5).as_radians(), Degrees(0.1).as_radians(), 11, &mut cells), Err(Errors::InvalidSize));
+
+		let mut cells : ArrayList<usize, 3> = ArrayList::new();
+		assert_eq!(GridDatabase::pattern(eq(0.0, 0.0), &stars,
+			Degrees(5.5).as_radians(), Degrees(0.1).as_radians(), 11, &mut cells), Ok(()));
+		assert_eq!(cells.size(), 3);
+	}
+
+//										~ score ~												 //
+	#[test]
+	// The score is the number of shared cells.
+	fn test_score ( )
+	{
+		let database = create_database();
+		let cells = vec![1, 2, 7];
+		assert_eq!(database.score(0, &cells), 2);
+		assert_eq!(database.score(1, &cells), 1);
+		assert_eq!(database.score(2, &cells), 1);
+		assert_eq!(database.score(0, &Vec::new()), 0);
+	}
+
+//										~ find_best ~											 //
+	#[test]
+	// The star with the most shared cells should be found.
+	fn test_find_best ( )
+	{
+		let database = create_database();
+		assert_eq!(database.find_best(&vec![1, 5, 7], 2), Some((0, 3)));
+		assert_eq!(database.find_best(&vec![2, 8], 2),    Some((2, 2)));
+	}
+
+	#[test]
+	// If the best score is too low or is shared, nothing is found.
+	fn test_find_best_none ( )
+	{
+		let database = create_database();
+		assert_eq!(database.find_best(&vec![1, 5, 7], 4), None);
+		assert_eq!(database.find_best(&vec![5, 7], 1),    None);
+		assert_eq!(database.find_best(&vec![0], 0),       None);
+		assert_eq!(database.find_best(&vec![9, 20], 0),   None);
+	}
+
+	#[test]
+	// The score of every star with a shared cell should match score.
+	fn test_find_best_score ( )
+	{
+		let database = create_database();
+		assert_eq!(database.find_best(&vec![1, 2, 7], 1), Some((0, 2)));
+		assert_eq!(database.find_best(&vec![2, 5, 7, 8], 1), Some((2, 3)));
+		assert_eq!(database.find_best(&vec![5], 1), None);
+	}
+
+//										~ next_star ~											 //
+	#[test]
+	// The next star is the lowest star in the cell after the last star.
+	fn test_next_star ( )
+	{
+		let database = create_database();
+		assert_eq!(database.next_star(5, None),    Some(0));
+		assert_eq!(database.next_star(5, Some(0)), Some(1));
+		assert_eq!(database.next_star(5, Some(1)), Some(2));
+		assert_eq!(database.next_star(5, Some(2)), None);
+		assert_eq!(database.next_star(8, Some(0)), Some(2));
+		assert_eq!(database.next_star(0, None),    None);
+		assert_eq!(database.next_star(9, None),    None);
+	}
+
+//										~ get_catalogue ~										 //
+	#[test]
+	fn test_get_catalogue ( )
+	{
+		let database = create_database();
+		assert_eq!(database.get_catalogue(2), CATALOGUE[2]);
+	}
+}
@@ -81,11 +81,13 @@ use crate::core_include::*;
 #[cfg(test)] use mockall::predicate::*;
 
 use super::StarPair;
+use super::StarTriangle;
 
 use crate::util::linear_lookup::LinearLookup;
 use crate::util::aliases::Decimal;
 use crate::util::units::Equatorial;
 use crate::util::units::Radians;
+use crate::util::list::ArrayList;
 use crate::util::err::Error;
 
 pub use crate::tracking_mode::database::database::Database as Database;
@@ -98,6 +100,11 @@ pub use crate::tracking_mode::database::database::MockDatabase as MockDatabase;
 
 mod k_vector;
 pub mod pyramid_database;
+pub mod triangle_database;
+pub mod grid_database;
+pub mod photometric_database;
+pub mod tiled_database;
+pub mod attitude_prior;
 pub mod database;
 pub mod chunk_iterator;
 pub mod search_result;
@@ -139,6 +146,32 @@ pub trait KVectorSearch
 
 
 
+/// The probability that the camera is pointing at a point on the celestial sphere.  
+///
+/// Used by [ChunkIteratorPrior](crate::tracking_mode::database::ChunkIteratorPrior) to search the most likely chunks first.  
+/// This can come from a coarse sun sensor, the last valid attitude or the orbit.  
+/// The value does not need to be normalized, only the order matters.  
+/// Any `fn ( Equatorial ) -> Decimal` is a prior.
+#[cfg_attr(test, automock)]
+pub trait AttitudePrior
+{
+	/// Returns how likely the boresight is at `point` (higher is more likely).
+	fn probability ( &self, point: Equatorial ) -> Decimal;
+}
+
+
+/// An [AttitudePrior](crate::tracking_mode::database::AttitudePrior) where the boresight is probably near a point.  
+/// The probability drops with the angle from `boresight`, at `uncertainty` the probability is half of the center.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PriorBoresight
+{
+	/// The most likely boresight.
+	pub boresight:   Equatorial,
+	/// How far the boresight could be from `boresight`.
+	pub uncertainty: Radians,
+}
+
+
 /// The result for querying for a close match star pair.
 /// Used in Database::find_close_ref.
 #[derive(Debug, Copy, Clone)]
@@ -177,6 +210,117 @@ pub struct PyramidDatabase <'a>
 
 
 
+/// Adds the magnitude of each catalogue star to a database ([Photometry](crate::tracking_mode::Photometry)).  
+///
+/// Every other method is passed to `database`, this can wrap any database without changing it.  
+/// The magnitudes must be in the same order as the catalogue of `database`.
+#[derive(Clone, Copy)]
+pub struct PhotometricDatabase <'a>
+{
+	/// The database to search.
+	pub database:  &'a dyn Database,
+
+	/// The magnitude of each star in the catalogue.
+	pub magnitude: &'a dyn LinearLookup<Decimal>,
+}
+
+
+
+/// The database for identifying stars when the focal length is not known ([ScaleInvariant](crate::tracking_mode::ScaleInvariant)).  
+///
+/// Instead of star pairs sorted by angular separation, this stores star triangles sorted by their smallest interior angle.  
+/// The k-vector is used in the same way, the only difference is the value is the smallest interior angle.  
+/// To generate, use `TriangleDatabaseGenerator` in *star_tracker_database*.
+#[derive(Clone, Copy)]
+pub struct TriangleDatabase <'a>
+{
+	/// The field of view used when the database was constructed.
+	/// This is the widest a side of a triangle can be.
+	pub fov:       Radians,
+
+	/// The equation of the k_vector which points to the k_vector table `k_vector`.
+	pub k_lookup:  KVector,
+
+	/// The k_vector table pointed to from `k_lookup`.
+	pub k_vector:  &'a dyn LinearLookup<usize>,
+
+	/// Triangles of stars in order of the smallest interior angle.  
+	/// The vertices are in the order (smallest angle, middle angle, largest angle).  
+	/// This points to the `catalogue`.
+	pub triangles: &'a dyn LinearLookup<StarTriangle<usize>>,
+
+	/// The stars location in the sky.
+	pub catalogue: &'a dyn LinearLookup<Equatorial>,
+}
+
+
+/// Splits the sky into tiles so the search only touches the pairs which can be in the same image ([ChunkIteratorTiled](crate::tracking_mode::database::ChunkIteratorTiled)).  
+///
+/// The tiles are points spread evenly around the celestial sphere (Fibonacci lattice).  
+/// A pair is stored in a tile if both stars are within `reach` of the tile, `reach` is the distance to the next tile plus half the field of view.  
+/// Wherever the camera points, every pair in the image is stored in the closest tile.  
+/// The pairs of every tile are stored one after another in `tile_pairs`, the pairs of tile `i` are from `tile_start[i]` to `tile_start[i + 1]`.  
+/// Each pair is an index of the pairs in `database` in ascending order, so each tile is also sorted by separation.  
+/// To generate, use `TiledDatabaseGenerator` in *star_tracker_database*.
+#[derive(Clone, Copy)]
+pub struct TiledDatabase <'a>
+{
+	/// The database the tiles point to.
+	pub database:   &'a dyn Database,
+
+	/// The max angle between a tile and the stars stored in it.
+	pub reach:      Radians,
+
+	/// The center of each tile.
+	pub tiles:      &'a dyn LinearLookup<Equatorial>,
+
+	/// The index in `tile_pairs` where each tile starts.  
+	/// There is one more element than `tiles` which is the size of `tile_pairs`.
+	pub tile_start: &'a dyn LinearLookup<usize>,
+
+	/// The pairs (index of `database` pairs) of every tile, ascending in each tile.
+	pub tile_pairs: &'a dyn LinearLookup<usize>,
+}
+
+
+/// The database for the grid algorithm ([Grid](crate::tracking_mode::Grid)).  
+///
+/// Each catalogue star has a pattern made from the stars around it.  
+/// The neighbouring stars are projected onto a plane touching the star and rotated so the closest neighbour is on the x axis.  
+/// The plane is split into a `grid_size` by `grid_size` grid and the pattern is the list of cells with a star in them.  
+/// The patterns of every star are stored one after another in `cells`, the pattern of star `i` is from `pattern_start[i]` to `pattern_start[i + 1]`.  
+/// The inverse is also stored so only the stars with a shared cell are scored, the stars with cell `c` are from `cell_start[c]` to `cell_start[c + 1]` in `cell_stars`.  
+/// To generate, use `GridDatabaseGenerator` in *star_tracker_database*.
+#[derive(Clone, Copy)]
+pub struct GridDatabase <'a>
+{
+	/// The max angle from a star to a neighbour in its pattern.
+	pub radius:        Radians,
+
+	/// Neighbours closer than this are ignored as they are hard to separate (double stars).
+	pub buffer:        Radians,
+
+	/// The number of cells on each side of the grid.
+	pub grid_size:     usize,
+
+	/// The index in `cells` where the pattern of each catalogue star starts.  
+	/// There is one more element than the catalogue which is the size of `cells`.
+	pub pattern_start: &'a dyn LinearLookup<usize>,
+
+	/// The occupied cells of every pattern (row * grid_size + column), sorted in each pattern.
+	pub cells:         &'a dyn LinearLookup<usize>,
+
+	/// The index in `cell_stars` where the stars of each cell start.  
+	/// There are `grid_size * grid_size + 1` elements, the last is the size of `cell_stars`.
+	pub cell_start:    &'a dyn LinearLookup<usize>,
+
+	/// The catalogue stars with each cell in their pattern, ascending in each cell.
+	pub cell_stars:    &'a dyn LinearLookup<usize>,
+
+	/// The stars location in the sky.
+	pub catalogue:     &'a dyn LinearLookup<Equatorial>,
+}
+
 
 /// A chunk iterator is a way of optimizing the database search process.  
 /// When searching the database for star matches, the database will return matches from around the entire celestial sphere.  
@@ -304,6 +448,7 @@ pub struct ChunkIteratorDeclination <'a>
 /// This is used so that you can search a small reagion of the sky.  
 /// The usecase is if you have a general idea of the location of the attitude but not good enough.
 /// For areas that go past 360 degrees, either use a negative min bounds or a value over 360 for max.
+/// Near the poles the area becomes very narrow, use [ChunkIteratorCone](crate::tracking_mode::database::ChunkIteratorCone) instead.
 pub struct ChunkAreaSearch <'a>
 {
 	/// The database to search.
@@ -317,4 +462,70 @@ pub struct ChunkAreaSearch <'a>
 	
 	/// Has begin been called without next?
 	started: bool,
-}
\ No newline at end of file
+}
+
+
+/// A chunk iterator where each chunk is a tile of a [TiledDatabase](crate::tracking_mode::database::TiledDatabase).  
+///
+/// The other chunk iterators search the whole database then reject the pairs outside the chunk with `same_region`.  
+/// This only looks at the pairs stored in the tile, the k-vector range is found in the tile with a binary search.  
+/// Because the tiles overlap, neighbouring tiles can produce the same result, use a `randomiser` to spread the search out.
+pub struct ChunkIteratorTiled <'a>
+{
+	/// The database to search.
+	database: TiledDatabase<'a>,
+
+	/// fn randomiser ( current step, number of steps ) -> index of tile (must cover all tiles from 0 to n - 1).  
+	/// Use `ChunkIteratorDeclination::randomise_parity`.
+	randomiser: fn (usize, usize) -> usize,
+
+	/// The current step.
+	index: usize,
+
+	/// The tile being searched, None before `next` is called.
+	tile: Option<usize>,
+}
+
+
+
+/// A chunk iterator which searches the tiles of a [TiledDatabase](crate::tracking_mode::database::TiledDatabase) in order of an [AttitudePrior](crate::tracking_mode::database::AttitudePrior).  
+///
+/// The other chunk iterators search in a fixed order, if the attitude is roughly known this wastes time in unlikely areas.  
+/// `begin` sorts the first `N` tiles by their probability, any tiles past `N` are searched last in order of index.  
+/// Every tile is still searched, the unlikely tiles are just searched last.  
+/// Tiles with the same probability are searched in order of index, a probability of NaN is the least likely.
+pub struct ChunkIteratorPrior <'a, const N: usize>
+{
+	/// The iterator for the current tile.
+	tiled: ChunkIteratorTiled<'a>,
+
+	/// The probability of each tile.
+	prior: &'a dyn AttitudePrior,
+
+	/// The probability and index of the first `N` tiles, most likely first.
+	order: ArrayList<(Decimal, usize), N>,
+
+	/// The number of tiles searched, None before `next` is called.
+	index: Option<usize>,
+}
+
+
+
+/// Like ChunkAreaSearch except the area is a cone (circle on the celestial sphere) around a point.  
+/// This is used when the boresight is roughly known from a previous attitude or another sensor.  
+/// As the distance is the angle between the stars and the center, it works the same at the poles and across 0/360 degrees right ascension.  
+/// A star pair is in the region if either star is within `radius` of `center`.
+pub struct ChunkIteratorCone <'a>
+{
+	/// The database to search.
+	database: &'a dyn Database,
+
+	/// The center of the cone.
+	center: Equatorial,
+
+	/// The angle from the center to the edge of the cone.
+	radius: Radians,
+
+	/// Has begin been called without next?
+	started: bool,
+}
@@ -0,0 +1,136 @@
+//! Implementation for [PhotometricDatabase](crate::tracking_mode::database::PhotometricDatabase).
+use crate::core_include::*;
+
+use crate::tracking_mode::database::PhotometricDatabase;
+use crate::tracking_mode::database::Database;
+use crate::tracking_mode::database::KVector;
+use crate::tracking_mode::StarPair;
+
+use crate::util::units::Equatorial;
+use crate::util::units::Radians;
+use crate::util::aliases::Decimal;
+
+impl <'a> Database for PhotometricDatabase <'a>
+{
+	/// Gets the star pair at the index in the array.
+	fn get_pairs          ( &self, i: usize ) -> StarPair<usize> { return self.database.get_pairs(i);          }
+	/// Gets the size of the star pairs array.
+	fn get_pairs_size     ( &self ) -> usize                     { return self.database.get_pairs_size();      }
+
+	/// Gets the catalogue array.
+	fn get_catalogue      ( &self, i: usize ) -> Equatorial      { return self.database.get_catalogue(i);      }
+	/// Gets the catalogue array.
+	fn get_catalogue_size ( &self ) -> usize                     { return self.database.get_catalogue_size();  }
+
+	/// Gets the k_vector array.
+	fn get_k_vector       ( &self, i: usize ) -> usize           { return self.database.get_k_vector(i);       }
+	/// Gets the k_vector array.
+	fn get_k_vector_size  ( &self ) -> usize                     { return self.database.get_k_vector_size();   }
+
+	/// Gets the k_vector lookup equation.
+	fn get_k_lookup       ( &self ) -> KVector                   { return self.database.get_k_lookup();        }
+
+	/// Gets the field of view the database was created for.
+	fn get_fov            ( &self ) -> Radians                   { return self.database.get_fov();             }
+
+	/// Gets the magnitude of the catalogue star, None if there is no magnitude for the star.
+	fn get_magnitude      ( &self, i: usize ) -> Option<Decimal>
+	{
+		if i < self.magnitude.size() { return Some(self.magnitude.get(i)); }
+		return None;
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::StarPair;
+	use crate::tracking_mode::database::PhotometricDatabase;
+	use crate::tracking_mode::database::PyramidDatabase;
+	use crate::tracking_mode::database::Database;
+	use crate::tracking_mode::database::KVector;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::Radians;
+	use crate::util::aliases::Decimal;
+
+	static K_VECTOR  : [usize; 3]           = [0, 1, 2];
+	static PAIRS     : [StarPair<usize>; 2] = [StarPair(0, 1), StarPair(1, 2)];
+	static CATALOGUE : [Equatorial; 3]      =
+	[
+		Equatorial{ra: Radians(0.0), dec: Radians(0.0)},
+		Equatorial{ra: Radians(0.1), dec: Radians(0.0)},
+		Equatorial{ra: Radians(0.2), dec: Radians(0.0)},
+	];
+	static MAGNITUDE : [Decimal; 2]         = [1.5, 4.0];
+
+	#[coverage(off)]
+	fn create_database ( ) -> PyramidDatabase<'static>
+	{
+		return PyramidDatabase
+		{
+			fov:       Radians(0.3),
+			k_lookup:  KVector::new(K_VECTOR.len(), 0.1, 0.2),
+			k_vector:  &K_VECTOR,
+			pairs:     &PAIRS,
+			catalogue: &CATALOGUE,
+		};
+	}
+
+
+//###############################################################################################//
+//
+//										Trait Database
+//
+// fn get_pairs          ( &self, usize ) -> StarPair<usize>
+// fn get_pairs_size     ( &self ) -> usize
+// fn get_catalogue      ( &self, usize ) -> Equatorial
+// fn get_catalogue_size ( &self ) -> usize
+// fn get_k_vector       ( &self, usize ) -> usize
+// fn get_k_vector_size  ( &self ) -> usize
+// fn get_k_lookup       ( &self ) -> KVector
+// fn get_fov            ( &self ) -> Radians
+// fn get_magnitude      ( &self, usize ) -> Option<Decimal>
+//
+//###############################################################################################//
+//										~ delegated ~											 //
+	#[test]
+	// Everything except the magnitude should come from the wrapped database.
+	fn test_delegated ( )
+	{
+		let inner    = create_database();
+		let database = PhotometricDatabase{database: &inner, magnitude: &MAGNITUDE};
+		assert_eq!(database.get_pairs(1),          PAIRS[1]);
+		assert_eq!(database.get_pairs_size(),      PAIRS.len());
+		assert_eq!(database.get_catalogue(2),      CATALOGUE[2]);
+		assert_eq!(database.get_catalogue_size(),  CATALOGUE.len());
+		assert_eq!(database.get_k_vector(1),       K_VECTOR[1]);
+		assert_eq!(database.get_k_vector_size(),   K_VECTOR.len());
+		assert_eq!(database.get_k_lookup().num_bins, inner.k_lookup.num_bins);
+		assert_eq!(database.get_fov(),             Radians(0.3));
+	}
+
+//										~ get_magnitude ~										 //
+	#[test]
+	// The magnitude should be found if it exists.
+	fn test_get_magnitude ( )
+	{
+		let inner    = create_database();
+		let database = PhotometricDatabase{database: &inner, magnitude: &MAGNITUDE};
+		assert_eq!(database.get_magnitude(0), Some(1.5));
+		assert_eq!(database.get_magnitude(1), Some(4.0));
+		assert_eq!(database.get_magnitude(2), None);
+		assert_eq!(inner.get_magnitude(0),    None);
+	}
+}
@@ -0,0 +1,165 @@
+//! Implementation for [TiledDatabase](crate::tracking_mode::database::TiledDatabase).
+use core::ops::Range;
+
+use crate::tracking_mode::database::TiledDatabase;
+
+impl <'a> TiledDatabase <'a>
+{
+	/// The number of tiles.
+	pub fn num_tiles ( &self ) -> usize
+	{
+		return self.tiles.size();
+	}
+
+
+	/// The range of `tile_pairs` which belongs to the tile.
+	/// # Arguments
+	/// * `tile` - The index of the tile.
+	pub fn tile_range ( &self, tile: usize ) -> Range<usize>
+	{
+		return self.tile_start.get(tile)..self.tile_start.get(tile + 1);
+	}
+
+
+	/// Finds the first element of the tile which points to a pair of `pair_index` or above.
+	/// # Arguments
+	/// * `tile`       - The index of the tile.
+	/// * `pair_index` - The index of the pair in `database`.
+	/// # Returns
+	/// The index in `tile_pairs`, the end of the tile if every pair is below `pair_index`.
+	pub fn lower_bound ( &self, tile: usize, pair_index: usize ) -> usize
+	{
+		let range     = self.tile_range(tile);
+		let mut start = range.start;
+		let mut end   = range.end;
+		while start < end
+		{
+			let mid = start + (end - start) / 2;
+			if self.tile_pairs.get(mid) < pair_index { start = mid + 1; }
+			else                                      { end   = mid;     }
+		}
+		return start;
+	}
+
+
+	/// Returns true if the pair is stored in the tile.
+	/// # Arguments
+	/// * `tile`       - The index of the tile.
+	/// * `pair_index` - The index of the pair in `database`.
+	pub fn contains ( &self, tile: usize, pair_index: usize ) -> bool
+	{
+		let index = self.lower_bound(tile, pair_index);
+		return index < self.tile_range(tile).end && self.tile_pairs.get(index) == pair_index;
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::database::TiledDatabase;
+	use crate::tracking_mode::database::MockDatabase;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::Radians;
+
+	// 3 tiles, the middle tile is empty.
+	#[coverage(off)]
+	fn create_database ( database: &MockDatabase ) -> TiledDatabase
+	{
+		static TILES      : [Equatorial; 3] = [Equatorial{ra: Radians(0.0), dec: Radians(0.0)}; 3];
+		static TILE_START : [usize; 4]      = [0, 4, 4, 6];
+		static TILE_PAIRS : [usize; 6]      = [1, 3, 4, 9, 0, 2];
+		return TiledDatabase
+		{
+			database:   database,
+			reach:      Radians(0.1),
+			tiles:      &TILES,
+			tile_start: &TILE_START,
+			tile_pairs: &TILE_PAIRS,
+		};
+	}
+
+
+//###############################################################################################//
+//
+//										TiledDatabase
+//
+// pub fn num_tiles   ( &self ) -> usize
+// pub fn tile_range  ( &self, usize ) -> Range<usize>
+// pub fn lower_bound ( &self, usize, usize ) -> usize
+// pub fn contains    ( &self, usize, usize ) -> bool
+//
+//###############################################################################################//
+//										~ num_tiles ~											 //
+	#[test]
+	fn test_num_tiles ( )
+	{
+		let mock = MockDatabase::new();
+		assert_eq!(create_database(&mock).num_tiles(), 3);
+	}
+
+//										~ tile_range ~											 //
+	#[test]
+	// The range should be between the start of the tile and the start of the next tile.
+	fn test_tile_range ( )
+	{
+		let mock = MockDatabase::new();
+		let database = create_database(&mock);
+		assert_eq!(database.tile_range(0), 0..4);
+		assert_eq!(database.tile_range(1), 4..4);
+		assert_eq!(database.tile_range(2), 4..6);
+	}
+
+//										~ lower_bound ~											 //
+	#[test]
+	// Should find the first pair equal or above the value.
+	fn test_lower_bound ( )
+	{
+		let mock = MockDatabase::new();
+		let database = create_database(&mock);
+		assert_eq!(database.lower_bound(0, 0), 0);
+		assert_eq!(database.lower_bound(0, 1), 0);
+		assert_eq!(database.lower_bound(0, 2), 1);
+		assert_eq!(database.lower_bound(0, 4), 2);
+		assert_eq!(database.lower_bound(0, 5), 3);
+		assert_eq!(database.lower_bound(0, 10), 4);
+		assert_eq!(database.lower_bound(2, 1), 5);
+	}
+
+	#[test]
+	// An empty tile should return the end of the tile.
+	fn test_lower_bound_empty ( )
+	{
+		let mock = MockDatabase::new();
+		let database = create_database(&mock);
+		assert_eq!(database.lower_bound(1, 0), 4);
+		assert_eq!(database.lower_bound(1, 100), 4);
+	}
+
+//										~ contains ~											 //
+	#[test]
+	// Only the pairs in the tile should be found.
+	fn test_contains ( )
+	{
+		let mock = MockDatabase::new();
+		let database = create_database(&mock);
+		assert!(database.contains(0, 1));
+		assert!(database.contains(0, 9));
+		assert!(!database.contains(0, 0));
+		assert!(!database.contains(0, 10));
+		assert!(!database.contains(1, 1));
+		assert!(database.contains(2, 0));
+		assert!(!database.contains(2, 9));
+	}
+}
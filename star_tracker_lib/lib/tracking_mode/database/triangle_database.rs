@@ -0,0 +1,274 @@
+//! Implementation for [TriangleDatabase](crate::tracking_mode::database::TriangleDatabase).
+use crate::core_include::*;
+use core::ops::Range;
+
+use crate::tracking_mode::database::TriangleDatabase;
+use crate::tracking_mode::database::KVectorSearch;
+use crate::tracking_mode::TriangleAngles;
+use crate::tracking_mode::StarTriangle;
+
+use crate::util::units::Equatorial;
+use crate::util::units::Radians;
+use crate::util::list::List;
+
+impl <'a> TriangleDatabase <'a>
+{
+	/// Finds triangles in the database with interior angles within tolerance of `find`.
+	/// # Arguments
+	/// * `find`      - The interior angles of the observed triangle.
+	/// * `tolerance` - How far each interior angle can be from the database triangle.
+	/// * `found`     - The triangles found, the vertices are ordered (smallest, middle, largest angle).
+	pub fn find_close_ref (
+		&self, find: TriangleAngles, tolerance: Radians, found: &mut dyn List<StarTriangle<usize>> )
+	{
+		for i in self.range(find, tolerance)
+		{
+			let triangle = self.triangles.get(i);
+			if self.angles(triangle).is_close(&find, tolerance) && !found.is_full()
+			{
+				let _ = found.push_back(triangle);
+			}
+		}
+	}
+
+
+	/// Finds a star which makes a triangle with the catalogue stars `a` and `b` with interior angles within tolerance of `find`.
+	/// This uses the k-vector instead of checking every catalogue star.
+	/// # Arguments
+	/// * `find`      - The interior angles of the observed triangle.
+	/// * `tolerance` - How far each interior angle can be from the database triangle.
+	/// * `a`         - A catalogue star in the triangle.
+	/// * `b`         - Another catalogue star in the triangle.
+	/// * `accept`    - Any other check the third star must pass.
+	/// # Returns
+	/// The catalogue index of the first third star which is accepted.
+	pub fn find_third (
+		&self,
+		find     : TriangleAngles,
+		tolerance: Radians,
+		a        : usize,
+		b        : usize,
+		accept   : &dyn Fn(usize) -> bool ) -> Option<usize>
+	{
+		for i in self.range(find, tolerance)
+		{
+			let triangle = self.triangles.get(i);
+			let vertices = [triangle.0, triangle.1, triangle.2];
+			if !vertices.contains(&a) || !vertices.contains(&b) { continue; }
+
+			for third in vertices
+			{
+				if third != a && third != b && self.angles(triangle).is_close(&find, tolerance) && accept(third)
+				{
+					return Some(third);
+				}
+			}
+		}
+		return None;
+	}
+
+
+	/// Finds the range of `triangles` which may have the same smallest angle as `find`.
+	/// # Arguments
+	/// * `find`      - The interior angles of the observed triangle.
+	/// * `tolerance` - How far the smallest angle can be from the database triangle.
+	fn range ( &self, find: TriangleAngles, tolerance: Radians ) -> Range<usize>
+	{
+		if self.triangles.size() == 0 || self.k_vector.size() == 0 { return 0..0; }
+		if let Ok(bins) = self.k_lookup.get_bins(find.smallest, tolerance)
+		{
+			// The last element of the k_vector is the end of the list and the bin before contains the max value.
+			// Every triangle is checked with `is_close` so widening the range is harmless.
+			let last      = self.k_vector.size() - 1;
+			let bin_start = core::cmp::min(bins.start, last.saturating_sub(1));
+			let bin_end   = core::cmp::min(bins.end,   last);
+
+			let start = core::cmp::min(self.k_vector.get(bin_start), self.triangles.size());
+			let end   = core::cmp::min(self.k_vector.get(bin_end),   self.triangles.size());
+			return start..end;
+		}
+		return 0..0;
+	}
+
+
+	/// Finds the interior angles of a triangle from the catalogue.
+	/// # Arguments
+	/// * `triangle` - A triangle of catalogue indices (as stored in `triangles`).
+	pub fn angles ( &self, triangle: StarTriangle<usize> ) -> TriangleAngles
+	{
+		let points = StarTriangle(
+			self.catalogue.get(triangle.0),
+			self.catalogue.get(triangle.1),
+			self.catalogue.get(triangle.2));
+		return TriangleAngles::from_equatorial(points).0;
+	}
+
+
+	/// Gets the star at the index of the catalogue.
+	pub fn get_catalogue ( &self, index: usize ) -> Equatorial
+	{
+		return self.catalogue.get(index);
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::database::TriangleDatabase;
+	use crate::tracking_mode::database::KVector;
+	use crate::tracking_mode::TriangleAngles;
+	use crate::tracking_mode::StarTriangle;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::Degrees;
+	use crate::util::units::Radians;
+	use crate::util::list::List;
+	use crate::util::test::TestEqual;
+
+	// A right angle triangle and a close to equilateral triangle.
+	static CATALOGUE: [Equatorial; 6] =
+	[
+		Equatorial{ra: Radians(0.00),  dec: Radians(0.00)},
+		Equatorial{ra: Radians(0.02),  dec: Radians(0.00)},
+		Equatorial{ra: Radians(0.00),  dec: Radians(0.01)},
+
+		Equatorial{ra: Radians(0.10),  dec: Radians(0.00)},
+		Equatorial{ra: Radians(0.11),  dec: Radians(0.00)},
+		Equatorial{ra: Radians(0.104), dec: Radians(0.009)},
+	];
+
+	// Ordered by (smallest angle, middle angle, largest angle).
+	// The smallest angles are 26.6 and 56.3 degrees.
+	static TRIANGLES: [StarTriangle<usize>; 2] = [StarTriangle(1, 2, 0), StarTriangle(4, 5, 3)];
+	static K_VECTOR:  [usize; 3] = [0, 1, 2];
+
+	fn create_database ( ) -> TriangleDatabase<'static>
+	{
+		return TriangleDatabase
+		{
+			fov:       Degrees(20.0).as_radians(),
+			k_lookup:  KVector::new(K_VECTOR.len(), 0.4, 1.1),
+			k_vector:  &K_VECTOR,
+			triangles: &TRIANGLES,
+			catalogue: &CATALOGUE,
+		};
+	}
+
+//###############################################################################################//
+//
+//										TriangleDatabase
+//
+// pub fn find_close_ref ( &self, TriangleAngles, Radians, &mut dyn List<StarTriangle<usize>> )
+// pub fn find_third     ( &self, TriangleAngles, Radians, usize, usize, &dyn Fn(usize) -> bool ) -> Option<usize>
+// fn range              ( &self, TriangleAngles, Radians ) -> Range<usize>
+// pub fn angles         ( &self, StarTriangle<usize> ) -> TriangleAngles
+// pub fn get_catalogue  ( &self, usize ) -> Equatorial
+//
+//###############################################################################################//
+//										~ find_close_ref ~										 //
+	#[test]
+	// Only the triangle with the same shape should be found.
+	fn test_find_close_ref ( )
+	{
+		let database = create_database();
+		for i in 0..TRIANGLES.len()
+		{
+			let find = database.angles(TRIANGLES[i]);
+			let mut found: Vec<StarTriangle<usize>> = Vec::new();
+			database.find_close_ref(find, Degrees(1.0).as_radians(), &mut found);
+			assert_eq!(found.size(), 1);
+			assert_eq!(found[0], TRIANGLES[i]);
+		}
+	}
+
+	#[test]
+	// If the angles are not in the database, nothing is found.
+	fn test_find_close_ref_none ( )
+	{
+		let database = create_database();
+		let find = TriangleAngles{smallest: Degrees(40.0).as_radians(), middle: Degrees(60.0).as_radians()};
+		let mut found: Vec<StarTriangle<usize>> = Vec::new();
+		database.find_close_ref(find, Degrees(1.0).as_radians(), &mut found);
+		assert_eq!(found.size(), 0);
+	}
+
+	#[test]
+	// The stored triangles must be ordered by angle.
+	fn test_find_close_ref_order ( )
+	{
+		for i in 0..TRIANGLES.len()
+		{
+			let t = TRIANGLES[i];
+			let points = StarTriangle(CATALOGUE[t.0], CATALOGUE[t.1], CATALOGUE[t.2]);
+			assert_eq!(TriangleAngles::from_equatorial(points).1, StarTriangle(0, 1, 2));
+		}
+	}
+
+//										~ find_third ~											 //
+	#[test]
+	// The third star of a triangle with the two stars should be found.
+	fn test_find_third ( )
+	{
+		let database = create_database();
+		let find = database.angles(TRIANGLES[1]);
+		assert_eq!(database.find_third(find, Degrees(1.0).as_radians(), 3, 4, &|_| true), Some(5));
+		assert_eq!(database.find_third(find, Degrees(1.0).as_radians(), 5, 3, &|_| true), Some(4));
+	}
+
+	#[test]
+	// If the stars are not in a triangle with the angles or the third star is rejected, nothing is found.
+	fn test_find_third_none ( )
+	{
+		let database = create_database();
+		let find = database.angles(TRIANGLES[1]);
+		assert_eq!(database.find_third(find, Degrees(1.0).as_radians(), 0, 1, &|_| true), None);
+		assert_eq!(database.find_third(find, Degrees(1.0).as_radians(), 3, 4, &|third| third != 5), None);
+
+		let find = TriangleAngles{smallest: Degrees(40.0).as_radians(), middle: Degrees(60.0).as_radians()};
+		assert_eq!(database.find_third(find, Degrees(1.0).as_radians(), 3, 4, &|_| true), None);
+	}
+
+//										~ range ~												 //
+	#[test]
+	// The range should contain the triangles with a similar smallest angle.
+	fn test_range ( )
+	{
+		let database = create_database();
+		assert!(database.range(database.angles(TRIANGLES[0]), Degrees(1.0).as_radians()).contains(&0));
+		assert!(database.range(database.angles(TRIANGLES[1]), Degrees(1.0).as_radians()).contains(&1));
+
+		// Outside of the k-vector.
+		let find = TriangleAngles{smallest: Degrees(80.0).as_radians(), middle: Degrees(80.0).as_radians()};
+		assert_eq!(database.range(find, Degrees(1.0).as_radians()), 0..0);
+	}
+
+//										~ angles ~												 //
+	#[test]
+	// The angles should be the angles of the catalogue triangle.
+	fn test_angles ( )
+	{
+		let database = create_database();
+		let angles = database.angles(StarTriangle(0, 1, 2));
+		angles.smallest.0.assert_close(&Degrees(26.565).as_radians().0, 0.001);
+		angles.middle.0.assert_close(&Degrees(63.435).as_radians().0, 0.001);
+	}
+
+//										~ get_catalogue ~										 //
+	#[test]
+	fn test_get_catalogue ( )
+	{
+		let database = create_database();
+		assert_eq!(database.get_catalogue(3), CATALOGUE[3]);
+	}
+}
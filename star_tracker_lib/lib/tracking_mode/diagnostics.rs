@@ -0,0 +1,522 @@
+//! Implementation of [SearchDiagnostics](crate::tracking_mode::SearchDiagnostics) and [ChunkIteratorDiagnostics](crate::tracking_mode::ChunkIteratorDiagnostics).
+use crate::core_include::*;
+
+use crate::tracking_mode::SearchDiagnostics;
+use crate::tracking_mode::SearchDiagnosticsNone;
+use crate::tracking_mode::SearchDiagnosticsRecord;
+use crate::tracking_mode::ChunkIteratorDiagnostics;
+use crate::tracking_mode::ConstellationResult;
+use crate::tracking_mode::StarTriangle;
+use crate::tracking_mode::Clock;
+
+use crate::tracking_mode::database::ChunkIterator;
+use crate::tracking_mode::database::SearchResult;
+use crate::tracking_mode::database::Database;
+
+use crate::util::units::Radians;
+use crate::util::units::Match;
+use crate::util::list::ArrayList;
+use crate::util::list::List;
+
+use core::cell::RefCell;
+
+impl SearchDiagnostics for SearchDiagnosticsNone
+{
+	fn begin            ( &mut self )                                        { }
+	fn chunk            ( &mut self )                                        { }
+	fn candidates       ( &mut self, _candidates: usize )                    { }
+	fn pilot_candidates ( &mut self, _candidates: usize )                    { }
+	fn triangle         ( &mut self, _triangle: Match<StarTriangle<usize>> ) { }
+	fn specularity      ( &mut self )                                        { }
+	fn pilot            ( &mut self, _found: bool )                          { }
+	fn end              ( &mut self, _result: ConstellationResult )          { }
+}
+
+
+
+impl <C: Clock, const N: usize> SearchDiagnosticsRecord <C, N>
+{
+	/// Creates an empty record.
+	/// # Arguments
+	/// * `clock` - The clock used to time the search.
+	pub fn new ( clock: C ) -> Self
+	{
+		return Self
+		{
+			clock:             clock,
+			start:             0,
+			ticks:             0,
+			triangles:         0,
+			sides:             0,
+			sides_empty:       0,
+			candidates:        0,
+			specularity:       0,
+			pilots:            0,
+			pilot_sides:       0,
+			pilot_candidates:  0,
+			pilot_failures:    0,
+			chunks:            0,
+			result:            None,
+			sample_triangles:  ArrayList::new(),
+			sample_candidates: ArrayList::new(),
+		};
+	}
+}
+
+
+impl <C: Clock, const N: usize> SearchDiagnostics for SearchDiagnosticsRecord <C, N>
+{
+	/// Resets the counts and starts the timer.
+	fn begin ( &mut self )
+	{
+		self.start            = self.clock.now();
+		self.ticks            = 0;
+		self.triangles        = 0;
+		self.sides            = 0;
+		self.sides_empty      = 0;
+		self.candidates       = 0;
+		self.specularity      = 0;
+		self.pilots           = 0;
+		self.pilot_sides      = 0;
+		self.pilot_candidates = 0;
+		self.pilot_failures   = 0;
+		self.chunks           = 0;
+		self.result           = None;
+		self.sample_triangles.clear();
+		self.sample_candidates.clear();
+	}
+
+	/// Counts the chunk.
+	fn chunk ( &mut self ) { self.chunks += 1; }
+
+	/// Counts the side and the candidates, the first N are sampled.
+	fn candidates ( &mut self, candidates: usize )
+	{
+		self.sides      += 1;
+		self.candidates += candidates;
+		if candidates == 0 { self.sides_empty += 1; }
+		let _ = self.sample_candidates.push_back(candidates);
+	}
+
+	/// Counts the pilot side and the candidates.
+	fn pilot_candidates ( &mut self, candidates: usize )
+	{
+		self.pilot_sides      += 1;
+		self.pilot_candidates += candidates;
+	}
+
+	/// Counts the triangle, the first N are sampled.
+	fn triangle ( &mut self, triangle: Match<StarTriangle<usize>> )
+	{
+		self.triangles += 1;
+		let _ = self.sample_triangles.push_back(triangle);
+	}
+
+	/// Counts the flipped triangle.
+	fn specularity ( &mut self ) { self.specularity += 1; }
+
+	/// Counts the pilot or the pilot failure.
+	fn pilot ( &mut self, found: bool )
+	{
+		if found { self.pilots += 1; } else { self.pilot_failures += 1; }
+	}
+
+	/// Stores the result and the time taken.
+	fn end ( &mut self, result: ConstellationResult )
+	{
+		self.result = Some(result);
+		self.ticks  = self.clock.now().wrapping_sub(self.start);
+	}
+}
+
+
+
+impl <'a> ChunkIteratorDiagnostics <'a>
+{
+	/// Wraps a database iterator used to find star triangles.
+	/// # Arguments
+	/// * `chunks`      - The database iterator.
+	/// * `diagnostics` - Where the chunks and candidates are reported.
+	pub fn new ( chunks: &'a mut dyn ChunkIterator, diagnostics: &'a mut dyn SearchDiagnostics ) -> Self
+	{
+		return Self{chunks: chunks, diagnostics: RefCell::new(diagnostics), pilot: false};
+	}
+
+
+	/// Wraps a database iterator used to find pilot stars, the sides are reported as `pilot_candidates`.
+	/// # Arguments
+	/// * `chunks`      - The database iterator.
+	/// * `diagnostics` - Where the chunks and candidates are reported.
+	pub fn new_pilot ( chunks: &'a mut dyn ChunkIterator, diagnostics: &'a mut dyn SearchDiagnostics ) -> Self
+	{
+		return Self{chunks: chunks, diagnostics: RefCell::new(diagnostics), pilot: true};
+	}
+}
+
+
+impl <'a> ChunkIterator for ChunkIteratorDiagnostics <'a>
+{
+	/// Resets the wrapped iterator.
+	fn begin ( &mut self ) { self.chunks.begin(); }
+
+	/// Steps the wrapped iterator, reports the chunk if there is one.
+	fn next ( &mut self ) -> bool
+	{
+		let next = self.chunks.next();
+		if next { self.diagnostics.get_mut().chunk(); }
+		return next;
+	}
+
+	/// Searches the wrapped iterator, reports the number of star pairs added to `found`.
+	fn find_close_ref_region ( &self, find: Radians, tolerance: Radians, found: &mut dyn List<SearchResult> )
+	{
+		let size = found.size();
+		self.chunks.find_close_ref_region(find, tolerance, found);
+		let candidates = found.size() - size;
+		if self.pilot { self.diagnostics.borrow_mut().pilot_candidates(candidates); }
+		else          { self.diagnostics.borrow_mut().candidates(candidates);       }
+	}
+
+	/// Returns the database of the wrapped iterator.
+	fn get_database ( &self ) -> &dyn Database { return self.chunks.get_database(); }
+
+	/// Returns the region of the wrapped iterator.
+	fn same_region ( &self, pair_index: usize ) -> bool { return self.chunks.same_region(pair_index); }
+}
+
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::SearchDiagnostics;
+	use crate::tracking_mode::SearchDiagnosticsNone;
+	use crate::tracking_mode::SearchDiagnosticsRecord;
+	use crate::tracking_mode::MockSearchDiagnostics;
+	use crate::tracking_mode::ChunkIteratorDiagnostics;
+	use crate::tracking_mode::ConstellationResult;
+	use crate::tracking_mode::StarTriangle;
+	use crate::tracking_mode::StarPair;
+	use crate::tracking_mode::MockClock;
+
+	use crate::tracking_mode::database::ChunkIterator;
+	use crate::tracking_mode::database::MockChunkIterator;
+	use crate::tracking_mode::database::SearchResult;
+
+	use crate::util::units::Radians;
+	use crate::util::units::Match;
+	use crate::util::list::List;
+
+	// A clock which returns 10, 25, 40...
+	#[coverage(off)]
+	fn clock ( ) -> MockClock
+	{
+		let mut clock = MockClock::new();
+		let mut time : u32 = 10;
+		clock.expect_now().returning(move || { time += 15; return time - 15; });
+		return clock;
+	}
+
+
+//###############################################################################################//
+//
+//										SearchDiagnosticsNone
+//
+// fn begin       ( &mut self )
+// fn chunk       ( &mut self )
+// fn candidates  ( &mut self, usize )
+// fn pilot_candidates ( &mut self, usize )
+// fn triangle    ( &mut self, Match<StarTriangle<usize>> )
+// fn specularity ( &mut self )
+// fn pilot       ( &mut self, bool )
+// fn end         ( &mut self, ConstellationResult )
+//
+//###############################################################################################//
+	#[test]
+	// Nothing should happen.
+	fn test_none ( )
+	{
+		let mut diagnostics = SearchDiagnosticsNone();
+		diagnostics.begin();
+		diagnostics.chunk();
+		diagnostics.candidates(1);
+		diagnostics.pilot_candidates(1);
+		diagnostics.triangle(Match{input: StarTriangle(0, 1, 2), output: StarTriangle(0, 1, 2), weight: 1.0});
+		diagnostics.specularity();
+		diagnostics.pilot(true);
+		diagnostics.end(ConstellationResult::Success{fails: 0});
+		assert_eq!(diagnostics, SearchDiagnosticsNone());
+	}
+
+
+
+//###############################################################################################//
+//
+//										SearchDiagnosticsRecord
+//
+// pub fn new     ( C ) -> Self
+// fn begin       ( &mut self )
+// fn chunk       ( &mut self )
+// fn candidates  ( &mut self, usize )
+// fn pilot_candidates ( &mut self, usize )
+// fn triangle    ( &mut self, Match<StarTriangle<usize>> )
+// fn specularity ( &mut self )
+// fn pilot       ( &mut self, bool )
+// fn end         ( &mut self, ConstellationResult )
+//
+//###############################################################################################//
+//										~ new ~													 //
+	#[test]
+	fn test_record_new ( )
+	{
+		let record : SearchDiagnosticsRecord<MockClock, 2> = SearchDiagnosticsRecord::new(MockClock::new());
+		assert_eq!(record.start, 0);
+		assert_eq!(record.ticks, 0);
+		assert_eq!(record.triangles, 0);
+		assert_eq!(record.sides, 0);
+		assert_eq!(record.chunks, 0);
+		assert_eq!(record.result, None);
+		assert_eq!(record.sample_triangles.size(), 0);
+		assert_eq!(record.sample_candidates.size(), 0);
+	}
+
+//										~ begin ~												 //
+	#[test]
+	// Every count should be reset and the timer started.
+	fn test_record_begin ( )
+	{
+		let mut record : SearchDiagnosticsRecord<MockClock, 2> = SearchDiagnosticsRecord::new(clock());
+		record.candidates(0);
+		record.pilot_candidates(2);
+		record.triangle(Match{input: StarTriangle(0, 1, 2), output: StarTriangle(3, 4, 5), weight: 1.0});
+		record.specularity();
+		record.pilot(true);
+		record.pilot(false);
+		record.chunk();
+		record.end(ConstellationResult::Success{fails: 0});
+		record.begin();
+		assert_eq!(record.start, 25);
+		assert_eq!(record.ticks, 0);
+		assert_eq!(record.triangles, 0);
+		assert_eq!(record.sides, 0);
+		assert_eq!(record.sides_empty, 0);
+		assert_eq!(record.candidates, 0);
+		assert_eq!(record.specularity, 0);
+		assert_eq!(record.pilots, 0);
+		assert_eq!(record.pilot_sides, 0);
+		assert_eq!(record.pilot_candidates, 0);
+		assert_eq!(record.pilot_failures, 0);
+		assert_eq!(record.chunks, 0);
+		assert_eq!(record.result, None);
+		assert_eq!(record.sample_triangles.size(), 0);
+		assert_eq!(record.sample_candidates.size(), 0);
+	}
+
+//										~ chunk ~												 //
+	#[test]
+	fn test_record_chunk ( )
+	{
+		let mut record : SearchDiagnosticsRecord<MockClock, 2> = SearchDiagnosticsRecord::new(MockClock::new());
+		record.chunk();
+		record.chunk();
+		assert_eq!(record.chunks, 2);
+	}
+
+//										~ candidates ~											 //
+	#[test]
+	// Each side should be counted and the first N sampled.
+	fn test_record_candidates ( )
+	{
+		let mut record : SearchDiagnosticsRecord<MockClock, 2> = SearchDiagnosticsRecord::new(MockClock::new());
+		record.candidates(3);
+		record.candidates(0);
+		record.candidates(5);
+		assert_eq!(record.sides, 3);
+		assert_eq!(record.sides_empty, 1);
+		assert_eq!(record.candidates, 8);
+		assert_eq!(record.sample_candidates.size(), 2);
+		assert_eq!(record.sample_candidates.get(0), 3);
+		assert_eq!(record.sample_candidates.get(1), 0);
+	}
+
+//										~ pilot_candidates ~									 //
+	#[test]
+	// Each pilot side should be counted separately to the triangle sides and not sampled.
+	fn test_record_pilot_candidates ( )
+	{
+		let mut record : SearchDiagnosticsRecord<MockClock, 2> = SearchDiagnosticsRecord::new(MockClock::new());
+		record.pilot_candidates(3);
+		record.pilot_candidates(0);
+		assert_eq!(record.pilot_sides, 2);
+		assert_eq!(record.pilot_candidates, 3);
+		assert_eq!(record.sides, 0);
+		assert_eq!(record.candidates, 0);
+		assert_eq!(record.sample_candidates.size(), 0);
+	}
+
+//										~ triangle ~											 //
+	#[test]
+	// Each triangle should be counted and the first N sampled.
+	fn test_record_triangle ( )
+	{
+		let mut record : SearchDiagnosticsRecord<MockClock, 1> = SearchDiagnosticsRecord::new(MockClock::new());
+		let a = Match{input: StarTriangle(0, 1, 2), output: StarTriangle(3, 4, 5), weight: 1.0};
+		let b = Match{input: StarTriangle(1, 2, 3), output: StarTriangle(6, 7, 8), weight: 1.0};
+		record.triangle(a);
+		record.triangle(b);
+		assert_eq!(record.triangles, 2);
+		assert_eq!(record.sample_triangles.size(), 1);
+		assert_eq!(record.sample_triangles.get(0), a);
+	}
+
+//										~ specularity ~											 //
+	#[test]
+	fn test_record_specularity ( )
+	{
+		let mut record : SearchDiagnosticsRecord<MockClock, 2> = SearchDiagnosticsRecord::new(MockClock::new());
+		record.specularity();
+		assert_eq!(record.specularity, 1);
+	}
+
+//										~ pilot ~												 //
+	#[test]
+	fn test_record_pilot ( )
+	{
+		let mut record : SearchDiagnosticsRecord<MockClock, 2> = SearchDiagnosticsRecord::new(MockClock::new());
+		record.pilot(true);
+		record.pilot(true);
+		record.pilot(false);
+		assert_eq!(record.pilots, 2);
+		assert_eq!(record.pilot_failures, 1);
+	}
+
+//										~ end ~													 //
+	#[test]
+	// The result and elapsed ticks should be stored.
+	fn test_record_end ( )
+	{
+		let mut record : SearchDiagnosticsRecord<MockClock, 2> = SearchDiagnosticsRecord::new(clock());
+		record.begin();
+		record.end(ConstellationResult::ErrorAborted{fails: 3});
+		assert_eq!(record.ticks, 15);
+		assert_eq!(record.result, Some(ConstellationResult::ErrorAborted{fails: 3}));
+	}
+
+	#[test]
+	// The clock is allowed to overflow.
+	fn test_record_end_overflow ( )
+	{
+		let mut clock = MockClock::new();
+		let mut time : u32 = u32::MAX - 4;
+		clock.expect_now().returning(move || { time = time.wrapping_add(10); return time; });
+		let mut record : SearchDiagnosticsRecord<MockClock, 2> = SearchDiagnosticsRecord::new(clock);
+		record.begin();
+		record.end(ConstellationResult::Success{fails: 0});
+		assert_eq!(record.ticks, 10);
+	}
+
+
+
+//###############################################################################################//
+//
+//										ChunkIteratorDiagnostics
+//
+// pub fn new                   ( &mut dyn ChunkIterator, &mut dyn SearchDiagnostics ) -> Self
+// pub fn new_pilot             ( &mut dyn ChunkIterator, &mut dyn SearchDiagnostics ) -> Self
+// fn begin                     ( &mut self )
+// fn next                      ( &mut self ) -> bool
+// fn find_close_ref_region     ( &self, Radians, Radians, &mut dyn List<SearchResult> )
+// fn get_database              ( &self ) -> &dyn Database
+// fn same_region               ( &self, usize ) -> bool
+//
+//###############################################################################################//
+//										~ new_pilot ~											 //
+	#[test]
+	// The sides should be reported as pilot candidates.
+	fn test_chunk_new_pilot ( )
+	{
+		let mut chunks = MockChunkIterator::new();
+		chunks.expect_find_close_ref_region().times(1).returning(|_, _, found|
+			{ found.push_back(SearchResult{result: StarPair(0, 1), error: 0.0}); });
+		let mut diagnostics = MockSearchDiagnostics::new();
+		diagnostics.expect_candidates().times(0);
+		diagnostics.expect_pilot_candidates().times(1).withf(|c| return *c == 1).returning(|_| return);
+
+		let iter = ChunkIteratorDiagnostics::new_pilot(&mut chunks, &mut diagnostics);
+		let mut found : Vec<SearchResult> = Vec::new();
+		iter.find_close_ref_region(Radians(0.1), Radians(0.2), &mut found);
+		assert_eq!(found.len(), 1);
+	}
+
+//										~ begin ~												 //
+	#[test]
+	fn test_chunk_begin ( )
+	{
+		let mut chunks = MockChunkIterator::new();
+		chunks.expect_begin().times(1).returning(|| return);
+		let mut diagnostics = MockSearchDiagnostics::new();
+		let mut iter = ChunkIteratorDiagnostics::new(&mut chunks, &mut diagnostics);
+		iter.begin();
+	}
+
+//										~ next ~												 //
+	#[test]
+	// Only a successful step is a chunk.
+	fn test_chunk_next ( )
+	{
+		let mut chunks = MockChunkIterator::new();
+		let mut count = 0;
+		chunks.expect_next().times(3).returning(move || { count += 1; return count < 3; });
+		let mut diagnostics = MockSearchDiagnostics::new();
+		diagnostics.expect_chunk().times(2).returning(|| return);
+		let mut iter = ChunkIteratorDiagnostics::new(&mut chunks, &mut diagnostics);
+		assert!(iter.next());
+		assert!(iter.next());
+		assert!(!iter.next());
+	}
+
+//									~ find_close_ref_region ~									 //
+	#[test]
+	// The number of results added should be reported.
+	fn test_chunk_find_close_ref_region ( )
+	{
+		let mut chunks = MockChunkIterator::new();
+		chunks.expect_find_close_ref_region()
+			.times(1)
+			.withf(|find, tolerance, _| return *find == Radians(0.1) && *tolerance == Radians(0.2))
+			.returning(|_, _, found|
+			{
+				found.push_back(SearchResult{result: StarPair(0, 1), error: 0.0});
+				found.push_back(SearchResult{result: StarPair(0, 2), error: 0.0});
+			});
+		let mut diagnostics = MockSearchDiagnostics::new();
+		diagnostics.expect_candidates().times(1).withf(|c| return *c == 2).returning(|_| return);
+		diagnostics.expect_pilot_candidates().times(0);
+
+		let iter = ChunkIteratorDiagnostics::new(&mut chunks, &mut diagnostics);
+		let mut found : Vec<SearchResult> = vec![SearchResult{result: StarPair(5, 5), error: 0.0}];
+		iter.find_close_ref_region(Radians(0.1), Radians(0.2), &mut found);
+		assert_eq!(found.len(), 3);
+	}
+
+//										~ same_region ~											 //
+	#[test]
+	fn test_chunk_same_region ( )
+	{
+		let mut chunks = MockChunkIterator::new();
+		chunks.expect_same_region().times(1).withf(|i| return *i == 3).returning(|_| return true);
+		let mut diagnostics = MockSearchDiagnostics::new();
+		let iter = ChunkIteratorDiagnostics::new(&mut chunks, &mut diagnostics);
+		assert!(iter.same_region(3));
+	}
+}
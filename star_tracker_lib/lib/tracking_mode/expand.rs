@@ -0,0 +1,284 @@
+//! Implementation of [Expand](crate::tracking_mode::Expand).
+use crate::core_include::*;
+
+use crate::tracking_mode::Expand;
+
+use crate::util::linear_lookup::LinearLookup;
+use crate::util::units::Equatorial;
+use crate::util::units::Quaternion;
+use crate::util::units::Match;
+use crate::util::list::List;
+
+impl Expand
+{
+	/// Identifies every observed star which agrees with `attitude`.
+	/// The stars already in `matches` are kept, any new star is added to the end with a weight of 1.
+	/// If two new stars are closest to the same catalogue star, the star with the smallest residual is identified.
+	/// If `matches` is full, the remaining stars are ignored.
+	///
+	/// # Arguments
+	/// * `stars`     - The observed stars (projected onto a unit sphere in the camera frame).
+	/// * `catalogue` - The catalogue the attitude was found from.
+	/// * `attitude`  - The rotation from inertial to camera (same as [AttitudeDetermination::estimate](crate::attitude_determination::AttitudeDetermination::estimate)).
+	/// * `matches`   - The identified stars (input: index of `stars`, output: index of `catalogue`).
+	///
+	/// # Returns
+	/// The number of stars added to `matches`.
+	///
+	/// # Example
+	/// ``` ignore
+	/// use star_tracker_lib::tracking_mode::Expand;
+	///
+	/// // Find the attitude with the pyramid.
+	/// Recursive::to_vectors(&stars_2d, database.catalogue, &intrinsic, &matches, &mut vectors);
+	/// let attitude = Quest::estimate(&vectors, None);
+	///
+	/// // Identify the rest of the image and find the attitude again.
+	/// let expand = Expand{tolerance: Degrees(0.05).as_radians()};
+	/// let added  = expand.expand(&stars_3d, database.catalogue, attitude, &mut matches);
+	/// Recursive::to_vectors(&stars_2d, database.catalogue, &intrinsic, &matches, &mut vectors);
+	/// let attitude = Quest::estimate(&vectors, None);
+	/// ```
+	pub fn expand (
+		&self,
+		stars    : &dyn List<Equatorial>,
+		catalogue: &dyn LinearLookup<Equatorial>,
+		attitude : Quaternion,
+		matches  : &mut dyn List<Match<usize>>,
+	) -> usize
+	{
+		let to_inertial = attitude.conjugate();
+		let residual    = | input: usize, output: usize |
+			to_inertial.rotate_point(stars.get(input).to_vector3()).to_equatorial().angle_distance(catalogue.get(output));
+
+		// The stars identified before expanding can not be replaced.
+		let identified = matches.size();
+		for i in 0..stars.size()
+		{
+			let mut used = false;
+			for j in 0..identified { used |= matches.get(j).input == i; }
+			if used { continue; }
+
+			// The closest catalogue star which was not identified before expanding.
+			let mut closest  : Option<usize> = None;
+			let mut distance = self.tolerance;
+			for j in 0..catalogue.size()
+			{
+				let dist = residual(i, j);
+				if dist < distance
+				{
+					let mut taken = false;
+					for k in 0..identified { taken |= matches.get(k).output == j; }
+					if !taken
+					{
+						closest  = Some(j);
+						distance = dist;
+					}
+				}
+			}
+			let output = match closest { Some(output) => output, None => continue };
+
+			// If another new star has the same catalogue star, the closest keeps it.
+			let mut conflict = None;
+			for k in identified..matches.size()
+			{
+				if matches.get(k).output == output { conflict = Some(k); }
+			}
+			match conflict
+			{
+				Some(k) =>
+				{
+					if distance < residual(matches.get(k).input, output)
+					{
+						let _ = matches.set(k, Match{input: i, output: output, weight: 1.0});
+					}
+				}
+				None =>
+				{
+					if matches.is_full() { break; }
+					let _ = matches.push_back(Match{input: i, output: output, weight: 1.0});
+				}
+			}
+		}
+		return matches.size() - identified;
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::Expand;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::Quaternion;
+	use crate::util::units::AngleAxis;
+	use crate::util::units::Vector3;
+	use crate::util::units::Degrees;
+	use crate::util::units::Match;
+	use crate::util::list::ArrayList;
+	use crate::util::list::List;
+	use crate::util::test::star_patch;
+	use crate::util::test::point_at;
+
+	// A patch of stars around (100, 10) degrees and a star outside the field of view.
+	#[coverage(off)]
+	fn catalogue ( ) -> Vec<Equatorial>
+	{
+		let mut stars = star_patch((100.0, 10.0), 8);
+		stars.push(Equatorial{ra: Degrees(160.0).as_radians(), dec: Degrees(10.0).as_radians()});
+		return stars;
+	}
+
+	// Points the camera (z axis) at (100, 10) degrees.
+	#[coverage(off)]
+	fn attitude ( ) -> Quaternion
+	{
+		return point_at(Equatorial{ra: Degrees(100.0).as_radians(), dec: Degrees(10.0).as_radians()});
+	}
+
+	// The observed stars are the catalogue in reverse order, index i of the stars is catalogue 7 - i.
+	#[coverage(off)]
+	fn stars ( ) -> Vec<Equatorial>
+	{
+		return catalogue()[0..8].iter().rev()
+			.map(|s| attitude().rotate_point(s.to_vector3()).to_equatorial()).collect();
+	}
+
+	// The pyramid found the first 4 stars.
+	#[coverage(off)]
+	fn pyramid ( ) -> Vec<Match<usize>>
+	{
+		return (0..4).map(|i| Match{input: i, output: 7 - i, weight: 1.0}).collect();
+	}
+
+	#[coverage(off)]
+	fn expand ( ) -> Expand
+	{
+		return Expand{tolerance: Degrees(0.05).as_radians()};
+	}
+
+
+//###############################################################################################//
+//
+//										Expand
+//
+// pub fn expand ( &self, &dyn List<Equatorial>, &dyn LinearLookup<Equatorial>, Quaternion,
+//                 &mut dyn List<Match<usize>> ) -> usize
+//
+//###############################################################################################//
+//										~ expand ~												 //
+	#[test]
+	// Every observed star should be identified.
+	fn test_expand ( )
+	{
+		let mut matches = pyramid();
+		let added = expand().expand(&stars(), &catalogue(), attitude(), &mut matches);
+
+		assert_eq!(added, 4);
+		assert_eq!(matches.size(), 8);
+		for i in 0..matches.size()
+		{
+			assert_eq!(matches[i].output, 7 - matches[i].input);
+			assert_eq!(matches[i].weight, 1.0);
+		}
+	}
+
+	#[test]
+	// Stars further than the tolerance should not be added.
+	fn test_expand_tolerance ( )
+	{
+		let mut stars = stars();
+		stars[4].dec = stars[4].dec + Degrees(0.03).as_radians(); // Within tolerance.
+		stars[5].dec = stars[5].dec + Degrees(0.5).as_radians();  // Too far.
+
+		let mut matches = pyramid();
+		let added = expand().expand(&stars, &catalogue(), attitude(), &mut matches);
+
+		assert_eq!(added, 3);
+		assert_eq!(matches.size(), 7);
+		assert_eq!(matches[4], Match{input: 4, output: 3, weight: 1.0});
+		assert_eq!(matches[5].input, 6);
+		assert_eq!(matches[6].input, 7);
+	}
+
+	#[test]
+	// A catalogue star which was identified before expanding should not be used again.
+	fn test_expand_duplicate ( )
+	{
+		let mut stars = stars();
+		stars.push(stars[0]); // A second blob on catalogue star 7.
+
+		let mut matches = pyramid();
+		let added = expand().expand(&stars, &catalogue(), attitude(), &mut matches);
+		assert_eq!(added, 4);
+		for i in 0..matches.size() { assert_ne!(matches[i].input, 8); }
+	}
+
+	#[test]
+	// If two new stars are on the same catalogue star, the closest should be identified even if it is found second.
+	fn test_expand_conflict_closer ( )
+	{
+		let mut stars = stars();
+		let mut blob  = stars[4];
+		stars[4].dec = stars[4].dec + Degrees(0.03).as_radians();
+		blob.dec     = blob.dec     + Degrees(0.01).as_radians();
+		stars.push(blob);
+
+		let mut matches = pyramid();
+		let added = expand().expand(&stars, &catalogue(), attitude(), &mut matches);
+		assert_eq!(added, 4);
+		assert_eq!(matches[4], Match{input: 8, output: 3, weight: 1.0});
+		for i in 0..matches.size() { assert_ne!(matches[i].input, 4); }
+	}
+
+	#[test]
+	// If two new stars are on the same catalogue star, a further star found second should not replace the first.
+	fn test_expand_conflict_further ( )
+	{
+		let mut stars = stars();
+		let mut blob  = stars[4];
+		stars[4].dec = stars[4].dec + Degrees(0.01).as_radians();
+		blob.dec     = blob.dec     + Degrees(0.04).as_radians();
+		stars.push(blob);
+
+		let mut matches = pyramid();
+		let added = expand().expand(&stars, &catalogue(), attitude(), &mut matches);
+		assert_eq!(added, 4);
+		assert_eq!(matches[4], Match{input: 4, output: 3, weight: 1.0});
+		for i in 0..matches.size() { assert_ne!(matches[i].input, 8); }
+	}
+
+	#[test]
+	// If the attitude is wrong, nothing should be added.
+	fn test_expand_wrong ( )
+	{
+		let wrong = AngleAxis{angle: Degrees(3.0).as_radians(), axis: Vector3{x: 0.0, y: 0.0, z: 1.0}}
+			.to_quaternion() * attitude();
+		let mut matches = pyramid();
+		let added = expand().expand(&stars(), &catalogue(), wrong, &mut matches);
+		assert_eq!(added, 0);
+		assert_eq!(matches.size(), 4);
+	}
+
+	#[test]
+	// If the matches are full, it should stop without panicking.
+	fn test_expand_full ( )
+	{
+		let mut matches : ArrayList<Match<usize>, 6> = ArrayList::new();
+		for pair in pyramid() { matches.push_back(pair); }
+		let added = expand().expand(&stars(), &catalogue(), attitude(), &mut matches);
+		assert_eq!(added, 2);
+		assert_eq!(matches.size(), 6);
+	}
+}
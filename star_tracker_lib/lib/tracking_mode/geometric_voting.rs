@@ -0,0 +1,407 @@
+//! Implementation of [GeometricVoting](crate::tracking_mode::GeometricVoting).
+use crate::core_include::*;
+
+use crate::tracking_mode::database::Database;
+use crate::tracking_mode::PairConsistency;
+use crate::tracking_mode::GeometricVotingResult;
+use crate::tracking_mode::GeometricVoting;
+
+use crate::util::units::Equatorial;
+use crate::util::units::Radians;
+use crate::util::units::Match;
+use crate::util::list::ArrayList;
+use crate::util::list::List;
+use crate::util::err::Errors;
+use crate::util::err::Error;
+
+impl <const N_FOUND: usize, const N_VOTES: usize> GeometricVoting <N_FOUND, N_VOTES>
+{
+	/// Creates the buffers used for the search.
+	pub fn new ( ) -> Self
+	{
+		return Self { found: ArrayList::new(), votes: ArrayList::new() };
+	}
+
+
+	/// Identifies the observed stars.
+	///
+	/// # Arguments
+	/// * `stars`           - The observed stars (projected onto a unit sphere).
+	/// * `database`        - The database to search (the same as the pyramid method).
+	/// * `angle_tolerance` - How much error a star pair can have until it is not considered the same.
+	/// * `min_stars`       - The minimum number of verified stars required for success.
+	/// * `matches`         - The output (input: index of `stars`, output: index of the catalogue).
+	///
+	/// # Returns
+	/// Success if at least `min_stars` stars were verified.  
+	/// Both results report the number of votes which were dropped as the votes were full.
+	///
+	/// # Example
+	/// ``` ignore
+	/// use star_tracker_lib::tracking_mode::GeometricVotingResult;
+	/// use star_tracker_lib::tracking_mode::GeometricVoting;
+	///
+	/// let database = // The same database as the pyramid method.
+	/// let stars_3d = // The observed stars.
+	///
+	/// // 1000 results per star pair and 10000 votes.
+	/// let mut voting = GeometricVoting::<1000, 10000>::new();
+	/// let mut output: Vec<Match<usize>> = Vec::new();
+	/// let result = voting.find(&stars_3d, &database, Degrees(0.05).as_radians(), 4, &mut output);
+	/// if let GeometricVotingResult::Success{verified, dropped: _} = result
+	/// {
+	/// 	println!("{} stars identified.", verified);
+	/// }
+	/// ```
+	pub fn find (
+		&mut self,
+		stars          : &dyn List<Equatorial>,
+		database       : &dyn Database,
+		angle_tolerance: Radians,
+		min_stars      : usize,
+		matches        : &mut dyn List<Match<usize>>,
+	) -> GeometricVotingResult
+	{
+		matches.clear();
+		self.votes.clear();
+		let mut dropped = 0;
+
+		// Every catalogue pair with the same separation as an observed pair votes for both stars.
+		for i in 0..stars.size()
+		{
+			for j in (i + 1)..stars.size()
+			{
+				let separation = stars.get(i).angle_distance(stars.get(j));
+				self.found.clear();
+				database.find_close_ref(separation, angle_tolerance, &mut self.found);
+				for k in 0..self.found.size()
+				{
+					let pair = self.found.get(k).result;
+					let votes = [(i, pair.0), (i, pair.1), (j, pair.0), (j, pair.1)];
+					for (input, output) in votes
+					{
+						if self.vote(input, output).is_err() { dropped += 1; }
+					}
+				}
+			}
+		}
+
+		// The most voted pairs are assigned first, so a catalogue star is only assigned to its most likely observed star.
+		while !matches.is_full()
+		{
+			let mut best : Option<Match<usize>> = None;
+			for k in 0..self.votes.size()
+			{
+				let vote = self.votes.get(k);
+				if best.map_or(true, |b| b.weight < vote.weight) && !Self::assigned(vote, matches)
+				{
+					best = Some(vote);
+				}
+			}
+			match best
+			{
+				Some(vote) => { let _ = matches.push_back(Match{input: vote.input, output: vote.output, weight: 0.0}); }
+				None       => break,
+			}
+		}
+		matches.sort_order(|a, b| a.input < b.input);
+
+		PairConsistency::filter(stars, &|index| database.find_star(index).ok(), angle_tolerance, matches);
+
+		let verified = matches.size();
+		if verified < min_stars || verified == 0
+		{
+			return GeometricVotingResult::ErrorInsufficientStars{verified: verified, dropped: dropped};
+		}
+		return GeometricVotingResult::Success{verified: verified, dropped: dropped};
+	}
+
+
+
+	/// True if the observed or catalogue star of `vote` is already in `matches`.
+	fn assigned ( vote: Match<usize>, matches: &dyn List<Match<usize>> ) -> bool
+	{
+		for i in 0..matches.size()
+		{
+			let current = matches.get(i);
+			if current.input == vote.input || current.output == vote.output
+			{
+				return true;
+			}
+		}
+		return false;
+	}
+
+
+
+	/// Adds a vote for the observed star being the catalogue star.
+	/// The votes are kept sorted by (input, output) so an existing vote is found with a binary search.
+	/// # Returns
+	/// Errors::InvalidSize if the vote is a new candidate and the votes are full (the vote is ignored).
+	fn vote ( &mut self, input: usize, output: usize ) -> Error<()>
+	{
+		// The first index which is not before (input, output).
+		let mut low  = 0;
+		let mut high = self.votes.size();
+		while low < high
+		{
+			let middle  = (low + high) / 2;
+			let current = self.votes.get(middle);
+			if (current.input, current.output) < (input, output) { low = middle + 1; }
+			else                                                 { high = middle;    }
+		}
+
+		if low < self.votes.size()
+		{
+			let mut current = self.votes.get(low);
+			if current.input == input && current.output == output
+			{
+				current.weight += 1.0;
+				return self.votes.set(low, current);
+			}
+		}
+
+		if self.votes.is_full()
+		{
+			return Err(Errors::InvalidSize);
+		}
+
+		// Shift everything after the new vote right.
+		let _ = self.votes.push_back(Match{input: input, output: output, weight: 1.0});
+		for i in ((low + 1)..self.votes.size()).rev()
+		{
+			let _ = self.votes.set(i, self.votes.get(i - 1));
+		}
+		return self.votes.set(low, Match{input: input, output: output, weight: 1.0});
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::database::PyramidDatabase;
+	use crate::tracking_mode::database::KVector;
+	use crate::tracking_mode::GeometricVotingResult;
+	use crate::tracking_mode::GeometricVoting;
+	use crate::tracking_mode::StarPair;
+
+	use crate::util::aliases::M_PI;
+	use crate::util::units::Equatorial;
+	use crate::util::units::AngleAxis;
+	use crate::util::units::Degrees;
+	use crate::util::units::Radians;
+	use crate::util::units::Match;
+	use crate::util::aliases::Decimal;
+	use crate::util::list::List;
+	use crate::util::err::Errors;
+	use crate::util::test::star_patch;
+	use crate::util::test::star_pairs;
+
+	// A set of stars with unique separations.
+	#[coverage(off)]
+	fn catalogue ( ) -> Vec<Equatorial>
+	{
+		return star_patch((30.0, 20.0), 7);
+	}
+
+	#[coverage(off)]
+	fn tolerance ( ) -> Radians { return Degrees(0.01).as_radians(); }
+
+
+//###############################################################################################//
+//
+//										GeometricVoting
+//
+// pub fn new  ( ) -> Self
+// pub fn find ( &mut self, &dyn List<Equatorial>, &dyn Database, Radians, usize, &mut dyn List<Match<usize>> )
+//            -> GeometricVotingResult
+// fn assigned  ( Match<usize>, &dyn List<Match<usize>> ) -> bool
+// fn vote      ( &mut self, usize, usize ) -> Error<()>
+//
+//###############################################################################################//
+//										~ find ~												 //
+	#[test]
+	// Every observed star should be identified.
+	fn test_find ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+
+		// The observed stars are in a different order to the catalogue.
+		let order = [3, 0, 6, 2, 5];
+		let stars : Vec<Equatorial> = order.iter().map(|i| catalogue[*i]).collect();
+
+		let mut voting  = GeometricVoting::<100, 100>::new();
+		let mut matches : Vec<Match<usize>> = Vec::new();
+		let result = voting.find(&stars, &database, tolerance(), 4, &mut matches);
+
+		assert!(matches!(result, GeometricVotingResult::Success{verified: 5, dropped: 0}));
+		assert_eq!(matches.size(), 5);
+		for i in 0..matches.size()
+		{
+			assert_eq!(order[matches[i].input], matches[i].output);
+			assert_eq!(matches[i].weight, 1.0);
+		}
+	}
+
+	#[test]
+	// A false star which matches one pair should be removed by the verification.
+	fn test_find_false_star ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+
+		// The false star is the same distance from star 0 as star 6 is.
+		let rotation = AngleAxis{angle: Radians(M_PI), axis: catalogue[0].to_vector3()}.to_quaternion();
+		let false_star = rotation.rotate_point(catalogue[6].to_vector3()).to_equatorial();
+		let stars = vec![catalogue[0], catalogue[1], false_star, catalogue[2], catalogue[3]];
+
+		let mut voting  = GeometricVoting::<100, 100>::new();
+		let mut matches : Vec<Match<usize>> = Vec::new();
+		let result = voting.find(&stars, &database, tolerance(), 4, &mut matches);
+
+		assert!(matches!(result, GeometricVotingResult::Success{verified: 4, dropped: 0}));
+		for i in 0..matches.size()
+		{
+			assert_ne!(matches[i].input, 2);
+			assert_eq!([0, 1, 0, 2, 3][matches[i].input], matches[i].output);
+		}
+	}
+
+	#[test]
+	// If there are not enough stars to verify, it should fail.
+	fn test_find_insufficient ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+
+		let mut voting  = GeometricVoting::<100, 100>::new();
+		let mut matches : Vec<Match<usize>> = Vec::new();
+
+		// 2 stars cannot be verified.
+		let stars = vec![catalogue[0], catalogue[1]];
+		let result = voting.find(&stars, &database, tolerance(), 1, &mut matches);
+		assert!(matches!(result, GeometricVotingResult::ErrorInsufficientStars{verified: 0, ..}));
+
+		// 3 stars are verified but less than min_stars.
+		let stars = vec![catalogue[0], catalogue[1], catalogue[2]];
+		let result = voting.find(&stars, &database, tolerance(), 4, &mut matches);
+		assert!(matches!(result, GeometricVotingResult::ErrorInsufficientStars{verified: 3, ..}));
+		assert_eq!(matches.size(), 3);
+
+		// No stars.
+		let stars : Vec<Equatorial> = vec![];
+		let result = voting.find(&stars, &database, tolerance(), 0, &mut matches);
+		assert!(matches!(result, GeometricVotingResult::ErrorInsufficientStars{verified: 0, ..}));
+		assert_eq!(matches.size(), 0);
+	}
+
+	#[test]
+	// If the votes are full, it should not panic and the dropped votes are reported.
+	fn test_find_full ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+
+		let mut voting  = GeometricVoting::<1, 2>::new();
+		let mut matches : Vec<Match<usize>> = Vec::new();
+		let result = voting.find(&catalogue, &database, Degrees(5.0).as_radians(), 4, &mut matches);
+		assert!(matches!(result, GeometricVotingResult::ErrorInsufficientStars{verified: _, dropped} if 0 < dropped));
+	}
+
+	#[test]
+	// Two observed stars should never be assigned the same catalogue star.
+	fn test_find_one_to_one ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+
+		// Star 1 is observed twice, both get the same votes.
+		let stars = vec![catalogue[0], catalogue[1], catalogue[1], catalogue[2], catalogue[3]];
+
+		let mut voting  = GeometricVoting::<100, 100>::new();
+		let mut matches : Vec<Match<usize>> = Vec::new();
+		voting.find(&stars, &database, tolerance(), 3, &mut matches);
+		for i in 0..matches.size()
+		{
+			for j in (i + 1)..matches.size()
+			{
+				assert_ne!(matches[i].output, matches[j].output);
+			}
+			assert_eq!([0, 1, 1, 2, 3][matches[i].input], matches[i].output);
+		}
+		assert!(matches.iter().any(|m| m.output == 1));
+	}
+
+//										~ assigned ~											 //
+	#[test]
+	// A vote is assigned if either star is already used.
+	fn test_assigned ( )
+	{
+		let matches = vec![Match{input: 0, output: 5, weight: 0.0}, Match{input: 1, output: 6, weight: 0.0}];
+		type Voting = GeometricVoting<1, 1>;
+		assert!( Voting::assigned(Match{input: 0, output: 7, weight: 0.0}, &matches));
+		assert!( Voting::assigned(Match{input: 2, output: 6, weight: 0.0}, &matches));
+		assert!(!Voting::assigned(Match{input: 2, output: 7, weight: 0.0}, &matches));
+		assert!(!Voting::assigned(Match{input: 0, output: 5, weight: 0.0}, &Vec::new()));
+	}
+
+//										~ vote ~												 //
+	#[test]
+	// The votes should be sorted and a repeated vote should increase the weight.
+	fn test_vote ( )
+	{
+		let mut voting = GeometricVoting::<1, 4>::new();
+		voting.vote(1, 2).expect("Not full.");
+		voting.vote(0, 9).expect("Not full.");
+		voting.vote(1, 0).expect("Not full.");
+		voting.vote(1, 2).expect("Existing.");
+		voting.vote(0, 9).expect("Existing.");
+		voting.vote(1, 2).expect("Existing.");
+
+		let votes : Vec<(usize, usize, Decimal)> =
+			(0..voting.votes.size()).map(|i| { let v = voting.votes.get(i); (v.input, v.output, v.weight) }).collect();
+		assert_eq!(votes, vec![(0, 9, 2.0), (1, 0, 1.0), (1, 2, 3.0)]);
+	}
+
+	#[test]
+	// A new candidate should be rejected if full, existing candidates can still be voted for.
+	fn test_vote_full ( )
+	{
+		let mut voting = GeometricVoting::<1, 2>::new();
+		voting.vote(3, 3).expect("Not full.");
+		voting.vote(1, 1).expect("Not full.");
+		assert_eq!(voting.vote(2, 2), Err(Errors::InvalidSize));
+		assert_eq!(voting.vote(0, 0), Err(Errors::InvalidSize));
+		assert_eq!(voting.vote(3, 3), Ok(()));
+		assert_eq!(voting.votes.size(), 2);
+		assert_eq!(voting.votes.get(0).input, 1);
+		assert_eq!(voting.votes.get(1).weight, 2.0);
+	}
+}
@@ -0,0 +1,285 @@
+//! Implementation of [Grid](crate::tracking_mode::Grid).
+use crate::core_include::*;
+
+use crate::tracking_mode::database::GridDatabase;
+use crate::tracking_mode::PairConsistency;
+use crate::tracking_mode::GridResult;
+use crate::tracking_mode::Grid;
+
+use crate::util::units::Equatorial;
+use crate::util::units::Radians;
+use crate::util::units::Match;
+use crate::util::list::ArrayList;
+use crate::util::list::List;
+
+impl <const N_CELLS: usize> Grid <N_CELLS>
+{
+	/// Creates the buffer used for the patterns.
+	pub fn new ( ) -> Self
+	{
+		return Self { cells: ArrayList::new() };
+	}
+
+
+	/// Identifies the observed stars.
+	///
+	/// # Arguments
+	/// * `stars`           - The observed stars (projected onto a unit sphere).
+	/// * `database`        - The grid database to search.
+	/// * `angle_tolerance` - How much error a star pair can have when verifying the identified stars.
+	/// * `min_score`       - The minimum number of cells an observed pattern must share with a catalogue pattern.
+	/// * `min_stars`       - The minimum number of verified stars required for success.
+	/// * `matches`         - The output (input: index of `stars`, output: index of the catalogue).
+	///
+	/// # Returns
+	/// Success if at least `min_stars` stars were verified.
+	///
+	/// # Example
+	/// ``` ignore
+	/// use star_tracker_lib::tracking_mode::GridResult;
+	/// use star_tracker_lib::tracking_mode::Grid;
+	///
+	/// let database = // Use GridDatabaseGenerator in star_tracker_database.
+	/// let stars_3d = // The observed stars.
+	///
+	/// // Each observed pattern can have up to 100 occupied cells.
+	/// let mut grid = Grid::<100>::new();
+	/// let mut output: Vec<Match<usize>> = Vec::new();
+	/// let result = grid.find(&stars_3d, &database, Degrees(0.05).as_radians(), 4, 4, &mut output);
+	/// if let GridResult::Success{verified} = result
+	/// {
+	/// 	println!("{} stars identified.", verified);
+	/// }
+	/// ```
+	pub fn find (
+		&mut self,
+		stars          : &dyn List<Equatorial>,
+		database       : &GridDatabase,
+		angle_tolerance: Radians,
+		min_score      : usize,
+		min_stars      : usize,
+		matches        : &mut dyn List<Match<usize>>,
+	) -> GridResult
+	{
+		matches.clear();
+		for i in 0..stars.size()
+		{
+			// A partial pattern could match the wrong star.
+			let pattern = GridDatabase::pattern(stars.get(i), stars,
+				database.radius, database.buffer, database.grid_size, &mut self.cells);
+			if pattern.is_err() { continue; }
+
+			if let Some((output, _score)) = database.find_best(&self.cells, min_score)
+			{
+				if matches.push_back(Match{input: i, output: output, weight: 0.0}).is_err() { break; }
+			}
+		}
+
+		PairConsistency::filter(stars, &|index| Some(database.get_catalogue(index)), angle_tolerance, matches);
+
+		let verified = matches.size();
+		if verified < min_stars || verified == 0
+		{
+			return GridResult::ErrorInsufficientStars{verified: verified};
+		}
+		return GridResult::Success{verified: verified};
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::database::GridDatabase;
+	use crate::tracking_mode::GridResult;
+	use crate::tracking_mode::Grid;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::AngleAxis;
+	use crate::util::units::Degrees;
+	use crate::util::units::Radians;
+	use crate::util::units::Match;
+	use crate::util::list::List;
+	use crate::util::test::star_patch;
+
+	// A patch of stars with irregular spacing.
+	#[coverage(off)]
+	fn catalogue ( ) -> Vec<Equatorial>
+	{
+		return star_patch((100.0, 10.0), 14);
+	}
+
+	// The pattern of every star in the catalogue and the stars of every cell (the same as GridDatabaseGenerator).
+	struct Patterns
+	{
+		pattern_start: Vec<usize>,
+		cells:         Vec<usize>,
+		cell_start:    Vec<usize>,
+		cell_stars:    Vec<usize>,
+	}
+
+	#[coverage(off)]
+	fn patterns ( catalogue: &Vec<Equatorial> ) -> Patterns
+	{
+		let mut pattern_start = Vec::new();
+		let mut cells         = Vec::new();
+		for i in 0..catalogue.len()
+		{
+			pattern_start.push(cells.len());
+			let mut pattern : Vec<usize> = Vec::new();
+			GridDatabase::pattern(catalogue[i], catalogue, radius(), buffer(), 11, &mut pattern);
+			cells.append(&mut pattern);
+		}
+		pattern_start.push(cells.len());
+
+		let mut cell_start = Vec::new();
+		let mut cell_stars = Vec::new();
+		for cell in 0..(11 * 11)
+		{
+			cell_start.push(cell_stars.len());
+			for i in 0..catalogue.len()
+			{
+				if cells[pattern_start[i]..pattern_start[i + 1]].contains(&cell) { cell_stars.push(i); }
+			}
+		}
+		cell_start.push(cell_stars.len());
+		return Patterns{pattern_start: pattern_start, cells: cells, cell_start: cell_start, cell_stars: cell_stars};
+	}
+
+	#[coverage(off)]
+	fn database <'a> ( patterns: &'a Patterns, catalogue: &'a Vec<Equatorial> ) -> GridDatabase<'a>
+	{
+		return GridDatabase{radius: radius(), buffer: buffer(), grid_size: 11,
+			pattern_start: &patterns.pattern_start, cells: &patterns.cells,
+			cell_start: &patterns.cell_start, cell_stars: &patterns.cell_stars, catalogue: catalogue};
+	}
+
+	#[coverage(off)]
+	fn radius ( ) -> Radians { return Degrees(5.5).as_radians(); }
+	#[coverage(off)]
+	fn buffer ( ) -> Radians { return Degrees(0.1).as_radians(); }
+	#[coverage(off)]
+	fn tolerance ( ) -> Radians { return Degrees(0.01).as_radians(); }
+
+	// Rotates the stars so they are in a different frame.
+	#[coverage(off)]
+	fn rotate ( stars: &Vec<Equatorial> ) -> Vec<Equatorial>
+	{
+		let rotation = AngleAxis{angle: Degrees(57.0).as_radians(),
+			axis: Equatorial{ra: Degrees(20.0).as_radians(), dec: Degrees(-40.0).as_radians()}.to_vector3()}
+			.to_quaternion();
+		return stars.iter().map(|s| rotation.rotate_point(s.to_vector3()).to_equatorial()).collect();
+	}
+
+
+//###############################################################################################//
+//
+//										Grid
+//
+// pub fn new  ( ) -> Self
+// pub fn find ( &mut self, &dyn List<Equatorial>, &GridDatabase, Radians, usize, usize, &mut dyn List<Match<usize>> )
+//            -> GridResult
+//
+//###############################################################################################//
+//										~ find ~												 //
+	#[test]
+	// Every observed star should be identified in a different frame and order.
+	fn test_find ( )
+	{
+		let catalogue = catalogue();
+		let patterns  = patterns(&catalogue);
+		let database  = database(&patterns, &catalogue);
+
+		let order = [3, 0, 6, 2, 5, 1, 4, 7, 8, 9, 10, 11, 12, 13];
+		let stars = rotate(&order.iter().map(|i| catalogue[*i]).collect());
+
+		let mut grid    = Grid::<100>::new();
+		let mut matches : Vec<Match<usize>> = Vec::new();
+		let result = grid.find(&stars, &database, tolerance(), 3, 4, &mut matches);
+
+		assert!(matches!(result, GridResult::Success{verified: 14}));
+		for i in 0..matches.size()
+		{
+			assert_eq!(order[matches[i].input], matches[i].output);
+			assert_eq!(matches[i].weight, 1.0);
+		}
+	}
+
+	#[test]
+	// Missing stars and a false star should not stop the remaining stars being identified.
+	fn test_find_false_and_missing ( )
+	{
+		let catalogue = catalogue();
+		let patterns  = patterns(&catalogue);
+		let database  = database(&patterns, &catalogue);
+
+		// Stars 5 and 9 are missing, the last star is false.
+		let order = [0, 1, 2, 3, 4, 6, 7, 8, 10, 11, 12, 13];
+		let mut observed : Vec<Equatorial> = order.iter().map(|i| catalogue[*i]).collect();
+		observed.push(Equatorial{ra: Degrees(98.0).as_radians(), dec: Degrees(6.0).as_radians()});
+		let stars = rotate(&observed);
+
+		let mut grid    = Grid::<100>::new();
+		let mut matches : Vec<Match<usize>> = Vec::new();
+		let result = grid.find(&stars, &database, tolerance(), 3, 4, &mut matches);
+
+		assert!(matches!(result, GridResult::Success{..}));
+		assert!(4 <= matches.size());
+		for i in 0..matches.size()
+		{
+			assert_ne!(matches[i].input, order.len());
+			assert_eq!(order[matches[i].input], matches[i].output);
+		}
+	}
+
+	#[test]
+	// If there are not enough stars, it should fail.
+	fn test_find_insufficient ( )
+	{
+		let catalogue = catalogue();
+		let patterns  = patterns(&catalogue);
+		let database  = database(&patterns, &catalogue);
+
+		let mut grid    = Grid::<100>::new();
+		let mut matches : Vec<Match<usize>> = Vec::new();
+
+		// The patterns are too small to reach min_score.
+		let stars = vec![catalogue[0], catalogue[1], catalogue[13]];
+		let result = grid.find(&stars, &database, tolerance(), 3, 1, &mut matches);
+		assert!(matches!(result, GridResult::ErrorInsufficientStars{verified: 0}));
+
+		// More stars than exist are required.
+		let result = grid.find(&catalogue, &database, tolerance(), 3, 15, &mut matches);
+		assert!(matches!(result, GridResult::ErrorInsufficientStars{verified: 14}));
+
+		// No stars.
+		let stars : Vec<Equatorial> = vec![];
+		let result = grid.find(&stars, &database, tolerance(), 3, 0, &mut matches);
+		assert!(matches!(result, GridResult::ErrorInsufficientStars{verified: 0}));
+		assert_eq!(matches.size(), 0);
+	}
+
+	#[test]
+	// If the pattern buffer is full, it should not panic.
+	fn test_find_full ( )
+	{
+		let catalogue = catalogue();
+		let patterns  = patterns(&catalogue);
+		let database  = database(&patterns, &catalogue);
+
+		let mut grid    = Grid::<1>::new();
+		let mut matches : Vec<Match<usize>> = Vec::new();
+		let result = grid.find(&catalogue, &database, tolerance(), 3, 4, &mut matches);
+		assert!(matches!(result, GridResult::ErrorInsufficientStars{verified: 0}));
+	}
+}
@@ -44,6 +44,114 @@
 //!
 //!
 //!
+//! # Unknown Focal Length
+//! If the focal length of the lens is not known, [ScaleInvariant](crate::tracking_mode::ScaleInvariant) can be used.  
+//! Instead of the angular separation of star pairs, the interior angles of star triangles are compared.  
+//! These angles do not change with the focal length, so the stars can be identified and the focal length estimated.  
+//! This requires a [TriangleDatabase](crate::tracking_mode::database::TriangleDatabase), use *TriangleDatabaseGenerator* in *star_tracker_database*.  
+//!
+//!
+//!
+//! # Geometric Voting
+//! [GeometricVoting](crate::tracking_mode::GeometricVoting) is an alternative to the pyramid method which uses the same database.  
+//! Every pair of observed stars votes for the catalogue stars it could be, the most voted catalogue star is chosen for each observed star.  
+//! The chosen stars are then verified against each other to remove false stars.  
+//! The output is the same as [Constellation::find](crate::tracking_mode::Constellation::find) so the methods can be swapped.  
+//!
+//!
+//!
+//! # Grid Algorithm
+//! Triangle based methods fail when the centroid error is similar to the tolerance of the database.  
+//! [Grid](crate::tracking_mode::Grid) (Padgett and Kreutz-Delgado 1997) instead compares the pattern of stars around each observed star.  
+//! The neighbours are placed on a coarse grid aligned with the closest neighbour, each cell of the grid is about the size of the tolerance.  
+//! A missing or false star only changes one cell, so the pattern still matches.  
+//! This requires a [GridDatabase](crate::tracking_mode::database::GridDatabase), use *GridDatabaseGenerator* in *star_tracker_database*.  
+//!
+//!
+//!
+//! # Verification
+//! A successful pyramid only means 4 stars matched, the rest of the image is not checked.  
+//! [Verification](crate::tracking_mode::Verification) rotates the remaining observed stars with the found attitude and counts how many land on a catalogue star.  
+//! The probability of this many stars agreeing by chance is found (Mortari 2004), if it is too high the attitude is likely wrong.  
+//! If every observed star was used to find the attitude, nothing can confirm it and the probability is 1.  
+//! Only the catalogue stars in the field of view are compared, these are found once with `region` or from the closest tile of a [TiledDatabase](crate::tracking_mode::database::TiledDatabase) with `region_tiled`.  
+//!
+//!
+//!
+//! # Expanding the Identified Stars
+//! `Constellation::find` stops once `num_stars` are found, usually only 4 of the observed stars are used to find the attitude.  
+//! [Expand](crate::tracking_mode::Expand) rotates the remaining observed stars with this attitude and identifies each with the closest catalogue star.  
+//! Finding the attitude again with every identified star is far more accurate than with the 4 stars of a pyramid.  
+//!
+//!
+//!
+//! # Photometry
+//! The algorithms above only use the position of the stars, a bright blob can be matched to the dimmest star in the catalogue.  
+//! Giving [StarTriangleIterator](crate::tracking_mode::StarTriangleIterator) a [Photometry](crate::tracking_mode::Photometry) calibration with `begin_photometric`
+//! removes any triangle or pilot where the brightness of the observed stars does not agree with the catalogue magnitudes.  
+//! The magnitudes are provided by wrapping the database in a [PhotometricDatabase](crate::tracking_mode::database::PhotometricDatabase).  
+//! If `begin` is used, no brightness checks occur.  
+//! The iterator stores the intensities, `N_MAX_STARS` must fit every star (e.g. `StarTriangleIterator<1000, 20>` for 20 stars).  
+//!
+//!
+//!
+//! # Triangle Selection
+//! By default, [StarTriangleIterator](crate::tracking_mode::StarTriangleIterator) tries triangles in the order of the star list with [KernelIterator](crate::tracking_mode::KernelIterator).  
+//! Any [TriangleSelection](crate::tracking_mode::TriangleSelection) can be used instead with `StarTriangleIterator::with_selection`.  
+//! [TriangleSelectionRanked](crate::tracking_mode::TriangleSelectionRanked) tries the triangles with the highest [TriangleQuality](crate::tracking_mode::TriangleQuality) first:
+//! - [TriangleQualityBrightness](crate::tracking_mode::TriangleQualityBrightness) prefers bright stars (high SNR) which are less likely to be false stars.
+//! - [TriangleQualityArea](crate::tracking_mode::TriangleQualityArea) prefers large triangles which [Specularity](crate::tracking_mode::Specularity) does not ignore.
+//! - [TriangleQualityDistinct](crate::tracking_mode::TriangleQualityDistinct) prefers triangles with sides which have few matches in the database.
+//!
+//! The intensities used by the brightness quality are set on the quality, they are separate from the photometric checks of `begin_photometric`:
+//! ``` ignore
+//! let selection = TriangleSelectionRanked::<_, 100>::new(TriangleQualityBrightness::<20>::new());
+//! let mut iterator : StarTriangleIterator<1000, 0, _> = StarTriangleIterator::with_selection(selection);
+//! iterator.get_selection().quality.set_intensity(&intensity);
+//! Constellation::find(&stars, &mut database, &mut iterator, ...);
+//! ```
+//!
+//!
+//!
+//! # Cooperative Searching
+//! `Constellation::find` does not return until the search succeeds, fails or is abandoned, this can take seconds.  
+//! [ConstellationSearch](crate::tracking_mode::ConstellationSearch) is the same search split into steps.  
+//! Each call to `step` tries at most `budget` triangles and pilots before returning [ConstellationStep::InProgress](crate::tracking_mode::ConstellationStep),
+//! the next call continues where it stopped.  
+//! This allows a watchdog to be fed or communications to be serviced during a search.  
+//! ``` ignore
+//! let mut search = ConstellationSearch::new(&stars, &mut database, &mut gen_tri, &mut gen_spec,
+//! 	&mut abort, angle_tolerance, 4..=4, &mut matches);
+//! let result = loop
+//! {
+//! 	match search.step(10)
+//! 	{
+//! 		ConstellationStep::InProgress   => feed_watchdog(),
+//! 		ConstellationStep::Done(result) => break result,
+//! 	}
+//! };
+//! ```
+//!
+//!
+//!
+//! # Diagnostics
+//! When a search fails, the `fails` count of [ConstellationResult](crate::tracking_mode::ConstellationResult) does not say why.  
+//! [Constellation::search](crate::tracking_mode::Constellation::search) reports each step of the search to a [SearchDiagnostics](crate::tracking_mode::SearchDiagnostics)
+//! (begin the triangle iterator with `begin`, `begin_uncertain` or `begin_photometric` first).  
+//! [SearchDiagnosticsRecord](crate::tracking_mode::SearchDiagnosticsRecord) counts the triangles tried, database candidates of each side,
+//! specularity rejections, pilot lookups, pilot failures, chunks visited and the ticks taken.  
+//! If most sides have no candidates, the angle tolerance or database is wrong.  
+//! If most triangles are rejected by specularity, the image is likely flipped.  
+//! If triangles are found without pilots, there are too few real stars or the database is missing stars.  
+//!
+//!
+//!
+//! # Tracking With a Known Attitude
+//! Once the attitude is known, the next frame does not need a lost-in-space search.  
+//! [Recursive](crate::tracking_mode::Recursive) projects the catalogue through the previous attitude and associates each observed star with the closest projected star.  
+//! If too few stars are associated (the attitude changed too much or the previous attitude was wrong), fall back to [Constellation::find](crate::tracking_mode::Constellation::find).  
+//!
+//!
 //!
 //! # How to Use
 //! ``` ignore
@@ -140,9 +248,11 @@
 use crate::core_include::*;
 
 use crate::tracking_mode::database::ChunkIterator;
+use crate::tracking_mode::database::Database;
 use crate::tracking_mode::database::SearchResult;
 
 use crate::util::units::Vector3;
+use crate::util::units::Quaternion;
 use crate::util::units::Equatorial;
 use crate::util::units::Match;
 use crate::util::units::Radians;
@@ -151,13 +261,28 @@ use crate::util::list::ArrayList;
 use crate::util::err::Error;
 use crate::util::aliases::Decimal;
 
+use core::cell::RefCell;
+
 pub mod kernel_iterator;
+pub mod triangle_selection;
 pub mod constellation;
+pub mod constellation_search;
+pub mod diagnostics;
+pub mod abandon_search;
 pub mod pilot_finder;
 pub mod star_pair;
 pub mod star_triangle;
 pub mod star_triangle_iterator;
 pub mod specularity;
+pub mod triangle_angles;
+pub mod scale_invariant;
+pub mod recursive;
+pub mod geometric_voting;
+pub mod grid;
+pub mod pair_consistency;
+pub mod verification;
+pub mod photometry;
+pub mod expand;
 pub mod database;
 
 
@@ -202,6 +327,7 @@ pub struct StarPyramid<T> ( pub T, pub T, pub T, pub T );
 /// const ARRAY_SIZE: usize = 100; // The number of database matches that can be stored.
 /// let finder = pilot_finder_array!(ARRAY_SIZE);
 /// ```
+/// Use `with_photometry` to skip any pilot where the brightness does not agree with the catalogue.
 pub struct PilotFinder<'a>
 {
 	/// This variable should be private but rust has weird rules making it impossible with macros...
@@ -209,7 +335,9 @@ pub struct PilotFinder<'a>
 	/// This variable should be private but rust has weird rules making it impossible with macros...
 	pub sides_b: &'a mut dyn List<SearchResult>,
 	/// This variable should be private but rust has weird rules making it impossible with macros...
-	pub sides_c: &'a mut dyn List<SearchResult>
+	pub sides_c: &'a mut dyn List<SearchResult>,
+	/// The calibration and intensity of each star if the brightness of the pilot is checked, use `with_photometry`.
+	pub photometry: Option<(Photometry, &'a dyn List<Decimal>)>,
 }
 
 /// A way of speeding up the database search algorithm.  
@@ -218,12 +346,18 @@ pub struct PilotFinder<'a>
 /// At 3 stars, there is one triangle.  
 /// At 4 stars, there is 4 triangles.  
 /// At 5 stars, there is 10... (exponential).  
-/// By only checking one at a time, it is likely that only 1 triangle needs to be generated.
-pub struct StarTriangleIterator <const N_MAX_MATCHES: usize>
+/// By only checking one at a time, it is likely that only 1 triangle needs to be generated.  
+///
+/// `N_MAX_STARS` is the number of star uncertainties that can be stored by `begin_uncertain`
+/// and the number of star intensities that can be stored by `begin_photometric`.  
+/// The default of 0 disables both, in a debug build `begin_uncertain` and `begin_photometric` panic if the stars do not fit.
+///
+/// `T` decides the order the star triangles are tried, the default is the order of the star list (see [TriangleSelection](crate::tracking_mode::TriangleSelection)).
+pub struct StarTriangleIterator <const N_MAX_MATCHES: usize, const N_MAX_STARS: usize = 0, T: TriangleSelection = KernelIterator>
 {
 	/// The iterator for comparing star pairs.
 	/// Ideal as does not priorities the first star pair found.
-	kernel: KernelIterator,
+	kernel: T,
 
 	/// The values searched by the kernel.
 	input:  StarTriangle<usize>,
@@ -263,6 +397,14 @@ pub struct StarTriangleIterator <const N_MAX_MATCHES: usize>
 	index_p_c: usize,
 
 	angle_tolerance: Radians,
+
+	/// The uncertainty of each star set by `begin_uncertain`.
+	uncertainty: ArrayList<Radians, {N_MAX_STARS}>,
+
+	/// The brightness calibration set by `begin_photometric`, None if brightness is not checked.
+	photometry: Option<Photometry>,
+	/// The intensity of each star set by `begin_photometric`.
+	intensity: ArrayList<Decimal, {N_MAX_STARS}>,
 }
 
 
@@ -313,14 +455,318 @@ pub struct KernelIterator
 
 
 
+/// Tries the star triangles from the highest to the lowest [TriangleQuality](crate::tracking_mode::TriangleQuality).  
+///
+/// The triangles are ranked on the first call to `next` after `begin`.  
+/// Only the best `N_MAX_TRIANGLES` triangles are ranked, the rest are tried afterwards in the order of [KernelIterator](crate::tracking_mode::KernelIterator).  
+/// Triangles of equal quality are tried in the same order as [KernelIterator](crate::tracking_mode::KernelIterator).  
+/// Use [new](crate::tracking_mode::TriangleSelectionRanked::new).
+pub struct TriangleSelectionRanked <Q: TriangleQuality, const N_MAX_TRIANGLES: usize>
+{
+	/// The score of each triangle.
+	pub quality: Q,
+
+	/// The best triangles sorted from the highest quality.
+	ranked : ArrayList<(Decimal, StarTriangle<usize>), N_MAX_TRIANGLES>,
+	/// The number of triangles which have been tried.
+	index  : usize,
+	/// Steps through the triangles which were not ranked once the ranked triangles are used.
+	kernel : KernelIterator,
+	/// The number of stars set by `begin`.
+	size   : usize,
+	/// False until the triangles are ranked after `begin`.
+	is_ranked: bool,
+}
+
+/// Scores a triangle by the combined intensity of the stars.  
+///
+/// A bright blob has a high signal to noise ratio and is less likely to be a false star or to have a large centroid error.  
+/// The intensity is set with [set_intensity](crate::tracking_mode::TriangleQualityBrightness::set_intensity) before each search.  
+/// Only the first `N_MAX_STARS` intensities are stored, any star without an intensity is 0.
+#[derive(Clone)]
+pub struct TriangleQualityBrightness <const N_MAX_STARS: usize>
+{
+	/// The intensity of each star.
+	intensity: ArrayList<Decimal, N_MAX_STARS>,
+}
+
+/// Scores a triangle by the magnitude of the scalar triple product of the stars.  
+///
+/// This is the value [Specularity](crate::tracking_mode::Specularity) uses, it is proportional to the area of the triangle.  
+/// A small or thin triangle is ignored by specularity and is sensitive to centroid error.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TriangleQualityArea ( );
+
+/// Scores a triangle by how few database pairs match each side.  
+///
+/// The quality is `1 / (1 + matches)` where matches is the number of pairs in the k-vector range of all 3 sides.  
+/// A triangle with few matches is likely to have only one solution.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct TriangleQualityDistinct
+{
+	/// The tolerance used to search the database (the same as the angle tolerance of the search).
+	pub tolerance: Radians,
+}
+
+
+
+/// The interior angles of a triangle.  
+///
+/// Unlike the angular separation of a star pair, the interior angles do not change when the image is scaled.  
+/// This allows stars to be identified when the focal length (field of view) is not known.  
+/// The largest angle is not stored as it is `PI - smallest - middle`.  
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TriangleAngles
+{
+	/// The smallest interior angle.
+	pub smallest: Radians,
+	/// The second smallest interior angle.
+	pub middle:   Radians,
+}
+
+
+/// Identifies stars without knowing the focal length of the camera.  
+///
+/// The interior angles of star triangles in the image are compared against a [TriangleDatabase](crate::tracking_mode::database::TriangleDatabase).  
+/// When a triangle matches, the focal length is estimated from the pixel and angular separations.  
+/// A 4th star is then projected with the estimated focal length and must match a catalogue star for the result to be accepted.  
+/// Use [find](crate::tracking_mode::ScaleInvariant::find).
+pub struct ScaleInvariant ( );
+
+
+/// Identifies stars using the attitude of the previous frame (recursive tracking).  
+///
+/// Each catalogue star within the field of view is projected onto the image with the previous attitude and the intrinsic parameters.  
+/// An observed star is associated with the closest projected star if it is within `gate` pixels.  
+/// The associated stars are then used to find the new attitude with [AttitudeDetermination](crate::attitude_determination::AttitudeDetermination).  
+/// This is far faster than a lost-in-space search, but relies on the attitude changing less than `gate` between frames.  
+/// Use [track](crate::tracking_mode::Recursive::track).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Recursive
+{
+	/// The max distance (pixels) between an observed and projected star for them to be associated.
+	pub gate      : Decimal,
+	/// The diagonal field of view of the camera, catalogue stars outside this are not projected.
+	pub fov       : Radians,
+	/// The minimum number of associated stars required for the attitude to be trusted.  
+	/// If there are less, use lost-in-space ([Constellation::find](crate::tracking_mode::Constellation::find)).
+	pub min_stars : usize,
+}
+
+
+/// Identifies stars with the geometric voting method (Kolomenkin et al. 2008).  
+///
+/// Every pair of observed stars is searched in the database, each matching catalogue pair votes for both of its stars as the identity of both observed stars.  
+/// The (observed star, catalogue star) pairs with the most votes are assigned first, each observed and catalogue star is only assigned once.  
+/// The assignments are then verified by comparing the separation of every pair of assigned stars, stars which do not agree with the others are removed.  
+/// Unlike the pyramid method, the time taken is similar for every image.  
+///
+/// `N_FOUND` is the max number of database results for a single star pair.  
+/// `N_VOTES` is the max number of different (observed star, catalogue star) votes.  
+/// Use [find](crate::tracking_mode::GeometricVoting::find).
+pub struct GeometricVoting <const N_FOUND: usize, const N_VOTES: usize>
+{
+	/// The database results of the current star pair.
+	found: ArrayList<SearchResult, N_FOUND>,
+	/// The votes (input: observed star, output: catalogue star, weight: number of votes), sorted by input then output.
+	votes: ArrayList<Match<usize>, N_VOTES>,
+}
+
+
+/// Identifies stars with the grid algorithm (Padgett and Kreutz-Delgado 1997).  
+///
+/// The pattern of each observed star is found with [GridDatabase::pattern](crate::tracking_mode::database::GridDatabase::pattern).  
+/// It is then compared against the pattern of every catalogue star sharing a cell, the catalogue star sharing the most cells is chosen.  
+/// The chosen stars are verified by comparing the separation of every pair of chosen stars.  
+///
+/// `N_CELLS` is the max number of occupied cells in the pattern of an observed star, a star with a larger pattern is skipped.  
+/// Use [find](crate::tracking_mode::Grid::find).
+pub struct Grid <const N_CELLS: usize>
+{
+	/// The pattern of the current observed star.
+	cells: ArrayList<usize, N_CELLS>,
+}
+
+
+/// Removes identified stars which do not agree with the other identified stars.  
+///
+/// Each pair of identified stars where the observed and catalogue separation are the same votes for both stars.  
+/// This is used by the methods which identify each star separately ([GeometricVoting](crate::tracking_mode::GeometricVoting) and [Grid](crate::tracking_mode::Grid)).  
+/// Use [filter](crate::tracking_mode::PairConsistency::filter).
+pub struct PairConsistency ( );
+
+
+/// Checks an attitude by comparing the whole image with the catalogue.  
+///
+/// Each observed star not used to find the attitude is rotated into the inertial frame.  
+/// If a catalogue star is within `tolerance`, the star agrees with the attitude.  
+/// A random star agrees with probability `p = (catalogue stars in the fov) * (area of tolerance) / (area of fov)`.  
+/// The false positive probability is the chance of at least this many stars agreeing at random (binomial distribution).  
+/// Use [verify](crate::tracking_mode::Verification::verify).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Verification
+{
+	/// The diagonal field of view of the camera.
+	pub fov       : Radians,
+	/// The max angle between an observed star and a catalogue star for them to agree.
+	pub tolerance : Radians,
+}
+
+
+/// Identifies every observed star using the attitude found from a few identified stars.  
+///
+/// Each observed star which is not identified is rotated into the inertial frame.  
+/// It is identified as the closest catalogue star within `tolerance` which was not identified before expanding.  
+/// If two observed stars are closest to the same catalogue star, the star with the smallest residual (the angle between the rotated observed star and the catalogue star) is used.  
+/// Use [expand](crate::tracking_mode::Expand::expand) then find the attitude again with every identified star.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Expand
+{
+	/// The max angle between a rotated observed star and a catalogue star for them to be the same star.
+	pub tolerance : Radians,
+}
+
+
+/// Converts between the catalogue magnitude and the observed intensity of a star.  
+///
+/// A star of magnitude `m` is expected to have an intensity of `zero_point * 10^(-0.4 m)`.  
+/// The `zero_point` depends on the camera and exposure, find it from the stars of an identified image.  
+/// If the `zero_point` is not known (0), only the brightness of the observed stars relative to each other is checked.  
+/// Use [consistent](crate::tracking_mode::Photometry::consistent) and [consistent_pair](crate::tracking_mode::Photometry::consistent_pair).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Photometry
+{
+	/// The intensity of a magnitude 0 star (0 if unknown).
+	pub zero_point : Decimal,
+	/// The allowed difference in magnitude between an observed star and the catalogue.
+	pub tolerance  : Decimal,
+}
+
+
+/// The output of [Verification::verify](crate::tracking_mode::Verification::verify).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct VerificationResult
+{
+	/// The number of catalogue stars in the field of view.
+	pub in_fov            : usize,
+	/// The number of observed stars which were not used to find the attitude.  
+	/// If every star was used, this is 0 and the attitude can not be verified.
+	pub tested            : usize,
+	/// The number of tested stars which agree with a catalogue star.
+	pub matched           : usize,
+	/// The probability that `matched` or more stars agree by chance (1 if nothing agreed).  
+	/// The lower this is, the more likely the attitude is correct.
+	pub false_probability : Decimal,
+}
+
+
+/// A [Constellation::find](crate::tracking_mode::Constellation::find) which can be paused and resumed.  
+///
+/// Each call to [step](crate::tracking_mode::ConstellationSearch::step) does a bounded amount of work.  
+/// The search state is kept in `gen_tri`, `database` and this struct, so nothing is allocated.  
+/// The stars, database and output can not be accessed until the search is dropped.
+pub struct ConstellationSearch <'a>
+{
+	/// The observed (image) stars.
+	stars   : &'a dyn List<Equatorial>,
+	/// The database storing reference.
+	database: &'a mut dyn ChunkIterator,
+	/// An object for generating a StarTriangle.
+	gen_tri : &'a mut dyn TriangleConstruct,
+	/// An object for checking Specularity.
+	gen_spec: &'a mut dyn SpecularityConstruct,
+	/// A way of abandoning a search if it takes too long.
+	abort   : &'a mut dyn AbandonSearch,
+	/// The how many stars are required.
+	num_stars: RangeInclusive<usize>,
+	/// The output.
+	matches : &'a mut dyn List<Match<usize>>,
+
+	/// The result so far, this is returned when the search ends.
+	result      : ConstellationResult,
+	/// True if `abort` has requested the search to stop.
+	should_abort: bool,
+	/// True if a triangle was found and pilot stars are being searched for.
+	pilot       : bool,
+	/// True if the search has ended.
+	done        : bool,
+	/// Where each step of the search is reported, set with `with_diagnostics`.
+	diagnostics : Option<&'a mut dyn SearchDiagnostics>,
+}
+
+
+/// A [SearchDiagnostics](crate::tracking_mode::SearchDiagnostics) which does nothing.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct SearchDiagnosticsNone ( );
+
+
+/// A [SearchDiagnostics](crate::tracking_mode::SearchDiagnostics) which counts each event of a search.  
+///
+/// The first `N_SAMPLES` triangles and side candidates are kept as samples.  
+/// Call `begin` (or use [Constellation::search](crate::tracking_mode::Constellation::search)) to reset the counts.
+pub struct SearchDiagnosticsRecord <C: Clock, const N_SAMPLES: usize>
+{
+	/// The clock used to time the search.
+	pub clock             : C,
+	/// The tick when the search began.
+	pub start             : u32,
+	/// The ticks between the search beginning and ending.
+	pub ticks             : u32,
+
+	/// The number of star triangles found by the database.
+	pub triangles         : usize,
+	/// The number of sides searched in the database.
+	pub sides             : usize,
+	/// The number of sides which had no candidates.
+	pub sides_empty       : usize,
+	/// The total candidates of every side searched.
+	pub candidates        : usize,
+	/// The number of triangles which were flipped.
+	pub specularity       : usize,
+	/// The number of pilot stars found.
+	pub pilots            : usize,
+	/// The number of pilot sides searched in the database.
+	pub pilot_sides       : usize,
+	/// The total candidates of every pilot side searched.
+	pub pilot_candidates  : usize,
+	/// The number of triangles which did not find enough pilot stars.
+	pub pilot_failures    : usize,
+	/// The number of database chunks visited.
+	pub chunks            : usize,
+	/// The result of the search, None until the search ends.
+	pub result            : Option<ConstellationResult>,
+
+	/// The first triangles found.
+	pub sample_triangles  : ArrayList<Match<StarTriangle<usize>>, N_SAMPLES>,
+	/// The number of candidates of the first sides searched.
+	pub sample_candidates : ArrayList<usize, N_SAMPLES>,
+}
+
+
+/// Reports the chunks visited and database candidates of each side to a [SearchDiagnostics](crate::tracking_mode::SearchDiagnostics).  
+///
+/// This wraps the database iterator given to [TriangleConstruct::next](crate::tracking_mode::TriangleConstruct::next)
+/// or [TriangleConstruct::next_pilot](crate::tracking_mode::TriangleConstruct::next_pilot) (see `new_pilot`).  
+/// As `find_close_ref_region` can not mutate, the diagnostics are in a RefCell.
+pub struct ChunkIteratorDiagnostics <'a>
+{
+	/// The database iterator being wrapped.
+	chunks     : &'a mut dyn ChunkIterator,
+	/// Where the chunks and candidates are reported.
+	diagnostics: RefCell<&'a mut dyn SearchDiagnostics>,
+	/// True if the sides are of a pilot star and are reported as `pilot_candidates`.
+	pilot      : bool,
+}
+
+
 /// A type of AbandonSearch 
 /// 
 /// where [should_abort](crate::tracking_mode::AbandonSearch::should_abort) calls are counted.    
 /// If this number exceeds the max failure value, `should_abort` will be set to true.    
 /// During a constellation search, if enough failures occur, the result is likely to be incorrect.    
 ///  
-/// You should also combine this with a timer and not just rely on failures.  
-/// A timer is not implemented as this is a no_std library.
+/// You should also combine this with a timer and not just rely on failures
+/// ([AbandonSearchClockFailure](crate::tracking_mode::AbandonSearchClockFailure)).
 pub struct AbandonSearchFailures
 {
 	/// The max number of failures allowed.
@@ -330,6 +776,43 @@ pub struct AbandonSearchFailures
 	count: usize,
 }
 
+/// A type of AbandonSearch 
+///
+/// where [should_abort](crate::tracking_mode::AbandonSearch::should_abort) is true once `timeout` ticks of the [Clock](crate::tracking_mode::Clock) have passed.  
+/// The timer starts when it is constructed.  
+/// The clock may overflow, as long as the search takes less than `u32::MAX` ticks it is still correct.
+/// # Example
+/// ```
+/// use star_tracker_lib::tracking_mode::AbandonSearchClock;
+/// use star_tracker_lib::tracking_mode::AbandonSearch;
+///
+/// fn clock ( ) -> u32 { return 0; } // e.g. clock() from the C standard library or the cycle counter.
+///
+/// let mut abort = AbandonSearchClock::new(clock as fn ( ) -> u32, 1000);
+/// assert!(!abort.should_abort());
+/// ```
+pub struct AbandonSearchClock <T: Clock>
+{
+	/// The source of time.
+	clock  : T,
+	/// The tick when the timer started.
+	start  : u32,
+	/// The number of ticks until the search is abandoned.
+	timeout: u32,
+}
+
+/// A type of AbandonSearch 
+///
+/// which is a combination of [AbandonSearchClock](crate::tracking_mode::AbandonSearchClock) and [AbandonSearchFailures](crate::tracking_mode::AbandonSearchFailures).  
+/// The search is abandoned if it takes too long or too many failures occur.
+pub struct AbandonSearchClockFailure <T: Clock>
+{
+	/// The timer.
+	pub timeout: AbandonSearchClock<T>,
+	/// The failure counter.
+	pub failure: AbandonSearchFailures,
+}
+
 
 
 //###############################################################################################//
@@ -341,7 +824,7 @@ pub struct AbandonSearchFailures
 /// The output from [find_all](crate::tracking_mode::Constellation::find_all).
 ///  
 /// This is to provide details on how reliable the result was.  
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum ConstellationResult
 {
 	/// No triangles were successfully identified.  
@@ -366,6 +849,78 @@ pub enum ConstellationResult
 	Success                   { fails: usize },
 }
 
+/// The output from [ConstellationSearch::step](crate::tracking_mode::ConstellationSearch::step).
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum ConstellationStep
+{
+	/// The budget was used before the search ended, call `step` again to continue.
+	InProgress,
+
+	/// The search has ended, this is the same as the output of [find](crate::tracking_mode::Constellation::find).
+	Done ( ConstellationResult ),
+}
+
+/// The output from [ScaleInvariant::find](crate::tracking_mode::ScaleInvariant::find).
+#[derive(Debug)]
+pub enum ScaleInvariantResult
+{
+	/// No triangles could be verified with a 4th star.  
+	/// `fails` refers to the number of image triangles which were tried and not verified.  
+	ErrorNoTriangleMatch { fails: usize },
+
+	/// The `abort: AbandonSearch` variable passed in has returned true to `should_abort`.   
+	/// `fails` refers to the number of image triangles which were tried and not verified.  
+	ErrorAborted         { fails: usize },
+
+	/// The algorithm succeeded.  
+	/// `focal_length` is the estimated focal length in the same units as the image (pixels).  
+	/// `fails` refers to the number of image triangles which were tried and not verified.  
+	Success              { fails: usize, focal_length: Decimal },
+}
+
+/// The output from [GeometricVoting::find](crate::tracking_mode::GeometricVoting::find).
+#[derive(Debug)]
+pub enum GeometricVotingResult
+{
+	/// Less than `min_stars` stars agreed with each other.  
+	/// `verified` is the number of stars which were verified.  
+	/// `dropped` is the number of votes ignored as `N_VOTES` was full.
+	ErrorInsufficientStars { verified: usize, dropped: usize },
+
+	/// At least `min_stars` stars agreed with each other.  
+	/// `verified` is the number of stars which were verified.  
+	/// `dropped` is the number of votes ignored as `N_VOTES` was full, if above 0 the correct star may have been missed.
+	Success                { verified: usize, dropped: usize },
+}
+
+/// The output from [Grid::find](crate::tracking_mode::Grid::find).
+#[derive(Debug)]
+pub enum GridResult
+{
+	/// Less than `min_stars` stars were identified and agreed with each other.  
+	/// `verified` is the number of stars which were verified.
+	ErrorInsufficientStars { verified: usize },
+
+	/// At least `min_stars` stars were identified and agreed with each other.  
+	/// `verified` is the number of stars which were verified.
+	Success                { verified: usize },
+}
+
+/// The output from [Recursive::track](crate::tracking_mode::Recursive::track).
+#[derive(Debug, Copy, Clone)]
+pub enum RecursiveResult
+{
+	/// Less than `min_stars` stars could be associated with the catalogue.  
+	/// The attitude has been lost, use lost-in-space ([Constellation::find](crate::tracking_mode::Constellation::find)).  
+	/// `found` is the number of stars which were associated.
+	ErrorInsufficientStars { found: usize },
+
+	/// Enough stars were associated.  
+	/// `attitude` is the new rotation from inertial to camera (same as [AttitudeDetermination::estimate](crate::attitude_determination::AttitudeDetermination::estimate)).  
+	/// `found` is the number of stars which were associated.
+	Success                { found: usize, attitude: Quaternion },
+}
+
 /// The return type for the star pyramid.  
 ///
 /// Either there is no match or less than 3 stars	(None)   
@@ -428,6 +983,64 @@ pub trait AbandonSearch
 
 
 
+/// A monotonic source of time for a no_std target.  
+///
+/// The units can be anything (milliseconds, clock cycles...) as long as they increase at a steady rate.  
+/// The value is allowed to overflow and restart at 0.  
+/// Any `fn ( ) -> u32` is a clock, such as `clock()` from the C standard library or the DWT cycle counter on a Cortex M.
+#[cfg_attr(test, automock)]
+pub trait Clock
+{
+	/// Returns the current number of ticks.
+	fn now ( &self ) -> u32;
+}
+
+
+
+/// Receives each step of a [ConstellationSearch](crate::tracking_mode::ConstellationSearch) to find why a search failed.  
+///
+/// Use [SearchDiagnosticsNone](crate::tracking_mode::SearchDiagnosticsNone) to ignore
+/// or [SearchDiagnosticsRecord](crate::tracking_mode::SearchDiagnosticsRecord) to count the events.
+#[cfg_attr(test, automock)]
+pub trait SearchDiagnostics
+{
+	/// The search has started.
+	fn begin ( &mut self );
+
+	/// The database iterator has moved to a new chunk.
+	fn chunk ( &mut self );
+
+	/// A side of a star triangle was searched in the database.
+	/// # Arguments
+	/// * `candidates` - The number of star pairs found.
+	fn candidates ( &mut self, candidates: usize );
+
+	/// A side between a pilot star and the triangle was searched in the database.
+	/// # Arguments
+	/// * `candidates` - The number of star pairs found.
+	fn pilot_candidates ( &mut self, candidates: usize );
+
+	/// A star triangle was found in the database.
+	/// # Arguments
+	/// * `triangle` - The observed and database triangle.
+	fn triangle ( &mut self, triangle: Match<StarTriangle<usize>> );
+
+	/// The triangle was flipped compared to the database (see [Specularity](crate::tracking_mode::Specularity)).
+	fn specularity ( &mut self );
+
+	/// A pilot star was searched for.
+	/// # Arguments
+	/// * `found` - True if a pilot was found, false if no more pilots could be found and there are not enough stars.
+	fn pilot ( &mut self, found: bool );
+
+	/// The search has ended.
+	/// # Arguments
+	/// * `result` - The result of the search.
+	fn end ( &mut self, result: ConstellationResult );
+}
+
+
+
 #[cfg_attr(test, automock)]
 /// Use to compare regions.  
 ///
@@ -447,6 +1060,7 @@ pub trait PyramidConstruct
 	/// Finds the pilot 
 	/// # Arguments
 	/// * `stars` - The stars from the image. 
+	/// * `uncertainty` - The uncertainty of each star (same index as `stars`), use an empty list to use `angle_tolerance` for every star.
 	/// * `database` - The database to lookup.
 	/// * `angle_tolerance` - How much error a star pair can have until it is not considered the same.
 	/// 	Used for searching the database.
@@ -457,6 +1071,7 @@ pub trait PyramidConstruct
 	fn find_pilot (
 				&mut self,
 				stars : &dyn List<Equatorial>,
+				uncertainty : &dyn List<Radians>,
 				database : &dyn ChunkIterator,
 				angle_tolerance: Radians,
 				input : StarTriangle<usize>,
@@ -496,6 +1111,78 @@ pub trait TriangleConstruct
 	/// * `angle_tolerance` - When searching the database, the tolerance to use.
 	/// * `stars` - The observed stars.
 	fn begin ( &mut self, angle_tolerance: Radians, stars: &dyn List<Equatorial> );
+
+	/// Prepares the StarTriangleIterator for iterating where each star has its own uncertainty.  
+	/// The tolerance of each star pair is the combined uncertainty of both stars (see [StarPair::tolerance](crate::tracking_mode::StarPair::tolerance)).  
+	/// By default, the uncertainty is ignored and `begin` is called.
+	/// # Arguments
+	/// * `angle_tolerance` - The tolerance to use for any star without an uncertainty.
+	/// * `uncertainty`     - The uncertainty of each star (same index as `stars`).
+	/// * `stars`           - The observed stars.
+	fn begin_uncertain (
+		&mut self, angle_tolerance: Radians, uncertainty: &dyn List<Radians>, stars: &dyn List<Equatorial> )
+	{
+		let _ = uncertainty;
+		self.begin(angle_tolerance, stars);
+	}
+
+	/// Prepares the StarTriangleIterator for iterating where the brightness of each star is compared to the catalogue.  
+	/// Any triangle or pilot with an implausible brightness is skipped (see [Photometry](crate::tracking_mode::Photometry)).  
+	/// The database must provide magnitudes (see [PhotometricDatabase](crate::tracking_mode::database::PhotometricDatabase)).  
+	/// By default, the brightness is ignored and `begin` is called.
+	/// # Arguments
+	/// * `angle_tolerance` - When searching the database, the tolerance to use.
+	/// * `photometry`      - The magnitude to intensity calibration.
+	/// * `intensity`       - The intensity of each star (same index as `stars`).
+	/// * `stars`           - The observed stars.
+	fn begin_photometric (
+		&mut self, angle_tolerance: Radians, photometry: Photometry,
+		intensity: &dyn List<Decimal>, stars: &dyn List<Equatorial> )
+	{
+		let _ = photometry;
+		let _ = intensity;
+		self.begin(angle_tolerance, stars);
+	}
+}
+
+
+/// Decides which star triangles a [StarTriangleIterator](crate::tracking_mode::StarTriangleIterator) tries and in what order.  
+///
+/// [KernelIterator](crate::tracking_mode::KernelIterator) uses the order of the star list.  
+/// [TriangleSelectionRanked](crate::tracking_mode::TriangleSelectionRanked) uses a [TriangleQuality](crate::tracking_mode::TriangleQuality).
+#[cfg_attr(test, automock)]
+pub trait TriangleSelection
+{
+	/// Restarts the selection for a new set of stars.
+	/// # Arguments
+	/// * `stars` - The observed stars.
+	fn begin ( &mut self, stars: &dyn List<Equatorial> );
+
+	/// Steps to the next triangle.
+	/// # Arguments
+	/// * `stars`    - The observed stars (the same as `begin`).
+	/// * `database` - The database being searched.
+	/// # Returns
+	/// False if there are no more triangles.
+	fn next ( &mut self, stars: &dyn List<Equatorial>, database: &dyn ChunkIterator ) -> bool;
+
+	/// The current triangle (indices of `stars`).
+	fn triangle ( &self ) -> StarTriangle<usize>;
+}
+
+
+/// Scores how useful a star triangle is for a [TriangleSelectionRanked](crate::tracking_mode::TriangleSelectionRanked).  
+///
+/// A higher value is tried first.
+#[cfg_attr(test, automock)]
+pub trait TriangleQuality
+{
+	/// Returns the quality of the triangle.
+	/// # Arguments
+	/// * `triangle` - The indices of the stars.
+	/// * `stars`    - The observed stars.
+	/// * `database` - The database being searched.
+	fn quality ( &self, triangle: StarTriangle<usize>, stars: &dyn List<Equatorial>, database: &dyn Database ) -> Decimal;
 }
 
 
@@ -0,0 +1,183 @@
+//! Implementation of [PairConsistency](crate::tracking_mode::PairConsistency).
+use crate::tracking_mode::PairConsistency;
+
+use crate::util::units::Equatorial;
+use crate::util::units::Radians;
+use crate::util::units::Match;
+use crate::util::list::List;
+
+impl PairConsistency
+{
+	/// Removes any identified star which does not agree with the other identified stars.
+	/// A star is kept if it agrees with at least 2 other stars and at least half as many as the most agreed star.
+	/// # Arguments
+	/// * `stars`           - The observed stars.
+	/// * `catalogue`       - Finds the catalogue star of an output index, None if it does not exist (the pair is skipped).
+	/// * `angle_tolerance` - How much error a star pair can have until it is not considered the same.
+	/// * `matches`         - The identified stars (input: index of `stars`, output: catalogue index), the weight is replaced with 1.
+	pub fn filter (
+		stars          : &dyn List<Equatorial>,
+		catalogue      : &dyn Fn(usize) -> Option<Equatorial>,
+		angle_tolerance: Radians,
+		matches        : &mut dyn List<Match<usize>>,
+	)
+	{
+		for i in 0..matches.size()
+		{
+			let current = matches.get(i);
+			let _ = matches.set(i, Match{input: current.input, output: current.output, weight: 0.0});
+		}
+
+		for a in 0..matches.size()
+		{
+			for b in (a + 1)..matches.size()
+			{
+				let mut match_a = matches.get(a);
+				let mut match_b = matches.get(b);
+				if match_a.output == match_b.output { continue; }
+
+				let (catalogue_a, catalogue_b) = match (catalogue(match_a.output), catalogue(match_b.output))
+				{
+					(Some(star_a), Some(star_b)) => (star_a, star_b),
+					_ => continue,
+				};
+
+				let observed = stars.get(match_a.input).angle_distance(stars.get(match_b.input));
+				let expected = catalogue_a.angle_distance(catalogue_b);
+				if (observed - expected).0.abs() < angle_tolerance.0
+				{
+					match_a.weight += 1.0;
+					match_b.weight += 1.0;
+					let _ = matches.set(a, match_a);
+					let _ = matches.set(b, match_b);
+				}
+			}
+		}
+
+		let mut most = 0.0;
+		for i in 0..matches.size()
+		{
+			if most < matches.get(i).weight { most = matches.get(i).weight; }
+		}
+
+		for i in (0..matches.size()).rev()
+		{
+			let current = matches.get(i);
+			if current.weight < 2.0 || current.weight < most / 2.0
+			{
+				let _ = matches.pop(i);
+			}
+			else
+			{
+				let _ = matches.set(i, Match{input: current.input, output: current.output, weight: 1.0});
+			}
+		}
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::PairConsistency;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::Degrees;
+	use crate::util::units::Radians;
+	use crate::util::units::Match;
+	use crate::util::list::List;
+	use crate::util::test::star_patch;
+
+	#[coverage(off)]
+	fn catalogue ( ) -> Vec<Equatorial>
+	{
+		return star_patch((30.0, 20.0), 5);
+	}
+
+	#[coverage(off)]
+	fn tolerance ( ) -> Radians { return Degrees(0.01).as_radians(); }
+
+	#[coverage(off)]
+	fn find ( catalogue: &Vec<Equatorial>, index: usize ) -> Option<Equatorial>
+	{
+		if index < catalogue.len() { return Some(catalogue[index]); }
+		return None;
+	}
+
+	// Each observed star is identified as the output at the same index.
+	#[coverage(off)]
+	fn identified ( outputs: &[usize] ) -> Vec<Match<usize>>
+	{
+		return outputs.iter().enumerate().map(|(i, o)| Match{input: i, output: *o, weight: 5.0}).collect();
+	}
+
+//###############################################################################################//
+//
+//										PairConsistency
+//
+// pub fn filter ( &dyn List<Equatorial>, &dyn Fn(usize) -> Option<Equatorial>, Radians, &mut dyn List<Match<usize>> )
+//
+//###############################################################################################//
+//										~ filter ~												 //
+	#[test]
+	// If every star agrees, every star is kept with a weight of 1.
+	fn test_filter_correct ( )
+	{
+		let catalogue   = catalogue();
+		let mut matches = identified(&[0, 1, 2, 3, 4]);
+		PairConsistency::filter(&catalogue, &|i| find(&catalogue, i), tolerance(), &mut matches);
+		assert_eq!(matches.size(), 5);
+		for i in 0..matches.size()
+		{
+			assert_eq!(matches[i], Match{input: i, output: i, weight: 1.0});
+		}
+	}
+
+	#[test]
+	// A wrongly identified star should be removed.
+	fn test_filter_wrong ( )
+	{
+		let catalogue   = catalogue();
+		let mut matches = identified(&[0, 1, 2, 4, 3]);
+		let stars = vec![catalogue[0], catalogue[1], catalogue[2], catalogue[4], catalogue[0]];
+		PairConsistency::filter(&stars, &|i| find(&catalogue, i), tolerance(), &mut matches);
+		assert_eq!(matches.size(), 4);
+		for i in 0..matches.size() { assert_ne!(matches[i].input, 4); }
+	}
+
+	#[test]
+	// A star which is not in the catalogue cannot agree and should be removed.
+	fn test_filter_missing ( )
+	{
+		let catalogue   = catalogue();
+		let mut matches = identified(&[0, 1, 2, 3, 10]);
+		let stars = vec![catalogue[0], catalogue[1], catalogue[2], catalogue[3], catalogue[4]];
+		PairConsistency::filter(&stars, &|i| find(&catalogue, i), tolerance(), &mut matches);
+		assert_eq!(matches.size(), 4);
+		assert_eq!(matches[3].output, 3);
+	}
+
+	#[test]
+	// Two stars can only agree with each other, at least 2 agreeing stars are required.
+	fn test_filter_insufficient ( )
+	{
+		let catalogue   = catalogue();
+		let mut matches = identified(&[0, 1]);
+		PairConsistency::filter(&catalogue, &|i| find(&catalogue, i), tolerance(), &mut matches);
+		assert_eq!(matches.size(), 0);
+
+		let mut matches = identified(&[0, 0, 0]);
+		PairConsistency::filter(&catalogue, &|i| find(&catalogue, i), tolerance(), &mut matches);
+		assert_eq!(matches.size(), 0);
+	}
+}
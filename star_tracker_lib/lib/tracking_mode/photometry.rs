@@ -0,0 +1,236 @@
+//! Implementation of [Photometry](crate::tracking_mode::Photometry).
+use crate::core_include::*;
+
+use crate::tracking_mode::Photometry;
+use crate::tracking_mode::StarPair;
+use crate::tracking_mode::database::Database;
+
+use crate::util::aliases::Decimal;
+use crate::util::list::List;
+
+impl Photometry
+{
+	/// The intensity a star of `magnitude` should have.
+	/// # Arguments
+	/// * `magnitude` - The catalogue magnitude of the star.
+	pub fn expected_intensity ( &self, magnitude: Decimal ) -> Decimal
+	{
+		return self.zero_point * (10.0 as Decimal).powf(-0.4 * magnitude);
+	}
+
+
+	/// Checks if an observed star could be a catalogue star of `magnitude`.
+	/// # Arguments
+	/// * `intensity` - The intensity of the observed star.
+	/// * `magnitude` - The magnitude of the catalogue star.
+	/// # Returns
+	/// True if the magnitudes differ by less than `tolerance` or if the zero point or intensity is unknown (0).
+	pub fn consistent ( &self, intensity: Decimal, magnitude: Decimal ) -> bool
+	{
+		if self.zero_point <= 0.0 || intensity <= 0.0 { return true; }
+		return Self::within(intensity / self.expected_intensity(magnitude), self.tolerance);
+	}
+
+
+	/// Checks if the brightness ratio of two observed stars agrees with the magnitudes of two catalogue stars.
+	/// This does not need the zero point, it stops a bright and dim observed star being matched the other way around.
+	/// # Arguments
+	/// * `intensity` - The intensity of the observed stars.
+	/// * `magnitude` - The magnitude of the catalogue stars (same order as `intensity`).
+	/// # Returns
+	/// True if the difference in magnitude agrees within `tolerance` of each star or if an intensity is unknown (0).
+	pub fn consistent_pair ( &self, intensity: StarPair<Decimal>, magnitude: StarPair<Decimal> ) -> bool
+	{
+		if intensity.0 <= 0.0 || intensity.1 <= 0.0 { return true; }
+		let observed = intensity.0 / intensity.1;
+		let expected = (10.0 as Decimal).powf(-0.4 * (magnitude.0 - magnitude.1));
+		return Self::within(observed / expected, 2.0 * self.tolerance);
+	}
+
+
+	/// Checks if the brightness of the observed stars agrees with the catalogue stars they are matched to.
+	/// Stars without an intensity or magnitude are not checked.
+	/// # Arguments
+	/// * `intensity` - The intensity of each observed star.
+	/// * `observed`  - The index of each observed star (index of `intensity`).
+	/// * `found`     - The index of the catalogue star each observed star is matched to.
+	/// * `database`  - The database containing the magnitudes.
+	/// # Returns
+	/// True if the brightness of every star and every pair agrees.
+	pub fn consistent_stars (
+		&self,
+		intensity: &dyn List<Decimal>,
+		observed : &[usize],
+		found    : &[usize],
+		database : &dyn Database ) -> bool
+	{
+		for i in 0..observed.len()
+		{
+			if intensity.size() <= observed[i] { continue; }
+			let magnitude_i = match database.get_magnitude(found[i]) { Some(m) => m, None => continue };
+			let intensity_i = intensity.get(observed[i]);
+			if !self.consistent(intensity_i, magnitude_i) { return false; }
+
+			for j in (i + 1)..observed.len()
+			{
+				if intensity.size() <= observed[j] { continue; }
+				let magnitude_j = match database.get_magnitude(found[j]) { Some(m) => m, None => continue };
+				let intensity_j = intensity.get(observed[j]);
+				if !self.consistent_pair(StarPair(intensity_i, intensity_j), StarPair(magnitude_i, magnitude_j))
+				{
+					return false;
+				}
+			}
+		}
+		return true;
+	}
+
+
+	/// True if `ratio` is within `tolerance` magnitudes of 1.
+	fn within ( ratio: Decimal, tolerance: Decimal ) -> bool
+	{
+		let limit = (10.0 as Decimal).powf(0.4 * tolerance);
+		return 1.0 / limit <= ratio && ratio <= limit;
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::Photometry;
+	use crate::tracking_mode::StarPair;
+	use crate::tracking_mode::database::MockDatabase;
+
+	use crate::util::test::TestEqual;
+
+
+//###############################################################################################//
+//
+//										Photometry
+//
+// pub fn expected_intensity ( &self, Decimal ) -> Decimal
+// pub fn consistent         ( &self, Decimal, Decimal ) -> bool
+// pub fn consistent_pair    ( &self, StarPair<Decimal>, StarPair<Decimal> ) -> bool
+// pub fn consistent_stars   ( &self, &dyn List<Decimal>, &[usize], &[usize], &dyn Database ) -> bool
+// fn within                 ( Decimal, Decimal ) -> bool
+//
+//###############################################################################################//
+//										~ expected_intensity ~									 //
+	#[test]
+	// Each magnitude is 10^0.4 (about 2.512) times dimmer.
+	fn test_expected_intensity ( )
+	{
+		let photometry = Photometry{zero_point: 1000.0, tolerance: 0.5};
+		photometry.expected_intensity(0.0).assert_close(&1000.0, 0.0001);
+		photometry.expected_intensity(5.0).assert_close(&10.0, 0.0001);
+		photometry.expected_intensity(1.0).assert_close(&398.107, 0.001);
+	}
+
+//										~ consistent ~											 //
+	#[test]
+	// The intensity must be within tolerance magnitudes of the expected intensity.
+	fn test_consistent ( )
+	{
+		let photometry = Photometry{zero_point: 1000.0, tolerance: 0.5};
+		assert!(photometry.consistent(10.0, 5.0));
+		assert!(photometry.consistent(14.0, 5.0));  // 0.37 magnitudes brighter.
+		assert!(photometry.consistent(7.0,  5.0));  // 0.39 magnitudes dimmer.
+		assert!(!photometry.consistent(17.0, 5.0)); // 0.58 magnitudes brighter.
+		assert!(!photometry.consistent(6.0,  5.0)); // 0.55 magnitudes dimmer.
+		assert!(!photometry.consistent(1000.0, 6.0));
+	}
+
+	#[test]
+	// If the zero point or intensity is unknown, anything is consistent.
+	fn test_consistent_unknown ( )
+	{
+		let photometry = Photometry{zero_point: 0.0, tolerance: 0.5};
+		assert!(photometry.consistent(1000.0, 6.0));
+
+		let photometry = Photometry{zero_point: 1000.0, tolerance: 0.5};
+		assert!(photometry.consistent(0.0, 6.0));
+	}
+
+//										~ consistent_pair ~										 //
+	#[test]
+	// The ratio of intensities must agree with the difference in magnitude.
+	fn test_consistent_pair ( )
+	{
+		let photometry = Photometry{zero_point: 0.0, tolerance: 0.25};
+		assert!(photometry.consistent_pair(StarPair(100.0, 10.0), StarPair(1.0, 3.5)));
+		assert!(photometry.consistent_pair(StarPair(10.0, 100.0), StarPair(3.5, 1.0)));
+		assert!(photometry.consistent_pair(StarPair(100.0, 14.0), StarPair(1.0, 3.5)));
+		assert!(!photometry.consistent_pair(StarPair(100.0, 20.0), StarPair(1.0, 3.5)));
+	}
+
+	#[test]
+	// A bright star and a dim star should not match a dim star and a bright star.
+	fn test_consistent_pair_order ( )
+	{
+		let photometry = Photometry{zero_point: 0.0, tolerance: 0.5};
+		assert!(photometry.consistent_pair(StarPair(100.0, 10.0), StarPair(2.0, 4.5)));
+		assert!(!photometry.consistent_pair(StarPair(100.0, 10.0), StarPair(4.5, 2.0)));
+	}
+
+	#[test]
+	// If an intensity is unknown, the pair is consistent.
+	fn test_consistent_pair_unknown ( )
+	{
+		let photometry = Photometry{zero_point: 0.0, tolerance: 0.25};
+		assert!(photometry.consistent_pair(StarPair(0.0, 10.0), StarPair(0.0, 6.0)));
+		assert!(photometry.consistent_pair(StarPair(10.0, 0.0), StarPair(0.0, 6.0)));
+	}
+
+//										~ consistent_stars ~									 //
+	#[test]
+	// Every star and every pair must agree with the catalogue.
+	fn test_consistent_stars ( )
+	{
+		let photometry = Photometry{zero_point: 10000.0, tolerance: 0.5};
+		let intensity  = vec![100.0, 10.0, 1.0];
+		let magnitude  = [5.0, 7.5, 10.0, 0.0];
+		let mut database = MockDatabase::new();
+		database.expect_get_magnitude().returning(move |i| magnitude.get(i).copied());
+
+		assert!(photometry.consistent_stars(&intensity, &[0, 1, 2], &[0, 1, 2], &database));
+		assert!(!photometry.consistent_stars(&intensity, &[0, 1, 2], &[1, 0, 2], &database));
+		assert!(!photometry.consistent_stars(&intensity, &[0, 1, 2], &[0, 1, 3], &database));
+	}
+
+	#[test]
+	// Stars without an intensity or a magnitude are not checked.
+	fn test_consistent_stars_unknown ( )
+	{
+		let photometry = Photometry{zero_point: 10000.0, tolerance: 0.5};
+		let intensity  = vec![100.0, 10.0];
+		let magnitude  = [5.0, 7.5];
+		let mut database = MockDatabase::new();
+		database.expect_get_magnitude().returning(move |i| magnitude.get(i).copied());
+
+		assert!(photometry.consistent_stars(&intensity, &[0, 1, 2], &[0, 1, 0], &database));
+		assert!(photometry.consistent_stars(&intensity, &[0, 1], &[0, 5], &database));
+	}
+
+//										~ within ~												 //
+	#[test]
+	// A tolerance of 1 magnitude is a ratio of about 2.512.
+	fn test_within ( )
+	{
+		assert!(Photometry::within(1.0,  0.0));
+		assert!(Photometry::within(2.5,  1.0));
+		assert!(Photometry::within(0.4,  1.0));
+		assert!(!Photometry::within(2.6, 1.0));
+		assert!(!Photometry::within(0.39, 1.0));
+	}
+}
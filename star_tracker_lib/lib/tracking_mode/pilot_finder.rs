@@ -4,6 +4,7 @@ use core_include::*;
 use super::PyramidConstruct;
 use super::StarTriangle;
 use super::PilotFinder;
+use super::Photometry;
 use super::StarPair;
 
 use crate::tracking_mode::database::ChunkIterator;
@@ -12,6 +13,7 @@ use crate::tracking_mode::database::SearchResult;
 use crate::util::units::Equatorial;
 use crate::util::units::Radians;
 use crate::util::units::Match;
+use crate::util::aliases::Decimal;
 use crate::util::list::List;
 use crate::util::err::Errors;
 use crate::util::err::Error;
@@ -24,7 +26,7 @@ use crate::util::err::Error;
 #[macro_export]
 macro_rules! pilot_finder_vec {
 	() => {
-		PilotFinder{sides_a: &mut Vec::new(), sides_b: &mut Vec::new(), sides_c: &mut Vec::new()}
+		PilotFinder{sides_a: &mut Vec::new(), sides_b: &mut Vec::new(), sides_c: &mut Vec::new(), photometry: None}
 	}
 }
 
@@ -36,15 +38,19 @@ macro_rules! pilot_finder_array {
 		PilotFinder{
 			sides_a: &mut ArrayList::<SearchResult, $length>::new(), 
 			sides_b: &mut ArrayList::<SearchResult, $length>::new(), 
-			sides_c: &mut ArrayList::<SearchResult, $length>::new()}
+			sides_c: &mut ArrayList::<SearchResult, $length>::new(),
+			photometry: None}
 	}
 }
 
 impl<'a> PyramidConstruct for PilotFinder<'a>
 {
-	/// Finds the pilot 
+	/// Finds the pilot.
+	/// The tolerance of each side is the combined uncertainty of both stars (see [StarPair::tolerance](crate::tracking_mode::StarPair::tolerance)).
+	/// If `with_photometry` was used, any pilot where the brightness of the 4 stars does not agree with the catalogue is skipped.
 	/// # Arguments
 	/// * `stars` - The stars from the image. 
+	/// * `uncertainty` - The uncertainty of each star (same index as `stars`), any star without an uncertainty uses `angle_tolerance`.
 	/// * `database` - The database to lookup.
 	/// * `angle_tolerance` - How much error a star pair can have until it is not considered the same.
 	/// 	Used for searching the database.
@@ -55,6 +61,7 @@ impl<'a> PyramidConstruct for PilotFinder<'a>
 	fn find_pilot (	
 				&mut self,
 				stars          : &dyn List<Equatorial>, 
+				uncertainty    : &dyn List<Radians>,
 				database       : &dyn ChunkIterator, 
 				angle_tolerance: Radians,
 				input          : StarTriangle<usize>,
@@ -69,17 +76,28 @@ impl<'a> PyramidConstruct for PilotFinder<'a>
 				let side_a = stars.get(input.0).angle_distance(star);
 				let side_b = stars.get(input.1).angle_distance(star);
 				let side_c = stars.get(input.2).angle_distance(star);
+
+				let tolerance_a = StarPair(input.0, ii).tolerance(uncertainty, angle_tolerance);
+				let tolerance_b = StarPair(input.1, ii).tolerance(uncertainty, angle_tolerance);
+				let tolerance_c = StarPair(input.2, ii).tolerance(uncertainty, angle_tolerance);
 			
 				self.sides_a.clear();
 				self.sides_b.clear();
 				self.sides_c.clear();
 
 				// Find the side angles to the pilot, if same for each star, it is the pilot.
-				database.find_close_ref_region(side_a, angle_tolerance, self.sides_a);
-				database.find_close_ref_region(side_b, angle_tolerance, self.sides_b);
-				database.find_close_ref_region(side_c, angle_tolerance, self.sides_c);
+				database.find_close_ref_region(side_a, tolerance_a, self.sides_a);
+				database.find_close_ref_region(side_b, tolerance_b, self.sides_b);
+				database.find_close_ref_region(side_c, tolerance_c, self.sides_c);
 
-				let pilot = self.confirm_pilot(output);
+				let photometry = self.photometry;
+				let accept = | pilot: usize | match photometry
+				{
+					Some((photometry, intensity)) => photometry.consistent_stars(intensity,
+						&[input.0, input.1, input.2, ii], &[output.0, output.1, output.2, pilot], database.get_database()),
+					None => true,
+				};
+				let pilot = self.confirm_pilot(output, &accept);
 
 				if pilot.is_some()
 				{
@@ -96,15 +114,28 @@ impl<'a> PyramidConstruct for PilotFinder<'a>
 
 impl<'a> PilotFinder<'a>
 {
+	/// Checks the brightness of every pilot with the catalogue magnitudes.
+	/// The database must provide magnitudes ([PhotometricDatabase](crate::tracking_mode::database::PhotometricDatabase)).
+	/// # Arguments
+	/// * `photometry` - The magnitude to intensity calibration.
+	/// * `intensity`  - The intensity of each star (same index as the stars given to `find_pilot`).
+	pub fn with_photometry ( mut self, photometry: Photometry, intensity: &'a dyn List<Decimal> ) -> Self
+	{
+		self.photometry = Some((photometry, intensity));
+		return self;
+	}
+
+
 /// Finds the index of the pilot and confirms it as valid.
 /// # Arguments
 /// * `output` - The confirmed triangle of the database.
 /// * `pair_a` - The found stars matching the distance from output.0 to pilot (consumed).
 /// * `pair_b` - The found stars matching the distance from output.1 to pilot.
 /// * `pair_c` - The found stars matching the distance from output.2 to pilot.
+/// * `accept` - Returns false if the pilot (catalogue index) should be skipped.
 /// # Returns
 /// The database catalogue index to the pilot or none if pilot could not be confirmed.
-fn confirm_pilot ( &mut self, output: StarTriangle<usize> ) -> Option<usize>
+fn confirm_pilot ( &mut self, output: StarTriangle<usize>, accept: &dyn Fn(usize) -> bool ) -> Option<usize>
 {
 	// After removing, the remaining star is the pilot and hopefully the star 0.
 	self.sides_a.remove_diff(self.sides_b, SearchResult::has_same_star);
@@ -127,7 +158,7 @@ fn confirm_pilot ( &mut self, output: StarTriangle<usize> ) -> Option<usize>
 			let connected_b = SearchResult::index_of_pair(b_pilot, self.sides_b).is_some();
 			let connected_c = SearchResult::index_of_pair(c_pilot, self.sides_c).is_some();
 			
-			if connected_a && connected_b && connected_c
+			if connected_a && connected_b && connected_c && accept(pilot)
 			{
 				return Option::Some(pilot);
 			}
@@ -163,15 +194,22 @@ mod test
 	use crate::tracking_mode::StarTriangle;
 	use crate::tracking_mode::StarPair;
 	use crate::tracking_mode::PilotFinder;
+	use crate::tracking_mode::Photometry;
 	
 	use crate::tracking_mode::database::MockDatabase;
+	use crate::tracking_mode::database::PyramidDatabase;
+	use crate::tracking_mode::database::PhotometricDatabase;
+	use crate::tracking_mode::database::KVector;
 	use crate::tracking_mode::database::ChunkIteratorNone;
 	use crate::tracking_mode::database::MockChunkIterator;
 	use crate::tracking_mode::database::SearchResult;
 	
 	use crate::util::units::Equatorial;
+	use crate::util::units::Degrees;
 	use crate::util::units::Radians;
 	use crate::util::units::Match;
+	use crate::util::test::star_patch;
+	use crate::util::test::star_pairs;
 	
 	
 	// Creates a search result with error 0.
@@ -185,7 +223,7 @@ mod test
 
 	macro_rules! pilot_finder_vec_test {
 		( $vec1:expr, $vec2:expr, $vec3:expr ) => {
-		PilotFinder{sides_a: &mut $vec1, sides_b: &mut $vec2, sides_c: &mut $vec3}
+		PilotFinder{sides_a: &mut $vec1, sides_b: &mut $vec2, sides_c: &mut $vec3, photometry: None}
 		}
 	}
 	
@@ -197,7 +235,8 @@ mod test
 // // Note, the search result error is not used in this function.
 // fn confirm_pilot ( 
 // 			&mut self, 
-//			&output ) -> Option<usize>
+//			&output,
+//			&dyn Fn(usize) -> bool ) -> Option<usize>
 //
 //###############################################################################################//
 
@@ -212,7 +251,7 @@ mod test
 		let mut stars_c : Vec<SearchResult> = vec![sr(12, 13), sr(14, 15), sr(16, 17)];
 		let mut finder = pilot_finder_vec_test!(stars_a, stars_b, stars_c);
 		
-		assert_eq!(finder.confirm_pilot(output), None);
+		assert_eq!(finder.confirm_pilot(output, &|_| true), None);
 		assert_eq!(stars_a.size(), 0); // Should have had everything removed.
 	}
 	
@@ -228,7 +267,7 @@ mod test
 		let mut stars_c : Vec<SearchResult> = vec![sr(12, 13), sr(16, 1)];
 		let mut finder = pilot_finder_vec_test!(stars_a, stars_b, stars_c);
 		
-		assert_eq!(finder.confirm_pilot(output), None);
+		assert_eq!(finder.confirm_pilot(output, &|_| true), None);
 		assert_eq!(stars_a.size(), 1); // Any without the pilot must be removed, only 1 is valid.
 	}
 	
@@ -244,7 +283,7 @@ mod test
 		let mut stars_c  : Vec<SearchResult> = vec![sr(12, 13), sr(16, 1)];
 		let mut finder = pilot_finder_vec_test!(stars_a, stars_b, stars_c);
 		
-		assert_eq!(finder.confirm_pilot(output), None);
+		assert_eq!(finder.confirm_pilot(output, &|_| true), None);
 		assert_eq!(stars_a.size(), 1); // Any without the pilot must be removed, only 1 is valid.
 	}
 	
@@ -259,7 +298,7 @@ mod test
 		let mut stars_c : Vec<SearchResult> = vec![sr(12, 13), sr(16, 1)];
 		let mut finder = pilot_finder_vec_test!(stars_a, stars_b, stars_c);
 		
-		assert_eq!(finder.confirm_pilot(output), None);
+		assert_eq!(finder.confirm_pilot(output, &|_| true), None);
 		assert_eq!(stars_a.size(), 1); // Any without the pilot must be removed, only 1 is valid.
 	}
 	
@@ -274,7 +313,7 @@ mod test
 		let mut stars_c : Vec<SearchResult> = vec![sr(12, 13), sr(16, 1)];
 		let mut finder = pilot_finder_vec_test!(stars_a, stars_b, stars_c);
 		
-		assert_eq!(finder.confirm_pilot(output), None);
+		assert_eq!(finder.confirm_pilot(output, &|_| true), None);
 		assert_eq!(stars_a.size(), 1); // Any without the pilot must be removed, only 1 is valid.
 	}
 	
@@ -290,7 +329,7 @@ mod test
 		let mut stars_c : Vec<SearchResult> = vec![sr(16, 0)];
 		let mut finder = pilot_finder_vec_test!(stars_a, stars_b, stars_c);
 		
-		assert_eq!(finder.confirm_pilot(output), None);
+		assert_eq!(finder.confirm_pilot(output, &|_| true), None);
 		assert_eq!(stars_a.size(), 1); // Any without the pilot must be removed, only 1 is valid.
 	}
 
@@ -311,7 +350,7 @@ mod test
 		let mut stars_c     : Vec<SearchResult> = vec![sr(12, 13), sr(16, 1)];
 		let mut finder = pilot_finder_vec_test!(stars_a, stars_b, stars_c);
 		
-		let output = finder.confirm_pilot(output);
+		let output = finder.confirm_pilot(output, &|_| true);
 		assert_eq!(output, Some(1));
 		assert_eq!(stars_a.size(), 1); // Any without the pilot must be removed, only 1 is valid.
 	}
@@ -329,10 +368,24 @@ mod test
 		let mut finder = pilot_finder_vec_test!(stars_a, stars_b, stars_c);
 		
 		// There are 2 potential pilots, the program will take the first (1).
-		let output = finder.confirm_pilot(output);
+		let output = finder.confirm_pilot(output, &|_| true);
 		assert_eq!(output, Some(1));
 		assert_eq!(stars_a.size(), 2); // Any without the pilot must be removed, only 1 is valid.
 	}
+
+	#[test]
+	// A pilot which is not accepted should be skipped.
+	fn test_confirm_pilot_accept ( )
+	{
+		let output = StarTriangle(0, 6, 16);
+		let mut stars_a : Vec<SearchResult> = vec![sr(0, 1),  sr(0, 2)];
+		let mut stars_b : Vec<SearchResult> = vec![sr(6, 1),  sr(6, 2)];
+		let mut stars_c : Vec<SearchResult> = vec![sr(16, 2), sr(16, 1)];
+		let mut finder = pilot_finder_vec_test!(stars_a, stars_b, stars_c);
+
+		assert_eq!(finder.confirm_pilot(output, &|pilot| pilot != 1), Some(2));
+		assert_eq!(finder.confirm_pilot(output, &|_| false), None);
+	}
 	
 	
 	
@@ -351,8 +404,9 @@ mod test
 //
 // fn find_pilot (	
 // 			&mut self,
-// 			stars    : &dyn List<Equatorial>, 
-// 			database : &dyn ChunkIterator, 
+// 			stars       : &dyn List<Equatorial>, 
+// 			uncertainty : &dyn List<Radians>,
+// 			database    : &dyn ChunkIterator, 
 // 			input    : StarTriangle<usize>,
 // 			output   : StarTriangle<usize> ) -> Error<Match<usize>>
 //
@@ -374,13 +428,13 @@ mod test
 		let angle_tolerance = Radians(0.0001);
 
 		let mut finder = pilot_finder_vec!();
-		assert!(finder.find_pilot(&stars, &chunk, angle_tolerance, triangle, triangle).is_err());
+		assert!(finder.find_pilot(&stars, &Vec::new(), &chunk, angle_tolerance, triangle, triangle).is_err());
 		stars.push(star);
 		
-		assert!(finder.find_pilot(&stars, &chunk, angle_tolerance, triangle, triangle).is_err());
+		assert!(finder.find_pilot(&stars, &Vec::new(), &chunk, angle_tolerance, triangle, triangle).is_err());
 		stars.push(star);
 		
-		assert!(finder.find_pilot(&stars, &chunk, angle_tolerance, triangle, triangle).is_err());
+		assert!(finder.find_pilot(&stars, &Vec::new(), &chunk, angle_tolerance, triangle, triangle).is_err());
 		stars.push(star);
 	}
 	
@@ -410,7 +464,7 @@ mod test
 		let mut finder = pilot_finder_vec!();
 		// This should be called 2 * 3 times as there are 3 calls per loop and 2 valid entries.
 		
-		assert! ( finder.find_pilot(&stars, &chunk, angle_tolerance, triangle, triangle).is_err() );
+		assert! ( finder.find_pilot(&stars, &Vec::new(), &chunk, angle_tolerance, triangle, triangle).is_err() );
 	}
 	
 	
@@ -435,8 +489,95 @@ mod test
 			{found.push_back(SearchResult{result: OUTPUT[index], error: 0.0}); index+=1;});
 			
 			
-		assert_eq! (finder.find_pilot(&stars, &chunk, angle_tolerance, triangle, triangle)
+		assert_eq! (finder.find_pilot(&stars, &Vec::new(), &chunk, angle_tolerance, triangle, triangle)
 				.expect("Err output"), 
 			Match::<usize>{input: 2, output: 4, weight: 1.0} );
 	}
-}
\ No newline at end of file
+
+
+	#[test]
+	// Each side should be searched with the combined uncertainty of the triangle star and the pilot.
+	fn test_find_pilot_uncertain ( )
+	{
+		let star = Equatorial{ra: Radians(0.0), dec: Radians(0.0)};
+		let pilot = Equatorial{ra: Radians(1.0), dec: Radians(1.0)};
+		let stars : Vec<Equatorial> = vec![star, star, pilot, star];
+		let uncertainty = vec![Radians(0.3), Radians(0.0), Radians(0.4), Radians(1.2)];
+		let triangle = StarTriangle(0, 1, 3);
+
+		let mut finder = pilot_finder_vec!();
+		static OUTPUT: [StarPair<usize>; 3] = [StarPair(0, 4), StarPair(1, 4), StarPair(3, 4)];
+		static TOLERANCE: [Radians; 3] = [Radians(0.5), Radians(0.4), Radians(1.2649110640673518)];
+		let mut index = 0;
+
+		let mut chunk  = MockChunkIterator::new();
+		chunk.expect_find_close_ref_region().times(1 * 3)
+		.returning(move |_, tolerance, found|
+			{
+				assert_eq!(tolerance, TOLERANCE[index]);
+				found.push_back(SearchResult{result: OUTPUT[index], error: 0.0});
+				index+=1;
+			});
+
+		assert_eq! (finder.find_pilot(&stars, &uncertainty, &chunk, Radians(10.0), triangle, triangle)
+				.expect("Err output"),
+			Match::<usize>{input: 2, output: 4, weight: 1.0} );
+	}
+
+
+//###############################################################################################//
+//
+//										With Photometry
+//
+// pub fn with_photometry ( self, Photometry, &'a dyn List<Decimal> ) -> Self
+//
+//###############################################################################################//
+
+	#[test]
+	// The calibration and intensity should be stored.
+	fn test_with_photometry ( )
+	{
+		let intensity  = vec![1.0, 2.0];
+		let photometry = Photometry{zero_point: 10.0, tolerance: 0.5};
+		let finder = pilot_finder_vec!();
+		let finder = finder.with_photometry(photometry, &intensity);
+		let (stored, stored_intensity) = finder.photometry.expect("Photometry should be set.");
+		assert_eq!(stored, photometry);
+		assert_eq!(stored_intensity.get(1), 2.0);
+	}
+
+	#[test]
+	// With a real database, a pilot with the wrong brightness should not be found.
+	fn test_find_pilot_photometric ( )
+	{
+		let catalogue = star_patch((30.0, 20.0), 4);
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let pyramid   = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let magnitude = vec![3.0, 4.0, 5.0, 6.0];
+		let database  = PhotometricDatabase{database: &pyramid, magnitude: &magnitude};
+		let chunk     = ChunkIteratorNone::new(&database);
+
+		let triangle   = StarTriangle(0, 1, 2);
+		let photometry = Photometry{zero_point: 0.0, tolerance: 0.5};
+		let tolerance  = Degrees(0.01).as_radians();
+		let expect     = Match{input: 3, output: 3, weight: 1.0};
+
+		// The pilot is 1 magnitude dimmer than star 2.
+		let intensity = vec![1000.0, 398.0, 158.0, 63.0];
+		let finder = pilot_finder_vec!();
+		let mut finder = finder.with_photometry(photometry, &intensity);
+		assert_eq!(finder.find_pilot(&catalogue, &Vec::new(), &chunk, tolerance, triangle, triangle), Ok(expect));
+
+		// The pilot is the brightest star.
+		let intensity = vec![1000.0, 398.0, 158.0, 5000.0];
+		let finder = pilot_finder_vec!();
+		let mut finder = finder.with_photometry(photometry, &intensity);
+		assert!(finder.find_pilot(&catalogue, &Vec::new(), &chunk, tolerance, triangle, triangle).is_err());
+
+		// Without photometry, the brightness is ignored.
+		let mut finder = pilot_finder_vec!();
+		assert_eq!(finder.find_pilot(&catalogue, &Vec::new(), &chunk, tolerance, triangle, triangle), Ok(expect));
+	}
+}
@@ -0,0 +1,412 @@
+//! Implementation of [Recursive](crate::tracking_mode::Recursive).
+use crate::core_include::*;
+
+use crate::tracking_mode::RecursiveResult;
+use crate::tracking_mode::Recursive;
+
+use crate::attitude_determination::AttitudeDetermination;
+
+use crate::projection::IntrinsicParameters;
+use crate::projection::SpaceCamera;
+use crate::projection::SpaceImage;
+
+use crate::util::linear_lookup::LinearLookup;
+use crate::util::units::Equatorial;
+use crate::util::units::Quaternion;
+use crate::util::units::Vector2;
+use crate::util::units::Vector3;
+use crate::util::units::Match;
+use crate::util::list::List;
+
+impl Recursive
+{
+	/// Identifies the stars using the previous attitude and finds the new attitude.
+	///
+	/// # Arguments
+	/// * `stars`     - The observed stars on the image (pixels).
+	/// * `catalogue` - The stars in the sky (the catalogue of the database).
+	/// * `intrinsic` - The intrinsic parameters used to project the stars onto the image.
+	/// * `attitude`  - The rotation from inertial to camera in the previous frame.
+	/// * `matches`   - The output of the associated stars (input: index of `stars`, output: index of `catalogue`).
+	/// * `vectors`   - A buffer for the attitude determination, it must be able to fit `matches`.
+	///
+	/// # Returns
+	/// Success with the new attitude if at least `min_stars` stars were associated.
+	/// ErrorInsufficientStars if lost-in-space is required.
+	pub fn track <T: AttitudeDetermination> (
+		&self,
+		stars    : &dyn List<Vector2>,
+		catalogue: &dyn LinearLookup<Equatorial>,
+		intrinsic: &IntrinsicParameters,
+		attitude : Quaternion,
+		matches  : &mut dyn List<Match<usize>>,
+		vectors  : &mut dyn List<Match<Vector3>>,
+	) -> RecursiveResult
+	{
+		self.associate(stars, catalogue, intrinsic, attitude, matches);
+		let found = matches.size();
+		if found < self.min_stars || found == 0
+		{
+			return RecursiveResult::ErrorInsufficientStars{found: found};
+		}
+
+		Self::to_vectors(stars, catalogue, intrinsic, matches, vectors);
+		return RecursiveResult::Success{found: found, attitude: T::estimate(vectors, None)};
+	}
+
+
+
+	/// Associates the observed stars with the catalogue stars projected through `attitude`.
+	/// Each observed star is matched with the closest projected star within `gate`.
+	/// If the list is full, the remaining stars are ignored.
+	///
+	/// # Arguments
+	/// * `stars`     - The observed stars on the image (pixels).
+	/// * `catalogue` - The stars in the sky (the catalogue of the database).
+	/// * `intrinsic` - The intrinsic parameters used to project the stars onto the image.
+	/// * `attitude`  - The rotation from inertial to camera.
+	/// * `matches`   - The output (input: index of `stars`, output: index of `catalogue`, weight: 1).
+	pub fn associate (
+		&self,
+		stars    : &dyn List<Vector2>,
+		catalogue: &dyn LinearLookup<Equatorial>,
+		intrinsic: &IntrinsicParameters,
+		attitude : Quaternion,
+		matches  : &mut dyn List<Match<usize>>,
+	)
+	{
+		matches.clear();
+		let min_z = (self.fov / 2.0).cos();
+
+		for i in 0..catalogue.size()
+		{
+			let point = attitude.rotate_point(catalogue.get(i).to_vector3());
+			if point.z <= 0.0 || point.z < min_z
+			{
+				continue;
+			}
+			let projected = intrinsic.to_image(SpaceCamera(point)).0;
+
+			// The closest observed star within the gate.
+			let mut closest : Option<usize> = None;
+			let mut distance = self.gate;
+			for j in 0..stars.size()
+			{
+				let dist = (stars.get(j) - projected).magnitude();
+				if dist <= distance
+				{
+					closest  = Some(j);
+					distance = dist;
+				}
+			}
+
+			// While searching, the weight is the distance so the closest catalogue star is kept.
+			if let Some(j) = closest
+			{
+				let mut exists = false;
+				for k in 0..matches.size()
+				{
+					let current = matches.get(k);
+					if current.input == j
+					{
+						exists = true;
+						if distance < current.weight
+						{
+							let _ = matches.set(k, Match{input: j, output: i, weight: distance});
+						}
+						break;
+					}
+				}
+				if !exists && !matches.is_full()
+				{
+					let _ = matches.push_back(Match{input: j, output: i, weight: distance});
+				}
+			}
+		}
+
+		for k in 0..matches.size()
+		{
+			let current = matches.get(k);
+			let _ = matches.set(k, Match{input: current.input, output: current.output, weight: 1.0});
+		}
+	}
+
+
+
+	/// Converts the matched indices into unit vectors for [AttitudeDetermination](crate::attitude_determination::AttitudeDetermination).
+	/// This can also be used on the output of a lost-in-space search to find the attitude relative to the camera.
+	///
+	/// # Arguments
+	/// * `stars`     - The observed stars on the image (pixels).
+	/// * `catalogue` - The stars in the sky (the catalogue of the database).
+	/// * `intrinsic` - The intrinsic parameters used to project the stars from the image.
+	/// * `matches`   - The matched indices (input: index of `stars`, output: index of `catalogue`).
+	/// * `vectors`   - The output (input: camera frame, output: inertial frame), cleared before use.
+	pub fn to_vectors (
+		stars    : &dyn List<Vector2>,
+		catalogue: &dyn LinearLookup<Equatorial>,
+		intrinsic: &IntrinsicParameters,
+		matches  : &dyn List<Match<usize>>,
+		vectors  : &mut dyn List<Match<Vector3>>,
+	)
+	{
+		vectors.clear();
+		for i in 0..matches.size()
+		{
+			let matched = matches.get(i);
+			let camera  = intrinsic.from_image(SpaceImage(stars.get(matched.input))).0;
+			let input   = camera.normalized().expect("z is always 1.");
+			let output  = catalogue.get(matched.output).to_vector3();
+			let _ = vectors.push_back(Match{input: input, output: output, weight: matched.weight});
+		}
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::RecursiveResult;
+	use crate::tracking_mode::Recursive;
+
+	use crate::attitude_determination::Quest;
+
+	use crate::projection::IntrinsicParameters;
+	use crate::projection::SpaceCamera;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::Quaternion;
+	use crate::util::units::AngleAxis;
+	use crate::util::units::Degrees;
+	use crate::util::units::Vector2;
+	use crate::util::units::Vector3;
+	use crate::util::units::Match;
+	use crate::util::list::ArrayList;
+	use crate::util::list::List;
+	use crate::util::test::TestEqual;
+
+	#[coverage(off)]
+	fn intrinsic ( ) -> IntrinsicParameters
+	{
+		return IntrinsicParameters::from_fov(Degrees(20.0).as_radians(), 1000.0, Vector2{x: 500.0, y: 500.0});
+	}
+
+	#[coverage(off)]
+	fn identity ( ) -> Quaternion
+	{
+		return Quaternion{w: 1.0, x: 0.0, y: 0.0, z: 0.0};
+	}
+
+	#[coverage(off)]
+	fn recursive ( ) -> Recursive
+	{
+		return Recursive{gate: 5.0, fov: Degrees(30.0).as_radians(), min_stars: 3};
+	}
+
+	// A set of stars close to +z (the center of the image with an identity attitude).
+	#[coverage(off)]
+	fn catalogue ( ) -> [Equatorial; 5]
+	{
+		return [
+			Vector3{x:  0.00, y:  0.00, z: 1.0}.to_equatorial(),
+			Vector3{x:  0.05, y:  0.00, z: 1.0}.to_equatorial(),
+			Vector3{x:  0.00, y:  0.06, z: 1.0}.to_equatorial(),
+			Vector3{x: -0.04, y:  0.03, z: 1.0}.to_equatorial(),
+			Vector3{x:  0.03, y: -0.07, z: 1.0}.to_equatorial(),
+		];
+	}
+
+	// Projects the catalogue onto the image with the given attitude.
+	#[coverage(off)]
+	fn project ( attitude: Quaternion, catalogue: &[Equatorial] ) -> ArrayList<Vector2, 10>
+	{
+		let mut stars = ArrayList::new();
+		for i in 0..catalogue.len()
+		{
+			let point = attitude.rotate_point(catalogue[i].to_vector3());
+			stars.push_back(intrinsic().to_image(SpaceCamera(point)).0);
+		}
+		return stars;
+	}
+
+
+//###############################################################################################//
+//
+//										Recursive
+//
+// pub fn track      ( &self, ... ) -> RecursiveResult
+// pub fn associate  ( &self, ... )
+// pub fn to_vectors ( ... )
+//
+//###############################################################################################//
+//										~ track ~												 //
+	#[test]
+	// If the attitude changes slightly, the stars should still be associated and the new attitude found.
+	fn test_track_success ( )
+	{
+		let catalogue = catalogue();
+		let actual = AngleAxis{angle: Degrees(0.05).as_radians(), axis: Vector3{x: 0.0, y: 1.0, z: 0.0}}.to_quaternion();
+		let stars = project(actual, &catalogue);
+
+		let mut matches : ArrayList<Match<usize>, 10>   = ArrayList::new();
+		let mut vectors : ArrayList<Match<Vector3>, 10> = ArrayList::new();
+		let result = recursive().track::<Quest>(&stars, &catalogue, &intrinsic(), identity(), &mut matches, &mut vectors);
+
+		if let RecursiveResult::Success{found, attitude} = result
+		{
+			assert_eq!(found, 5);
+			let forward = Vector3{x: 0.0, y: 0.0, z: 1.0};
+			let expected = actual.rotate_point(forward);
+			let found    = attitude.rotate_point(forward);
+			found.x.assert_close(&expected.x, 0.00001);
+			found.y.assert_close(&expected.y, 0.00001);
+			found.z.assert_close(&expected.z, 0.00001);
+		}
+		else { panic!("Expected success: {:?}", result); }
+	}
+
+	#[test]
+	// If the attitude changes more than the gate, lost-in-space should be required.
+	fn test_track_insufficient ( )
+	{
+		let catalogue = catalogue();
+		let actual = AngleAxis{angle: Degrees(2.0).as_radians(), axis: Vector3{x: 0.0, y: 1.0, z: 0.0}}.to_quaternion();
+		let stars = project(actual, &catalogue);
+
+		let mut matches : ArrayList<Match<usize>, 10>   = ArrayList::new();
+		let mut vectors : ArrayList<Match<Vector3>, 10> = ArrayList::new();
+		let result = recursive().track::<Quest>(&stars, &catalogue, &intrinsic(), identity(), &mut matches, &mut vectors);
+		assert!(matches!(result, RecursiveResult::ErrorInsufficientStars{found: 0}));
+	}
+
+	#[test]
+	// min_stars of 0 should still require a star to find an attitude.
+	fn test_track_no_stars ( )
+	{
+		let catalogue = catalogue();
+		let stars : ArrayList<Vector2, 10> = ArrayList::new();
+		let mut matches : ArrayList<Match<usize>, 10>   = ArrayList::new();
+		let mut vectors : ArrayList<Match<Vector3>, 10> = ArrayList::new();
+		let mut tracker = recursive();
+		tracker.min_stars = 0;
+		let result = tracker.track::<Quest>(&stars, &catalogue, &intrinsic(), identity(), &mut matches, &mut vectors);
+		assert!(matches!(result, RecursiveResult::ErrorInsufficientStars{found: 0}));
+	}
+
+
+//										~ associate ~											 //
+	#[test]
+	// Each observed star should be associated with the correct catalogue star.
+	fn test_associate ( )
+	{
+		let catalogue = catalogue();
+		let mut stars = project(identity(), &catalogue);
+		// Reverse the order and add noise.
+		stars.sort_order(|a, b| a.x > b.x);
+		for i in 0..stars.size() { stars.set(i, stars.get(i) + Vector2{x: 1.0, y: -1.0}); }
+
+		let mut matches : ArrayList<Match<usize>, 10> = ArrayList::new();
+		recursive().associate(&stars, &catalogue, &intrinsic(), identity(), &mut matches);
+
+		assert_eq!(matches.size(), 5);
+		let projected = project(identity(), &catalogue);
+		for i in 0..matches.size()
+		{
+			let found = matches.get(i);
+			let offset = stars.get(found.input) - projected.get(found.output);
+			assert!(offset.magnitude() < 2.0);
+			assert_eq!(found.weight, 1.0);
+		}
+	}
+
+	#[test]
+	// Stars outside of the gate should not be associated.
+	fn test_associate_gate ( )
+	{
+		let catalogue = catalogue();
+		let mut stars = project(identity(), &catalogue);
+		stars.set(0, stars.get(0) + Vector2{x: 5.1, y: 0.0});
+		stars.set(1, stars.get(1) + Vector2{x: 0.0, y: 4.9});
+
+		let mut matches : ArrayList<Match<usize>, 10> = ArrayList::new();
+		recursive().associate(&stars, &catalogue, &intrinsic(), identity(), &mut matches);
+
+		assert_eq!(matches.size(), 4);
+		for i in 0..matches.size() { assert_ne!(matches.get(i).input, 0); }
+	}
+
+	#[test]
+	// Stars behind the camera or outside the field of view project onto the image but should be ignored.
+	fn test_associate_fov ( )
+	{
+		let catalogue = [
+			Vector3{x: 0.0, y: 0.0, z: -1.0}.to_equatorial(),
+			Vector3{x: 1.0, y: 0.0, z:  1.0}.to_equatorial()];
+		let stars = [Vector2{x: 500.0, y: 500.0}, intrinsic().to_image(SpaceCamera(catalogue[1].to_vector3())).0];
+		let stars = ArrayList::<Vector2, 2>::from_array(&stars);
+
+		let mut matches : ArrayList<Match<usize>, 10> = ArrayList::new();
+		recursive().associate(&stars, &catalogue, &intrinsic(), identity(), &mut matches);
+		assert_eq!(matches.size(), 0);
+	}
+
+	#[test]
+	// If two catalogue stars are close to the same observed star, the closest should be used.
+	fn test_associate_closest ( )
+	{
+		let catalogue = [
+			Vector3{x: 0.001, y: 0.0, z: 1.0}.to_equatorial(),
+			Vector3{x: 0.000, y: 0.0, z: 1.0}.to_equatorial(),
+			Vector3{x: 0.002, y: 0.0, z: 1.0}.to_equatorial()];
+		let stars = ArrayList::<Vector2, 1>::from_array(&[Vector2{x: 500.0, y: 500.0}]);
+
+		let mut matches : ArrayList<Match<usize>, 10> = ArrayList::new();
+		recursive().associate(&stars, &catalogue, &intrinsic(), identity(), &mut matches);
+		assert_eq!(matches.size(), 1);
+		assert_eq!(matches.get(0), Match{input: 0, output: 1, weight: 1.0});
+	}
+
+	#[test]
+	// If the output is full, the rest of the stars should be ignored.
+	fn test_associate_full ( )
+	{
+		let catalogue = catalogue();
+		let stars = project(identity(), &catalogue);
+		let mut matches : ArrayList<Match<usize>, 2> = ArrayList::new();
+		recursive().associate(&stars, &catalogue, &intrinsic(), identity(), &mut matches);
+		assert_eq!(matches.size(), 2);
+	}
+
+
+//										~ to_vectors ~											 //
+	#[test]
+	// The observed star should be converted to a unit vector in the camera frame.
+	fn test_to_vectors ( )
+	{
+		let catalogue = catalogue();
+		let stars = project(identity(), &catalogue);
+		let matches = ArrayList::<Match<usize>, 1>::from_array(&[Match{input: 1, output: 2, weight: 0.5}]);
+
+		let mut vectors : ArrayList<Match<Vector3>, 10> = ArrayList::new();
+		vectors.push_back(Match{input: Vector3{x: 0.0, y: 0.0, z: 0.0}, output: Vector3{x: 0.0, y: 0.0, z: 0.0}, weight: 0.0});
+		Recursive::to_vectors(&stars, &catalogue, &intrinsic(), &matches, &mut vectors);
+
+		assert_eq!(vectors.size(), 1);
+		let expected = catalogue[1].to_vector3();
+		vectors.get(0).input.x.assert_close(&expected.x, 0.00001);
+		vectors.get(0).input.y.assert_close(&expected.y, 0.00001);
+		vectors.get(0).input.z.assert_close(&expected.z, 0.00001);
+		assert_eq!(vectors.get(0).output, catalogue[2].to_vector3());
+		assert_eq!(vectors.get(0).weight, 0.5);
+	}
+}
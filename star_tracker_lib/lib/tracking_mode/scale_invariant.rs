@@ -0,0 +1,568 @@
+//! Implementation of [ScaleInvariant](crate::tracking_mode::ScaleInvariant).
+use crate::core_include::*;
+
+use crate::tracking_mode::database::TriangleDatabase;
+use crate::tracking_mode::SpecularityConstruct;
+use crate::tracking_mode::ScaleInvariantResult;
+use crate::tracking_mode::ScaleInvariant;
+use crate::tracking_mode::KernelIterator;
+use crate::tracking_mode::TriangleAngles;
+use crate::tracking_mode::AbandonSearch;
+use crate::tracking_mode::StarTriangle;
+
+use crate::projection::IntrinsicParameters;
+use crate::projection::Distortion;
+use crate::projection::SpaceImage;
+
+use crate::util::aliases::Decimal;
+use crate::util::units::Vector2;
+use crate::util::units::Vector3;
+use crate::util::units::Radians;
+use crate::util::units::Match;
+use crate::util::list::List;
+
+impl ScaleInvariant
+{
+	/// The number of gauss newton iterations used to refine the focal length.
+	const FOCAL_ITERATIONS: usize = 10;
+
+	/// The step size (relative to the focal length) used to find the derivative.
+	const FOCAL_STEP: Decimal = 0.0001;
+
+
+	/// Identifies stars in the image without knowing the focal length.
+	///
+	/// # Arguments
+	/// * `stars`             - The observed stars on the image (pixels).
+	/// * `principle_point`   - The center of the image (pixels).
+	/// * `database`          - The triangle database to search.
+	/// * `gen_spec`          - An object for checking Specularity.
+	/// * `abort`             - A way of abandoning a search if it takes too long.
+	/// * `feature_tolerance` - How much error the interior angles of a triangle can have.
+	/// * `angle_tolerance`   - How much error the angular separation to the 4th star can have.
+	/// * `candidates`        - An empty list to store the database triangles which match each image triangle.
+	///   This is required as the library is no_std.
+	/// * `matches`           - The output, (input: index of `stars`, output: index of the catalogue).
+	///   This needs a capacity of at least 4.
+	///
+	/// # Returns
+	/// Success with the estimated focal length if 4 stars could be identified.
+	pub fn find (
+		stars            : &dyn List<Vector2>,
+		principle_point  : Vector2,
+		database         : &TriangleDatabase,
+		gen_spec         : &mut dyn SpecularityConstruct,
+		abort            : &mut dyn AbandonSearch,
+		feature_tolerance: Radians,
+		angle_tolerance  : Radians,
+		candidates       : &mut dyn List<StarTriangle<usize>>,
+		matches          : &mut dyn List<Match<usize>>,
+	) -> ScaleInvariantResult
+	{
+		let mut result = ScaleInvariantResult::ErrorNoTriangleMatch{fails: 0};
+		let mut kernel = KernelIterator::new(stars.size());
+		matches.clear();
+
+		while kernel.step()
+		{
+			if abort.should_abort()
+			{
+				matches.clear();
+				return ScaleInvariantResult::ErrorAborted{fails: *result.get_fails()};
+			}
+
+			// The vertices are ordered by angle so they can be compared with the database.
+			let points = StarTriangle(stars.get(kernel.i), stars.get(kernel.j), stars.get(kernel.k));
+			let (angles, order) = TriangleAngles::from_image(points);
+			let input = TriangleAngles::reorder(StarTriangle(kernel.i, kernel.j, kernel.k), order);
+
+			if !angles.is_ambiguous(feature_tolerance)
+			{
+				candidates.clear();
+				database.find_close_ref(angles, feature_tolerance, candidates);
+
+				for i in 0..candidates.size()
+				{
+					let output = candidates.get(i);
+					matches.clear();
+					let _= matches.push_back(Match{input: input.0, output: output.0, weight: 1.0});
+					let _= matches.push_back(Match{input: input.1, output: output.1, weight: 1.0});
+					let _= matches.push_back(Match{input: input.2, output: output.2, weight: 1.0});
+
+					let focal_length = Self::estimate_focal_length(stars, principle_point, database, matches);
+					let intrinsic    = Self::intrinsic(focal_length, principle_point);
+
+					let observed = StarTriangle(
+						Self::to_camera(&intrinsic, stars.get(input.0)),
+						Self::to_camera(&intrinsic, stars.get(input.1)),
+						Self::to_camera(&intrinsic, stars.get(input.2)));
+					let expected = StarTriangle(
+						database.get_catalogue(output.0),
+						database.get_catalogue(output.1),
+						database.get_catalogue(output.2)).to_vector3();
+
+					if gen_spec.same(&observed, &expected)
+					{
+						let pilot = Self::find_pilot(stars, &intrinsic, database,
+							feature_tolerance, angle_tolerance, input, output);
+						if let Some(pilot) = pilot
+						{
+							if matches.push_back(pilot).is_ok()
+							{
+								let focal_length =
+									Self::estimate_focal_length(stars, principle_point, database, matches);
+								return ScaleInvariantResult::Success{
+									fails: *result.get_fails(), focal_length: focal_length};
+							}
+						}
+					}
+				}
+			}
+			matches.clear();
+			*result.get_fails() += 1;
+		}
+		return result;
+	}
+
+
+	/// Estimates the focal length from the matched stars.
+	/// An initial guess is found from the ratio of the pixel and angular separations.
+	/// This is then refined so the projected angles best fit the catalogue angles.
+	///
+	/// # Arguments
+	/// * `stars`           - The observed stars on the image (pixels).
+	/// * `principle_point` - The center of the image (pixels).
+	/// * `database`        - The database containing the catalogue.
+	/// * `matches`         - (input: index of `stars`, output: index of the catalogue).
+	///
+	/// # Returns
+	/// The focal length in pixels or 0 if there is less than 2 matches.
+	pub fn estimate_focal_length (
+		stars          : &dyn List<Vector2>,
+		principle_point: Vector2,
+		database       : &TriangleDatabase,
+		matches        : &dyn List<Match<usize>>,
+	) -> Decimal
+	{
+		// Small angle approximation: pixel distance = focal length * angle.
+		let mut pixels = 0.0;
+		let mut angles = 0.0;
+		for i in 0..matches.size()
+		{
+			for j in (i + 1)..matches.size()
+			{
+				let a = matches.get(i);
+				let b = matches.get(j);
+				pixels += (stars.get(a.input) - stars.get(b.input)).magnitude();
+				angles += database.get_catalogue(a.output).angle_distance(database.get_catalogue(b.output)).0;
+			}
+		}
+		if angles <= 0.0 { return 0.0; }
+		let mut focal_length = pixels / angles;
+
+		// Gauss Newton, minimizing the difference between the projected and catalogue angles.
+		for _ in 0..Self::FOCAL_ITERATIONS
+		{
+			let step = focal_length * Self::FOCAL_STEP;
+			let mut jtj = 0.0;
+			let mut jtr = 0.0;
+			for i in 0..matches.size()
+			{
+				for j in (i + 1)..matches.size()
+				{
+					let a = matches.get(i);
+					let b = matches.get(j);
+					let expected = database.get_catalogue(a.output).angle_distance(database.get_catalogue(b.output)).0;
+
+					let angle = | f: Decimal | -> Decimal
+					{
+						let intrinsic = Self::intrinsic(f, principle_point);
+						let point_a = Self::to_camera(&intrinsic, stars.get(a.input));
+						let point_b = Self::to_camera(&intrinsic, stars.get(b.input));
+						return point_a.angle_distance(point_b).0;
+					};
+
+					let residual   = angle(focal_length) - expected;
+					let derivative = (angle(focal_length + step) - angle(focal_length - step)) / (2.0 * step);
+					jtj += derivative * derivative;
+					jtr += derivative * residual;
+				}
+			}
+			if jtj <= 0.0 { break; }
+			focal_length -= jtr / jtj;
+		}
+		return focal_length;
+	}
+
+
+	/// Finds a 4th star which matches the angular separation to each star of the triangle.
+	/// The 4th star makes a triangle with the first 2 stars which is looked up in the database.
+	/// # Arguments
+	/// * `stars`             - The observed stars on the image (pixels).
+	/// * `intrinsic`         - The estimated intrinsic parameters.
+	/// * `database`          - The database containing the catalogue.
+	/// * `feature_tolerance` - How much error the interior angles of a triangle can have.
+	/// * `angle_tolerance`   - How much error each angular separation can have.
+	/// * `input`             - The triangle from the image.
+	/// * `output`            - The triangle from the catalogue in the same order as input.
+	/// # Returns
+	/// The match of the pilot or None if no pilot could be found.
+	fn find_pilot (
+		stars            : &dyn List<Vector2>,
+		intrinsic        : &IntrinsicParameters,
+		database         : &TriangleDatabase,
+		feature_tolerance: Radians,
+		angle_tolerance  : Radians,
+		input            : StarTriangle<usize>,
+		output           : StarTriangle<usize>,
+	) -> Option<Match<usize>>
+	{
+		let triangle_in = StarTriangle(
+			Self::to_camera(intrinsic, stars.get(input.0)),
+			Self::to_camera(intrinsic, stars.get(input.1)),
+			Self::to_camera(intrinsic, stars.get(input.2)));
+		let triangle_out = StarTriangle(
+			database.get_catalogue(output.0),
+			database.get_catalogue(output.1),
+			database.get_catalogue(output.2));
+
+		for ii in 0..stars.size()
+		{
+			if ii != input.0 && ii != input.1 && ii != input.2
+			{
+				let star   = Self::to_camera(intrinsic, stars.get(ii));
+				let side_a = triangle_in.0.angle_distance(star);
+				let side_b = triangle_in.1.angle_distance(star);
+				let side_c = triangle_in.2.angle_distance(star);
+
+				let accept = | jj: usize | -> bool
+				{
+					let pilot = database.get_catalogue(jj);
+					return jj != output.2 &&
+						(triangle_out.0.angle_distance(pilot) - side_a).abs() < angle_tolerance.0 &&
+						(triangle_out.1.angle_distance(pilot) - side_b).abs() < angle_tolerance.0 &&
+						(triangle_out.2.angle_distance(pilot) - side_c).abs() < angle_tolerance.0;
+				};
+
+				let points = StarTriangle(
+					triangle_in.0.to_equatorial(), triangle_in.1.to_equatorial(), star.to_equatorial());
+				let angles = TriangleAngles::from_equatorial(points).0;
+				if let Some(jj) = database.find_third(angles, feature_tolerance, output.0, output.1, &accept)
+				{
+					return Some(Match{input: ii, output: jj, weight: 1.0});
+				}
+			}
+		}
+		return None;
+	}
+
+
+	/// Creates intrinsic parameters with a square pixel and no distortion.
+	fn intrinsic ( focal_length: Decimal, principle_point: Vector2 ) -> IntrinsicParameters
+	{
+		return IntrinsicParameters{
+			focal_length:    Vector2{x: focal_length, y: focal_length},
+			principle_point: principle_point,
+			distortion:      Distortion::none()};
+	}
+
+
+	/// Converts an image point to a normalized camera space vector.
+	fn to_camera ( intrinsic: &IntrinsicParameters, point: Vector2 ) -> Vector3
+	{
+		let mut camera = intrinsic.from_image(SpaceImage(point)).0;
+		let _= camera.normalize();
+		return camera;
+	}
+}
+
+
+
+impl ScaleInvariantResult
+{
+	/// Returns the number of fails inside any ScaleInvariantResult which can be edited.
+	pub fn get_fails ( &mut self ) -> &mut usize
+	{
+		match self
+		{
+			ScaleInvariantResult::ErrorNoTriangleMatch { fails }    => fails,
+			ScaleInvariantResult::ErrorAborted         { fails }    => fails,
+			ScaleInvariantResult::Success              { fails, .. } => fails,
+		}
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::database::TriangleDatabase;
+	use crate::tracking_mode::database::KVector;
+	use crate::tracking_mode::AbandonSearchFailures;
+	use crate::tracking_mode::ScaleInvariantResult;
+	use crate::tracking_mode::ScaleInvariant;
+	use crate::tracking_mode::TriangleAngles;
+	use crate::tracking_mode::StarTriangle;
+	use crate::tracking_mode::Specularity;
+
+	use crate::projection::IntrinsicParameters;
+	use crate::projection::Distortion;
+	use crate::projection::SpaceCamera;
+
+	use crate::util::aliases::Decimal;
+	use crate::util::aliases::M_PI;
+	use crate::util::units::Equatorial;
+	use crate::util::units::Vector2;
+	use crate::util::units::Degrees;
+	use crate::util::units::Radians;
+	use crate::util::units::Match;
+	use crate::util::list::List;
+	use crate::util::test::TestEqual;
+
+	// Stars around ra: 0, dec: 0 so the camera can look down the x axis.
+	static CATALOGUE: [Equatorial; 5] =
+	[
+		Equatorial{ra: Radians( 0.000), dec: Radians( 0.000)},
+		Equatorial{ra: Radians( 0.050), dec: Radians( 0.010)},
+		Equatorial{ra: Radians( 0.020), dec: Radians( 0.060)},
+		Equatorial{ra: Radians(-0.040), dec: Radians( 0.030)},
+		Equatorial{ra: Radians(-0.010), dec: Radians(-0.070)},
+	];
+
+	static PRINCIPLE_POINT: Vector2 = Vector2{x: 640.0, y: 480.0};
+
+	/// Creates a database of every unambiguous triangle in the catalogue.
+	#[coverage(off)]
+	fn create_database ( k_vector: &mut Vec<usize>, triangles: &mut Vec<StarTriangle<usize>> )
+	{
+		for i in 0..CATALOGUE.len()
+		{
+			for j in (i + 1)..CATALOGUE.len()
+			{
+				for k in (j + 1)..CATALOGUE.len()
+				{
+					let points = StarTriangle(CATALOGUE[i], CATALOGUE[j], CATALOGUE[k]);
+					let (_, order) = TriangleAngles::from_equatorial(points);
+					triangles.push(TriangleAngles::reorder(StarTriangle(i, j, k), order));
+				}
+			}
+		}
+		// 1 bin containing every triangle.
+		k_vector.push(0);
+		k_vector.push(triangles.len());
+	}
+
+	/// Projects the catalogue onto the image with the camera looking at ra: 0, dec: 0.
+	/// Camera x is +ra (world y), camera y is +dec (world z) and camera z is world x.
+	#[coverage(off)]
+	fn project ( focal_length: Decimal ) -> Vec<Vector2>
+	{
+		let intrinsic = IntrinsicParameters{
+			focal_length: Vector2{x: focal_length, y: focal_length},
+			principle_point: PRINCIPLE_POINT,
+			distortion: Distortion::none()};
+
+		let mut stars = Vec::new();
+		for i in 0..CATALOGUE.len()
+		{
+			let world  = CATALOGUE[i].to_vector3();
+			let camera = SpaceCamera(crate::util::units::Vector3{x: world.y, y: world.z, z: world.x});
+			stars.push(intrinsic.to_image(camera).0);
+		}
+		return stars;
+	}
+
+//###############################################################################################//
+//
+//										ScaleInvariant
+//
+// pub fn find                  ( ... ) -> ScaleInvariantResult
+// pub fn estimate_focal_length ( &dyn List<Vector2>, Vector2, &TriangleDatabase, &dyn List<Match<usize>> ) -> Decimal
+// fn find_pilot                ( &dyn List<Vector2>, &IntrinsicParameters, &TriangleDatabase, Radians, Radians,
+//                                StarTriangle<usize>, StarTriangle<usize> ) -> Option<Match<usize>>
+//
+//###############################################################################################//
+//										~ find ~												 //
+	#[test]
+	// With a perfect image, every star should be matched to the correct catalogue star for any focal length.
+	fn test_find ( )
+	{
+		let mut k_vector  = Vec::new();
+		let mut triangles = Vec::new();
+		create_database(&mut k_vector, &mut triangles);
+		let database = TriangleDatabase{
+			fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(2, 0.0, M_PI / 3.0),
+			k_vector: &k_vector, triangles: &triangles, catalogue: &CATALOGUE};
+
+		for focal_length in [500.0, 1000.0, 3000.0]
+		{
+			let stars = project(focal_length);
+			let mut candidates = Vec::new();
+			let mut matches: Vec<Match<usize>> = Vec::new();
+			let mut abort = AbandonSearchFailures::new(100);
+			let result = ScaleInvariant::find(&stars, PRINCIPLE_POINT, &database,
+				&mut Specularity::default(), &mut abort,
+				Degrees(0.1).as_radians(), Degrees(0.01).as_radians(), &mut candidates, &mut matches);
+
+			if let ScaleInvariantResult::Success{focal_length: found, ..} = result
+			{
+				found.assert_close(&focal_length, 0.01);
+			}
+			else { panic!("{:?}", result); }
+
+			assert_eq!(matches.size(), 4);
+			for i in 0..matches.size()
+			{
+				assert_eq!(matches[i].input, matches[i].output);
+			}
+		}
+	}
+
+	#[test]
+	// If there are not enough stars, no match can be found.
+	fn test_find_no_match ( )
+	{
+		let mut k_vector  = Vec::new();
+		let mut triangles = Vec::new();
+		create_database(&mut k_vector, &mut triangles);
+		let database = TriangleDatabase{
+			fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(2, 0.0, M_PI / 3.0),
+			k_vector: &k_vector, triangles: &triangles, catalogue: &CATALOGUE};
+
+		// Only 3 stars, there is no pilot.
+		let mut stars = project(1000.0);
+		stars.truncate(3);
+		let mut candidates = Vec::new();
+		let mut matches: Vec<Match<usize>> = Vec::new();
+		let mut abort = AbandonSearchFailures::new(100);
+		let mut result = ScaleInvariant::find(&stars, PRINCIPLE_POINT, &database,
+			&mut Specularity::default(), &mut abort,
+			Degrees(0.1).as_radians(), Degrees(0.01).as_radians(), &mut candidates, &mut matches);
+
+		assert_eq!(*result.get_fails(), 1);
+		assert!(matches!(result, ScaleInvariantResult::ErrorNoTriangleMatch{..}));
+		assert_eq!(matches.size(), 0);
+	}
+
+	#[test]
+	// If abort is true, the search should stop.
+	fn test_find_abort ( )
+	{
+		let mut k_vector  = Vec::new();
+		let mut triangles = Vec::new();
+		create_database(&mut k_vector, &mut triangles);
+		let database = TriangleDatabase{
+			fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(2, 0.0, M_PI / 3.0),
+			k_vector: &k_vector, triangles: &triangles, catalogue: &CATALOGUE};
+
+		let stars = project(1000.0);
+		let mut candidates = Vec::new();
+		let mut matches: Vec<Match<usize>> = Vec::new();
+		let mut abort = AbandonSearchFailures::new(0);
+		let result = ScaleInvariant::find(&stars, PRINCIPLE_POINT, &database,
+			&mut Specularity::default(), &mut abort,
+			Degrees(0.1).as_radians(), Degrees(0.01).as_radians(), &mut candidates, &mut matches);
+
+		assert!(matches!(result, ScaleInvariantResult::ErrorAborted{fails: 0}));
+	}
+
+//										~ estimate_focal_length ~								 //
+	#[test]
+	// With the correct matches, the focal length should be found.
+	fn test_estimate_focal_length ( )
+	{
+		let mut k_vector  = Vec::new();
+		let mut triangles = Vec::new();
+		create_database(&mut k_vector, &mut triangles);
+		let database = TriangleDatabase{
+			fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(2, 0.0, M_PI / 3.0),
+			k_vector: &k_vector, triangles: &triangles, catalogue: &CATALOGUE};
+
+		let stars = project(1234.0);
+		let mut matches: Vec<Match<usize>> = Vec::new();
+		for i in 0..stars.len() { matches.push(Match{input: i, output: i, weight: 1.0}); }
+		let focal_length = ScaleInvariant::estimate_focal_length(&stars, PRINCIPLE_POINT, &database, &matches);
+		focal_length.assert_close(&1234.0, 0.01);
+	}
+
+	#[test]
+	// If there are not enough stars, there is no distance to compare.
+	fn test_estimate_focal_length_insufficient ( )
+	{
+		let mut k_vector  = Vec::new();
+		let mut triangles = Vec::new();
+		create_database(&mut k_vector, &mut triangles);
+		let database = TriangleDatabase{
+			fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(2, 0.0, M_PI / 3.0),
+			k_vector: &k_vector, triangles: &triangles, catalogue: &CATALOGUE};
+
+		let stars = project(1234.0);
+		let matches: Vec<Match<usize>> = vec![Match{input: 0, output: 0, weight: 1.0}];
+		let focal_length = ScaleInvariant::estimate_focal_length(&stars, PRINCIPLE_POINT, &database, &matches);
+		assert_eq!(focal_length, 0.0);
+	}
+
+//										~ find_pilot ~											 //
+	#[test]
+	// The pilot is found from the database triangle it makes with the first 2 stars.
+	fn test_find_pilot ( )
+	{
+		let mut k_vector  = Vec::new();
+		let mut triangles = Vec::new();
+		create_database(&mut k_vector, &mut triangles);
+		let database = TriangleDatabase{
+			fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(2, 0.0, M_PI / 3.0),
+			k_vector: &k_vector, triangles: &triangles, catalogue: &CATALOGUE};
+
+		let stars     = project(1000.0);
+		let intrinsic = ScaleInvariant::intrinsic(1000.0, PRINCIPLE_POINT);
+		let pilot = ScaleInvariant::find_pilot(&stars, &intrinsic, &database,
+			Degrees(0.1).as_radians(), Degrees(0.01).as_radians(), StarTriangle(0, 1, 2), StarTriangle(0, 1, 2));
+		assert_eq!(pilot, Some(Match{input: 3, output: 3, weight: 1.0}));
+
+		let pilot = ScaleInvariant::find_pilot(&stars, &intrinsic, &database,
+			Degrees(0.1).as_radians(), Degrees(0.01).as_radians(), StarTriangle(4, 1, 2), StarTriangle(4, 1, 2));
+		assert_eq!(pilot, Some(Match{input: 0, output: 0, weight: 1.0}));
+	}
+
+	#[test]
+	// If the pilot triangle is not in the database or the separations do not match, there is no pilot.
+	fn test_find_pilot_none ( )
+	{
+		let mut k_vector  = Vec::new();
+		let mut triangles = Vec::new();
+		create_database(&mut k_vector, &mut triangles);
+		let stars     = project(1000.0);
+		let intrinsic = ScaleInvariant::intrinsic(1000.0, PRINCIPLE_POINT);
+
+		// Only the triangle (0, 1, 2) is in the database.
+		let only : Vec<StarTriangle<usize>> = triangles.iter().filter(|t|
+			[t.0, t.1, t.2].contains(&0) && [t.0, t.1, t.2].contains(&1) && [t.0, t.1, t.2].contains(&2))
+			.copied().collect();
+		let database = TriangleDatabase{
+			fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, M_PI / 3.0),
+			k_vector: &vec![0, 1], triangles: &only, catalogue: &CATALOGUE};
+		let pilot = ScaleInvariant::find_pilot(&stars, &intrinsic, &database,
+			Degrees(0.1).as_radians(), Degrees(0.01).as_radians(), StarTriangle(0, 1, 2), StarTriangle(0, 1, 2));
+		assert_eq!(pilot, None);
+
+		// The wrong focal length changes the separations.
+		let database = TriangleDatabase{
+			fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(2, 0.0, M_PI / 3.0),
+			k_vector: &k_vector, triangles: &triangles, catalogue: &CATALOGUE};
+		let intrinsic = ScaleInvariant::intrinsic(1100.0, PRINCIPLE_POINT);
+		let pilot = ScaleInvariant::find_pilot(&stars, &intrinsic, &database,
+			Degrees(0.1).as_radians(), Degrees(0.01).as_radians(), StarTriangle(0, 1, 2), StarTriangle(0, 1, 2));
+		assert_eq!(pilot, None);
+	}
+}
@@ -3,6 +3,7 @@ use core_include::*;
 
 use super::StarPair;
 use util::list::List;
+use util::units::Radians;
 
 // use crate::tracking_mode::database::SearchSave;
 
@@ -186,6 +187,32 @@ impl StarPair<usize>
 		}
 		return Option::None;
 	}
+
+
+	/// Finds the tolerance of the angular separation between the two stars.
+	/// The uncertainty of each star is combined as independent errors (root sum square).
+	/// # Arguments
+	/// * `uncertainty` - The uncertainty of each star (same index as the star).
+	/// * `default`     - The tolerance to use if either star does not have an uncertainty.
+	///
+	/// ```
+	/// use star_tracker_lib::tracking_mode::StarPair;
+	/// use star_tracker_lib::util::units::Radians;
+	///
+	/// let uncertainty = vec![Radians(3.0), Radians(4.0)];
+	/// assert_eq!(StarPair(0, 1).tolerance(&uncertainty, Radians(1.0)), Radians(5.0));
+	/// assert_eq!(StarPair(0, 2).tolerance(&uncertainty, Radians(1.0)), Radians(1.0));
+	/// ```
+	pub fn tolerance ( &self, uncertainty: &dyn List<Radians>, default: Radians ) -> Radians
+	{
+		if uncertainty.size() <= self.0 || uncertainty.size() <= self.1
+		{
+			return default;
+		}
+		let a = uncertainty.get(self.0).0;
+		let b = uncertainty.get(self.1).0;
+		return Radians((a * a + b * b).sqrt());
+	}
 }
 
 
@@ -213,6 +240,8 @@ impl StarPair<usize>
 mod test
 {
 	use util::list::List;
+	use util::units::Radians;
+	use util::aliases::Decimal;
 	
 	use tracking_mode::StarPair;
 
@@ -386,4 +415,28 @@ mod test
 		find = StarPair(5, 6);
 		assert_eq!(StarPair::index_of(find, &lst), None);
 	}
+
+
+
+	//
+	// fn tolerance ( &self, &dyn List<Radians>, Radians ) -> Radians
+	// where T: usize
+	#[test]
+	// The uncertainty of both stars should be combined.
+	fn test_tolerance ( )
+	{
+		let uncertainty = vec![Radians(0.3), Radians(0.4), Radians(1.2)];
+		assert_eq!(StarPair(0, 1).tolerance(&uncertainty, Radians(10.0)), Radians(0.5));
+		assert_eq!(StarPair(1, 0).tolerance(&uncertainty, Radians(10.0)), Radians(0.5));
+		assert_eq!(StarPair(2, 2).tolerance(&uncertainty, Radians(10.0)), Radians(1.2 * (2.0 as Decimal).sqrt()));
+	}
+
+	#[test]
+	// If a star does not have an uncertainty, the default should be used.
+	fn test_tolerance_default ( )
+	{
+		let uncertainty = vec![Radians(0.3), Radians(0.4)];
+		assert_eq!(StarPair(0, 2).tolerance(&uncertainty, Radians(10.0)), Radians(10.0));
+		assert_eq!(StarPair(3, 1).tolerance(&Vec::new(), Radians(10.0)), Radians(10.0));
+	}
 }
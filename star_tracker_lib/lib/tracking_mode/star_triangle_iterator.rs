@@ -5,7 +5,10 @@ use crate::tracking_mode::StarTriangleIterator;
 use crate::tracking_mode::TriangleConstruct;
 // use crate::tracking_mode::IterationResult;
 use crate::tracking_mode::KernelIterator;
+use crate::tracking_mode::TriangleSelection;
 use crate::tracking_mode::StarTriangle;
+use crate::tracking_mode::Photometry;
+use crate::tracking_mode::StarPair;
 
 use crate::tracking_mode::database::ChunkIterator;
 
@@ -14,9 +17,10 @@ use crate::util::list::List;
 use crate::util::units::Equatorial;
 use crate::util::units::Radians;
 use crate::util::units::Match;
+use crate::util::aliases::Decimal;
 
 
-impl <const N: usize> TriangleConstruct for StarTriangleIterator<N>
+impl <const N: usize, const S: usize, T: TriangleSelection> TriangleConstruct for StarTriangleIterator<N, S, T>
 {
 	/// Call this to get the next StarTriangle observed/database pair.
 	/// # Arguments
@@ -36,7 +40,7 @@ impl <const N: usize> TriangleConstruct for StarTriangleIterator<N>
 
 			// Once all possibilities for a single kernal step are exhausted.
 			// The kernal will step, new stars will be chosen and a list of database matches are generated.
-			while !StarTriangleIterator::<N>::step(
+			while !Self::step(
 				&mut self.index_a, &mut self.index_b, &mut self.index_c,
 				self.pair_a.size(), self.pair_b.size(), self.pair_c.size())
 			{
@@ -50,14 +54,16 @@ impl <const N: usize> TriangleConstruct for StarTriangleIterator<N>
 
 			let triangle = StarTriangle::construct_triangle(a.result, b.result, c.result);
 
-			// Leaves the loop if a, b and c sides of the database are connected.
-			if triangle.is_some()
+			// Leaves the loop if a, b and c sides of the database are connected and the brightness agrees.
+			if let Some(output) = triangle
 			{
 				let input  = self.input;
-				let output = triangle.unwrap();
-				let error  = a.error + b.error + c.error;
-				tries = Option::Some(Match{input: input, output: output, weight: error});
-				break '_outer; // Rust implementation of a do while loop.
+				if self.photometric(&[input.0, input.1, input.2], &[output.0, output.1, output.2], database)
+				{
+					let error  = a.error + b.error + c.error;
+					tries = Option::Some(Match{input: input, output: output, weight: error});
+					break '_outer; // Rust implementation of a do while loop.
+				}
 			}
 		}
 		self.expected_triangle = tries;
@@ -81,7 +87,7 @@ impl <const N: usize> TriangleConstruct for StarTriangleIterator<N>
 		{
 			'_outer: loop // This is the correct use of a do while loop.
 			{
-				while !( StarTriangleIterator::<N>::step(
+				while !( Self::step(
 					&mut self.index_p_a, &mut self.index_p_b, &mut self.index_p_c,
 					self.pair_p_a.size(), self.pair_p_b.size(), self.pair_p_c.size()) )
 				{
@@ -111,9 +117,13 @@ impl <const N: usize> TriangleConstruct for StarTriangleIterator<N>
 						valid &= b.result.has(pilot);
 						valid &= c.result.has(pilot);
 
+						let input = self.index_p as usize;
+						valid &= self.photometric(
+							&[expected.input.0,  expected.input.1,  expected.input.2,  input],
+							&[expected.output.0, expected.output.1, expected.output.2, pilot], database);
+
 						if valid
 						{
-							let input  = self.index_p as usize;
 							let output = pilot as usize;
 							let error  = a.error + b.error + c.error;
 							return Some(Match{input: input, output: output, weight: error});
@@ -153,7 +163,7 @@ impl <const N: usize> TriangleConstruct for StarTriangleIterator<N>
 	/// ```
 	fn begin ( &mut self, angle_tolerance: Radians, stars: &dyn List<Equatorial> )
 	{
-		self.kernel = KernelIterator::new(stars.size());
+		self.kernel.begin(stars);
 		self.pair_a.clear();
 		self.pair_b.clear();
 		self.pair_c.clear();
@@ -170,6 +180,52 @@ impl <const N: usize> TriangleConstruct for StarTriangleIterator<N>
 		self.input = StarTriangle(0,0,0);
 		self.expected_triangle = None;
 		self.angle_tolerance = angle_tolerance;
+		self.uncertainty.clear();
+		self.photometry = None;
+		self.intensity.clear();
+	}
+
+
+
+	/// Prepares the StarTriangleIterator for iterating where each star has its own uncertainty.
+	/// Only the first `N_MAX_STARS` uncertainties are stored, the rest use `angle_tolerance`.  
+	/// In a debug build, this panics if there are more uncertainties than `N_MAX_STARS` (the default of 0 stores none).
+	/// # Arguments
+	/// * `angle_tolerance` - The tolerance to use for any star without an uncertainty.
+	/// * `uncertainty`     - The uncertainty of each star (same index as `stars`).
+	/// * `stars`           - The observed stars.
+	fn begin_uncertain (
+		&mut self, angle_tolerance: Radians, uncertainty: &dyn List<Radians>, stars: &dyn List<Equatorial> )
+	{
+		debug_assert!(uncertainty.size() <= S, "N_MAX_STARS is too small to store the uncertainty of every star.");
+		self.begin(angle_tolerance, stars);
+		for i in 0..uncertainty.size()
+		{
+			if self.uncertainty.push_back(uncertainty.get(i)).is_err() { break; }
+		}
+	}
+
+
+
+	/// Prepares the StarTriangleIterator for iterating where the brightness of each star is compared to the catalogue.
+	/// Only the first `N_MAX_STARS` intensities are stored, the rest are not checked.  
+	/// In a debug build, this panics if there are more intensities than `N_MAX_STARS` (the default of 0 stores none).
+	/// # Arguments
+	/// * `angle_tolerance` - When searching the database, the tolerance to use.
+	/// * `photometry`      - The magnitude to intensity calibration.
+	/// * `intensity`       - The intensity of each star (same index as `stars`).
+	/// * `stars`           - The observed stars.
+	fn begin_photometric (
+		&mut self, angle_tolerance: Radians, photometry: Photometry,
+		intensity: &dyn List<Decimal>, stars: &dyn List<Equatorial> )
+	{
+		debug_assert!(intensity.size() <= S, "N_MAX_STARS is too small to store the intensity of every star.");
+		self.begin(angle_tolerance, stars);
+		self.photometry = Some(photometry);
+		for i in 0..intensity.size()
+		{
+			if self.intensity.push_back(intensity.get(i)).is_err() { break; }
+		}
 	}
 
 }
@@ -177,16 +233,32 @@ impl <const N: usize> TriangleConstruct for StarTriangleIterator<N>
 
 
 
-impl<const N: usize> StarTriangleIterator<N>
+impl<const N: usize, const S: usize> StarTriangleIterator<N, S>
 {
-	/// Constructs a new StarTriangleIterator.
+	/// Constructs a new StarTriangleIterator which tries the triangles in the order of the star list.
 	/// DOES NOT ASSIGN ANY VALUES.
 	/// CALL `begin` AFTER THIS.
 	pub fn new ( ) -> Self
+	{
+		return Self::with_selection(KernelIterator::new(0));
+	}
+}
+
+
+
+
+impl<const N: usize, const S: usize, T: TriangleSelection> StarTriangleIterator<N, S, T>
+{
+	/// Constructs a new StarTriangleIterator which tries the triangles in the order of `selection`.
+	/// DOES NOT ASSIGN ANY VALUES.
+	/// CALL `begin` AFTER THIS.
+	/// # Arguments
+	/// * `selection` - Decides which triangles are tried first (see [TriangleSelection](crate::tracking_mode::TriangleSelection)).
+	pub fn with_selection ( selection: T ) -> Self
 	{
 		return Self
 		{
-			kernel: KernelIterator::new(0),
+			kernel: selection,
 			pair_a: ArrayList::new(),
 			pair_b: ArrayList::new(),
 			pair_c: ArrayList::new(),
@@ -203,6 +275,36 @@ impl<const N: usize> StarTriangleIterator<N>
 			input: StarTriangle(0,0,0),
 			expected_triangle: Option::None,
 			angle_tolerance: Radians(0.0),
+			uncertainty: ArrayList::new(),
+			photometry: None,
+			intensity: ArrayList::new(),
+		};
+	}
+
+
+	/// The selection given to `with_selection`.  
+	/// Use this to set the state of the selection before a search (e.g. the intensity of a brightness ranking).
+	pub fn get_selection ( &mut self ) -> &mut T
+	{
+		return &mut self.kernel;
+	}
+
+
+
+	/// Checks if the brightness of the observed stars agrees with the catalogue stars they are matched to.
+	/// Stars without an intensity or magnitude are not checked.
+	/// # Arguments
+	/// * `observed` - The index of each observed star.
+	/// * `found`    - The index of the catalogue star each observed star is matched to.
+	/// * `database` - The database containing the magnitudes (not accessed if `begin_photometric` was not used).
+	/// # Returns
+	/// True if `begin_photometric` was not used or the brightness of every star and pair agrees.
+	fn photometric ( &self, observed: &[usize], found: &[usize], database: &dyn ChunkIterator ) -> bool
+	{
+		return match self.photometry
+		{
+			Some(photometry) => photometry.consistent_stars(&self.intensity, observed, found, database.get_database()),
+			None             => true,
 		};
 	}
 
@@ -250,28 +352,34 @@ impl<const N: usize> StarTriangleIterator<N>
 		if !database.next()
 		{
 			database.begin();
-			if !self.kernel.step()
+			if !self.kernel.next(stars, database)
 			{
 				return false;
 			}
 		}
 
 		// Ensures input is set.
-		self.input = StarTriangle(self.kernel.i, self.kernel.j, self.kernel.k);
+		self.input = self.kernel.triangle();
+		let i = self.input.0;
+		let j = self.input.1;
+		let k = self.input.2;
 
 		// The angular distance between observed stars.
-		let side_a = stars.get(self.kernel.i).angle_distance(stars.get(self.kernel.j)); // i, j
-		let side_b = stars.get(self.kernel.i).angle_distance(stars.get(self.kernel.k)); // i, k
-		let side_c = stars.get(self.kernel.j).angle_distance(stars.get(self.kernel.k)); // j, k
+		let side_a = stars.get(i).angle_distance(stars.get(j)); // i, j
+		let side_b = stars.get(i).angle_distance(stars.get(k)); // i, k
+		let side_c = stars.get(j).angle_distance(stars.get(k)); // j, k
 
 		self.pair_a.clear();
 		self.pair_b.clear();
 		self.pair_c.clear();
 
 		// Search the database for each side.
-		database.find_close_ref_region(side_a, self.angle_tolerance, &mut self.pair_a);
-		database.find_close_ref_region(side_b, self.angle_tolerance, &mut self.pair_b);
-		database.find_close_ref_region(side_c, self.angle_tolerance, &mut self.pair_c);
+		let tolerance_a = StarPair(i, j).tolerance(&self.uncertainty, self.angle_tolerance);
+		let tolerance_b = StarPair(i, k).tolerance(&self.uncertainty, self.angle_tolerance);
+		let tolerance_c = StarPair(j, k).tolerance(&self.uncertainty, self.angle_tolerance);
+		database.find_close_ref_region(side_a, tolerance_a, &mut self.pair_a);
+		database.find_close_ref_region(side_b, tolerance_b, &mut self.pair_b);
+		database.find_close_ref_region(side_c, tolerance_c, &mut self.pair_c);
 
 		// With new arrays, the iterations must go back to the beginning.
 		self.index_a = -1;
@@ -312,9 +420,13 @@ impl<const N: usize> StarTriangleIterator<N>
 			self.pair_p_a.clear();
 			self.pair_p_b.clear();
 			self.pair_p_c.clear();
-			database.find_close_ref_region(side_a, self.angle_tolerance, &mut self.pair_p_a);
-			database.find_close_ref_region(side_b, self.angle_tolerance, &mut self.pair_p_b);
-			database.find_close_ref_region(side_c, self.angle_tolerance, &mut self.pair_p_c);
+			let pilot = self.index_p as usize;
+			let tolerance_a = StarPair(expected.input.0, pilot).tolerance(&self.uncertainty, self.angle_tolerance);
+			let tolerance_b = StarPair(expected.input.1, pilot).tolerance(&self.uncertainty, self.angle_tolerance);
+			let tolerance_c = StarPair(expected.input.2, pilot).tolerance(&self.uncertainty, self.angle_tolerance);
+			database.find_close_ref_region(side_a, tolerance_a, &mut self.pair_p_a);
+			database.find_close_ref_region(side_b, tolerance_b, &mut self.pair_p_b);
+			database.find_close_ref_region(side_c, tolerance_c, &mut self.pair_p_c);
 
 			self.index_p_a = -1;
 			self.index_p_b = 0;
@@ -346,15 +458,51 @@ mod test
 	use crate::tracking_mode::StarTriangleIterator;
 	use crate::tracking_mode::TriangleConstruct;
 	use crate::tracking_mode::KernelIterator;
+	use crate::tracking_mode::MockTriangleSelection;
+	use crate::tracking_mode::TriangleSelection;
+	use crate::tracking_mode::TriangleSelectionRanked;
+	use crate::tracking_mode::TriangleQualityBrightness;
 	use crate::tracking_mode::StarTriangle;
 	use crate::tracking_mode::StarPair;
+	use crate::tracking_mode::Photometry;
 	use crate::tracking_mode::database::MockChunkIterator;
+	use crate::tracking_mode::database::ChunkIteratorNone;
+	use crate::tracking_mode::database::ChunkIterator;
+	use crate::tracking_mode::database::MockDatabase;
 	use crate::tracking_mode::database::SearchResult;
 
 	use crate::util::units::Equatorial;
 	use crate::util::units::Radians;
 	use crate::util::units::Match;
 	use crate::util::list::List;
+	use crate::util::aliases::Decimal;
+
+//###############################################################################################//
+//
+//										Selection
+//
+// pub fn get_selection ( &mut self ) -> &mut T
+//
+//###############################################################################################//
+
+	#[test]
+	// The intensity of a brightness ranking should reach the selection without a photometric search or storing intensities.
+	fn test_get_selection ( )
+	{
+		let stars : Vec<Equatorial> = vec![Equatorial::zero(); 4];
+		let selection = TriangleSelectionRanked::new(TriangleQualityBrightness::<4>::new());
+		let mut iterator: StarTriangleIterator<4, 0, TriangleSelectionRanked<TriangleQualityBrightness<4>, 4>> =
+			StarTriangleIterator::with_selection(selection);
+		iterator.get_selection().quality.set_intensity(&vec![1.0, 2.0, 4.0, 8.0]);
+		iterator.begin(Radians(0.1), &stars);
+
+		let database = MockDatabase::new();
+		let chunk = ChunkIteratorNone::new(&database);
+		assert!(iterator.get_selection().next(&stars, &chunk));
+		assert_eq!(iterator.get_selection().triangle(), StarTriangle(1, 2, 3));
+	}
+
+
 
 //###############################################################################################//
 //
@@ -401,6 +549,39 @@ mod test
 
 
 
+	#[test]
+	// begin_photometric should store the calibration and the intensities.
+	// begin should clear them so the brightness is not checked.
+	fn test_begin_photometric ( )
+	{
+		let stars : Vec<Equatorial>=vec![Equatorial::zero(),Equatorial::zero(),Equatorial::zero()];
+		let intensity = vec![1.0, 2.0, 3.0];
+		let photometry = Photometry{zero_point: 10.0, tolerance: 0.5};
+		let mut iterator: StarTriangleIterator<4, 3> = StarTriangleIterator::new();
+		iterator.begin_photometric(Radians(1.0), photometry, &intensity, &stars);
+		assert_eq!(iterator.photometry, Some(photometry));
+		assert_eq!(iterator.intensity.size(), 3);
+		assert_eq!(iterator.intensity.get(1), 2.0);
+
+		iterator.begin(Radians(1.0), &stars);
+		assert_eq!(iterator.photometry, None);
+		assert_eq!(iterator.intensity.size(), 0);
+	}
+
+	#[test]
+	#[should_panic]
+	// If the intensities do not fit, the brightness would silently not be checked.
+	fn test_begin_photometric_overflow ( )
+	{
+		let stars : Vec<Equatorial>=vec![Equatorial::zero(),Equatorial::zero(),Equatorial::zero()];
+		let intensity = vec![1.0, 2.0, 3.0];
+		let photometry = Photometry{zero_point: 10.0, tolerance: 0.5};
+		let mut iterator: StarTriangleIterator<4> = StarTriangleIterator::new();
+		iterator.begin_photometric(Radians(1.0), photometry, &intensity, &stars);
+	}
+
+
+
 //###############################################################################################//
 //
 //										Next
@@ -609,6 +790,35 @@ mod test
 
 
 
+	#[test]
+	// With begin_photometric, a triangle where the brightness disagrees with the catalogue should be skipped.
+	fn test_next_photometric ( )
+	{
+		let stars = vec![Equatorial::zero(), Equatorial::zero(), Equatorial::zero()];
+		let intensity = vec![100.0, 10.0, 1.0];
+		let photometry = Photometry{zero_point: 0.0, tolerance: 0.5};
+
+		// Triangle 3, 4, 5 is the same shape but the brightness is the wrong way around.
+		let pairs = [StarPair(3, 4), StarPair(0, 1), StarPair(3, 5), StarPair(0, 2), StarPair(4, 5), StarPair(1, 2)];
+		let magnitude = [1.0, 3.5, 6.0, 6.0, 3.5, 1.0];
+		let mut index = 0;
+		let mut database = MockDatabase::new();
+		database.expect_find_close_ref_range().times(3)
+			.returning(move |_, _| { index += 2; return (index - 2)..index; });
+		database.expect_get_pairs().returning(move |i| pairs[i]);
+		database.expect_get_magnitude().returning(move |i| Some(magnitude[i]));
+		let mut chunk = ChunkIteratorNone::new(&database);
+		chunk.next(); // The region is used so the kernel steps to the first triangle.
+
+		let mut iterator: StarTriangleIterator<4, 3> = StarTriangleIterator::new();
+		iterator.begin_photometric(Radians(0.1), photometry, &intensity, &stars);
+		let expect = Match{input: StarTriangle(0, 1, 2), output: StarTriangle(0, 1, 2), weight: 3.0};
+		assert_eq!(Some(expect), iterator.next(&stars, &mut chunk));
+	}
+
+
+
+
 //###############################################################################################//
 //
 //										NextPilot
@@ -964,6 +1174,109 @@ mod test
 	}
 
 
+	#[test]
+	// The triangle from the selection should be searched.
+	fn test_prep_new_kernel_selection ( )
+	{
+		let mut stars : Vec<Equatorial> = Vec::new();
+		stars.push_back(Equatorial{ra: Radians(0.0), dec: Radians(0.0)});
+		stars.push_back(Equatorial{ra: Radians(1.0), dec: Radians(0.0)});
+		stars.push_back(Equatorial{ra: Radians(3.0), dec: Radians(0.0)});
+		stars.push_back(Equatorial{ra: Radians(4.0), dec: Radians(0.0)});
+		let intensity = vec![1.0, 2.0, 3.0, 4.0];
+		let photometry = Photometry{zero_point: 0.0, tolerance: 1.0};
+
+		let mut selection = MockTriangleSelection::new();
+		selection.expect_begin().times(1).returning(|_| return);
+		selection.expect_next().times(1).returning(|_, _| return true);
+		selection.expect_triangle().times(1).returning(|| return StarTriangle(2, 1, 0));
+
+		let mut iterator: StarTriangleIterator<4, 4, MockTriangleSelection> = StarTriangleIterator::with_selection(selection);
+		iterator.begin_photometric(Radians(0.1), photometry, &intensity, &stars);
+
+		let mut chunk = MockChunkIterator::new();
+		chunk.expect_find_close_ref_region().times(3)
+			.returning(|angle, _, found|
+				found.push_back(SearchResult{result: StarPair(0, angle.0.round() as usize), error: 0.0}).expect(""));
+		chunk.expect_next().times(1).returning(|| return false);
+		chunk.expect_begin().times(1).returning(|| return);
+
+		assert!(iterator.prep_new_kernel(&stars, &mut chunk));
+		assert_eq!(iterator.input, StarTriangle(2, 1, 0));
+		assert_eq!(StarPair(0, 2), iterator.pair_a.get(0).result); // (0,3) to (0,1)
+		assert_eq!(StarPair(0, 3), iterator.pair_b.get(0).result); // (0,3) to (0,0)
+		assert_eq!(StarPair(0, 1), iterator.pair_c.get(0).result); // (0,1) to (0,0)
+	}
+
+
+	#[test]
+	// With begin_uncertain, each star pair should be searched with the combined uncertainty.
+	fn test_prep_new_kernel_uncertain ( )
+	{
+		let mut stars : Vec<Equatorial> = Vec::new();
+		stars.push_back(Equatorial{ra: Radians(0.0), dec: Radians(0.0)});
+		stars.push_back(Equatorial{ra: Radians(1.0), dec: Radians(0.0)});
+		stars.push_back(Equatorial{ra: Radians(3.0), dec: Radians(0.0)});
+		let uncertainty = vec![Radians(0.3), Radians(0.4), Radians(1.2)];
+		const NUM_MATCH : usize = 4;
+		let mut iterator: StarTriangleIterator<NUM_MATCH, 3> = StarTriangleIterator::new();
+		iterator.begin_uncertain(Radians(10.0), &uncertainty, &stars);
+
+		let mut chunk = MockChunkIterator::new();
+		chunk.expect_find_close_ref_region().times(3)
+			.returning(|angle, tolerance, found|
+			{
+				// The separation is 1 for (i, j), 3 for (i, k) and 2 for (j, k).
+				let expected = match angle.0.round() as usize
+				{
+					1 => Radians((0.3 * 0.3 + 0.4 * 0.4 as Decimal).sqrt()),
+					3 => Radians((0.3 * 0.3 + 1.2 * 1.2 as Decimal).sqrt()),
+					_ => Radians((0.4 * 0.4 + 1.2 * 1.2 as Decimal).sqrt()),
+				};
+				assert_eq!(tolerance, expected);
+				found.push_back(SearchResult{result: StarPair(0, 0), error: 0.0}).expect("");
+			});
+
+		chunk.expect_next().times(1).returning(|| return false);
+		chunk.expect_begin().times(1).returning(|| return);
+		assert!(iterator.prep_new_kernel(&stars, &mut chunk));
+	}
+
+	#[test]
+	// Without uncertainties, the global tolerance should be used.
+	fn test_prep_new_kernel_uncertain_empty ( )
+	{
+		let mut stars : Vec<Equatorial> = Vec::new();
+		stars.push_back(Equatorial{ra: Radians(0.0), dec: Radians(0.0)});
+		stars.push_back(Equatorial{ra: Radians(1.0), dec: Radians(0.0)});
+		stars.push_back(Equatorial{ra: Radians(3.0), dec: Radians(0.0)});
+		let uncertainty : Vec<Radians> = Vec::new();
+		const NUM_MATCH : usize = 4;
+		let mut iterator: StarTriangleIterator<NUM_MATCH> = StarTriangleIterator::new();
+		iterator.begin_uncertain(Radians(0.123), &uncertainty, &stars);
+
+		let mut chunk = MockChunkIterator::new();
+		chunk.expect_find_close_ref_region().times(3)
+			.returning(|_, _, found| found.push_back(SearchResult{result: StarPair(0, 0), error: 0.0}).expect(""))
+			.withf(|_, tolerance, _| return *tolerance == Radians(0.123) );
+
+		chunk.expect_next().times(1).returning(|| return false);
+		chunk.expect_begin().times(1).returning(|| return);
+		assert!(iterator.prep_new_kernel(&stars, &mut chunk));
+	}
+
+	#[test]
+	#[should_panic]
+	// If the uncertainties do not fit, they would silently not be used.
+	fn test_begin_uncertain_overflow ( )
+	{
+		let stars : Vec<Equatorial> = vec![Equatorial::zero(), Equatorial::zero(), Equatorial::zero()];
+		let uncertainty = vec![Radians(0.3), Radians(0.4), Radians(1.2)];
+		let mut iterator: StarTriangleIterator<4, 2> = StarTriangleIterator::new();
+		iterator.begin_uncertain(Radians(0.123), &uncertainty, &stars);
+	}
+
+
 	
 //###############################################################################################//
 //
@@ -1063,10 +1376,65 @@ mod test
 		
 		assert!(!iterator.prep_new_pilot(&stars, &mut chunk));
 	}
-	
-	
-	
 
 
 
+//###############################################################################################//
+//
+//										Photometric
+//
+// 	fn photometric ( &self, &[usize], &[usize], &dyn ChunkIterator ) -> bool
+//
+//###############################################################################################//
+
+	#[test]
+	// If begin_photometric was not used, the database should not be touched.
+	fn test_photometric_disabled ( )
+	{
+		let stars : Vec<Equatorial> = vec![Equatorial::zero(), Equatorial::zero(), Equatorial::zero()];
+		let mut iterator: StarTriangleIterator<4, 3> = StarTriangleIterator::new();
+		iterator.begin(Radians(0.1), &stars);
+		let chunk = MockChunkIterator::new();
+		assert!(iterator.photometric(&[0, 1, 2], &[0, 1, 2], &chunk));
+	}
+
+	#[test]
+	// Every star and every pair of stars must agree with the catalogue (this is how the pilot is checked).
+	fn test_photometric ( )
+	{
+		let stars : Vec<Equatorial> = vec![Equatorial::zero(); 4];
+		let intensity = vec![100.0, 10.0, 1.0, 1000.0];
+		let photometry = Photometry{zero_point: 10000.0, tolerance: 0.5};
+		let mut iterator: StarTriangleIterator<4, 4> = StarTriangleIterator::new();
+		iterator.begin_photometric(Radians(0.1), photometry, &intensity, &stars);
+
+		let magnitude = [5.0, 7.5, 10.0, 2.5, 7.5];
+		let mut database = MockDatabase::new();
+		database.expect_get_magnitude().returning(move |i| Some(magnitude[i]));
+		let chunk = ChunkIteratorNone::new(&database);
+
+		assert!(iterator.photometric(&[0, 1, 2, 3], &[0, 1, 2, 3], &chunk));
+		assert!(iterator.photometric(&[3, 0], &[3, 0], &chunk));
+		assert!(!iterator.photometric(&[0, 1, 2, 3], &[1, 0, 2, 3], &chunk));
+		assert!(!iterator.photometric(&[0, 1, 2, 3], &[0, 1, 2, 4], &chunk));
+	}
+
+	#[test]
+	// Stars without an intensity or a magnitude should not be checked.
+	fn test_photometric_unknown ( )
+	{
+		let stars : Vec<Equatorial> = vec![Equatorial::zero(); 3];
+		let intensity = vec![100.0, 10.0];
+		let photometry = Photometry{zero_point: 10000.0, tolerance: 0.5};
+		let mut iterator: StarTriangleIterator<4, 2> = StarTriangleIterator::new();
+		iterator.begin_photometric(Radians(0.1), photometry, &intensity, &stars);
+
+		// Star 2 has no intensity, catalogue 3 has no magnitude.
+		let mut database = MockDatabase::new();
+		database.expect_get_magnitude().returning(|i| if i == 3 { None } else { Some(5.0 + 2.5 * i as Decimal) });
+		let chunk = ChunkIteratorNone::new(&database);
+		assert!(iterator.photometric(&[0, 1, 2], &[0, 1, 0], &chunk));
+		assert!(iterator.photometric(&[0, 1], &[0, 3], &chunk));
+		assert!(!iterator.photometric(&[0, 1], &[1, 0], &chunk));
+	}
 }
@@ -0,0 +1,262 @@
+//! Implementation of [TriangleAngles](crate::tracking_mode::TriangleAngles).
+use crate::core_include::*;
+
+use crate::tracking_mode::TriangleAngles;
+use crate::tracking_mode::StarTriangle;
+
+use crate::util::aliases::Decimal;
+use crate::util::aliases::M_PI;
+use crate::util::units::Equatorial;
+use crate::util::units::Vector2;
+use crate::util::units::Radians;
+
+use crate::util::Maths;
+
+impl TriangleAngles
+{
+	/// Finds the interior angles of a triangle from the length of each side (law of cosines).
+	/// # Arguments
+	/// * `sides` - The length of the side opposite each vertex (sides\[0\] is opposite vertex 0).
+	/// # Returns
+	/// The angles and the vertices in the order (smallest angle, middle angle, largest angle).
+	/// Use `reorder` to apply this order to a triangle.
+	/// If the triangle has a side of 0 length, all angles are 0.
+	pub fn from_sides ( sides: [Decimal; 3] ) -> (Self, StarTriangle<usize>)
+	{
+		let mut angles: [Decimal; 3] = [0.0; 3];
+		for i in 0..3
+		{
+			let opposite = sides[i];
+			let side_a   = sides[(i + 1) % 3];
+			let side_b   = sides[(i + 2) % 3];
+			if side_a * side_b <= 0.0
+			{
+				return (Self{smallest: Radians(0.0), middle: Radians(0.0)}, StarTriangle(0, 1, 2));
+			}
+
+			let mut cos = (side_a * side_a + side_b * side_b - opposite * opposite) / (2.0 * side_a * side_b);
+			if cos < -1.0 { cos = -1.0; }
+			if 1.0 < cos  { cos = 1.0;  }
+			angles[i] = cos.acos();
+		}
+
+		// Sorts the 3 indices by angle.
+		let mut order = [0, 1, 2];
+		for i in 0..3
+		{
+			for j in (i + 1)..3
+			{
+				if angles[order[j]] < angles[order[i]] { order.swap(i, j); }
+			}
+		}
+
+		let result = Self{smallest: Radians(angles[order[0]]), middle: Radians(angles[order[1]])};
+		return (result, StarTriangle(order[0], order[1], order[2]));
+	}
+
+
+	/// Finds the interior angles of a triangle on the image plane.
+	/// # Arguments
+	/// * `points` - The position of each star on the image.
+	/// # Returns
+	/// The angles and the order of the vertices (smallest angle, middle angle, largest angle).
+	pub fn from_image ( points: StarTriangle<Vector2> ) -> (Self, StarTriangle<usize>)
+	{
+		let sides = [
+			(points.1 - points.2).magnitude(),
+			(points.0 - points.2).magnitude(),
+			(points.0 - points.1).magnitude()];
+		return Self::from_sides(sides);
+	}
+
+
+	/// Finds the interior angles of a triangle on the celestial sphere.
+	/// The angular separation is used as the side length.
+	/// For small triangles, this is close to the triangle the camera will see.
+	/// # Arguments
+	/// * `points` - The position of each star.
+	/// # Returns
+	/// The angles and the order of the vertices (smallest angle, middle angle, largest angle).
+	pub fn from_equatorial ( points: StarTriangle<Equatorial> ) -> (Self, StarTriangle<usize>)
+	{
+		let sides = [
+			points.1.angle_distance(points.2).0,
+			points.0.angle_distance(points.2).0,
+			points.0.angle_distance(points.1).0];
+		return Self::from_sides(sides);
+	}
+
+
+	/// Reorders a triangle with the order provided by `from_sides`.
+	/// # Arguments
+	/// * `triangle` - The triangle to reorder.
+	/// * `order`    - The output of from_sides, from_image or from_equatorial.
+	pub fn reorder <T: Copy> ( triangle: StarTriangle<T>, order: StarTriangle<usize> ) -> StarTriangle<T>
+	{
+		let vertices = [triangle.0, triangle.1, triangle.2];
+		return StarTriangle(vertices[order.0], vertices[order.1], vertices[order.2]);
+	}
+
+
+	/// The largest interior angle.
+	pub fn largest ( &self ) -> Radians
+	{
+		return Radians(M_PI) - self.smallest - self.middle;
+	}
+
+
+	/// If any of the angles are within tolerance of each other, the order of the vertices cannot be trusted.
+	/// If the smallest angle is within tolerance of 0, the triangle is a line and cannot be trusted.
+	pub fn is_ambiguous ( &self, tolerance: Radians ) -> bool
+	{
+		return
+			self.smallest.0 < tolerance.0 ||
+			(self.middle - self.smallest).0 < tolerance.0 ||
+			(self.largest() - self.middle).0 < tolerance.0;
+	}
+
+
+	/// Returns true if both angles are within tolerance.
+	pub fn is_close ( &self, other: &Self, tolerance: Radians ) -> bool
+	{
+		return
+			(self.smallest - other.smallest).abs() < tolerance.0 &&
+			(self.middle   - other.middle  ).abs() < tolerance.0;
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::TriangleAngles;
+	use crate::tracking_mode::StarTriangle;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::Vector2;
+	use crate::util::units::Radians;
+	use crate::util::units::Degrees;
+	use crate::util::test::TestEqual;
+
+//###############################################################################################//
+//
+//										Construction
+//
+// pub fn from_sides      ( [Decimal; 3] )           -> (Self, StarTriangle<usize>)
+// pub fn from_image      ( StarTriangle<Vector2> )    -> (Self, StarTriangle<usize>)
+// pub fn from_equatorial ( StarTriangle<Equatorial> ) -> (Self, StarTriangle<usize>)
+// pub fn reorder         ( StarTriangle<T>, StarTriangle<usize> ) -> StarTriangle<T>
+//
+//###############################################################################################//
+//										~ from_sides ~											 //
+	#[test]
+	// A 3, 4, 5 triangle has a right angle opposite 5.
+	fn test_from_sides ( )
+	{
+		let (angles, order) = TriangleAngles::from_sides([5.0, 3.0, 4.0]);
+		angles.smallest.0.assert_close(&Degrees(36.8698976).as_radians().0, 0.00001);
+		assert!(angles.largest().test_close(&Degrees(90.0).as_radians(), 0.00001));
+		assert_eq!(order, StarTriangle(1, 2, 0));
+	}
+
+	#[test]
+	// If a side is 0, the triangle is invalid.
+	fn test_from_sides_zero ( )
+	{
+		let (angles, _) = TriangleAngles::from_sides([0.0, 3.0, 4.0]);
+		assert_eq!(angles.smallest, Radians(0.0));
+		assert_eq!(angles.middle, Radians(0.0));
+	}
+
+//										~ from_image ~											 //
+	#[test]
+	// Scaling the image should not change the angles.
+	fn test_from_image_scale ( )
+	{
+		let a = Vector2{x: 0.0, y: 0.0};
+		let b = Vector2{x: 3.0, y: 1.0};
+		let c = Vector2{x: 1.0, y: 5.0};
+		let (angles, order) = TriangleAngles::from_image(StarTriangle(a, b, c));
+		let (scaled, order_scaled) = TriangleAngles::from_image(StarTriangle(a * 7.0, b * 7.0, c * 7.0));
+		assert!(angles.is_close(&scaled, Radians(0.00001)));
+		assert_eq!(order, order_scaled);
+	}
+
+//										~ from_equatorial ~										 //
+	#[test]
+	// The angles of a small triangle on the sphere should add to 180 degrees.
+	fn test_from_equatorial ( )
+	{
+		let a = Equatorial{ra: Degrees(0.0).as_radians(), dec: Degrees(0.0).as_radians()};
+		let b = Equatorial{ra: Degrees(1.0).as_radians(), dec: Degrees(0.0).as_radians()};
+		let c = Equatorial{ra: Degrees(0.0).as_radians(), dec: Degrees(1.0).as_radians()};
+		let (angles, order) = TriangleAngles::from_equatorial(StarTriangle(a, b, c));
+		angles.smallest.0.assert_close(&Degrees(45.0).as_radians().0, 0.001);
+		angles.largest().0.assert_close(&Degrees(90.0).as_radians().0, 0.001);
+		assert_eq!(order.2, 0);
+	}
+
+//										~ reorder ~												 //
+	#[test]
+	fn test_reorder ( )
+	{
+		let triangle = StarTriangle(10, 11, 12);
+		assert_eq!(TriangleAngles::reorder(triangle, StarTriangle(2, 0, 1)), StarTriangle(12, 10, 11));
+	}
+
+//###############################################################################################//
+//
+//										Comparison
+//
+// pub fn largest      ( &self ) -> Radians
+// pub fn is_ambiguous ( &self, Radians ) -> bool
+// pub fn is_close     ( &self, &Self, Radians ) -> bool
+//
+//###############################################################################################//
+//										~ largest ~												 //
+	#[test]
+	fn test_largest ( )
+	{
+		let angles = TriangleAngles{smallest: Degrees(30.0).as_radians(), middle: Degrees(60.0).as_radians()};
+		assert!(angles.largest().test_close(&Degrees(90.0).as_radians(), 0.00001));
+	}
+
+//										~ is_ambiguous ~										 //
+	#[test]
+	// Similar angles or a very thin triangle is ambiguous.
+	fn test_is_ambiguous ( )
+	{
+		let tolerance = Degrees(1.0).as_radians();
+		let valid    = TriangleAngles{smallest: Degrees(30.0).as_radians(), middle: Degrees(60.0).as_radians()};
+		let thin     = TriangleAngles{smallest: Degrees(0.5).as_radians(),  middle: Degrees(60.0).as_radians()};
+		let same_1   = TriangleAngles{smallest: Degrees(30.0).as_radians(), middle: Degrees(30.5).as_radians()};
+		let same_2   = TriangleAngles{smallest: Degrees(30.0).as_radians(), middle: Degrees(74.8).as_radians()};
+		assert!(!valid.is_ambiguous(tolerance));
+		assert!(thin.is_ambiguous(tolerance));
+		assert!(same_1.is_ambiguous(tolerance));
+		assert!(same_2.is_ambiguous(tolerance));
+	}
+
+//										~ is_close ~											 //
+	#[test]
+	// Both angles must be within tolerance.
+	fn test_is_close ( )
+	{
+		let tolerance = Degrees(1.0).as_radians();
+		let a = TriangleAngles{smallest: Degrees(30.0).as_radians(), middle: Degrees(60.0).as_radians()};
+		let b = TriangleAngles{smallest: Degrees(30.9).as_radians(), middle: Degrees(59.1).as_radians()};
+		let c = TriangleAngles{smallest: Degrees(30.0).as_radians(), middle: Degrees(61.1).as_radians()};
+		assert!(a.is_close(&b, tolerance));
+		assert!(!a.is_close(&c, tolerance));
+	}
+}
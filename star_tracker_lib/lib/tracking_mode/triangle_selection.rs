@@ -0,0 +1,519 @@
+//! Implementation of [TriangleSelection](crate::tracking_mode::TriangleSelection) and [TriangleQuality](crate::tracking_mode::TriangleQuality).
+use crate::tracking_mode::TriangleSelection;
+use crate::tracking_mode::TriangleSelectionRanked;
+use crate::tracking_mode::TriangleQuality;
+use crate::tracking_mode::TriangleQualityBrightness;
+use crate::tracking_mode::TriangleQualityArea;
+use crate::tracking_mode::TriangleQualityDistinct;
+use crate::tracking_mode::KernelIterator;
+use crate::tracking_mode::StarTriangle;
+
+use crate::tracking_mode::database::Database;
+use crate::tracking_mode::database::ChunkIterator;
+
+use crate::util::units::Equatorial;
+use crate::util::list::ArrayList;
+use crate::util::list::List;
+use crate::util::aliases::Decimal;
+
+
+impl TriangleSelection for KernelIterator
+{
+	/// Restarts the kernel with the number of stars.
+	fn begin ( &mut self, stars: &dyn List<Equatorial> )
+	{
+		*self = KernelIterator::new(stars.size());
+	}
+
+	/// Steps the kernel, the stars and database are not used.
+	fn next ( &mut self, _stars: &dyn List<Equatorial>, _database: &dyn ChunkIterator ) -> bool
+	{
+		return self.step();
+	}
+
+	/// The current i, j and k of the kernel.
+	fn triangle ( &self ) -> StarTriangle<usize> { return StarTriangle(self.i, self.j, self.k); }
+}
+
+
+
+
+impl <Q: TriangleQuality, const N: usize> TriangleSelectionRanked <Q, N>
+{
+	/// Creates a new selection.
+	/// CALL `begin` AFTER THIS.
+	/// # Arguments
+	/// * `quality` - The score of each triangle, higher is tried first.
+	pub fn new ( quality: Q ) -> Self
+	{
+		return Self{quality: quality, ranked: ArrayList::new(), index: 0, kernel: KernelIterator::new(0), size: 0, is_ranked: false};
+	}
+
+
+	/// Scores every triangle and keeps the best `N` sorted from the highest quality.
+	/// # Arguments
+	/// * `stars`    - The observed stars.
+	/// * `database` - The database being searched.
+	fn rank ( &mut self, stars: &dyn List<Equatorial>, database: &dyn Database )
+	{
+		// Equal triangles stay in the order of the kernel.
+		fn in_order ( a: &(Decimal, StarTriangle<usize>), b: &(Decimal, StarTriangle<usize>) ) -> bool
+		{
+			return b.0 < a.0;
+		}
+
+		self.ranked.clear();
+		let mut kernel = KernelIterator::new(self.size);
+		while kernel.step()
+		{
+			let triangle = StarTriangle(kernel.i, kernel.j, kernel.k);
+			let quality  = self.quality.quality(triangle, stars, database);
+			self.ranked.slot((quality, triangle), in_order);
+		}
+		self.is_ranked = true;
+	}
+
+
+	/// True if the triangle is one of the ranked triangles.
+	/// # Arguments
+	/// * `triangle` - The triangle to find.
+	fn is_ranked_triangle ( &self, triangle: StarTriangle<usize> ) -> bool
+	{
+		for i in 0..self.ranked.size()
+		{
+			if self.ranked.get(i).1 == triangle { return true; }
+		}
+		return false;
+	}
+}
+
+
+impl <Q: TriangleQuality, const N: usize> TriangleSelection for TriangleSelectionRanked <Q, N>
+{
+	/// Restarts the selection, the triangles are ranked on the next call to `next`.
+	fn begin ( &mut self, stars: &dyn List<Equatorial> )
+	{
+		self.ranked.clear();
+		self.index     = 0;
+		self.size      = stars.size();
+		self.kernel    = KernelIterator::new(stars.size());
+		self.is_ranked = false;
+	}
+
+	/// Steps to the next best triangle.  
+	/// Once the ranked triangles are used, the triangles which were not ranked are stepped in the order of the kernel.
+	fn next ( &mut self, stars: &dyn List<Equatorial>, database: &dyn ChunkIterator ) -> bool
+	{
+		if !self.is_ranked
+		{
+			self.rank(stars, database.get_database());
+		}
+		if self.index < self.ranked.size()
+		{
+			self.index += 1;
+			return true;
+		}
+		while self.kernel.step()
+		{
+			if !self.is_ranked_triangle(self.kernel.triangle())
+			{
+				self.index = self.ranked.size() + 1;
+				return true;
+			}
+		}
+		return false;
+	}
+
+	/// The current triangle, StarTriangle(0, 0, 0) before `next`.
+	fn triangle ( &self ) -> StarTriangle<usize>
+	{
+		if self.index == 0 { return StarTriangle(0, 0, 0); }
+		if self.ranked.size() < self.index { return self.kernel.triangle(); }
+		return self.ranked.get(self.index - 1).1;
+	}
+}
+
+
+
+
+impl <const N: usize> TriangleQualityBrightness <N>
+{
+	/// Creates the quality with no intensities (every triangle is 0).
+	pub fn new ( ) -> Self
+	{
+		return Self{intensity: ArrayList::new()};
+	}
+
+
+	/// Sets the intensity of each star, call this before each search.
+	/// Only the first `N` intensities are stored.
+	/// # Arguments
+	/// * `intensity` - The intensity of each star (same index as the stars searched).
+	pub fn set_intensity ( &mut self, intensity: &dyn List<Decimal> )
+	{
+		self.intensity.clear();
+		for i in 0..intensity.size()
+		{
+			if self.intensity.push_back(intensity.get(i)).is_err() { break; }
+		}
+	}
+}
+
+
+impl <const N: usize> TriangleQuality for TriangleQualityBrightness <N>
+{
+	/// The sum of the intensity of each star.
+	fn quality ( &self, triangle: StarTriangle<usize>, _stars: &dyn List<Equatorial>, _database: &dyn Database ) -> Decimal
+	{
+		let mut sum = 0.0;
+		for star in [triangle.0, triangle.1, triangle.2]
+		{
+			if star < self.intensity.size() { sum += self.intensity.get(star); }
+		}
+		return sum;
+	}
+}
+
+
+impl TriangleQuality for TriangleQualityArea
+{
+	/// The magnitude of the scalar triple product (the same as [Specularity](crate::tracking_mode::Specularity)).
+	fn quality ( &self, triangle: StarTriangle<usize>, stars: &dyn List<Equatorial>, _database: &dyn Database ) -> Decimal
+	{
+		let a = stars.get(triangle.0).to_vector3();
+		let b = stars.get(triangle.1).to_vector3();
+		let c = stars.get(triangle.2).to_vector3();
+		return b.cross(c).dot(a).abs();
+	}
+}
+
+
+impl TriangleQuality for TriangleQualityDistinct
+{
+	/// `1 / (1 + matches)` where matches is the number of database pairs close to each side.
+	fn quality ( &self, triangle: StarTriangle<usize>, stars: &dyn List<Equatorial>, database: &dyn Database ) -> Decimal
+	{
+		let a = stars.get(triangle.0);
+		let b = stars.get(triangle.1);
+		let c = stars.get(triangle.2);
+
+		let mut matches = 0;
+		matches += database.find_close_ref_range(a.angle_distance(b), self.tolerance).len();
+		matches += database.find_close_ref_range(a.angle_distance(c), self.tolerance).len();
+		matches += database.find_close_ref_range(b.angle_distance(c), self.tolerance).len();
+		return 1.0 / (1.0 + matches as Decimal);
+	}
+}
+
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::TriangleSelection;
+	use crate::tracking_mode::TriangleSelectionRanked;
+	use crate::tracking_mode::TriangleQuality;
+	use crate::tracking_mode::MockTriangleQuality;
+	use crate::tracking_mode::TriangleQualityBrightness;
+	use crate::tracking_mode::TriangleQualityArea;
+	use crate::tracking_mode::TriangleQualityDistinct;
+	use crate::tracking_mode::KernelIterator;
+	use crate::tracking_mode::StarTriangle;
+
+	use crate::tracking_mode::database::MockDatabase;
+	use crate::tracking_mode::database::ChunkIteratorNone;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::Radians;
+	use crate::util::units::Degrees;
+	use crate::util::aliases::Decimal;
+	use crate::util::test::TestEqual;
+	use crate::util::list::List;
+
+	// 4 stars along the equator.
+	#[coverage(off)]
+	fn stars ( ) -> Vec<Equatorial>
+	{
+		return vec![
+			Equatorial{ra: Degrees(0.0).as_radians(), dec: Degrees(0.0).as_radians()},
+			Equatorial{ra: Degrees(1.0).as_radians(), dec: Degrees(0.0).as_radians()},
+			Equatorial{ra: Degrees(2.0).as_radians(), dec: Degrees(0.0).as_radians()},
+			Equatorial{ra: Degrees(3.0).as_radians(), dec: Degrees(0.0).as_radians()}];
+	}
+
+	// Collects every triangle of the selection.
+	#[coverage(off)]
+	fn collect ( selection: &mut dyn TriangleSelection, stars: &Vec<Equatorial> ) -> Vec<StarTriangle<usize>>
+	{
+		let database = MockDatabase::new();
+		let chunk = ChunkIteratorNone::new(&database);
+		let mut out = Vec::new();
+		while selection.next(stars, &chunk) { out.push(selection.triangle()); }
+		return out;
+	}
+
+
+//###############################################################################################//
+//
+//										KernelIterator
+//
+// fn begin    ( &mut self, &dyn List<Equatorial> )
+// fn next     ( &mut self, &dyn List<Equatorial>, &dyn ChunkIterator ) -> bool
+// fn triangle ( &self ) -> StarTriangle<usize>
+//
+//###############################################################################################//
+//										~ begin ~												 //
+	#[test]
+	// Begin should restart the kernel with the number of stars.
+	fn test_kernel_begin ( )
+	{
+		let mut kernel = KernelIterator::new(0);
+		TriangleSelection::begin(&mut kernel, &stars());
+		assert_eq!(kernel.size, 4);
+		assert_eq!(kernel.triangle(), StarTriangle(0, 0, 0));
+	}
+
+//										~ next ~												 //
+	#[test]
+	// The selection should be the same as stepping the kernel.
+	fn test_kernel_next ( )
+	{
+		let mut kernel = KernelIterator::new(0);
+		TriangleSelection::begin(&mut kernel, &stars());
+		let expected = vec![StarTriangle(0, 1, 2), StarTriangle(1, 2, 3), StarTriangle(0, 1, 3), StarTriangle(0, 2, 3)];
+		assert_eq!(collect(&mut kernel, &stars()), expected);
+	}
+
+
+
+//###############################################################################################//
+//
+//										TriangleSelectionRanked
+//
+// pub fn new             ( Q ) -> Self
+// fn rank                ( &mut self, &dyn List<Equatorial>, &dyn Database )
+// fn is_ranked_triangle  ( &self, StarTriangle<usize> ) -> bool
+// fn begin               ( &mut self, &dyn List<Equatorial> )
+// fn next                ( &mut self, &dyn List<Equatorial>, &dyn ChunkIterator ) -> bool
+// fn triangle            ( &self ) -> StarTriangle<usize>
+//
+//###############################################################################################//
+//										~ new ~													 //
+	#[test]
+	fn test_ranked_new ( )
+	{
+		let selection : TriangleSelectionRanked<TriangleQualityArea, 4> = TriangleSelectionRanked::new(TriangleQualityArea());
+		assert_eq!(selection.ranked.size(), 0);
+		assert_eq!(selection.index, 0);
+		assert_eq!(selection.size, 0);
+		assert!(!selection.is_ranked);
+	}
+
+//										~ rank ~												 //
+	#[test]
+	// The triangles should be sorted from the highest quality, equal quality in the order of the kernel.
+	fn test_ranked_rank ( )
+	{
+		let mut quality = MockTriangleQuality::new();
+		quality.expect_quality().times(4).returning(|t, _, _| return [0.0, 0.5, 0.0, 1.0][t.0 + t.1 + t.2 - 3]);
+		let mut selection : TriangleSelectionRanked<MockTriangleQuality, 4> = TriangleSelectionRanked::new(quality);
+		selection.begin(&stars());
+		selection.rank(&stars(), &MockDatabase::new());
+		assert!(selection.is_ranked);
+		assert_eq!(selection.ranked.get(0), (1.0, StarTriangle(1, 2, 3)));
+		assert_eq!(selection.ranked.get(1), (0.5, StarTriangle(0, 1, 3)));
+		assert_eq!(selection.ranked.get(2), (0.0, StarTriangle(0, 1, 2)));
+		assert_eq!(selection.ranked.get(3), (0.0, StarTriangle(0, 2, 3)));
+	}
+
+	#[test]
+	// Only the best N triangles should be kept.
+	fn test_ranked_rank_overflow ( )
+	{
+		let mut quality = MockTriangleQuality::new();
+		quality.expect_quality().times(4).returning(|t, _, _| return (t.0 + t.1 + t.2) as Decimal);
+		let mut selection : TriangleSelectionRanked<MockTriangleQuality, 2> = TriangleSelectionRanked::new(quality);
+		selection.begin(&stars());
+		selection.rank(&stars(), &MockDatabase::new());
+		assert_eq!(selection.ranked.size(), 2);
+		assert_eq!(selection.ranked.get(0).1, StarTriangle(1, 2, 3));
+		assert_eq!(selection.ranked.get(1).1, StarTriangle(0, 2, 3));
+	}
+
+//										~ is_ranked_triangle ~									 //
+	#[test]
+	fn test_ranked_is_ranked_triangle ( )
+	{
+		let mut quality = MockTriangleQuality::new();
+		quality.expect_quality().times(4).returning(|t, _, _| return (t.0 + t.1 + t.2) as Decimal);
+		let mut selection : TriangleSelectionRanked<MockTriangleQuality, 2> = TriangleSelectionRanked::new(quality);
+		selection.begin(&stars());
+		assert!(!selection.is_ranked_triangle(StarTriangle(1, 2, 3)));
+		selection.rank(&stars(), &MockDatabase::new());
+		assert!(selection.is_ranked_triangle(StarTriangle(1, 2, 3)));
+		assert!(selection.is_ranked_triangle(StarTriangle(0, 2, 3)));
+		assert!(!selection.is_ranked_triangle(StarTriangle(0, 1, 2)));
+	}
+
+//										~ begin ~												 //
+	#[test]
+	// Begin should reset the selection so it is ranked again.
+	fn test_ranked_begin ( )
+	{
+		let mut selection : TriangleSelectionRanked<TriangleQualityArea, 4> = TriangleSelectionRanked::new(TriangleQualityArea());
+		selection.begin(&stars());
+		collect(&mut selection, &stars());
+		selection.begin(&vec![Equatorial::zero(); 3]);
+		assert_eq!(selection.ranked.size(), 0);
+		assert_eq!(selection.index, 0);
+		assert_eq!(selection.size, 3);
+		assert_eq!(selection.kernel.size, 3);
+		assert!(!selection.is_ranked);
+	}
+
+//										~ next ~												 //
+	#[test]
+	// Every triangle should be returned once from the highest quality, the quality is only found once.
+	fn test_ranked_next ( )
+	{
+		let mut quality = MockTriangleQuality::new();
+		quality.expect_quality().times(4).returning(|t, _, _| return [0.0, 0.5, 0.0, 1.0][t.0 + t.1 + t.2 - 3]);
+		let mut selection : TriangleSelectionRanked<MockTriangleQuality, 4> = TriangleSelectionRanked::new(quality);
+		selection.begin(&stars());
+		let expected = vec![StarTriangle(1, 2, 3), StarTriangle(0, 1, 3), StarTriangle(0, 1, 2), StarTriangle(0, 2, 3)];
+		assert_eq!(collect(&mut selection, &stars()), expected);
+		assert!(!selection.next(&stars(), &ChunkIteratorNone::new(&MockDatabase::new())));
+	}
+
+	#[test]
+	// Once the best N triangles are used, the rest should be tried in the order of the kernel.
+	fn test_ranked_next_fallback ( )
+	{
+		let mut quality = MockTriangleQuality::new();
+		quality.expect_quality().times(4).returning(|t, _, _| return (t.0 + t.1 + t.2) as Decimal);
+		let mut selection : TriangleSelectionRanked<MockTriangleQuality, 2> = TriangleSelectionRanked::new(quality);
+		selection.begin(&stars());
+		let expected = vec![StarTriangle(1, 2, 3), StarTriangle(0, 2, 3), StarTriangle(0, 1, 2), StarTriangle(0, 1, 3)];
+		assert_eq!(collect(&mut selection, &stars()), expected);
+		assert!(!selection.next(&stars(), &ChunkIteratorNone::new(&MockDatabase::new())));
+	}
+
+	#[test]
+	// There are no triangles with less than 3 stars.
+	fn test_ranked_next_insufficient ( )
+	{
+		let mut selection : TriangleSelectionRanked<TriangleQualityArea, 4> = TriangleSelectionRanked::new(TriangleQualityArea());
+		let stars = vec![Equatorial::zero(); 2];
+		selection.begin(&stars);
+		assert_eq!(collect(&mut selection, &stars).len(), 0);
+	}
+
+//										~ triangle ~											 //
+	#[test]
+	// Before next, the triangle is StarTriangle(0, 0, 0) like the kernel.
+	fn test_ranked_triangle ( )
+	{
+		let mut selection : TriangleSelectionRanked<TriangleQualityArea, 4> = TriangleSelectionRanked::new(TriangleQualityArea());
+		selection.begin(&stars());
+		assert_eq!(selection.triangle(), StarTriangle(0, 0, 0));
+	}
+
+
+
+//###############################################################################################//
+//
+//										TriangleQuality
+//
+// pub fn new           ( ) -> Self
+// pub fn set_intensity ( &mut self, &dyn List<Decimal> )
+// fn quality           ( &self, StarTriangle<usize>, &dyn List<Equatorial>, &dyn Database ) -> Decimal
+//
+//###############################################################################################//
+//										~ new ~													 //
+	#[test]
+	fn test_brightness_new ( )
+	{
+		let quality : TriangleQualityBrightness<4> = TriangleQualityBrightness::new();
+		assert_eq!(quality.intensity.size(), 0);
+	}
+
+//										~ set_intensity ~										 //
+	#[test]
+	// Only the first N intensities should be stored, the previous intensities are replaced.
+	fn test_brightness_set_intensity ( )
+	{
+		let mut quality : TriangleQualityBrightness<2> = TriangleQualityBrightness::new();
+		quality.set_intensity(&vec![1.0, 2.0, 3.0]);
+		assert_eq!(quality.intensity.size(), 2);
+		assert_eq!(quality.intensity.get(1), 2.0);
+		quality.set_intensity(&vec![5.0]);
+		assert_eq!(quality.intensity.size(), 1);
+		assert_eq!(quality.intensity.get(0), 5.0);
+	}
+
+//										~ quality ~												 //
+	#[test]
+	// The intensity should be summed, any star without an intensity is 0.
+	fn test_quality_brightness ( )
+	{
+		let mut quality : TriangleQualityBrightness<4> = TriangleQualityBrightness::new();
+		let db = MockDatabase::new();
+		quality.quality(StarTriangle(0, 2, 3), &stars(), &db).assert_close(&0.0, 0.0001);
+		quality.set_intensity(&vec![1.0, 2.0, 4.0]);
+		quality.quality(StarTriangle(0, 1, 2), &stars(), &db).assert_close(&7.0, 0.0001);
+		quality.quality(StarTriangle(0, 2, 3), &stars(), &db).assert_close(&5.0, 0.0001);
+	}
+
+	#[test]
+	// The brightest triangle should be tried first without a photometric search.
+	fn test_quality_brightness_ranked ( )
+	{
+		let mut selection = TriangleSelectionRanked::<_, 4>::new(TriangleQualityBrightness::<4>::new());
+		selection.quality.set_intensity(&vec![1.0, 2.0, 4.0, 8.0]);
+		selection.begin(&stars());
+		assert_eq!(collect(&mut selection, &stars())[0], StarTriangle(1, 2, 3));
+	}
+
+	#[test]
+	// A straight line has no area, a larger triangle has a larger quality.
+	fn test_quality_area ( )
+	{
+		let mut stars = stars();
+		let quality = TriangleQualityArea();
+		let db = MockDatabase::new();
+		quality.quality(StarTriangle(0, 1, 2), &stars, &db).assert_close(&0.0, 0.0000001);
+
+		stars.push(Equatorial{ra: Degrees(0.0).as_radians(), dec: Degrees(1.0).as_radians()});
+		stars.push(Equatorial{ra: Degrees(0.0).as_radians(), dec: Degrees(2.0).as_radians()});
+		let small = quality.quality(StarTriangle(0, 1, 4), &stars, &db);
+		let large = quality.quality(StarTriangle(0, 2, 5), &stars, &db);
+		let flip  = quality.quality(StarTriangle(0, 4, 1), &stars, &db);
+		assert!(0.0 < small);
+		assert!(small < large);
+		small.assert_close(&flip, 0.0000001);
+	}
+
+	#[test]
+	// The quality should decrease with the number of database matches of all 3 sides.
+	fn test_quality_distinct ( )
+	{
+		let mut db = MockDatabase::new();
+		db.expect_find_close_ref_range()
+			.times(3)
+			.withf(|_, tolerance| return *tolerance == Radians(0.1))
+			.returning(|find, _| if find < Degrees(1.5).as_radians() { return 0..2 } else { return 0..3 });
+		let quality = TriangleQualityDistinct{tolerance: Radians(0.1)};
+		// Sides of 1, 2 and 1 degrees.
+		quality.quality(StarTriangle(0, 1, 2), &stars(), &db).assert_close(&(1.0 / 8.0), 0.0001);
+	}
+}
@@ -0,0 +1,514 @@
+//! Implementation of [Verification](crate::tracking_mode::Verification).
+use crate::core_include::*;
+
+use crate::tracking_mode::VerificationResult;
+use crate::tracking_mode::Verification;
+use crate::tracking_mode::database::TiledDatabase;
+
+use crate::util::linear_lookup::LinearLookup;
+use crate::util::aliases::Decimal;
+use crate::util::units::Equatorial;
+use crate::util::units::Quaternion;
+use crate::util::units::Vector3;
+use crate::util::units::Radians;
+use crate::util::units::Match;
+use crate::util::list::List;
+
+impl Verification
+{
+	/// Checks how many observed stars agree with the catalogue when rotated by `attitude`.  
+	/// Only the catalogue stars in `region` are compared, use [region](crate::tracking_mode::Verification::region)
+	/// or [region_tiled](crate::tracking_mode::Verification::region_tiled) to find them.  
+	/// If every observed star was identified, nothing is left to test and the attitude can not be verified (false_probability is 1).  
+	/// The identified stars always agree as the attitude was found from them.
+	/// # Arguments
+	/// * `stars`      - The observed stars (projected onto a unit sphere in the camera frame).
+	/// * `identified` - The stars used to find the attitude (input: index of `stars`), these are not tested.
+	/// * `catalogue`  - The catalogue the attitude was found from.
+	/// * `region`     - The catalogue stars (index of `catalogue`) in the field of view.
+	/// * `attitude`   - The rotation from inertial to camera (same as [AttitudeDetermination::estimate](crate::attitude_determination::AttitudeDetermination::estimate)).
+	/// # Returns
+	/// The number of agreeing stars and the probability they agreed by chance.
+	///
+	/// # Example
+	/// ``` ignore
+	/// use star_tracker_lib::tracking_mode::Verification;
+	///
+	/// let verification = Verification{fov: database.fov, tolerance: Degrees(0.05).as_radians()};
+	/// let mut region : ArrayList<usize, 200> = ArrayList::new();
+	/// verification.region(database.catalogue, attitude, &mut region);
+	/// let result = verification.verify(&stars_3d, &matches, database.catalogue, &region, attitude);
+	/// if 0.001 < result.false_probability
+	/// {
+	/// 	println!("The attitude is not reliable.");
+	/// }
+	/// ```
+	pub fn verify (
+		&self,
+		stars     : &dyn List<Equatorial>,
+		identified: &dyn List<Match<usize>>,
+		catalogue : &dyn LinearLookup<Equatorial>,
+		region    : &dyn List<usize>,
+		attitude  : Quaternion,
+	) -> VerificationResult
+	{
+		let to_inertial = attitude.conjugate();
+
+		let mut tested  = 0;
+		let mut matched = 0;
+		for i in 0..stars.size()
+		{
+			let mut used = false;
+			for j in 0..identified.size() { used |= identified.get(j).input == i; }
+			if used { continue; }
+
+			tested += 1;
+			let inertial = to_inertial.rotate_point(stars.get(i).to_vector3()).to_equatorial();
+			for j in 0..region.size()
+			{
+				if inertial.angle_distance(catalogue.get(region.get(j))) < self.tolerance
+				{
+					matched += 1;
+					break;
+				}
+			}
+		}
+
+		// The chance of a random star landing on a catalogue star (ratio of spherical cap areas).
+		let in_fov         = region.size();
+		let area_tolerance = 1.0 - self.tolerance.cos();
+		let area_fov       = 1.0 - (self.fov / 2.0).cos();
+		let chance = if 0.0 < area_fov { in_fov as Decimal * area_tolerance / area_fov } else { 1.0 };
+
+		return VerificationResult
+		{
+			in_fov:            in_fov,
+			tested:            tested,
+			matched:           matched,
+			false_probability: Self::probability(tested, matched, chance),
+		};
+	}
+
+
+
+	/// Finds the catalogue stars in the field of view by checking every star in the catalogue.  
+	/// If `region` is full, the remaining stars are ignored, make it larger than the number of stars in the field of view.
+	/// # Arguments
+	/// * `catalogue` - The catalogue the attitude was found from.
+	/// * `attitude`  - The rotation from inertial to camera.
+	/// * `region`    - The output, the index of each catalogue star in the field of view (cleared before use).
+	pub fn region (
+		&self,
+		catalogue: &dyn LinearLookup<Equatorial>,
+		attitude : Quaternion,
+		region   : &mut dyn List<usize> )
+	{
+		region.clear();
+		let boresight = Self::boresight(attitude);
+		for i in 0..catalogue.size()
+		{
+			if boresight.angle_distance(catalogue.get(i)) < self.fov / 2.0 && region.push_back(i).is_err()
+			{
+				return;
+			}
+		}
+	}
+
+
+
+	/// Finds the catalogue stars in the field of view from the tile closest to the boresight.  
+	/// Only the pairs of one tile are checked instead of the whole catalogue.  
+	/// If `region` is full, the remaining stars are ignored, make it larger than the number of stars in the field of view.
+	/// # Arguments
+	/// * `tiled`    - The tiled database, the catalogue is the catalogue of `tiled.database`.
+	/// * `attitude` - The rotation from inertial to camera.
+	/// * `region`   - The output, the index of each catalogue star in the field of view in ascending order (cleared before use).
+	pub fn region_tiled (
+		&self,
+		tiled   : &TiledDatabase,
+		attitude: Quaternion,
+		region  : &mut dyn List<usize> )
+	{
+		region.clear();
+		let boresight = Self::boresight(attitude);
+
+		let mut closest : Option<(Radians, usize)> = None;
+		for i in 0..tiled.num_tiles()
+		{
+			let distance = boresight.angle_distance(tiled.tiles.get(i));
+			if closest.map_or(true, |c| distance < c.0) { closest = Some((distance, i)); }
+		}
+		let tile = match closest { Some(closest) => closest.1, None => return };
+
+		for i in tiled.tile_range(tile)
+		{
+			let pair = tiled.database.get_pairs(tiled.tile_pairs.get(i));
+			for star in [pair.0, pair.1]
+			{
+				let position = match tiled.database.find_star(star) { Ok(position) => position, Err(_) => continue };
+				if self.fov / 2.0 <= boresight.angle_distance(position) { continue; }
+				if !Self::insert(region, star) { return; }
+			}
+		}
+	}
+
+
+
+	/// The center of the image in the inertial frame.
+	/// # Arguments
+	/// * `attitude` - The rotation from inertial to camera.
+	fn boresight ( attitude: Quaternion ) -> Equatorial
+	{
+		return attitude.conjugate().rotate_point(Vector3{x: 0.0, y: 0.0, z: 1.0}).to_equatorial();
+	}
+
+
+	/// Inserts a star into an ascending list if it is not already in the list.
+	/// # Arguments
+	/// * `region` - The ascending list.
+	/// * `star`   - The star to insert.
+	/// # Returns
+	/// False if the list is full and the star was not in the list.
+	fn insert ( region: &mut dyn List<usize>, star: usize ) -> bool
+	{
+		let mut start = 0;
+		let mut end   = region.size();
+		while start < end
+		{
+			let mid = start + (end - start) / 2;
+			if region.get(mid) < star { start = mid + 1; }
+			else                      { end   = mid;     }
+		}
+		if start < region.size() && region.get(start) == star { return true; }
+		if region.is_full() { return false; }
+		region.slot(star, |a, b| a < b);
+		return true;
+	}
+
+
+
+	/// The probability of at least `matched` of `tested` stars agreeing when each has a `chance` of agreeing.
+	/// This is the upper tail of the binomial distribution.
+	/// # Arguments
+	/// * `tested`  - The number of stars tested.
+	/// * `matched` - The number of stars which agreed.
+	/// * `chance`  - The probability of a single random star agreeing.
+	pub fn probability ( tested: usize, matched: usize, chance: Decimal ) -> Decimal
+	{
+		if matched == 0 || 1.0 <= chance { return 1.0; }
+		if tested < matched || chance <= 0.0 { return 0.0; }
+
+		// C(tested, matched) p^matched (1-p)^(tested-matched)
+		let mut term = chance.powf(matched as Decimal) * (1.0 - chance).powf((tested - matched) as Decimal);
+		for i in 0..matched
+		{
+			term *= (tested - i) as Decimal / (matched - i) as Decimal;
+		}
+
+		let mut sum = 0.0;
+		for i in matched..=tested
+		{
+			sum += term;
+			term *= (tested - i) as Decimal / (i + 1) as Decimal * chance / (1.0 - chance);
+		}
+		return sum.min(1.0);
+	}
+}
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use crate::tracking_mode::Verification;
+	use crate::tracking_mode::database::TiledDatabase;
+	use crate::tracking_mode::database::PyramidDatabase;
+	use crate::tracking_mode::database::KVector;
+
+	use crate::util::units::Equatorial;
+	use crate::util::units::Quaternion;
+	use crate::util::units::AngleAxis;
+	use crate::util::units::Vector3;
+	use crate::util::units::Degrees;
+	use crate::util::units::Match;
+	use crate::util::test::TestEqual;
+	use crate::util::list::ArrayList;
+	use crate::util::list::List;
+	use crate::util::test::star_patch;
+	use crate::util::test::star_pairs;
+	use crate::util::test::point_at;
+
+	// A patch of stars around (100, 10) degrees and a star outside the field of view.
+	#[coverage(off)]
+	fn catalogue ( ) -> Vec<Equatorial>
+	{
+		let mut stars = star_patch((100.0, 10.0), 8);
+		stars.push(Equatorial{ra: Degrees(160.0).as_radians(), dec: Degrees(10.0).as_radians()});
+		return stars;
+	}
+
+	// Points the camera (z axis) at (100, 10) degrees.
+	#[coverage(off)]
+	fn attitude ( ) -> Quaternion
+	{
+		return point_at(Equatorial{ra: Degrees(100.0).as_radians(), dec: Degrees(10.0).as_radians()});
+	}
+
+	#[coverage(off)]
+	fn verification ( ) -> Verification
+	{
+		return Verification{fov: Degrees(20.0).as_radians(), tolerance: Degrees(0.05).as_radians()};
+	}
+
+	// The first 4 stars were used to find the attitude.
+	#[coverage(off)]
+	fn identified ( ) -> Vec<Match<usize>>
+	{
+		return (0..4).map(|i| Match{input: i, output: i, weight: 1.0}).collect();
+	}
+
+	// The catalogue stars rotated into the camera frame.
+	#[coverage(off)]
+	fn observe ( catalogue: &[Equatorial] ) -> Vec<Equatorial>
+	{
+		return catalogue.iter().map(|s| attitude().rotate_point(s.to_vector3()).to_equatorial()).collect();
+	}
+
+	// The whole catalogue in one tile, another tile far away holds the star outside the field of view.
+	#[coverage(off)]
+	fn tiled <'a> ( database: &'a PyramidDatabase<'a>, tiles: &'a Vec<Equatorial>, start: &'a Vec<usize>, pairs: &'a Vec<usize> )
+		-> TiledDatabase<'a>
+	{
+		return TiledDatabase{database: database, reach: Degrees(15.0).as_radians(), tiles: tiles, tile_start: start, tile_pairs: pairs};
+	}
+
+
+//###############################################################################################//
+//
+//										Verification
+//
+// pub fn verify       ( &self, &dyn List<Equatorial>, &dyn List<Match<usize>>, &dyn LinearLookup<Equatorial>,
+//                       &dyn List<usize>, Quaternion ) -> VerificationResult
+// pub fn region       ( &self, &dyn LinearLookup<Equatorial>, Quaternion, &mut dyn List<usize> )
+// pub fn region_tiled ( &self, &TiledDatabase, Quaternion, &mut dyn List<usize> )
+// fn boresight        ( Quaternion ) -> Equatorial
+// fn insert           ( &mut dyn List<usize>, usize ) -> bool
+// pub fn probability  ( usize, usize, Decimal ) -> Decimal
+//
+//###############################################################################################//
+//										~ verify ~												 //
+	#[test]
+	// With the correct attitude, every tested star should agree and the probability should be tiny.
+	fn test_verify_correct ( )
+	{
+		let catalogue = catalogue();
+		let stars     = observe(&catalogue[0..8]);
+		let region : Vec<usize> = (0..8).collect();
+
+		let result = verification().verify(&stars, &identified(), &catalogue, &region, attitude());
+		assert_eq!(result.in_fov,  8);
+		assert_eq!(result.tested,  4);
+		assert_eq!(result.matched, 4);
+		assert!(result.false_probability < 0.000001);
+	}
+
+	#[test]
+	// With the wrong attitude, the tested stars should not agree.
+	fn test_verify_wrong ( )
+	{
+		let catalogue = catalogue();
+		let stars     = observe(&catalogue[0..8]);
+		let region : Vec<usize> = (0..8).collect();
+
+		let wrong = AngleAxis{angle: Degrees(3.0).as_radians(), axis: Vector3{x: 0.0, y: 0.0, z: 1.0}}
+			.to_quaternion() * attitude();
+		let result = verification().verify(&stars, &identified(), &catalogue, &region, wrong);
+		assert_eq!(result.tested,  4);
+		assert_eq!(result.matched, 0);
+		assert_eq!(result.false_probability, 1.0);
+	}
+
+	#[test]
+	// Only the catalogue stars in the region are compared.
+	fn test_verify_region ( )
+	{
+		let catalogue = catalogue();
+		let stars     = observe(&catalogue[0..8]);
+		let region : Vec<usize> = vec![0, 1, 2, 3, 4, 5];
+
+		let result = verification().verify(&stars, &identified(), &catalogue, &region, attitude());
+		assert_eq!(result.in_fov,  6);
+		assert_eq!(result.tested,  4);
+		assert_eq!(result.matched, 2);
+	}
+
+	#[test]
+	// If every star was used to find the attitude, nothing is left to test.
+	// The pilot always agrees with the attitude found from it, so the attitude can not be verified.
+	fn test_verify_all_identified ( )
+	{
+		let catalogue = catalogue();
+		let stars     = observe(&catalogue[0..4]);
+		let region : Vec<usize> = (0..8).collect();
+
+		let result = verification().verify(&stars, &identified(), &catalogue, &region, attitude());
+		assert_eq!(result.tested,  0);
+		assert_eq!(result.matched, 0);
+		assert_eq!(result.false_probability, 1.0);
+	}
+
+	#[test]
+	// A triangle alone has nothing to confirm it.
+	fn test_verify_triangle ( )
+	{
+		let catalogue = catalogue();
+		let stars     = observe(&catalogue[0..3]);
+		let region : Vec<usize> = (0..8).collect();
+		let identified : Vec<Match<usize>> = identified()[0..3].to_vec();
+
+		let result = verification().verify(&stars, &identified, &catalogue, &region, attitude());
+		assert_eq!(result.tested,  0);
+		assert_eq!(result.matched, 0);
+		assert_eq!(result.false_probability, 1.0);
+	}
+
+//										~ region ~												 //
+	#[test]
+	// The stars within half the field of view of the boresight.
+	fn test_region ( )
+	{
+		let catalogue = catalogue();
+		let mut region : Vec<usize> = vec![10];
+		verification().region(&catalogue, attitude(), &mut region);
+		assert_eq!(region, (0..8).collect::<Vec<usize>>());
+	}
+
+	#[test]
+	// If the region is full, the remaining stars are ignored.
+	fn test_region_full ( )
+	{
+		let catalogue = catalogue();
+		let mut region : ArrayList<usize, 3> = ArrayList::new();
+		verification().region(&catalogue, attitude(), &mut region);
+		assert_eq!(region.size(), 3);
+		assert_eq!(region.get(2), 2);
+	}
+
+//										~ region_tiled ~										 //
+	#[test]
+	// Only the closest tile is used, each star is added once.
+	fn test_region_tiled ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+
+		let tiles = vec![catalogue[8], Equatorial{ra: Degrees(100.0).as_radians(), dec: Degrees(10.0).as_radians()}];
+		let mut tile_pairs : Vec<usize> = Vec::new();
+		for i in 0..pairs.len()
+		{
+			if pairs[i].0 < 8 && pairs[i].1 < 8 { tile_pairs.push(i); }
+		}
+		// The first tile is empty.
+		let start = vec![0, 0, tile_pairs.len()];
+		let tiled = tiled(&database, &tiles, &start, &tile_pairs);
+
+		let mut region : Vec<usize> = vec![10];
+		verification().region_tiled(&tiled, attitude(), &mut region);
+		assert_eq!(region, (0..8).collect::<Vec<usize>>());
+
+		// The empty tile is the closest.
+		let far = point_at(catalogue[8]);
+		verification().region_tiled(&tiled, far, &mut region);
+		assert_eq!(region.len(), 0);
+	}
+
+	#[test]
+	// Without tiles, there is no region.
+	fn test_region_tiled_empty ( )
+	{
+		let catalogue = catalogue();
+		let pairs     = star_pairs(&catalogue);
+		let k_vector  = vec![0, pairs.len()];
+		let database  = PyramidDatabase{fov: Degrees(20.0).as_radians(), k_lookup: KVector::new(1, 0.0, 0.2),
+			k_vector: &k_vector, pairs: &pairs, catalogue: &catalogue};
+		let tiles : Vec<Equatorial> = Vec::new();
+		let start : Vec<usize> = vec![0];
+		let tile_pairs : Vec<usize> = Vec::new();
+		let tiled = tiled(&database, &tiles, &start, &tile_pairs);
+
+		let mut region : Vec<usize> = vec![10];
+		verification().region_tiled(&tiled, attitude(), &mut region);
+		assert_eq!(region.len(), 0);
+	}
+
+//										~ boresight ~											 //
+	#[test]
+	// The z axis of the camera in the inertial frame.
+	fn test_boresight ( )
+	{
+		let boresight = Verification::boresight(attitude());
+		boresight.ra.0.assert_close(&Degrees(100.0).as_radians().0, 0.0001);
+		boresight.dec.0.assert_close(&Degrees(10.0).as_radians().0, 0.0001);
+	}
+
+//										~ insert ~												 //
+	#[test]
+	// The list stays ascending and each star is added once.
+	fn test_insert ( )
+	{
+		let mut region : Vec<usize> = Vec::new();
+		assert!(Verification::insert(&mut region, 5));
+		assert!(Verification::insert(&mut region, 2));
+		assert!(Verification::insert(&mut region, 5));
+		assert!(Verification::insert(&mut region, 9));
+		assert_eq!(region, vec![2, 5, 9]);
+	}
+
+	#[test]
+	// A star already in a full list is not an error.
+	fn test_insert_full ( )
+	{
+		let mut region : ArrayList<usize, 2> = ArrayList::new();
+		assert!(Verification::insert(&mut region, 5));
+		assert!(Verification::insert(&mut region, 2));
+		assert!(Verification::insert(&mut region, 5));
+		assert!(!Verification::insert(&mut region, 1));
+		assert_eq!(region.get(0), 2);
+		assert_eq!(region.get(1), 5);
+	}
+
+//										~ probability ~											 //
+	#[test]
+	// The upper tail of the binomial distribution.
+	fn test_probability ( )
+	{
+		// P(X >= 1), X ~ B(2, 0.5) = 1 - 0.25
+		Verification::probability(2, 1, 0.5).assert_close(&0.75, 0.000001);
+		// P(X >= 3), X ~ B(3, 0.1) = 0.001
+		Verification::probability(3, 3, 0.1).assert_close(&0.001, 0.000001);
+		// P(X >= 2), X ~ B(4, 0.2) = 1 - 0.8^4 - 4 * 0.2 * 0.8^3
+		Verification::probability(4, 2, 0.2).assert_close(&(1.0 - 0.4096 - 0.4096), 0.000001);
+	}
+
+	#[test]
+	// Invalid or certain inputs.
+	fn test_probability_edge ( )
+	{
+		assert_eq!(Verification::probability(5, 0, 0.1), 1.0);
+		assert_eq!(Verification::probability(5, 2, 1.5), 1.0);
+		assert_eq!(Verification::probability(5, 2, 0.0), 0.0);
+		assert_eq!(Verification::probability(1, 2, 0.1), 0.0);
+	}
+}
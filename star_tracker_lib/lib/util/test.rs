@@ -14,6 +14,7 @@ use crate::util::units::Vector2;
 use crate::util::units::Vector3;
 use crate::util::units::Equatorial;
 use crate::util::units::AngleAxis;
+#[cfg(test)] use crate::tracking_mode::StarPair;
 
 use crate::util::Maths;
 
@@ -151,6 +152,61 @@ impl TestEqual for AngleAxis {
 
 
 
+//###############################################################################################//
+//										---	Star Fixtures ---
+//###############################################################################################//
+
+/// The offsets (degrees of ra, dec) of a patch of stars with irregular spacing.
+/// Every pair of stars has a different separation so each star can be identified.
+#[cfg(test)]
+pub const STAR_PATCH_OFFSETS : [(Decimal, Decimal); 14] = [
+	(0.0, 0.0), (1.3, 0.4), (-0.8, 1.9), (2.1, -1.7), (-2.6, -0.9), (0.5, 2.8), (-1.4, -2.4),
+	(3.2, 1.1), (-3.4, 2.2), (1.7, -3.1), (-0.3, -1.2), (2.6, 2.9), (-2.1, 3.6), (0.9, -0.6)];
+
+
+/// A patch of stars with irregular spacing ([STAR_PATCH_OFFSETS](crate::util::test::STAR_PATCH_OFFSETS)).
+/// # Arguments
+/// * `center` - The position of the first star (in degrees).
+/// * `count`  - The number of stars (up to 14).
+#[cfg(test)]
+#[coverage(off)]
+pub fn star_patch ( center: (Decimal, Decimal), count: usize ) -> Vec<Equatorial>
+{
+	return STAR_PATCH_OFFSETS[0..count].iter().map(|(ra, dec)|
+		Equatorial{ra: Degrees(center.0 + ra).as_radians(), dec: Degrees(center.1 + dec).as_radians()}).collect();
+}
+
+
+/// Every pair of stars sorted by separation (for a database with a single k-vector bin).
+#[cfg(test)]
+#[coverage(off)]
+pub fn star_pairs ( catalogue: &Vec<Equatorial> ) -> Vec<StarPair<usize>>
+{
+	let mut pairs = Vec::new();
+	for i in 0..catalogue.len()
+	{
+		for j in (i + 1)..catalogue.len() { pairs.push(StarPair(i, j)); }
+	}
+	pairs.sort_by(|a, b|
+		catalogue[a.0].angle_distance(catalogue[a.1]).0.partial_cmp(
+		&catalogue[b.0].angle_distance(catalogue[b.1]).0).unwrap());
+	return pairs;
+}
+
+
+/// The rotation (inertial to camera) which points the camera (z axis) at `center`.
+#[cfg(test)]
+#[coverage(off)]
+pub fn point_at ( center: Equatorial ) -> Quaternion
+{
+	let center  = center.to_vector3();
+	let forward = Vector3{x: 0.0, y: 0.0, z: 1.0};
+	let axis    = forward.cross(center).normalized().unwrap();
+	return AngleAxis{angle: center.angle_distance(forward), axis: axis}.to_quaternion();
+}
+
+
+
 
 
 
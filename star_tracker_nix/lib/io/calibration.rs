@@ -0,0 +1,86 @@
+use std::io::Read;
+use std::io::Write;
+use std::fs::File;
+
+use star_tracker_lib::util::aliases::Decimal;
+use star_tracker_lib::util::units::Vector2;
+use star_tracker_lib::util::units::Pixel;
+
+use star_tracker_lib::projection::IntrinsicParameters;
+use star_tracker_lib::projection::Distortion;
+
+use crate::io::Calibration;
+
+impl Calibration
+{
+	/// Writes the calibration to a json file.
+	/// The format is:
+	/// ``` ignore
+	/// {
+	///     "focal_length":    { "x": 1000.0, "y": 1000.0 },
+	///     "principle_point": { "x": 640.0,  "y": 360.0  },
+	///     "distortion":      { "radial": [k1, k2, k3], "tangential": [p1, p2] },
+	///     "image_size":      { "x": 1280,   "y": 720    },
+	///     "rms": 0.1
+	/// }
+	/// ```
+	pub fn write ( &self, path: &str ) -> std::io::Result<()>
+	{
+		let param = &self.intrinsic;
+		let json = serde_json::json!({
+			"focal_length":    { "x": param.focal_length.x,    "y": param.focal_length.y },
+			"principle_point": { "x": param.principle_point.x, "y": param.principle_point.y },
+			"distortion":
+			{
+				"radial":     param.distortion.radial,
+				"tangential": param.distortion.tangential,
+			},
+			"image_size": { "x": self.image_size.x, "y": self.image_size.y },
+			"rms":        self.rms,
+		});
+
+		let mut file = File::create(path)?;
+		file.write_all(serde_json::to_string_pretty(&json)?.as_bytes())?;
+		return Ok(());
+	}
+
+
+	/// Reads a calibration file created by `write`.
+	/// Returns None if the file does not exist or is invalid.
+	pub fn read ( path: &str ) -> Option<Calibration>
+	{
+		let mut file = File::open(path).ok()?;
+		let mut json_str = String::new();
+		file.read_to_string(&mut json_str).ok()?;
+		let json: serde_json::Value = serde_json::from_str(&json_str).ok()?;
+
+		let focal      = Calibration::read_vector2(json.get("focal_length")?)?;
+		let principle  = Calibration::read_vector2(json.get("principle_point")?)?;
+
+		let distortion = json.get("distortion")?;
+		let radial     = distortion.get("radial")?.as_array()?;
+		let tangential = distortion.get("tangential")?.as_array()?;
+		if radial.len() != 3 || tangential.len() != 2 { return None; }
+
+		let mut dist = Distortion::none();
+		for i in 0..radial.len()     { dist.radial[i]     = radial[i].as_f64()? as Decimal; }
+		for i in 0..tangential.len() { dist.tangential[i] = tangential[i].as_f64()? as Decimal; }
+
+		let img  = json.get("image_size")?;
+		let size = Pixel{x: img.get("x")?.as_u64()? as usize, y: img.get("y")?.as_u64()? as usize};
+		let rms  = json.get("rms")?.as_f64()? as Decimal;
+
+		let intrinsic = IntrinsicParameters{
+			focal_length: focal, principle_point: principle, distortion: dist};
+		return Some(Calibration{intrinsic: intrinsic, image_size: size, rms: rms});
+	}
+
+
+	/// Reads a json object with an x and y field.
+	fn read_vector2 ( json: &serde_json::Value ) -> Option<Vector2>
+	{
+		let x = json.get("x")?.as_f64()? as Decimal;
+		let y = json.get("y")?.as_f64()? as Decimal;
+		return Some(Vector2{x: x, y: y});
+	}
+}
@@ -6,6 +6,10 @@
 //!
 //! # IO
 //! 
+//! # Calibration
+//! Reads and writes the json file created by the `calibrate` binary.  
+//! The file contains the intrinsic parameters (including lens distortion) of a camera.  
+//!
 //! # Sample
 //! Recursively reads through the sample directory.  
 //! The sample directory should contain a set of folders containing dark frames, corr files and images.
@@ -40,10 +44,13 @@
 use star_tracker_lib::util::units::Equatorial;
 use star_tracker_lib::util::units::Vector2;
 use star_tracker_lib::util::units::Radians;
+use star_tracker_lib::util::units::Pixel;
 use star_tracker_lib::util::aliases::Decimal;
+use star_tracker_lib::projection::IntrinsicParameters;
 
 
 pub mod sample;
+pub mod calibration;
 // pub mod template;
 
 
@@ -60,6 +67,17 @@ pub struct Sample
 	pub file_img:  Vec<String>,
 }
 
+/// The intrinsic parameters of a camera found by the `calibrate` binary.
+pub struct Calibration
+{
+	/// The focal length, principle point and distortion of the lens (in pixels).
+	pub intrinsic:  IntrinsicParameters,
+	/// The size of the images used to perform the calibration.
+	pub image_size: Pixel,
+	/// The root mean square reprojection error of the calibration (in pixels).
+	pub rms:        Decimal,
+}
+
 /// Provides stats about the error of a single star in an image to where it should be.
 pub struct StarError
 {
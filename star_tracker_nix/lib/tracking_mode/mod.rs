@@ -1,7 +1,25 @@
 //! Allows for better failure conditions such as timeout.
 //!
+//! # Parallel Search
+//! `Constellation::find` searches one triangle at a time on a single thread.  
+//! [ParallelSearch](crate::tracking_mode::ParallelSearch) splits the kernel triples between worker threads,
+//! the first worker to succeed (and pass `verify`) cancels the others.  
+//! ``` ignore
+//! let search = ParallelSearch::new(4, Duration::from_millis(1000), 10);
+//! let result = search.find::<1000, _, _>(&stars, |search|
+//! 	{
+//! 		let database = gen.get_database();
+//! 		search(&mut ChunkIteratorNone::new(&database));
+//! 	}, |found| 4 <= found.len(), angle_tolerance, 4..=4, &mut matches);
+//! ```
+use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
 use star_tracker_lib::tracking_mode::AbandonSearch;
 use star_tracker_lib::tracking_mode::AbandonSearchFailures;
+use star_tracker_lib::tracking_mode::KernelIterator;
+
+pub mod parallel_search;
 
 
 
@@ -25,6 +43,50 @@ pub struct AbandonSearchTimeoutFailure
 }
 
 
+/// Aborts a search when another thread has cancelled it or `abort` should abort.
+pub struct AbandonSearchCancel <'a, T: AbandonSearch>
+{
+	/// Set to true by the thread which succeeded.
+	pub cancel: &'a AtomicBool,
+	/// The abort condition of this thread.
+	pub abort : T,
+}
+
+
+/// Only steps to every `workers` triangle of a [KernelIterator](star_tracker_lib::tracking_mode::KernelIterator) starting at `worker`.  
+/// Each worker of a [ParallelSearch](crate::tracking_mode::ParallelSearch) tries different triangles.
+pub struct TriangleSelectionShare
+{
+	/// The sequence of all the triangles.
+	kernel : KernelIterator,
+	/// The index of this worker.
+	worker : usize,
+	/// The number of workers sharing the triangles.
+	workers: usize,
+	/// The number of kernel steps.
+	index  : usize,
+}
+
+
+/// Runs `Constellation::find` on multiple threads.  
+///
+/// Each worker has its own [StarTriangleIterator](star_tracker_lib::tracking_mode::StarTriangleIterator) and database iterator.  
+/// The kernel triples are shared between the workers with [TriangleSelectionShare](crate::tracking_mode::TriangleSelectionShare).  
+/// When a worker succeeds and its matches are verified, the other workers are cancelled and the matches of the successful worker are returned.  
+/// Use [find](crate::tracking_mode::ParallelSearch::find).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParallelSearch
+{
+	/// The number of threads (at least 1).
+	pub workers     : usize,
+	/// How long each worker can search for.
+	pub timeout     : Duration,
+	/// The number of failed triangles until a worker gives up.
+	/// As each worker has a different set of triangles, the total failures can be `workers * max_failures`.
+	pub max_failures: usize,
+}
+
+
 
 
 impl AbandonSearchTimeout
@@ -0,0 +1,451 @@
+//! Implementation of [ParallelSearch](crate::tracking_mode::ParallelSearch), [TriangleSelectionShare](crate::tracking_mode::TriangleSelectionShare) and [AbandonSearchCancel](crate::tracking_mode::AbandonSearchCancel).
+use std::ops::RangeInclusive;
+use std::sync::atomic::Ordering;
+use std::sync::atomic::AtomicBool;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use star_tracker_lib::tracking_mode::Constellation;
+use star_tracker_lib::tracking_mode::ConstellationResult;
+use star_tracker_lib::tracking_mode::StarTriangleIterator;
+use star_tracker_lib::tracking_mode::TriangleSelection;
+use star_tracker_lib::tracking_mode::KernelIterator;
+use star_tracker_lib::tracking_mode::StarTriangle;
+use star_tracker_lib::tracking_mode::Specularity;
+use star_tracker_lib::tracking_mode::AbandonSearch;
+use star_tracker_lib::tracking_mode::database::ChunkIterator;
+
+use star_tracker_lib::util::units::Equatorial;
+use star_tracker_lib::util::units::Radians;
+use star_tracker_lib::util::units::Match;
+use star_tracker_lib::util::list::List;
+
+use crate::tracking_mode::ParallelSearch;
+use crate::tracking_mode::TriangleSelectionShare;
+use crate::tracking_mode::AbandonSearchCancel;
+use crate::tracking_mode::AbandonSearchTimeoutFailure;
+
+
+
+impl <'a, T: AbandonSearch> AbandonSearch for AbandonSearchCancel <'a, T>
+{
+	/// If another thread has cancelled the search or `abort` should abort, returns true.
+	fn should_abort ( &mut self ) -> bool
+	{
+		return self.cancel.load(Ordering::Relaxed) || self.abort.should_abort();
+	}
+}
+
+
+
+impl TriangleSelectionShare
+{
+	/// Creates a selection for one worker.
+	/// # Arguments
+	/// * `worker`  - The index of this worker (0 to workers - 1).
+	/// * `workers` - The number of workers sharing the triangles.
+	pub fn new ( worker: usize, workers: usize ) -> Self
+	{
+		return Self{kernel: KernelIterator::new(0), worker: worker, workers: workers.max(1), index: 0};
+	}
+}
+
+
+impl TriangleSelection for TriangleSelectionShare
+{
+	/// Restarts the kernel with the number of stars.
+	fn begin ( &mut self, stars: &dyn List<Equatorial> )
+	{
+		self.kernel.begin(stars);
+		self.index = 0;
+	}
+
+	/// Steps the kernel until a triangle of this worker is found.
+	fn next ( &mut self, stars: &dyn List<Equatorial>, database: &dyn ChunkIterator ) -> bool
+	{
+		while self.kernel.next(stars, database)
+		{
+			let owned = self.index % self.workers == self.worker;
+			self.index += 1;
+			if owned { return true; }
+		}
+		return false;
+	}
+
+	/// The current triangle of the kernel.
+	fn triangle ( &self ) -> StarTriangle<usize> { return self.kernel.triangle(); }
+}
+
+
+
+impl ParallelSearch
+{
+	/// Creates a search with the number of threads and the abort conditions of each thread.
+	/// # Arguments
+	/// * `workers`      - The number of threads, use `std::thread::available_parallelism`.
+	/// * `timeout`      - How long each worker can search for.
+	/// * `max_failures` - The number of failed triangles until a worker gives up.
+	pub fn new ( workers: usize, timeout: Duration, max_failures: usize ) -> Self
+	{
+		return Self{workers: workers.max(1), timeout: timeout, max_failures: max_failures};
+	}
+
+
+	/// The same as `Constellation::find` with `StarTriangleIterator<N>` and `Specularity::default()`, except the search is split between threads.
+	///
+	/// As the database iterators cannot be shared between threads, each worker calls `with_database` to construct its own.
+	/// `with_database` must construct a database iterator and pass it to the search given to it.
+	///
+	/// A worker which succeeds runs `verify` on its matches before cancelling the other workers.
+	/// A success which is not verified is counted as ErrorInsufficientPyramids and the other workers keep searching.
+	/// # Arguments
+	/// * `stars`           - The observed stars.
+	/// * `with_database`   - Constructs a database iterator and passes it into the given search.
+	/// * `verify`          - Returns true if the matches of a worker are correct (e.g. with [Verification](star_tracker_lib::tracking_mode::Verification)).
+	/// * `angle_tolerance` - When searching the database, the tolerance to use.
+	/// * `num_stars`       - The min and max number of stars in the constellation.
+	/// * `matches`         - The stars found by the successful worker, cleared before use.
+	/// # Returns
+	/// The result of the first worker with a verified success.
+	/// If every worker failed, the failures are combined (see [combine](crate::tracking_mode::ParallelSearch::combine)).
+	///
+	/// # Example
+	/// ``` ignore
+	/// let search = ParallelSearch::new(4, Duration::from_millis(1000), 10);
+	/// let result = search.find::<1000, _, _>(&stars, |search|
+	/// 	{
+	/// 		let database = gen.get_database();
+	/// 		search(&mut ChunkIteratorDeclination::new(&database, Degrees(20.0).as_radians(), 1.2, ChunkIteratorDeclination::randomise_parity));
+	/// 	}, |found| 4 <= found.len(), angle_tolerance, 4..=4, &mut matches);
+	/// ```
+	pub fn find <const N: usize, F, V> (
+		&self,
+		stars          : &Vec<Equatorial>,
+		with_database  : F,
+		verify         : V,
+		angle_tolerance: Radians,
+		num_stars      : RangeInclusive<usize>,
+		matches        : &mut dyn List<Match<usize>>,
+	) -> ConstellationResult
+	where
+		F: Fn ( &mut dyn FnMut ( &mut dyn ChunkIterator ) ) + Sync,
+		V: Fn ( &Vec<Match<usize>> ) -> bool + Sync,
+	{
+		matches.clear();
+		let cancel : AtomicBool = AtomicBool::new(false);
+		let winner : Mutex<Option<(ConstellationResult, Vec<Match<usize>>)>> = Mutex::new(None);
+		let mut results : Vec<ConstellationResult> = Vec::new();
+
+		// The triangle iterator is large and is on the stack of the thread.
+		let stack_size = std::mem::size_of::<StarTriangleIterator<N, 0, TriangleSelectionShare>>() + 8 * 1024 * 1024;
+
+		std::thread::scope(|scope|
+		{
+			let mut handles = Vec::new();
+			for worker in 0..self.workers
+			{
+				let cancel        = &cancel;
+				let winner        = &winner;
+				let with_database = &with_database;
+				let verify        = &verify;
+				let num_stars     = num_stars.clone();
+
+				let handle = std::thread::Builder::new()
+					.stack_size(stack_size)
+					.spawn_scoped(scope, move ||
+					{
+						let selection = TriangleSelectionShare::new(worker, self.workers);
+						let mut triangles : StarTriangleIterator<N, 0, TriangleSelectionShare> =
+							StarTriangleIterator::with_selection(selection);
+						let mut abort = AbandonSearchCancel
+						{
+							cancel: cancel,
+							abort:  AbandonSearchTimeoutFailure::new(self.timeout, self.max_failures)
+						};
+						let mut found  : Vec<Match<usize>> = Vec::new();
+						let mut result = ConstellationResult::ErrorNoTriangleMatch{fails: 0};
+
+						with_database(&mut |database: &mut dyn ChunkIterator|
+						{
+							result = Constellation::find(stars, database, &mut triangles, &mut Specularity::default(),
+								&mut abort, angle_tolerance, num_stars.clone(), &mut found);
+						});
+
+						// Only the first verified success is kept and cancels the other workers.
+						if let ConstellationResult::Success{fails} = result
+						{
+							if !verify(&found)
+							{
+								return ConstellationResult::ErrorInsufficientPyramids{fails: fails + 1};
+							}
+							if cancel.compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire).is_ok()
+							{
+								*winner.lock().expect("Search thread panicked.") = Some((result, found));
+							}
+						}
+						return result;
+					})
+					.expect("Could not create search thread.");
+				handles.push(handle);
+			}
+
+			for handle in handles
+			{
+				results.push(handle.join().expect("Search thread panicked."));
+			}
+		});
+
+		if let Some((result, found)) = winner.into_inner().expect("Search thread panicked.")
+		{
+			for i in 0..found.len()
+			{
+				if matches.push_back(found[i]).is_err() { break; }
+			}
+			return result;
+		}
+		return Self::combine(&results);
+	}
+
+
+
+	/// Combines the results of workers which all failed.
+	/// The failures are summed.
+	/// If any worker found a triangle, the result is ErrorInsufficientPyramids.
+	/// Otherwise, if any worker was aborted, the result is ErrorAborted.
+	/// Otherwise the result is ErrorNoTriangleMatch.
+	/// # Arguments
+	/// * `results` - The result of each worker.
+	pub fn combine ( results: &Vec<ConstellationResult> ) -> ConstellationResult
+	{
+		let mut fails      = 0;
+		let mut aborted    = false;
+		let mut triangle   = false;
+		for result in results
+		{
+			match *result
+			{
+				ConstellationResult::ErrorNoTriangleMatch     {fails: f} => { fails += f;                   },
+				ConstellationResult::ErrorAborted             {fails: f} => { fails += f; aborted  = true;  },
+				ConstellationResult::ErrorInsufficientPyramids{fails: f} => { fails += f; triangle = true;  },
+				ConstellationResult::Success                  {fails: f} => { return ConstellationResult::Success{fails: f}; },
+			}
+		}
+
+		if triangle { return ConstellationResult::ErrorInsufficientPyramids{fails: fails}; }
+		if aborted  { return ConstellationResult::ErrorAborted{fails: fails}; }
+		return ConstellationResult::ErrorNoTriangleMatch{fails: fails};
+	}
+}
+
+
+
+
+//###############################################################################################//
+//###############################################################################################//
+//
+//										Unit Tests
+//
+//###############################################################################################//
+//###############################################################################################//
+
+#[cfg(test)]
+#[allow(unused_must_use)]
+mod test
+{
+	use std::sync::atomic::Ordering;
+	use std::sync::atomic::AtomicBool;
+
+	use star_tracker_lib::tracking_mode::ConstellationResult;
+	use star_tracker_lib::tracking_mode::TriangleSelection;
+	use star_tracker_lib::tracking_mode::KernelIterator;
+	use star_tracker_lib::tracking_mode::StarTriangle;
+	use star_tracker_lib::tracking_mode::StarPair;
+	use star_tracker_lib::tracking_mode::AbandonSearch;
+	use star_tracker_lib::tracking_mode::AbandonSearchFailures;
+	use star_tracker_lib::tracking_mode::database::ChunkIteratorNone;
+	use star_tracker_lib::tracking_mode::database::PyramidDatabase;
+	use star_tracker_lib::tracking_mode::database::KVector;
+
+	use star_tracker_lib::util::units::Equatorial;
+	use star_tracker_lib::util::units::Radians;
+
+	use crate::tracking_mode::ParallelSearch;
+	use crate::tracking_mode::TriangleSelectionShare;
+	use crate::tracking_mode::AbandonSearchCancel;
+
+	// The kernel does not look at the positions, only the number of stars.
+	fn stars ( num: usize ) -> Vec<Equatorial>
+	{
+		return vec![Equatorial{ra: Radians(0.0), dec: Radians(0.0)}; num];
+	}
+
+	// Every triangle of a selection in order.
+	fn triangles ( selection: &mut dyn TriangleSelection, stars: &Vec<Equatorial> ) -> Vec<StarTriangle<usize>>
+	{
+		let k_vector  : Vec<usize>            = Vec::new();
+		let pairs     : Vec<StarPair<usize>>  = Vec::new();
+		let catalogue : Vec<Equatorial>       = Vec::new();
+		let database = PyramidDatabase
+		{
+			fov:       Radians(0.0),
+			k_lookup:  KVector::new(1, 0.0, 1.0),
+			k_vector:  &k_vector,
+			pairs:     &pairs,
+			catalogue: &catalogue,
+		};
+		let iterator = ChunkIteratorNone::new(&database);
+
+		let mut found = Vec::new();
+		selection.begin(stars);
+		while selection.next(stars, &iterator) { found.push(selection.triangle()); }
+		return found;
+	}
+
+
+//###############################################################################################//
+//
+//										AbandonSearchCancel
+//
+// fn should_abort ( &mut self ) -> bool
+//
+//###############################################################################################//
+//										~ should_abort ~										 //
+	#[test]
+	// The search should continue until another thread cancels it.
+	fn test_should_abort_cancel ( )
+	{
+		let cancel    = AtomicBool::new(false);
+		let mut abort = AbandonSearchCancel{cancel: &cancel, abort: AbandonSearchFailures::new(10)};
+		assert!(!abort.should_abort());
+		assert!(!abort.should_abort());
+
+		cancel.store(true, Ordering::Relaxed);
+		assert!(abort.should_abort());
+		assert!(abort.should_abort());
+	}
+
+	#[test]
+	// The search should abort when the abort condition of the thread does, even if not cancelled.
+	fn test_should_abort_inner ( )
+	{
+		let cancel    = AtomicBool::new(false);
+		let mut abort = AbandonSearchCancel{cancel: &cancel, abort: AbandonSearchFailures::new(1)};
+		assert!(!abort.should_abort());
+		assert!(abort.should_abort());
+		assert!(!cancel.load(Ordering::Relaxed));
+	}
+
+
+//###############################################################################################//
+//
+//										TriangleSelectionShare
+//
+// pub fn new ( usize, usize ) -> Self
+// fn begin    ( &mut self, &dyn List<Equatorial> )
+// fn next     ( &mut self, &dyn List<Equatorial>, &dyn ChunkIterator ) -> bool
+// fn triangle ( &self ) -> StarTriangle<usize>
+//
+//###############################################################################################//
+//										~ new ~													 //
+	#[test]
+	// There should always be at least one worker.
+	fn test_new ( )
+	{
+		let selection = TriangleSelectionShare::new(2, 3);
+		assert_eq!(selection.worker, 2);
+		assert_eq!(selection.workers, 3);
+		assert_eq!(selection.index, 0);
+		assert_eq!(TriangleSelectionShare::new(0, 0).workers, 1);
+	}
+
+//										~ next ~												 //
+	#[test]
+	// Every kernel triple should go to exactly one worker, in the order of the kernel.
+	fn test_next_shared ( )
+	{
+		let stars  = stars(7);
+		let kernel = triangles(&mut KernelIterator::new(0), &stars);
+		assert_eq!(kernel.len(), 35);
+
+		for workers in 1..5
+		{
+			let mut owner : Vec<Option<usize>> = vec![None; kernel.len()];
+			for worker in 0..workers
+			{
+				let found = triangles(&mut TriangleSelectionShare::new(worker, workers), &stars);
+				for triangle in &found
+				{
+					let index = kernel.iter().position(|t| t == triangle).expect("The triangle should be from the kernel.");
+					assert_eq!(owner[index], None);
+					owner[index] = Some(worker);
+				}
+			}
+			for i in 0..kernel.len() { assert_eq!(owner[i], Some(i % workers)); }
+		}
+	}
+
+	#[test]
+	// Calling begin should restart the triangles.
+	fn test_next_begin ( )
+	{
+		let stars = stars(5);
+		let mut selection = TriangleSelectionShare::new(1, 2);
+		let first  = triangles(&mut selection, &stars);
+		let second = triangles(&mut selection, &stars);
+		assert!(0 < first.len());
+		assert_eq!(first, second);
+	}
+
+
+//###############################################################################################//
+//
+//										ParallelSearch
+//
+// pub fn combine ( &Vec<ConstellationResult> ) -> ConstellationResult
+//
+//###############################################################################################//
+//										~ combine ~												 //
+	#[test]
+	// A success should be returned with its own failures.
+	fn test_combine_success ( )
+	{
+		let results = vec![
+			ConstellationResult::ErrorInsufficientPyramids{fails: 3},
+			ConstellationResult::Success{fails: 2},
+			ConstellationResult::ErrorAborted{fails: 5}];
+		assert_eq!(ParallelSearch::combine(&results), ConstellationResult::Success{fails: 2});
+	}
+
+	#[test]
+	// If any worker found a triangle, the result is ErrorInsufficientPyramids with the summed failures.
+	fn test_combine_insufficient_pyramids ( )
+	{
+		let results = vec![
+			ConstellationResult::ErrorAborted{fails: 1},
+			ConstellationResult::ErrorNoTriangleMatch{fails: 2},
+			ConstellationResult::ErrorInsufficientPyramids{fails: 4}];
+		assert_eq!(ParallelSearch::combine(&results), ConstellationResult::ErrorInsufficientPyramids{fails: 7});
+	}
+
+	#[test]
+	// If no worker found a triangle and any was aborted, the result is ErrorAborted with the summed failures.
+	fn test_combine_aborted ( )
+	{
+		let results = vec![
+			ConstellationResult::ErrorNoTriangleMatch{fails: 2},
+			ConstellationResult::ErrorAborted{fails: 3},
+			ConstellationResult::ErrorNoTriangleMatch{fails: 1}];
+		assert_eq!(ParallelSearch::combine(&results), ConstellationResult::ErrorAborted{fails: 6});
+	}
+
+	#[test]
+	// If every worker ran out of triangles, the result is ErrorNoTriangleMatch with the summed failures.
+	fn test_combine_no_triangle_match ( )
+	{
+		let results = vec![
+			ConstellationResult::ErrorNoTriangleMatch{fails: 2},
+			ConstellationResult::ErrorNoTriangleMatch{fails: 3}];
+		assert_eq!(ParallelSearch::combine(&results), ConstellationResult::ErrorNoTriangleMatch{fails: 5});
+		assert_eq!(ParallelSearch::combine(&Vec::new()), ConstellationResult::ErrorNoTriangleMatch{fails: 0});
+	}
+}
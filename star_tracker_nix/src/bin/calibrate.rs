@@ -0,0 +1,236 @@
+//! This finds the intrinsic parameters and lens distortion of a camera from images of a checkerboard.
+//! The result is written to a json file which can be read with `Calibration::read`.
+//!
+
+extern crate star_tracker_lib;
+extern crate star_tracker_nix;
+extern crate opencv;
+
+use std::path::Path;
+use std::ffi::OsStr;
+use std::env;
+
+use opencv::imgcodecs::imread;
+use opencv::imgcodecs::ImreadModes;
+use opencv::calib3d::find_chessboard_corners;
+use opencv::calib3d::calibrate_camera;
+use opencv::calib3d::project_points;
+use opencv::calib3d::CALIB_CB_ADAPTIVE_THRESH;
+use opencv::calib3d::CALIB_CB_NORMALIZE_IMAGE;
+use opencv::imgproc::corner_sub_pix;
+
+use opencv::core::Mat;
+use opencv::core::Size;
+use opencv::core::Point2f;
+use opencv::core::Point3f;
+use opencv::core::Vector;
+use opencv::core::TermCriteria;
+use opencv::core::TermCriteria_Type;
+use opencv::prelude::MatTraitConst;
+use opencv::prelude::MatTraitConstManual;
+
+use star_tracker_lib::util::aliases::Decimal;
+use star_tracker_lib::util::units::Vector2;
+use star_tracker_lib::util::units::Pixel;
+
+use star_tracker_lib::projection::IntrinsicParameters;
+use star_tracker_lib::projection::Distortion;
+
+use star_tracker_nix::io::Calibration;
+
+
+pub fn main ( )
+{
+	println!(r#"
+
+
+				===== Calibrate =====
+This finds the intrinsic parameters and lens distortion of a camera from images of a checkerboard.
+Every image in the folder (and sub folders) is searched for the checkerboard corners.
+Images where the checkerboard cannot be found are skipped.
+The result is written to a json file which can be used to create IntrinsicParameters.
+
+The arguments are: folder, inner corners across, inner corners down, output file.
+reset; cargo run --bin calibrate samples/8mm_checker_calib/distorted 9 6 calibration.json
+
+
+	"#);
+
+	let args: Vec<String> = env::args().collect();
+	let folder  = args.get(1).cloned().unwrap_or("samples/8mm_checker_calib/distorted".to_string());
+	let columns = args.get(2).and_then(|e| e.parse::<i32>().ok()).unwrap_or(9);
+	let rows    = args.get(3).and_then(|e| e.parse::<i32>().ok()).unwrap_or(6);
+	let output  = args.get(4).cloned().unwrap_or(format!("{}/calibration.json", folder));
+
+	let board_size = Size::new(columns, rows);
+
+	// The size of each square is irrelevant for the intrinsic parameters.
+	let mut board: Vector<Point3f> = Vector::new();
+	for y in 0..rows
+	{
+		for x in 0..columns
+		{
+			board.push(Point3f::new(x as f32, y as f32, 0.0));
+		}
+	}
+
+
+	println!("Finding Checkerboards");
+	let mut files: Vec<String> = Vec::new();
+	find_images(&folder, &mut files);
+	files.sort();
+
+	let criteria = TermCriteria::new(
+		TermCriteria_Type::COUNT as i32 + TermCriteria_Type::EPS as i32, 30, 0.001).unwrap();
+
+	let mut image_size     : Option<Size>                   = None;
+	let mut object_points  : Vector<Vector<Point3f>>        = Vector::new();
+	let mut image_points   : Vector<Vector<Point2f>>        = Vector::new();
+	let mut used_files     : Vec<String>                    = Vec::new();
+	for file in files
+	{
+		let img = imread(&file, ImreadModes::IMREAD_GRAYSCALE as i32).expect("Could not read image.");
+		let size = img.size().unwrap();
+		if image_size.is_some() && image_size != Some(size)
+		{
+			println!("\t* SKIPPED (different size)  {}", file);
+			continue;
+		}
+
+		let mut corners: Vector<Point2f> = Vector::new();
+		let flags = CALIB_CB_ADAPTIVE_THRESH + CALIB_CB_NORMALIZE_IMAGE;
+		let found = find_chessboard_corners(&img, board_size, &mut corners, flags).unwrap_or(false);
+		if !found
+		{
+			println!("\t* SKIPPED (no checkerboard) {}", file);
+			continue;
+		}
+
+		corner_sub_pix(&img, &mut corners, Size::new(11, 11), Size::new(-1, -1), criteria).unwrap();
+		println!("\t* FOUND                     {}", file);
+
+		image_size = Some(size);
+		object_points.push(board.clone());
+		image_points.push(corners);
+		used_files.push(file);
+	}
+
+	if image_size.is_none()
+	{
+		println!("No checkerboards found, check the number of inner corners ({} x {}).", columns, rows);
+		return;
+	}
+	let image_size = image_size.unwrap();
+
+
+	println!("Calibrating with {} images.", used_files.len());
+	let mut camera_matrix = Mat::default();
+	let mut dist_coeffs   = Mat::default();
+	let mut rvecs: Vector<Mat> = Vector::new();
+	let mut tvecs: Vector<Mat> = Vector::new();
+	let rms = calibrate_camera(&object_points, &image_points, image_size,
+		&mut camera_matrix, &mut dist_coeffs, &mut rvecs, &mut tvecs, 0, criteria)
+		.expect("Calibration failed.");
+
+	let focal_length = Vector2{
+		x: *camera_matrix.at_2d::<f64>(0, 0).unwrap() as Decimal,
+		y: *camera_matrix.at_2d::<f64>(1, 1).unwrap() as Decimal};
+	let principle_point = Vector2{
+		x: *camera_matrix.at_2d::<f64>(0, 2).unwrap() as Decimal,
+		y: *camera_matrix.at_2d::<f64>(1, 2).unwrap() as Decimal};
+
+	// opencv ordering is k1, k2, p1, p2, k3.
+	let coeff = | i: i32 | -> Decimal { return *dist_coeffs.at::<f64>(i).unwrap() as Decimal; };
+	let distortion = Distortion{
+		radial:     [coeff(0), coeff(1), coeff(4)],
+		tangential: [coeff(2), coeff(3)]};
+
+	let intrinsic = IntrinsicParameters{
+		focal_length: focal_length, principle_point: principle_point, distortion: distortion};
+
+
+	println!("Reprojection Error (pixels)");
+	println!("\t{:60} | {:>8} | {:>8} | {:>8}", "File", "Mean", "Dev", "Max");
+	let mut errors: Vec<Decimal> = Vec::new();
+	for i in 0..used_files.len()
+	{
+		let mut projected: Vector<Point2f> = Vector::new();
+		project_points(&object_points.get(i).unwrap(), &rvecs.get(i).unwrap(), &tvecs.get(i).unwrap(),
+			&camera_matrix, &dist_coeffs, &mut projected, &mut Mat::default(), 0.0).unwrap();
+
+		let found = image_points.get(i).unwrap();
+		let mut errors_img: Vec<Decimal> = Vec::new();
+		for j in 0..found.len()
+		{
+			let a = found.get(j).unwrap();
+			let b = projected.get(j).unwrap();
+			let error = ((a.x - b.x).powf(2.0) + (a.y - b.y).powf(2.0)).sqrt() as Decimal;
+			errors_img.push(error);
+		}
+		let (mean, dev, max) = stats(&errors_img);
+		println!("\t{:60} | {:8.4} | {:8.4} | {:8.4}", used_files[i], mean, dev, max);
+		errors.append(&mut errors_img);
+	}
+	let (mean, dev, max) = stats(&errors);
+	println!("\t{:60} | {:8.4} | {:8.4} | {:8.4}", "TOTAL", mean, dev, max);
+	println!("\tRMS: {:.4}", rms);
+
+
+	println!("Result");
+	println!("\tFocal Length:    {:?}", intrinsic.focal_length);
+	println!("\tPrinciple Point: {:?}", intrinsic.principle_point);
+	println!("\tRadial:          {:?}", intrinsic.distortion.radial);
+	println!("\tTangential:      {:?}", intrinsic.distortion.tangential);
+
+	let calibration = Calibration{
+		intrinsic: intrinsic,
+		image_size: Pixel{x: image_size.width as usize, y: image_size.height as usize},
+		rms: rms as Decimal};
+	calibration.write(&output).expect("Could not write calibration file.");
+	println!("\tWritten to: {}", output);
+}
+
+
+
+/// Recursively finds all the images in the folder.
+fn find_images ( path: &str, files: &mut Vec<String> )
+{
+	for f in std::fs::read_dir(path).expect("Invalid folder.")
+	{
+		let file = f.unwrap().path();
+		if file.is_dir()
+		{
+			find_images(file.to_str().unwrap(), files);
+		}
+		else
+		{
+			let extension = Path::new(&file).extension().and_then(OsStr::to_str).unwrap_or("");
+			if extension == "png" || extension == "jpg" || extension == "bmp" || extension == "jpeg"
+			{
+				files.push(file.as_path().to_str().unwrap().to_string());
+			}
+		}
+	}
+}
+
+
+/// Finds the mean, standard deviation and max of the errors.
+fn stats ( errors: &Vec<Decimal> ) -> (Decimal, Decimal, Decimal)
+{
+	if errors.len() == 0 { return (0.0, 0.0, 0.0); }
+
+	let mut mean = 0.0;
+	let mut max  = 0.0;
+	for e in errors
+	{
+		mean += e;
+		if max < *e { max = *e; }
+	}
+	mean /= errors.len() as Decimal;
+
+	let mut dev = 0.0;
+	for e in errors { dev += (e - mean).powf(2.0); }
+	dev = (dev / errors.len() as Decimal).sqrt();
+
+	return (mean, dev, max);
+}
@@ -56,6 +56,10 @@ use star_tracker_lib::tracking_mode::StarPyramid;
 use star_tracker_lib::tracking_mode::StarPair;
 use star_tracker_lib::tracking_mode::Specularity;
 use star_tracker_lib::tracking_mode::StarTriangleIterator;
+use star_tracker_lib::tracking_mode::TriangleConstruct;
+use star_tracker_lib::tracking_mode::GeometricVoting;
+use star_tracker_lib::tracking_mode::Verification;
+use star_tracker_lib::tracking_mode::SearchDiagnosticsRecord;
 // use star_tracker_lib::tracking_mode::AbandonSearch;
 use star_tracker_lib::tracking_mode::database::ChunkIterator;
 use star_tracker_lib::tracking_mode::database::ChunkIteratorNone;
@@ -65,6 +69,7 @@ use star_tracker_lib::tracking_mode::database::ChunkIteratorDeclination;
 
 use star_tracker_lib::attitude_determination::AttitudeDetermination;
 use star_tracker_lib::attitude_determination::Quest;
+use star_tracker_lib::attitude_determination::Mounting;
 
 use star_tracker_nix::io::Io;
 use star_tracker_nix::tracking_mode::AbandonSearchTimeoutFailure;
@@ -141,6 +146,8 @@ reset; cargo run --bin demo 16mm_checker_2
 	let mut num_fails = 0;
 	let mut num_success = 0;
 	let mut time_success = 0;
+	// The voting buffers are too large for the stack, they are reused for every image.
+	let mut voting = Box::new(GeometricVoting::<1000, 10000>::new());
 	let samples = star_tracker_nix::io::Sample::load_samples();
 	
 	for sample in samples
@@ -304,6 +311,10 @@ reset; cargo run --bin demo 16mm_checker_2
 			let extrinsic_projection = ExtrinsicParameters::look_at(reference_forward, reference_up)
 				.expect("Ensure extrinsic projection up and forward are not the same value.");
 
+			// The rotation of the camera relative to the body of the spacecraft.
+			// Use Mounting::estimate to find this if you have reference attitudes.
+			let mounting = Mounting::identity();
+
 			// Read the blob positions and convert them to 3d equatorial space.
 			let mut stars_3d : Vec<Equatorial> = Vec::new();
 			for i in 0..stars_2d.len()
@@ -328,15 +339,21 @@ reset; cargo run --bin demo 16mm_checker_2
 
 			// Finds a `constellation` which is 4 stars forming a pyramid shape.
 			// Refer to star_tracker_lib::tracking_mode::{mod, Constellation::find}
+			// The diagnostics count the triangles, database candidates and pilots tried.
 			let mut found_all : Vec<Match<usize>> = Vec::new();
-			let success = Constellation::find (
+			let clock : fn ( ) -> u32 = ||
+				std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() as u32;
+			let mut diagnostics : SearchDiagnosticsRecord<fn ( ) -> u32, 8> = SearchDiagnosticsRecord::new(clock);
+			let mut triangles = StarTriangleIterator::<10000>::new();
+			triangles.begin(angle_tolerance, &stars_3d);
+			let success = Constellation::search (
 				&stars_3d, &mut database_iterator,
-				&mut StarTriangleIterator::<10000>::new(),
+				&mut triangles,
 				&mut Specularity::default(),
 				&mut AbandonSearchTimeoutFailure::new(Duration::from_millis(time_good as u64), fails_good),
-				angle_tolerance,
 				4..=4,
-				&mut found_all
+				&mut found_all,
+				Some(&mut diagnostics)
 			);
 			let time_tracking = timer.elapsed().as_millis();
 			let mut found_stars : Vec<Match<Vector3>> = Vec::new();
@@ -358,6 +375,33 @@ reset; cargo run --bin demo 16mm_checker_2
 				fails_bands[fails].push(sample.file_img[image_index].clone());
 					println!("SUCCESS; with {} fails.", fails);
 			}}
+
+			let average_candidates = diagnostics.candidates as Decimal / diagnostics.sides.max(1) as Decimal;
+			println!("\tSearch: {} triangles, {} sides ({} empty, {:.1} candidates avg), {} flipped, {} pilots, {} pilot sides ({} candidates), {} pilot failures, {} chunks, {}ms.",
+				diagnostics.triangles, diagnostics.sides, diagnostics.sides_empty, average_candidates,
+				diagnostics.specularity, diagnostics.pilots, diagnostics.pilot_sides, diagnostics.pilot_candidates,
+				diagnostics.pilot_failures, diagnostics.chunks, diagnostics.ticks);
+
+			// Geometric voting identifies the same stars without searching for a pyramid.
+			// Refer to star_tracker_lib::tracking_mode::{mod, GeometricVoting::find}
+			let timer = std::time::Instant::now();
+			let mut found_voting : Vec<Match<usize>> = Vec::new();
+			let result_voting = voting.find(
+				&stars_3d, &database, angle_tolerance, 4, &mut found_voting);
+			let time_voting = timer.elapsed().as_millis();
+
+			let mut voting_agrees = 0;
+			for i in 0..found_voting.size()
+			{
+				for j in 0..found_all.size()
+				{
+					if found_voting[i].input == found_all[j].input && found_voting[i].output == found_all[j].output
+					{
+						voting_agrees += 1;
+					}
+				}
+			}
+			println!("VOTING: {:?}; {} of {} agree with the pyramid.", result_voting, voting_agrees, found_all.size());
 			
 			if found_all.size() < 3
 			{
@@ -399,11 +443,21 @@ reset; cargo run --bin demo 16mm_checker_2
 			let rotate_to_cam  : Quaternion = Quest::estimate(&found_stars, None);
 			let rotate_to_world: Quaternion = rotate_to_cam.conjugate();
 			let world_center = rotate_to_world.rotate_point(reference_forward.to_vector3());
+			let rotate_to_body : Quaternion = mounting.to_body(rotate_to_cam);
 			let time_attitude = timer.elapsed().as_millis();
 
 			println!();
 			println!("Found Center: {}", world_center.to_equatorial().formatted());
 			println!("Found Center: {}", world_center.to_equatorial());
+			println!("Attitude (inertial to body): {:?}", rotate_to_body);
+
+			// Checks the rest of the image agrees with the pyramid.
+			let verification = Verification{fov: fov, tolerance: angle_tolerance};
+			let mut region : Vec<usize> = Vec::new();
+			verification.region(database.catalogue, rotate_to_cam, &mut region);
+			let verified = verification.verify(&stars_3d, &found_all, database.catalogue, &region, rotate_to_cam);
+			println!("Verification: {} of {} stars agree ({} catalogue stars in view), false positive probability: {:e}",
+				verified.matched, verified.tested, verified.in_fov, verified.false_probability);
 			
 			
 			if 3 < found_all.size()
@@ -457,6 +511,7 @@ reset; cargo run --bin demo 16mm_checker_2
 			println!("Time blob detection: {} ms", time_blob);
 			println!("Time projection:     {} ms", time_project);
 			println!("Time tracking:       {} ms", time_tracking);
+			println!("Time voting:         {} ms", time_voting);
 			println!("Time quest:          {} ms", time_attitude);
 
 
@@ -8,6 +8,7 @@
 #[macro_use]
 extern crate star_tracker_lib;
 extern crate star_tracker_nix;
+extern crate star_tracker_database;
 extern crate image;
 extern crate rand;
 extern crate opencv;
@@ -35,12 +36,16 @@ use star_tracker_lib::util::linear_lookup::LinearLookup;
 
 
 use star_tracker_lib::tracking_mode::Constellation;
+use star_tracker_lib::tracking_mode::ConstellationResult;
 use star_tracker_lib::tracking_mode::StarPyramid;
 use star_tracker_lib::tracking_mode::Specularity;
 use star_tracker_lib::tracking_mode::StarTriangleIterator;
 use star_tracker_lib::tracking_mode::AbandonSearch;
 use star_tracker_lib::tracking_mode::AbandonSearchNone;
 use star_tracker_lib::tracking_mode::PilotFinder;
+use star_tracker_lib::tracking_mode::GeometricVoting;
+use star_tracker_lib::tracking_mode::Grid;
+use star_tracker_lib::tracking_mode::Verification;
 use star_tracker_lib::tracking_mode::database::ChunkIterator;
 use star_tracker_lib::tracking_mode::database::ChunkIteratorNone;
 use star_tracker_lib::tracking_mode::database::ChunkIteratorRegional;
@@ -52,16 +57,21 @@ use star_tracker_lib::tracking_mode::database::SearchResult;
 use star_tracker_lib::image_processing::Image;
 
 use star_tracker_lib::projection::ExtrinsicParameters;
+use star_tracker_lib::projection::IntrinsicParameters;
+use star_tracker_lib::projection::ThermalModel;
 use star_tracker_lib::projection::SpaceWorld;
 use star_tracker_lib::projection::SpaceImage;
 
 use star_tracker_lib::attitude_determination::Quest;
 use star_tracker_lib::attitude_determination::AttitudeDetermination;
+use star_tracker_lib::attitude_determination::AttitudeVoting;
 
 use star_tracker_nix::io::Star;
 use star_tracker_nix::io::Io;
 use star_tracker_nix::tracking_mode::DatabaseGenerator;
 use star_tracker_nix::tracking_mode::AbandonSearchTimeoutFailure;
+use star_tracker_nix::tracking_mode::ParallelSearch;
+use star_tracker_database::tracking_mode::GridDatabaseGenerator;
 use star_tracker_nix::image_processing::Color;
 use star_tracker_nix::image_processing::CVImage;
 
@@ -110,11 +120,38 @@ This also provides a step by step guide to use the tracking mode algorithm.
 	const VARIATION_POSITION_MEAN    : Radians = Degrees(0.012).as_radians(); // Mean of Error.
 	
 	const CAP_STARS             : usize   = 20; // Max stars in image.
+
+	// Grid algorithm (compared against the pyramid method).
+	const GRID_RADIUS           : Radians = Degrees(6.0).as_radians(); // Neighbours in a pattern (less than FOV / 2).
+	const GRID_SIZE             : usize   = 41; // Cells on each side of the grid (odd).
+	const GRID_MIN_SCORE        : usize   = 4;  // Shared cells for a pattern to match.
+
+	// Multiple solution voting (compared against the pyramid method).
+	// The stars are split into subsets, each subset is identified separately and the attitudes must agree.
+	const VOTING_SUBSETS        : usize   = 3; // Disjoint subsets of the observed stars.
+	const VOTING_QUORUM         : usize   = 2; // Attitudes which must agree.
+	const VOTING_TOLERANCE      : Radians = Degrees(0.5).as_radians(); // Max angle between agreeing attitudes.
+
+	// Thermal drift of the lens.
+	// Each test uses the next temperature in the sweep.
+	// The actual lens drifts by FOCAL_LENGTH_PPM, the star tracker only knows the calibrated FOCAL_LENGTH_PPM_MODEL.
+	// If THERMAL_COMPENSATION is false, the star tracker assumes the nominal lens (TEMPERATURE_REFERENCE).
+	// If true, the star tracker applies the calibrated model to the nominal lens at the temperature.
+	const TEMPERATURE_REFERENCE : Decimal = 20.0;  // The temperature the lens was calibrated at (C).
+	const TEMPERATURE_MIN       : Decimal = -40.0; // The coldest temperature in the sweep (C).
+	const TEMPERATURE_MAX       : Decimal = 80.0;  // The hottest temperature in the sweep (C).
+	const TEMPERATURE_STEPS     : usize   = 7;     // The number of temperatures in the sweep.
+	const FOCAL_LENGTH_PPM      : Decimal = 5.0;   // The actual change in focal length per degree.
+	const FOCAL_LENGTH_PPM_MODEL: Decimal = 4.5;   // The calibrated change in focal length per degree.
+	const THERMAL_COMPENSATION  : bool    = true;  // If the star tracker knows the temperature.
+	const SENSOR_SIZE           : Pixel   = Pixel{x: 1280, y: 720};
 	
 	
 	// Loose conditions
 	const TIME_GOOD             : u128 = 500; // ms until autofail.
 	const FAILURE_GOOD          : usize = 300; // # of triangle failures until a failure.
+	const THREADS               : usize = 4;   // Threads searching each image (1 uses Constellation::find).
+	const VERIFY_PROBABILITY    : Decimal = 0.001; // Max false positive probability of a worker's solution (threads only).
 	let ERROR_GOOD            : Radians = Degrees(1.0).to_radians(); // The acceptable error of the result
 	
 	//###############################################################################################//
@@ -234,6 +271,10 @@ This also provides a step by step guide to use the tracking mode algorithm.
 	println!(" - {} angles generated.", database.pairs.size());
 	println!(" - {} bins", database.k_vector.size());
 
+	let gen_grid = GridDatabaseGenerator::gen_database(&stars_limit_reg, GRID_RADIUS, DOUBLE_STAR_TOLERANCE, GRID_SIZE);
+	let database_grid = gen_grid.get_database();
+	println!(" - {} grid cells.", gen_grid.cells.len());
+
 
 
 
@@ -245,6 +286,15 @@ This also provides a step by step guide to use the tracking mode algorithm.
 //
 //###############################################################################################//
 
+	let sensor_diagonal = ((SENSOR_SIZE.x.pow(2) + SENSOR_SIZE.y.pow(2)) as Decimal).sqrt();
+	let sensor_center   = Vector2{x: SENSOR_SIZE.x as Decimal / 2.0, y: SENSOR_SIZE.y as Decimal / 2.0};
+	let lens_reference  = IntrinsicParameters::from_fov(FOV, sensor_diagonal, sensor_center);
+	let thermal = ThermalModel::linear_ppm(lens_reference, TEMPERATURE_REFERENCE, FOCAL_LENGTH_PPM);
+	let thermal_model = ThermalModel::linear_ppm(lens_reference, TEMPERATURE_REFERENCE, FOCAL_LENGTH_PPM_MODEL);
+
+	// (successful pyramids, tests) for each temperature in the sweep.
+	let mut temperature_results : Vec<(usize, usize)> = vec![(0, 0); TEMPERATURE_STEPS];
+
 	let observation_points = Distribute::angle_to_points(SAMPLE_FOV);
 	let observation = Distribute::fibonacci_lattice(observation_points);
 	println!("Performing Lost In Space");
@@ -264,6 +314,20 @@ This also provides a step by step guide to use the tracking mode algorithm.
 	let mut avg_time_error     = 0;
 	let mut avg_time_cap       = 0;
 	let mut avg_time_false_positive= 0;
+	let mut num_voting         = 0;
+	let mut num_voting_false   = 0;
+	let mut avg_time_voting    = 0;
+	// The voting buffers are too large for the stack, they are reused for every test.
+	let mut voting             = Box::new(GeometricVoting::<1000, 10000>::new());
+	let mut num_grid           = 0;
+	let mut num_grid_false     = 0;
+	let mut avg_time_grid      = 0;
+	let mut num_subset         = 0;
+	let mut num_subset_false   = 0;
+	let mut num_multi          = 0;
+	let mut num_multi_false    = 0;
+	let mut num_multi_rejected = 0;
+	let mut avg_time_multi     = 0;
 
 	// let center = Equatorial{ra: Degrees(30.0).to_radians(), dec: Degrees(50.0).to_radians()};
 	for center in observation
@@ -273,6 +337,14 @@ This also provides a step by step guide to use the tracking mode algorithm.
 
 		let mut observable : Vec<Equatorial> = Vec::new();
 
+		// The lens is at the temperature, the star tracker may or may not know this.
+		let temperature_index = test_num % TEMPERATURE_STEPS;
+		let temperature = TEMPERATURE_MIN + temperature_index as Decimal *
+			(TEMPERATURE_MAX - TEMPERATURE_MIN) / (TEMPERATURE_STEPS - 1).max(1) as Decimal;
+		let lens_actual  = thermal.at_temperature(temperature);
+		let lens_assumed =
+			if THERMAL_COMPENSATION { thermal_model.at_temperature(temperature) } else { lens_reference };
+
 		// Uses look_at to set center to +z.
 		// This is how the image will present the information to the algorithm.
 		let up = Equatorial{ra: Radians(0.0), dec: Degrees(90.0).to_radians()};
@@ -295,7 +367,10 @@ This also provides a step by step guide to use the tracking mode algorithm.
 				position.dec = position.dec +
 					Radians(gen_random_sd(&mut rng, VARIATION_POSITION_MEAN.0, VARIATION_POSITION_STD_DEV.0));
 
-				let rotated = rotation.to_image(SpaceWorld(position.to_vector3())).0;
+				// Projects onto the sensor with the actual lens and back with the assumed lens.
+				let camera  = rotation.to_image(SpaceWorld(position.to_vector3()));
+				let image   = lens_actual.to_image(camera);
+				let rotated = lens_assumed.from_image(image).0;
 				observable.push(rotated.to_equatorial());
 			}
 			i += 1;
@@ -325,16 +400,43 @@ This also provides a step by step guide to use the tracking mode algorithm.
 		// 
 		let mut found_all : Vec<Match<usize>> = Vec::new();
 
-		let success = Constellation::find(
-			&observable, &mut database_iterator,
-			&mut StarTriangleIterator::<1000>::new(),
-			&mut Specularity::default(),
-			&mut AbandonSearchTimeoutFailure::new(Duration::from_millis(TIME_GOOD as u64), FAILURE_GOOD),
-			// &mut AbandonSearchNone(),
-			ANGLE_TOLERANCE,
-			4..=4,
-			&mut found_all,
-		);
+		let success = if THREADS <= 1
+		{
+			Constellation::find(
+				&observable, &mut database_iterator,
+				&mut StarTriangleIterator::<1000>::new(),
+				&mut Specularity::default(),
+				&mut AbandonSearchTimeoutFailure::new(Duration::from_millis(TIME_GOOD as u64), FAILURE_GOOD),
+				// &mut AbandonSearchNone(),
+				ANGLE_TOLERANCE,
+				4..=4,
+				&mut found_all,
+			)
+		}
+		else
+		{
+			// Each thread needs its own database iterator.
+			let search = ParallelSearch::new(THREADS, Duration::from_millis(TIME_GOOD as u64), FAILURE_GOOD);
+			search.find::<1000, _, _>(&observable, |search|
+				{
+					let database = gen.get_database_regional();
+					search(&mut ChunkIteratorNone::new(&database));
+				},
+				|found|
+				{
+					// A worker must agree with the rest of the image before the others are cancelled.
+					let database = gen.get_database_regional();
+					let attitude = matches_attitude(found, &observable, database.catalogue);
+					let verification = Verification{fov: FOV, tolerance: ANGLE_TOLERANCE};
+					let mut region : Vec<usize> = Vec::new();
+					verification.region(database.catalogue, attitude, &mut region);
+					return verification.verify(&observable, found, database.catalogue, &region, attitude).false_probability <= VERIFY_PROBABILITY;
+				},
+				ANGLE_TOLERANCE,
+				4..=4,
+				&mut found_all,
+			)
+		};
 		
 		// let success = Constellation::old_find(
 		// 	&observable, &mut database_iterator,
@@ -348,10 +450,81 @@ This also provides a step by step guide to use the tracking mode algorithm.
 		// );
 		
 		let time = timer.elapsed();
+
+		// Geometric voting on the same observation for comparison.
+		let timer_voting : std::time::Instant = std::time::Instant::now();
+		let mut found_voting : Vec<Match<usize>> = Vec::new();
+		let _ = voting.find(
+			&observable, &database, ANGLE_TOLERANCE, 4, &mut found_voting);
+		avg_time_voting += timer_voting.elapsed().as_millis();
+		if 3 <= found_voting.len()
+		{
+			if matches_correct(&found_voting, center, &observable, database.catalogue, ERROR_GOOD)
+			{ num_voting += 1; }
+			else
+			{ num_voting_false += 1; }
+		}
+
+		// The grid algorithm on the same observation for comparison.
+		let timer_grid : std::time::Instant = std::time::Instant::now();
+		let mut found_grid : Vec<Match<usize>> = Vec::new();
+		let _ = Grid::<1000>::new().find(
+			&observable, &database_grid, ANGLE_TOLERANCE, GRID_MIN_SCORE, 4, &mut found_grid);
+		avg_time_grid += timer_grid.elapsed().as_millis();
+		if 3 <= found_grid.len()
+		{
+			if matches_correct(&found_grid, center, &observable, database_grid.catalogue, ERROR_GOOD)
+			{ num_grid += 1; }
+			else
+			{ num_grid_false += 1; }
+		}
+
+		// Multiple solution voting on the same observation for comparison.
+		// Each subset is a separate lost in space search, a false positive in one subset is out voted by the others.
+		let timer_multi : std::time::Instant = std::time::Instant::now();
+		let mut attitudes : Vec<Quaternion> = Vec::new();
+		for index in 0..VOTING_SUBSETS
+		{
+			let mut subset       : Vec<Equatorial>   = Vec::new();
+			let mut found_subset : Vec<Match<usize>> = Vec::new();
+			AttitudeVoting::subset(&observable, VOTING_SUBSETS, index, &mut subset);
+			let result = Constellation::find(
+				&subset, &mut database_iterator,
+				&mut StarTriangleIterator::<1000>::new(),
+				&mut Specularity::default(),
+				&mut AbandonSearchTimeoutFailure::new(Duration::from_millis(TIME_GOOD as u64), FAILURE_GOOD),
+				ANGLE_TOLERANCE,
+				4..=4,
+				&mut found_subset,
+			);
+			if let ConstellationResult::Success{fails: _} = result
+			{
+				let attitude = matches_attitude(&found_subset, &subset, database.catalogue);
+				if attitude_correct(attitude, center, ERROR_GOOD) { num_subset += 1; }
+				else                                              { num_subset_false += 1; }
+				attitudes.push(attitude);
+			}
+		}
+
+		let attitude_voting = AttitudeVoting{tolerance: VOTING_TOLERANCE, quorum: VOTING_QUORUM, subsets: VOTING_SUBSETS};
+		let mut agreed : Vec<Quaternion> = Vec::new();
+		match attitude_voting.vote(&attitudes, &mut agreed)
+		{
+			Some(attitude) =>
+			{
+				if attitude_correct(attitude, center, ERROR_GOOD) { num_multi += 1; }
+				else                                              { num_multi_false += 1; }
+			}
+			None => { num_multi_rejected += 1; }
+		}
+		avg_time_multi += timer_multi.elapsed().as_millis();
+
+		temperature_results[temperature_index].1 += 1;
 		match print_result(test_num, time, found_all, center, rotation, observable, database.catalogue, TIME_GOOD, ERROR_GOOD)
 		{
 			Result::Pyramid       =>{
 				num_pyramid        +=1; avg_time_pyramid  += time.as_millis(); 
+				temperature_results[temperature_index].0 += 1;
 				draw_pt(center, Color::Blue, 4, &mut sky_map);
 			}
 			Result::Triangle      =>{
@@ -389,12 +562,24 @@ This also provides a step by step guide to use the tracking mode algorithm.
 	println!("{}\t falsely identified pyramids.", num_error_pyramid);
 	println!("{}\t falsely identified triangles.", num_error_triangle);
 	println!("{}\t exceeded time requirement and was valid.", num_error_time);
+	println!("{}\t identified by geometric voting.", num_voting);
+	println!("{}\t falsely identified by geometric voting.", num_voting_false);
+	println!("{}\t identified by grid.", num_grid);
+	println!("{}\t falsely identified by grid.", num_grid_false);
+	println!("{}\t subsets identified.", num_subset);
+	println!("{}\t subsets falsely identified.", num_subset_false);
+	println!("{}\t identified by multiple solution voting.", num_multi);
+	println!("{}\t falsely identified by multiple solution voting.", num_multi_false);
+	println!("{}\t rejected by multiple solution voting (no quorum).", num_multi_rejected);
 
 	println!("{:.2} ms\t avg time pyramid." ,avg_time_pyramid as Decimal /num_pyramid as Decimal);
 	println!("{:.2} ms\t avg time triangle.",avg_time_triangle as Decimal/num_triangle as Decimal);
 	println!("{:.2} ms\t avg time error.",   avg_time_error as Decimal   /(num_error + num_error_triangle) as Decimal);
 	println!("{:.2} ms\t avg time cap.",     avg_time_cap as Decimal   /(num_error_time as Decimal));
 	println!("{:.2} ms\t avg time false_positive.",   avg_time_false_positive as Decimal /(num_error_pyramid as Decimal));
+	println!("{:.2} ms\t avg time geometric voting.", avg_time_voting as Decimal / test_num as Decimal);
+	println!("{:.2} ms\t avg time grid.", avg_time_grid as Decimal / test_num as Decimal);
+	println!("{:.2} ms\t avg time multiple solution voting.", avg_time_multi as Decimal / test_num as Decimal);
 
 	println!("");
 	println!("");
@@ -412,6 +597,27 @@ This also provides a step by step guide to use the tracking mode algorithm.
 	println!("HIDDEN STARS:   {}", HIDDEN_STARS);
 	println!("");
 	println!("STARS IN IMAGE: {}", CAP_STARS);
+	println!("");
+	println!("GRID RADIUS:    {}", GRID_RADIUS.to_degrees());
+	println!("GRID SIZE:      {}", GRID_SIZE);
+	println!("GRID MIN SCORE: {}", GRID_MIN_SCORE);
+	println!("");
+	println!("VOTING SUBSETS: {}", VOTING_SUBSETS);
+	println!("VOTING QUORUM:  {}", VOTING_QUORUM);
+	println!("VOTING TOL:     {}", VOTING_TOLERANCE.to_degrees());
+	println!("");
+	println!("FOCAL DRIFT:    {} ppm/C", FOCAL_LENGTH_PPM);
+	println!("FOCAL MODEL:    {} ppm/C", FOCAL_LENGTH_PPM_MODEL);
+	println!("COMPENSATED:    {}", THERMAL_COMPENSATION);
+	println!("");
+	println!("------------- Temperature -------------");
+	println!("Temperature (C)\t|\tPyramids\t|\tTests");
+	for i in 0..TEMPERATURE_STEPS
+	{
+		let temperature = TEMPERATURE_MIN + i as Decimal *
+			(TEMPERATURE_MAX - TEMPERATURE_MIN) / (TEMPERATURE_STEPS - 1).max(1) as Decimal;
+		println!("{:.1}\t\t|\t{}\t\t|\t{}", temperature, temperature_results[i].0, temperature_results[i].1);
+	}
 
 	// star_tracker_nix::image_processing::NixImage::save(&sky_map, "sky_sim.png");
 	// let _ = wait_key(0);
@@ -476,6 +682,42 @@ enum Result
 
 
 
+/// Returns true if the identified stars produce an attitude close to the actual location.
+fn matches_correct (
+	matches: &Vec<Match<usize>>,
+	location: Equatorial,
+	stars_image: &Vec<Equatorial>, stars_database: &dyn LinearLookup<Equatorial>,
+	allowed_error: Radians ) -> bool
+{
+	return attitude_correct(matches_attitude(matches, stars_image, stars_database), location, allowed_error);
+}
+
+
+/// Returns the rotation from the world to the camera of the identified stars.
+fn matches_attitude (
+	matches: &Vec<Match<usize>>,
+	stars_image: &Vec<Equatorial>, stars_database: &dyn LinearLookup<Equatorial> ) -> Quaternion
+{
+	let mut found_stars : Vec<Match<Vector3>> = Vec::new();
+	for i in 0..matches.len()
+	{
+		let input  = stars_image[matches[i].input].to_vector3();
+		let output = stars_database.get(matches[i].output).to_vector3();
+		found_stars.push( Match{ input:  input, output: output, weight: 1.0 } );
+	}
+	return Quest::estimate(&found_stars, None);
+}
+
+
+/// Returns true if the boresight of the attitude is close to the actual location.
+fn attitude_correct ( rotate_to_cam: Quaternion, location: Equatorial, allowed_error: Radians ) -> bool
+{
+	let rotate_to_world: Quaternion = rotate_to_cam.conjugate();
+	let world_center = rotate_to_world.rotate_point(Vector3{x: 0.0, y: 0.0, z: 1.0});
+	return world_center.angle_distance(location.to_vector3()) < allowed_error;
+}
+
+
 fn print_result ( 
 	test_num: usize, 
 	time: std::time::Duration, 